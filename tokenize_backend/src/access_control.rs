@@ -0,0 +1,427 @@
+//! Role- and attribute-based authorization
+//!
+//! Each role is granted permissions either for any resource or only for resources
+//! the caller owns (the asset's `owner` or the wallet's `owner`). The same policy
+//! is enforced twice: route filters reject callers whose roles never grant a
+//! permission, and `TokenizationCore` checks the ownership condition against the
+//! actual asset or wallet.
+//!
+//! Core calls made inside `TokenizationCore::as_subject` are checked against that
+//! subject. Calls made outside it run as the platform itself and are not
+//! restricted, which keeps internal jobs and existing callers working.
+
+use crate::auth::Principal;
+use crate::core_component::TokenizationCore;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// Prefix of every authorization error returned by the core
+pub const PERMISSION_DENIED: &str = "Permission denied";
+
+thread_local! {
+    static CURRENT_SUBJECT: RefCell<Option<Subject>> = const { RefCell::new(None) };
+}
+
+/// Roles a principal can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Issuer,
+    Investor,
+    ComplianceOfficer,
+    TransferAgent,
+    Auditor,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Issuer,
+        Role::Investor,
+        Role::ComplianceOfficer,
+        Role::TransferAgent,
+        Role::Auditor,
+        Role::Admin,
+    ];
+
+    /// Parse a role name such as `compliance_officer`
+    pub fn parse(name: &str) -> Option<Role> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+}
+
+/// Operations subject to authorization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    ReadAsset,
+    CreateAsset,
+    UpdateAsset,
+    DeleteAsset,
+    CreateWallet,
+    ReadWallet,
+    UpdateWallet,
+    DeleteWallet,
+    Mint,
+    Burn,
+    Transfer,
+    ComplianceCheck,
+    /// Cap tables, NAV and integrity reports
+    ReadReports,
+    ReadAudit,
+    ReadEvents,
+    ManageCatalog,
+    ManageWebhooks,
+    ManageApiKeys,
+    /// Approve or reject operations held for four-eyes approval
+    ApproveActions,
+    ConfigureApprovals,
+    /// Inspect, reload and dry-run policy bundles
+    ManagePolicies,
+    /// Freeze and unfreeze wallets, assets, owners and the platform
+    ManageFreezes,
+    /// Sign for the platform on custodial and hybrid wallets and arrange their custody
+    ManageCustody,
+    /// Generate, rotate and retire platform keys
+    ManageKeys,
+    /// List platform keys and sign with them
+    UseKeys,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 25] = [
+        Permission::ReadAsset,
+        Permission::CreateAsset,
+        Permission::UpdateAsset,
+        Permission::DeleteAsset,
+        Permission::CreateWallet,
+        Permission::ReadWallet,
+        Permission::UpdateWallet,
+        Permission::DeleteWallet,
+        Permission::Mint,
+        Permission::Burn,
+        Permission::Transfer,
+        Permission::ComplianceCheck,
+        Permission::ReadReports,
+        Permission::ReadAudit,
+        Permission::ReadEvents,
+        Permission::ManageCatalog,
+        Permission::ManageWebhooks,
+        Permission::ManageApiKeys,
+        Permission::ApproveActions,
+        Permission::ConfigureApprovals,
+        Permission::ManagePolicies,
+        Permission::ManageFreezes,
+        Permission::ManageCustody,
+        Permission::ManageKeys,
+        Permission::UseKeys,
+    ];
+}
+
+/// Which resources a grant covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    Any,
+    /// Only assets or wallets whose owner is the subject
+    Own,
+}
+
+/// Scope in which `role` holds `permission`, if at all
+pub fn grant(role: Role, permission: Permission) -> Option<Scope> {
+    use Permission::*;
+    match role {
+        Role::Admin => Some(Scope::Any),
+        Role::Issuer => match permission {
+            ReadAsset => Some(Scope::Any),
+            CreateAsset | UpdateAsset | DeleteAsset | Mint | Burn | ReadReports => Some(Scope::Own),
+            CreateWallet | ReadWallet | UpdateWallet | DeleteWallet | Transfer => Some(Scope::Own),
+            _ => None,
+        },
+        Role::Investor => match permission {
+            ReadAsset => Some(Scope::Any),
+            CreateWallet | ReadWallet | UpdateWallet | DeleteWallet | Transfer => Some(Scope::Own),
+            _ => None,
+        },
+        Role::ComplianceOfficer => match permission {
+            ReadAsset | ReadWallet | ComplianceCheck | ReadReports | ReadAudit | ReadEvents | ApproveActions
+            | ManagePolicies | ManageFreezes => Some(Scope::Any),
+            _ => None,
+        },
+        Role::TransferAgent => match permission {
+            ReadAsset | ReadWallet | UpdateWallet | Mint | Burn | Transfer | ReadReports | ReadEvents
+            | ApproveActions | ManageCustody | UseKeys => Some(Scope::Any),
+            _ => None,
+        },
+        Role::Auditor => match permission {
+            ReadAsset | ReadWallet | ReadReports | ReadAudit | ReadEvents => Some(Scope::Any),
+            _ => None,
+        },
+    }
+}
+
+/// Caller identity and roles as seen by the policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subject {
+    pub id: String,
+    pub roles: Vec<Role>,
+}
+
+impl Subject {
+    pub fn new(id: &str, roles: &[Role]) -> Self {
+        Self {
+            id: id.to_string(),
+            roles: roles.to_vec(),
+        }
+    }
+
+    /// Whether any role grants the permission, in any scope
+    pub fn may(&self, permission: Permission) -> bool {
+        self.roles.iter().any(|role| grant(*role, permission).is_some())
+    }
+}
+
+impl From<&Principal> for Subject {
+    /// Role names the policy does not know are ignored
+    fn from(principal: &Principal) -> Self {
+        Self {
+            id: principal.subject.clone(),
+            roles: principal.roles.iter().filter_map(|name| Role::parse(name)).collect(),
+        }
+    }
+}
+
+/// Check a permission against a resource owned by `owner`, if it has one
+pub fn authorize(subject: &Subject, permission: Permission, owner: Option<&str>) -> Result<(), String> {
+    let allowed = subject.roles.iter().any(|role| match grant(*role, permission) {
+        Some(Scope::Any) => true,
+        Some(Scope::Own) => owner == Some(subject.id.as_str()),
+        None => false,
+    });
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("{}: {} may not perform {:?}", PERMISSION_DENIED, subject.id, permission))
+    }
+}
+
+/// Whether an error from the core is an authorization failure
+pub fn is_permission_denied(error: &str) -> bool {
+    error.starts_with(PERMISSION_DENIED)
+}
+
+/// Subject the current thread is acting for, if any
+pub fn current_subject() -> Option<Subject> {
+    CURRENT_SUBJECT.with(|subject| subject.borrow().clone())
+}
+
+/// Check a permission for the current subject; unrestricted outside `as_subject`
+pub(crate) fn authorize_current(permission: Permission, owner: Option<&str>) -> Result<(), String> {
+    match current_subject() {
+        Some(subject) => authorize(&subject, permission, owner),
+        None => Ok(()),
+    }
+}
+
+/// Run `f` as the platform itself, without the current subject's restrictions
+pub(crate) fn without_subject<T>(f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT_SUBJECT.with(|current| current.borrow_mut().take());
+    let _guard = SubjectGuard(previous);
+    f()
+}
+
+struct SubjectGuard(Option<Subject>);
+
+impl Drop for SubjectGuard {
+    fn drop(&mut self) {
+        CURRENT_SUBJECT.with(|subject| *subject.borrow_mut() = self.0.take());
+    }
+}
+
+impl TokenizationCore {
+    /// Run `f` on behalf of `subject`: its calls into the core are authorized
+    /// against the subject's roles and audited under its ID
+    pub fn as_subject<T>(&self, subject: &Subject, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT_SUBJECT.with(|current| current.borrow_mut().replace(subject.clone()));
+        let _guard = SubjectGuard(previous);
+        self.with_actor(&subject.id, f)
+    }
+
+    /// Check a permission on a wallet for the current subject
+    pub(crate) fn authorize_wallet(&self, permission: Permission, wallet_id: &str) -> Result<(), String> {
+        if current_subject().is_none() {
+            return Ok(());
+        }
+        let owner = self
+            .wallets
+            .read()
+            .map_err(|_| "Failed to acquire read lock")?
+            .get(wallet_id)
+            .map(|wallet| wallet.owner.clone());
+        authorize_current(permission, owner.as_deref())
+    }
+
+    /// Refuse unless the current subject owns the wallet, whatever its roles
+    ///
+    /// For settings that decide who may sign for a wallet, which no grant for any
+    /// wallet should reach.
+    pub(crate) fn authorize_wallet_owner(&self, wallet_id: &str) -> Result<(), String> {
+        let Some(subject) = current_subject() else {
+            return Ok(());
+        };
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let wallet = wallets.get(wallet_id).ok_or("Wallet not found")?;
+        if wallet.owner != subject.id {
+            return Err(format!("{}: only the owner of wallet {} may do this", PERMISSION_DENIED, wallet_id));
+        }
+        Ok(())
+    }
+
+    /// Check a permission on an asset for the current subject
+    pub(crate) fn authorize_asset(&self, permission: Permission, asset_id: &str) -> Result<(), String> {
+        if current_subject().is_none() {
+            return Ok(());
+        }
+        let owner = self
+            .assets
+            .read()
+            .map_err(|_| "Failed to acquire read lock")?
+            .get(asset_id)
+            .map(|asset| asset.owner.clone());
+        authorize_current(permission, owner.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock));
+        for (id, owner) in [("asset1", "issuer-1"), ("asset2", "issuer-2")] {
+            core.create_asset(TokenizedAsset {
+                value: 10.0,
+                ..asset(id, owner, AssetType::Equity)
+            })
+            .unwrap();
+        }
+        for (id, owner) in [("wallet1", "investor-1"), ("wallet2", "investor-2"), ("treasury", "issuer-1")] {
+            core.create_wallet(wallet(id, owner, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 100.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_permissions_matrix() {
+        use Permission::*;
+        use Scope::*;
+        // One row per permission, columns in `Role::ALL` order:
+        // issuer, investor, compliance officer, transfer agent, auditor, admin
+        let matrix: [(Permission, [Option<Scope>; 6]); 25] = [
+            (ReadAsset, [Some(Any), Some(Any), Some(Any), Some(Any), Some(Any), Some(Any)]),
+            (CreateAsset, [Some(Own), None, None, None, None, Some(Any)]),
+            (UpdateAsset, [Some(Own), None, None, None, None, Some(Any)]),
+            (DeleteAsset, [Some(Own), None, None, None, None, Some(Any)]),
+            (CreateWallet, [Some(Own), Some(Own), None, None, None, Some(Any)]),
+            (ReadWallet, [Some(Own), Some(Own), Some(Any), Some(Any), Some(Any), Some(Any)]),
+            (UpdateWallet, [Some(Own), Some(Own), None, Some(Any), None, Some(Any)]),
+            (DeleteWallet, [Some(Own), Some(Own), None, None, None, Some(Any)]),
+            (Mint, [Some(Own), None, None, Some(Any), None, Some(Any)]),
+            (Burn, [Some(Own), None, None, Some(Any), None, Some(Any)]),
+            (Transfer, [Some(Own), Some(Own), None, Some(Any), None, Some(Any)]),
+            (ComplianceCheck, [None, None, Some(Any), None, None, Some(Any)]),
+            (ReadReports, [Some(Own), None, Some(Any), Some(Any), Some(Any), Some(Any)]),
+            (ReadAudit, [None, None, Some(Any), None, Some(Any), Some(Any)]),
+            (ReadEvents, [None, None, Some(Any), Some(Any), Some(Any), Some(Any)]),
+            (ManageCatalog, [None, None, None, None, None, Some(Any)]),
+            (ManageWebhooks, [None, None, None, None, None, Some(Any)]),
+            (ManageApiKeys, [None, None, None, None, None, Some(Any)]),
+            (ApproveActions, [None, None, Some(Any), Some(Any), None, Some(Any)]),
+            (ConfigureApprovals, [None, None, None, None, None, Some(Any)]),
+            (ManagePolicies, [None, None, Some(Any), None, None, Some(Any)]),
+            (ManageFreezes, [None, None, Some(Any), None, None, Some(Any)]),
+            (ManageCustody, [None, None, None, Some(Any), None, Some(Any)]),
+            (ManageKeys, [None, None, None, None, None, Some(Any)]),
+            (UseKeys, [None, None, None, Some(Any), None, Some(Any)]),
+        ];
+        assert_eq!(matrix.map(|(p, _)| p), Permission::ALL);
+        for (permission, expected) in matrix {
+            for (role, scope) in Role::ALL.into_iter().zip(expected) {
+                assert_eq!(grant(role, permission), scope, "{:?} / {:?}", role, permission);
+
+                let subject = Subject::new("alice", &[role]);
+                let own = authorize(&subject, permission, Some("alice")).is_ok();
+                let other = authorize(&subject, permission, Some("bob")).is_ok();
+                assert_eq!(own, scope.is_some(), "{:?} / {:?} on own resource", role, permission);
+                assert_eq!(other, scope == Some(Any), "{:?} / {:?} on other resource", role, permission);
+            }
+        }
+    }
+
+    #[test]
+    fn test_core_enforces_ownership() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(clock);
+        let investor = Subject::new("investor-1", &[Role::Investor]);
+        let issuer = Subject::new("issuer-1", &[Role::Issuer]);
+        let auditor = Subject::new("auditor-1", &[Role::Auditor]);
+        let officer = Subject::new("officer-1", &[Role::ComplianceOfficer]);
+
+        // Investors may only read their own wallets
+        core.as_subject(&investor, || {
+            assert!(core.get_wallet("wallet1").unwrap().is_some());
+            assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 100.0);
+            assert!(is_permission_denied(&core.get_wallet("wallet2").unwrap_err()));
+            assert!(core.get_wallet_holdings("wallet2").is_err());
+            core.transfer_asset("wallet1", "wallet2", "asset1", 10.0).unwrap();
+            assert!(core.transfer_asset("wallet2", "wallet1", "asset1", 5.0).is_err());
+            assert!(core.perform_compliance_check("asset1").is_err());
+
+            // Existing wallets cannot be resubmitted, and new ones start empty
+            let mut wallet = core.get_wallet("wallet1").unwrap().unwrap();
+            wallet.balance = 1_000_000.0;
+            assert_eq!(core.create_wallet(wallet.clone()).unwrap_err(), "Wallet already exists");
+            wallet.id = "wallet3".to_string();
+            assert!(is_permission_denied(&core.create_wallet(wallet.clone()).unwrap_err()));
+            wallet.balance = 0.0;
+            wallet.assets.clear();
+            core.create_wallet(wallet).unwrap();
+        });
+
+        // Issuers manage and mint only their own assets
+        core.as_subject(&issuer, || {
+            let mut asset = core.get_asset("asset1").unwrap().unwrap();
+            asset.name = "Renamed".to_string();
+            asset.compliance_status = ComplianceStatus::Approved;
+            asset.owner = "issuer-2".to_string();
+            core.update_asset("asset1", asset.clone()).unwrap();
+            let stored = core.get_asset("asset1").unwrap().unwrap();
+            assert_eq!(
+                (stored.name.as_str(), stored.owner.as_str(), stored.compliance_status),
+                ("Renamed", "issuer-1", ComplianceStatus::Pending)
+            );
+            assert_eq!(core.create_asset(asset).unwrap_err(), "Asset already exists");
+            core.mint_to_wallet("treasury", "asset1", 5.0).unwrap();
+            assert!(core.mint_to_wallet("treasury", "asset2", 5.0).is_err());
+            let mut other = core.get_asset("asset2").unwrap().unwrap();
+            other.name = "Hijacked".to_string();
+            assert!(core.update_asset("asset2", other).is_err());
+        });
+
+        assert!(core.as_subject(&auditor, || core.mint_to_wallet("wallet1", "asset1", 1.0)).is_err());
+        assert!(core.as_subject(&auditor, || core.get_audit_log()).is_ok());
+        assert_eq!(
+            core.as_subject(&officer, || core.perform_compliance_check("asset2")),
+            Ok(ComplianceStatus::Approved)
+        );
+
+        // Outside `as_subject` the core is unrestricted, and entries are audited per subject
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 10.0);
+        let entries = core.get_audit_log().unwrap();
+        assert!(entries.iter().any(|e| e.actor == "officer-1"));
+        assert!(entries.iter().any(|e| e.actor == "investor-1"));
+    }
+}
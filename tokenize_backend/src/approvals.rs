@@ -0,0 +1,571 @@
+//! Four-eyes approval of sensitive operations
+//!
+//! An approval rule makes an operation kind (large transfers, mints, deletes and
+//! compliance overrides) require N approvals out of a set of M eligible approvers.
+//! A mint rule also covers offering closes and fund dealings, which mint in bulk;
+//! those are submitted and approved as a whole.
+//! While a rule is configured, principals acting through `as_subject` can no longer
+//! run the operation with a single call; they submit it as a `PendingAction`
+//! instead. Approvals must come from distinct principals other than the requester.
+//! Once enough have been collected the operation runs on behalf of the requester,
+//! and the action keeps its full approval history whatever the outcome. Actions
+//! that are not decided before their deadline expire.
+//!
+//! Platform-internal calls made outside `as_subject` are not gated, matching the
+//! authorization checks.
+
+use crate::access_control::{authorize_current, current_subject, without_subject, Permission};
+use crate::audit::{current_actor, AuditAction, EntityType};
+use crate::core_component::{ComplianceStatus, DeletePolicy, TokenizationCore};
+use crate::issuance::allocation_strategy;
+use serde::{Deserialize, Serialize};
+
+/// Default time an action stays open for approval
+pub const DEFAULT_APPROVAL_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// How often the server expires actions past their deadline
+pub const APPROVAL_EXPIRY_INTERVAL_SECS: u64 = 60;
+
+/// Kinds of operation an approval rule can cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OperationKind {
+    Transfer,
+    Mint,
+    DeleteAsset,
+    DeleteWallet,
+    ComplianceOverride,
+}
+
+/// An operation waiting for approval, with everything needed to run it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ApprovalOperation {
+    Transfer {
+        from_wallet_id: String,
+        to_wallet_id: String,
+        asset_id: String,
+        quantity: f64,
+    },
+    Mint {
+        wallet_id: String,
+        asset_id: String,
+        quantity: f64,
+    },
+    DeleteAsset {
+        asset_id: String,
+        policy: DeletePolicy,
+    },
+    DeleteWallet {
+        wallet_id: String,
+        policy: DeletePolicy,
+    },
+    ComplianceOverride {
+        asset_id: String,
+        status: ComplianceStatus,
+    },
+    /// Close an offering with a built-in allocation strategy, minting every fill
+    CloseOffering {
+        offering_id: String,
+        strategy: String,
+    },
+    /// Deal a fund's queued orders, minting units for subscriptions
+    RunDealing {
+        asset_id: String,
+    },
+}
+
+impl ApprovalOperation {
+    pub fn kind(&self) -> OperationKind {
+        match self {
+            ApprovalOperation::Transfer { .. } => OperationKind::Transfer,
+            ApprovalOperation::Mint { .. }
+            | ApprovalOperation::CloseOffering { .. }
+            | ApprovalOperation::RunDealing { .. } => OperationKind::Mint,
+            ApprovalOperation::DeleteAsset { .. } => OperationKind::DeleteAsset,
+            ApprovalOperation::DeleteWallet { .. } => OperationKind::DeleteWallet,
+            ApprovalOperation::ComplianceOverride { .. } => OperationKind::ComplianceOverride,
+        }
+    }
+
+    /// Token quantity moved or issued, for rules with a size threshold
+    ///
+    /// Offering closes and dealings only learn their quantities when they run, so
+    /// every rule for their kind applies to them whatever its threshold.
+    pub fn quantity(&self) -> Option<f64> {
+        match self {
+            ApprovalOperation::Transfer { quantity, .. } | ApprovalOperation::Mint { quantity, .. } => Some(*quantity),
+            _ => None,
+        }
+    }
+}
+
+/// N-of-M approval requirement for one kind of operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub operation: OperationKind,
+    /// Only operations of at least this quantity need approval; `None` gates all of them
+    pub min_quantity: Option<f64>,
+    /// Number of distinct approvals needed (N)
+    pub required_approvals: usize,
+    /// Principals allowed to approve (M); empty means anyone with the `ApproveActions` permission
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// Seconds an action stays open before it expires
+    pub expires_after_secs: u64,
+}
+
+impl ApprovalRule {
+    pub fn new(operation: OperationKind, required_approvals: usize, approvers: Vec<String>) -> Self {
+        Self {
+            operation,
+            min_quantity: None,
+            required_approvals,
+            approvers,
+            expires_after_secs: DEFAULT_APPROVAL_WINDOW_SECS,
+        }
+    }
+
+    /// Whether an operation of this kind and size needs approval under the rule
+    pub fn applies_to(&self, kind: OperationKind, quantity: Option<f64>) -> bool {
+        self.operation == kind
+            && match (self.min_quantity, quantity) {
+                (Some(min), Some(quantity)) => quantity >= min,
+                _ => true,
+            }
+    }
+}
+
+/// An approver's decision
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+/// One entry in an action's approval history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    pub approver: String,
+    pub decision: Decision,
+    pub decided_at: u64,
+    pub comment: Option<String>,
+}
+
+/// Where a pending action stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ActionStatus {
+    Pending,
+    /// Quorum was reached and the operation is running
+    Executing,
+    Executed,
+    /// Approved, but the operation itself failed
+    Failed { error: String },
+    Rejected,
+    Expired,
+}
+
+impl ActionStatus {
+    /// Status name without details, e.g. `Failed`
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActionStatus::Pending => "Pending",
+            ActionStatus::Executing => "Executing",
+            ActionStatus::Executed => "Executed",
+            ActionStatus::Failed { .. } => "Failed",
+            ActionStatus::Rejected => "Rejected",
+            ActionStatus::Expired => "Expired",
+        }
+    }
+}
+
+/// An operation awaiting, or having gone through, four-eyes approval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAction {
+    pub id: String,
+    pub operation: ApprovalOperation,
+    pub requested_by: String,
+    pub requested_at: u64,
+    pub expires_at: u64,
+    pub required_approvals: usize,
+    pub approvers: Vec<String>,
+    pub history: Vec<ApprovalRecord>,
+    pub status: ActionStatus,
+    pub closed_at: Option<u64>,
+}
+
+impl PendingAction {
+    /// Number of approvals collected so far
+    pub fn approval_count(&self) -> usize {
+        self.history.iter().filter(|r| r.decision == Decision::Approve).count()
+    }
+}
+
+/// Result of submitting an operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Submission {
+    /// No rule applied, so the operation ran immediately
+    Executed,
+    /// The operation is waiting for approval
+    Pending(Box<PendingAction>),
+}
+
+impl TokenizationCore {
+    /// Add or replace the approval rule for an operation kind
+    pub fn configure_approval_rule(&self, rule: ApprovalRule) -> Result<(), String> {
+        authorize_current(Permission::ConfigureApprovals, None)?;
+        if rule.required_approvals == 0 {
+            return Err("At least one approval must be required".to_string());
+        }
+        if !rule.approvers.is_empty() && rule.required_approvals > rule.approvers.len() {
+            return Err("More approvals required than there are approvers".to_string());
+        }
+        let mut rules = self.approval_rules.write().map_err(|_| "Failed to acquire write lock")?;
+        rules.insert(rule.operation, rule);
+        Ok(())
+    }
+
+    /// Stop requiring approval for an operation kind
+    pub fn remove_approval_rule(&self, operation: OperationKind) -> Result<ApprovalRule, String> {
+        authorize_current(Permission::ConfigureApprovals, None)?;
+        let mut rules = self.approval_rules.write().map_err(|_| "Failed to acquire write lock")?;
+        rules.remove(&operation).ok_or_else(|| "Approval rule not found".to_string())
+    }
+
+    /// All configured approval rules
+    pub fn get_approval_rules(&self) -> Result<Vec<ApprovalRule>, String> {
+        let rules = self.approval_rules.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut rules: Vec<ApprovalRule> = rules.values().cloned().collect();
+        rules.sort_by_key(|r| format!("{:?}", r.operation));
+        Ok(rules)
+    }
+
+    fn approval_rule_for(&self, kind: OperationKind, quantity: Option<f64>) -> Result<Option<ApprovalRule>, String> {
+        let rules = self.approval_rules.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(rules.get(&kind).filter(|rule| rule.applies_to(kind, quantity)).cloned())
+    }
+
+    /// Refuse a direct call by a subject when the operation needs approval
+    pub(crate) fn check_four_eyes(&self, kind: OperationKind, quantity: Option<f64>) -> Result<(), String> {
+        if current_subject().is_none() {
+            return Ok(());
+        }
+        match self.approval_rule_for(kind, quantity)? {
+            Some(rule) => Err(format!(
+                "{:?} requires {} approvals; submit it for approval",
+                kind, rule.required_approvals
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the current subject may request the operation at all
+    fn authorize_operation(&self, operation: &ApprovalOperation) -> Result<(), String> {
+        match operation {
+            ApprovalOperation::Transfer { from_wallet_id, .. } => self.authorize_wallet(Permission::Transfer, from_wallet_id),
+            ApprovalOperation::Mint { asset_id, .. } => self.authorize_asset(Permission::Mint, asset_id),
+            ApprovalOperation::DeleteAsset { asset_id, .. } => self.authorize_asset(Permission::DeleteAsset, asset_id),
+            ApprovalOperation::DeleteWallet { wallet_id, .. } => self.authorize_wallet(Permission::DeleteWallet, wallet_id),
+            ApprovalOperation::ComplianceOverride { asset_id, .. } => {
+                self.authorize_asset(Permission::ComplianceCheck, asset_id)
+            }
+            ApprovalOperation::CloseOffering { offering_id, strategy } => {
+                allocation_strategy(strategy).ok_or_else(|| format!("Unknown allocation strategy {}", strategy))?;
+                self.authorize_offering(offering_id)
+            }
+            ApprovalOperation::RunDealing { asset_id } => {
+                self.authorize_asset(Permission::Mint, asset_id)?;
+                self.authorize_asset(Permission::Burn, asset_id)
+            }
+        }
+    }
+
+    fn execute_operation(&self, operation: &ApprovalOperation) -> Result<(), String> {
+        match operation {
+            ApprovalOperation::Transfer {
+                from_wallet_id,
+                to_wallet_id,
+                asset_id,
+                quantity,
+            } => self.transfer_asset(from_wallet_id, to_wallet_id, asset_id, *quantity),
+            ApprovalOperation::Mint {
+                wallet_id,
+                asset_id,
+                quantity,
+            } => self.mint_to_wallet(wallet_id, asset_id, *quantity),
+            ApprovalOperation::DeleteAsset { asset_id, policy } => self.delete_asset_with_policy(asset_id, *policy),
+            ApprovalOperation::DeleteWallet { wallet_id, policy } => self.delete_wallet(wallet_id, *policy),
+            ApprovalOperation::ComplianceOverride { asset_id, status } => {
+                self.override_compliance_status(asset_id, status.clone())
+            }
+            ApprovalOperation::CloseOffering { offering_id, strategy } => {
+                let strategy = allocation_strategy(strategy).ok_or_else(|| format!("Unknown allocation strategy {}", strategy))?;
+                self.close_offering(offering_id, strategy.as_ref()).map(|_| ())
+            }
+            ApprovalOperation::RunDealing { asset_id } => self.run_dealing(asset_id).map(|_| ()),
+        }
+    }
+
+    /// Run an operation, or queue it for approval if a rule covers it
+    pub fn submit_operation(&self, operation: ApprovalOperation) -> Result<Submission, String> {
+        self.authorize_operation(&operation)?;
+        let Some(rule) = self.approval_rule_for(operation.kind(), operation.quantity())? else {
+            self.execute_operation(&operation)?;
+            return Ok(Submission::Executed);
+        };
+
+        let now = self.now();
+        let mut actions = self.pending_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        let action = PendingAction {
+            id: format!("action-{}", actions.len() + 1),
+            operation,
+            requested_by: current_actor(),
+            requested_at: now,
+            expires_at: now + rule.expires_after_secs,
+            required_approvals: rule.required_approvals,
+            approvers: rule.approvers,
+            history: Vec::new(),
+            status: ActionStatus::Pending,
+            closed_at: None,
+        };
+        actions.insert(action.id.clone(), action.clone());
+        self.record_audit(AuditAction::Create, EntityType::PendingAction, &action.id, None::<&PendingAction>, Some(&action))?;
+        Ok(Submission::Pending(Box::new(action)))
+    }
+
+    /// Approve a pending action as the current actor, running it once enough approvals are in
+    pub fn approve_action(&self, action_id: &str, comment: Option<String>) -> Result<PendingAction, String> {
+        self.decide_action(action_id, Decision::Approve, comment)
+    }
+
+    /// Reject a pending action as the current actor; it will not run
+    pub fn reject_action(&self, action_id: &str, comment: Option<String>) -> Result<PendingAction, String> {
+        self.decide_action(action_id, Decision::Reject, comment)
+    }
+
+    fn decide_action(&self, action_id: &str, decision: Decision, comment: Option<String>) -> Result<PendingAction, String> {
+        let approver = current_actor();
+        let now = self.now();
+        let (before, action) = {
+            let mut actions = self.pending_actions.write().map_err(|_| "Failed to acquire write lock")?;
+            let action = actions.get_mut(action_id).ok_or("Pending action not found")?;
+            if action.status != ActionStatus::Pending {
+                return Err(format!("Action is already {:?}", action.status));
+            }
+            if now > action.expires_at {
+                let before = action.clone();
+                action.status = ActionStatus::Expired;
+                action.closed_at = Some(now);
+                self.record_audit(AuditAction::Cancel, EntityType::PendingAction, action_id, Some(&before), Some(&*action))?;
+                return Err("Pending action has expired".to_string());
+            }
+            if action.approvers.is_empty() {
+                authorize_current(Permission::ApproveActions, None)?;
+            } else if !action.approvers.contains(&approver) {
+                return Err(format!("{} is not an approver for this action", approver));
+            }
+            if approver == action.requested_by {
+                return Err("Requester cannot approve their own action".to_string());
+            }
+            if action.history.iter().any(|r| r.approver == approver) {
+                return Err(format!("{} has already decided on this action", approver));
+            }
+
+            let before = action.clone();
+            action.history.push(ApprovalRecord {
+                approver: approver.clone(),
+                decision,
+                decided_at: now,
+                comment,
+            });
+            if decision == Decision::Reject {
+                action.status = ActionStatus::Rejected;
+                action.closed_at = Some(now);
+            } else if action.approval_count() >= action.required_approvals {
+                // Claim the action so that a further approval cannot run it again
+                action.status = ActionStatus::Executing;
+            }
+            (before, action.clone())
+        };
+        let audit_action = match decision {
+            Decision::Approve => AuditAction::Approve,
+            Decision::Reject => AuditAction::Reject,
+        };
+        self.record_audit(audit_action, EntityType::PendingAction, action_id, Some(&before), Some(&action))?;
+        if action.status != ActionStatus::Executing {
+            return Ok(action);
+        }
+
+        // Quorum reached: run on behalf of the requester, outside any approver's permissions
+        let outcome = without_subject(|| self.with_actor(&action.requested_by, || self.execute_operation(&action.operation)));
+        let mut actions = self.pending_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        let stored = actions.get_mut(action_id).ok_or("Pending action not found")?;
+        let before = stored.clone();
+        stored.status = match outcome {
+            Ok(()) => ActionStatus::Executed,
+            Err(error) => ActionStatus::Failed { error },
+        };
+        stored.closed_at = Some(now);
+        let action = stored.clone();
+        drop(actions);
+        self.record_audit(AuditAction::Process, EntityType::PendingAction, action_id, Some(&before), Some(&action))?;
+        Ok(action)
+    }
+
+    /// Expire every pending action past its deadline, returning their IDs
+    pub fn expire_pending_actions(&self) -> Result<Vec<String>, String> {
+        let now = self.now();
+        let mut expired = Vec::new();
+        let mut actions = self.pending_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        for action in actions.values_mut() {
+            if action.status == ActionStatus::Pending && now > action.expires_at {
+                let before = action.clone();
+                action.status = ActionStatus::Expired;
+                action.closed_at = Some(now);
+                self.record_audit(AuditAction::Cancel, EntityType::PendingAction, &action.id, Some(&before), Some(&*action))?;
+                expired.push(action.id.clone());
+            }
+        }
+        expired.sort();
+        Ok(expired)
+    }
+
+    /// Get a pending action with its approval history
+    pub fn get_pending_action(&self, action_id: &str) -> Result<Option<PendingAction>, String> {
+        let actions = self.pending_actions.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(actions.get(action_id).cloned())
+    }
+
+    /// Pending actions, optionally only those whose status has the given label, oldest first
+    pub fn get_pending_actions(&self, status: Option<&str>) -> Result<Vec<PendingAction>, String> {
+        let actions = self.pending_actions.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut found: Vec<PendingAction> = actions
+            .values()
+            .filter(|a| status.is_none_or(|s| a.status.label() == s))
+            .cloned()
+            .collect();
+        // IDs are numbered in submission order
+        found.sort_by_key(|a| a.id.trim_start_matches("action-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(asset("asset1", "issuer", AssetType::Equity)).unwrap();
+        for (id, owner) in [("wallet1", "alice"), ("wallet2", "bob")] {
+            core.create_wallet(wallet(id, owner, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 1_000.0).unwrap();
+        core
+    }
+
+    fn large_transfer(quantity: f64) -> ApprovalOperation {
+        ApprovalOperation::Transfer {
+            from_wallet_id: "wallet1".to_string(),
+            to_wallet_id: "wallet2".to_string(),
+            asset_id: "asset1".to_string(),
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_large_transfers_need_two_distinct_approvers() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(&clock);
+        let mut rule = ApprovalRule::new(
+            OperationKind::Transfer,
+            2,
+            vec!["ops-1".to_string(), "ops-2".to_string(), "ops-3".to_string()],
+        );
+        rule.min_quantity = Some(500.0);
+        core.configure_approval_rule(rule).unwrap();
+
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let ops = |id: &str| Subject::new(id, &[Role::TransferAgent]);
+
+        // Small transfers run directly; large ones cannot bypass the queue
+        assert_eq!(core.as_subject(&alice, || core.submit_operation(large_transfer(100.0))), Ok(Submission::Executed));
+        assert!(core.as_subject(&alice, || core.transfer_asset("wallet1", "wallet2", "asset1", 600.0)).is_err());
+        let Submission::Pending(action) = core.as_subject(&alice, || core.submit_operation(large_transfer(600.0))).unwrap() else {
+            panic!("expected a pending action");
+        };
+        assert_eq!(action.requested_by, "alice");
+
+        assert!(core.as_subject(&alice, || core.approve_action(&action.id, None)).is_err());
+        assert!(core.as_subject(&ops("mallory"), || core.approve_action(&action.id, None)).is_err());
+        let after_first = core.as_subject(&ops("ops-1"), || core.approve_action(&action.id, Some("ok".to_string()))).unwrap();
+        assert_eq!(after_first.status, ActionStatus::Pending);
+        assert!(core.as_subject(&ops("ops-1"), || core.approve_action(&action.id, None)).is_err());
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 100.0);
+
+        let executed = core.as_subject(&ops("ops-2"), || core.approve_action(&action.id, None)).unwrap();
+        assert_eq!(executed.status, ActionStatus::Executed);
+        assert_eq!(executed.history.len(), 2);
+        assert_eq!(executed.history[0].comment.as_deref(), Some("ok"));
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 700.0);
+
+        // A third approver arriving after quorum cannot run the transfer again
+        assert!(core.as_subject(&ops("ops-3"), || core.approve_action(&action.id, None)).is_err());
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 700.0);
+
+        // The transfer is audited as the requester's
+        let transfer = core.get_audit_log().unwrap().into_iter().rev().find(|e| e.action == AuditAction::Transfer).unwrap();
+        assert_eq!(transfer.actor, "alice");
+    }
+
+    #[test]
+    fn test_rejection_and_expiry_close_actions() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(&clock);
+        let mut rule = ApprovalRule::new(OperationKind::DeleteAsset, 1, vec![]);
+        rule.expires_after_secs = 3_600;
+        core.configure_approval_rule(rule).unwrap();
+        core.configure_approval_rule(ApprovalRule::new(OperationKind::ComplianceOverride, 1, vec![])).unwrap();
+
+        let issuer = Subject::new("issuer", &[Role::Issuer]);
+        let officer = Subject::new("officer", &[Role::ComplianceOfficer]);
+        let delete = ApprovalOperation::DeleteAsset {
+            asset_id: "asset1".to_string(),
+            policy: DeletePolicy::SoftDelete,
+        };
+        let Submission::Pending(first) = core.as_subject(&issuer, || core.submit_operation(delete.clone())).unwrap() else {
+            panic!("expected a pending action");
+        };
+        let rejected = core.as_subject(&officer, || core.reject_action(&first.id, Some("still held".to_string()))).unwrap();
+        assert_eq!(rejected.status, ActionStatus::Rejected);
+        assert!(core.get_asset("asset1").unwrap().is_some());
+
+        let Submission::Pending(second) = core.as_subject(&issuer, || core.submit_operation(delete)).unwrap() else {
+            panic!("expected a pending action");
+        };
+        clock.advance(3_601);
+        assert_eq!(core.expire_pending_actions().unwrap(), vec![second.id.clone()]);
+        assert!(core.as_subject(&officer, || core.approve_action(&second.id, None)).is_err());
+        assert_eq!(core.get_pending_actions(Some("Expired")).unwrap().len(), 1);
+
+        // Compliance approval by a single officer is blocked while the rule exists
+        assert!(core.as_subject(&officer, || core.perform_compliance_check("asset1")).is_err());
+        let override_status = ApprovalOperation::ComplianceOverride {
+            asset_id: "asset1".to_string(),
+            status: ComplianceStatus::Rejected,
+        };
+        let Submission::Pending(third) = core.as_subject(&officer, || core.submit_operation(override_status)).unwrap() else {
+            panic!("expected a pending action");
+        };
+        let admin = Subject::new("admin", &[Role::Admin]);
+        assert_eq!(
+            core.as_subject(&admin, || core.approve_action(&third.id, None)).unwrap().status,
+            ActionStatus::Executed
+        );
+        assert_eq!(core.get_asset("asset1").unwrap().unwrap().compliance_status, ComplianceStatus::Rejected);
+    }
+}
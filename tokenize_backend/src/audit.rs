@@ -0,0 +1,444 @@
+//! Append-only, hash-chained audit trail
+//!
+//! Every mutation of `TokenizationCore` appends an entry recording the acting
+//! principal, the affected entity and a field-level before/after diff. Each entry
+//! stores the SHA-256 hash of its own contents chained to the previous entry's
+//! hash, so editing, removing or reordering any entry breaks verification.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::core_component::TokenizationCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+/// Hash used as the predecessor of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Actor recorded when no principal has been set
+pub const SYSTEM_ACTOR: &str = "system";
+
+thread_local! {
+    static CURRENT_ACTOR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Principal on whose behalf the current thread is mutating the core
+pub fn current_actor() -> String {
+    CURRENT_ACTOR.with(|actor| actor.borrow().clone()).unwrap_or_else(|| SYSTEM_ACTOR.to_string())
+}
+
+struct ActorGuard(Option<String>);
+
+impl Drop for ActorGuard {
+    fn drop(&mut self) {
+        CURRENT_ACTOR.with(|actor| *actor.borrow_mut() = self.0.take());
+    }
+}
+
+/// What kind of change an entry records
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Mint,
+    Burn,
+    Transfer,
+    ComplianceDecision,
+    Process,
+    Cancel,
+    Approve,
+    Reject,
+    Freeze,
+    Unfreeze,
+    Rotate,
+    Retire,
+    Sign,
+}
+
+/// Kind of entity an entry refers to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntityType {
+    Asset,
+    Wallet,
+    HoldingRestriction,
+    CorporateAction,
+    Bond,
+    Fund,
+    FundOrder,
+    Offering,
+    DilutiveInstrument,
+    PendingAction,
+    SpendingLimits,
+    Freeze,
+    Custody,
+    SigningRequest,
+    WalletKey,
+    ManagedKey,
+    SignerPolicy,
+    Proposal,
+    Guardians,
+    Recovery,
+    ReserveReport,
+    Reconciliation,
+}
+
+/// A single field's value before and after a change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// One link of the audit chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub timestamp: u64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub changes: Vec<FieldChange>,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash of the entry's contents chained to its predecessor
+    pub fn compute_hash(&self) -> String {
+        #[derive(Serialize)]
+        struct Contents<'a> {
+            sequence: u64,
+            timestamp: u64,
+            actor: &'a str,
+            action: &'a AuditAction,
+            entity_type: &'a EntityType,
+            entity_id: &'a str,
+            changes: &'a [FieldChange],
+        }
+        let contents = Contents {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            actor: &self.actor,
+            action: &self.action,
+            entity_type: &self.entity_type,
+            entity_id: &self.entity_id,
+            changes: &self.changes,
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(self.previous_hash.as_bytes());
+        hasher.update(serde_json::to_vec(&contents).unwrap_or_default());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// First point at which an audit chain fails verification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditViolation {
+    pub sequence: u64,
+    pub reason: String,
+}
+
+/// Verify that every entry is intact and correctly chained to its predecessor
+pub fn verify_audit_chain(entries: &[AuditEntry]) -> Result<(), AuditViolation> {
+    let mut previous_hash = GENESIS_HASH.to_string();
+    for (index, entry) in entries.iter().enumerate() {
+        let violation = |reason: &str| AuditViolation {
+            sequence: entry.sequence,
+            reason: reason.to_string(),
+        };
+        if entry.sequence != index as u64 {
+            return Err(violation("Sequence gap or reordering"));
+        }
+        if entry.previous_hash != previous_hash {
+            return Err(violation("Previous hash does not match the chain"));
+        }
+        if entry.compute_hash() != entry.hash {
+            return Err(violation("Entry contents do not match its hash"));
+        }
+        previous_hash = entry.hash.clone();
+    }
+    Ok(())
+}
+
+/// Field-level diff between two serialized states
+///
+/// Objects are compared key by key; anything else is reported as a single change
+/// with an empty field name.
+pub fn diff_values(before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    match (before, after) {
+        (Some(Value::Object(_)), _) | (_, Some(Value::Object(_))) => {
+            let empty = serde_json::Map::new();
+            let before_fields = before.and_then(Value::as_object).unwrap_or(&empty);
+            let after_fields = after.and_then(Value::as_object).unwrap_or(&empty);
+            let keys: BTreeSet<&String> = before_fields.keys().chain(after_fields.keys()).collect();
+            keys.into_iter()
+                .filter(|key| before_fields.get(*key) != after_fields.get(*key))
+                .map(|key| FieldChange {
+                    field: key.clone(),
+                    before: before_fields.get(key).cloned(),
+                    after: after_fields.get(key).cloned(),
+                })
+                .collect()
+        }
+        _ if before == after => Vec::new(),
+        _ => vec![FieldChange {
+            field: String::new(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }],
+    }
+}
+
+/// The append-only log itself
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    pub(crate) entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Append a new entry, chaining it to the current head
+    pub fn append(
+        &mut self,
+        timestamp: u64,
+        actor: String,
+        action: AuditAction,
+        entity_type: EntityType,
+        entity_id: String,
+        changes: Vec<FieldChange>,
+    ) -> &AuditEntry {
+        let previous_hash = self
+            .entries
+            .last()
+            .map(|e| e.hash.clone())
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+        let mut entry = AuditEntry {
+            sequence: self.entries.len() as u64,
+            timestamp,
+            actor,
+            action,
+            entity_type,
+            entity_id,
+            changes,
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        self.entries.push(entry);
+        &self.entries[self.entries.len() - 1]
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Hash of the newest entry, committing to the whole chain
+    pub fn head_hash(&self) -> String {
+        self.entries
+            .last()
+            .map(|e| e.hash.clone())
+            .unwrap_or_else(|| GENESIS_HASH.to_string())
+    }
+}
+
+/// Filter for audit queries; unset fields match everything
+///
+/// A wallet ID also matches mint, burn and transfer entries whose holding changes
+/// touch that wallet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub entity_type: Option<EntityType>,
+    pub entity_id: Option<String>,
+    pub actor: Option<String>,
+    /// Inclusive lower bound on the timestamp
+    pub from: Option<u64>,
+    /// Inclusive upper bound on the timestamp
+    pub to: Option<u64>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let involves_wallet = |id: &String| {
+            self.entity_type.as_ref().is_none_or(|t| *t == EntityType::Wallet)
+                && entry.changes.iter().any(|c| c.field == format!("holdings.{}", id))
+        };
+        let entity_matches = match (&self.entity_type, &self.entity_id) {
+            (_, Some(id)) if involves_wallet(id) => true,
+            (entity_type, entity_id) => {
+                entity_type.as_ref().is_none_or(|t| *t == entry.entity_type)
+                    && entity_id.as_ref().is_none_or(|id| *id == entry.entity_id)
+            }
+        };
+        entity_matches
+            && self.actor.as_ref().is_none_or(|a| *a == entry.actor)
+            && self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.to.is_none_or(|to| entry.timestamp <= to)
+    }
+}
+
+impl TokenizationCore {
+    /// Run `f` with `actor` recorded as the principal of every mutation it makes
+    pub fn with_actor<T>(&self, actor: &str, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT_ACTOR.with(|current| current.borrow_mut().replace(actor.to_string()));
+        let _guard = ActorGuard(previous);
+        f()
+    }
+
+    /// Append an audit entry for a change to an entity
+    pub(crate) fn record_audit<B: Serialize, A: Serialize>(
+        &self,
+        action: AuditAction,
+        entity_type: EntityType,
+        entity_id: &str,
+        before: Option<&B>,
+        after: Option<&A>,
+    ) -> Result<(), String> {
+        let before = before.and_then(|b| serde_json::to_value(b).ok());
+        let after = after.and_then(|a| serde_json::to_value(a).ok());
+        let changes = diff_values(before.as_ref(), after.as_ref());
+        self.record_audit_changes(action, entity_type, entity_id, changes)
+    }
+
+    /// Append an audit entry with explicit field changes
+    pub(crate) fn record_audit_changes(
+        &self,
+        action: AuditAction,
+        entity_type: EntityType,
+        entity_id: &str,
+        changes: Vec<FieldChange>,
+    ) -> Result<(), String> {
+        let now = self.now();
+        let mut log = self.audit_log.write().map_err(|_| "Failed to acquire write lock")?;
+        log.append(now, current_actor(), action, entity_type, entity_id.to_string(), changes);
+        Ok(())
+    }
+
+    /// Get every audit entry, oldest first
+    pub fn get_audit_log(&self) -> Result<Vec<AuditEntry>, String> {
+        authorize_current(Permission::ReadAudit, None)?;
+        let log = self.audit_log.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(log.entries().to_vec())
+    }
+
+    /// Get audit entries matching a query, oldest first
+    pub fn query_audit_log(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, String> {
+        authorize_current(Permission::ReadAudit, None)?;
+        let log = self.audit_log.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(log.entries().iter().filter(|e| query.matches(e)).cloned().collect())
+    }
+
+    /// Get the change history of one entity
+    pub fn get_entity_history(&self, entity_type: EntityType, entity_id: &str) -> Result<Vec<AuditEntry>, String> {
+        self.query_audit_log(&AuditQuery {
+            entity_type: Some(entity_type),
+            entity_id: Some(entity_id.to_string()),
+            ..AuditQuery::default()
+        })
+    }
+
+    /// Verify the integrity of the whole audit chain
+    pub fn verify_audit_log(&self) -> Result<Result<(), AuditViolation>, String> {
+        authorize_current(Permission::ReadAudit, None)?;
+        let log = self.audit_log.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(verify_audit_chain(log.entries()))
+    }
+}
+
+/// Field change for a single quantity, e.g. one wallet's holding
+pub(crate) fn quantity_change(field: String, before: f64, after: f64) -> FieldChange {
+    FieldChange {
+        field,
+        before: Some(Value::from(before)),
+        after: Some(Value::from(after)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.with_actor("issuer-admin", || {
+            core.create_asset(TokenizedAsset {
+                value: 100.0,
+                ..asset("asset1", "issuer", AssetType::Equity)
+            })
+            .unwrap();
+        });
+        for id in ["wallet1", "wallet2"] {
+            core.create_wallet(wallet(id, id, WalletType::Custodial)).unwrap();
+        }
+        core
+    }
+
+    #[test]
+    fn test_mutations_are_recorded_with_actor_and_diff() {
+        let clock = ManualClock::new(100);
+        let core = setup(&clock);
+
+        clock.set(200);
+        core.with_actor("compliance-officer", || core.perform_compliance_check("asset1")).unwrap();
+        core.mint_to_wallet("wallet1", "asset1", 10.0).unwrap();
+        core.transfer_asset("wallet1", "wallet2", "asset1", 4.0).unwrap();
+
+        let history = core.get_entity_history(EntityType::Asset, "asset1").unwrap();
+        let actions: Vec<AuditAction> = history.iter().map(|e| e.action.clone()).collect();
+        assert_eq!(
+            actions,
+            vec![AuditAction::Create, AuditAction::ComplianceDecision, AuditAction::Mint, AuditAction::Transfer]
+        );
+        assert_eq!(history[0].actor, "issuer-admin");
+        assert_eq!(history[1].actor, "compliance-officer");
+        assert_eq!(history[2].actor, SYSTEM_ACTOR);
+        assert!(history[1]
+            .changes
+            .iter()
+            .any(|c| c.field == "compliance_status" && c.after == Some(Value::from("Approved"))));
+        assert!(history[3].changes.iter().any(|c| c.field == "holdings.wallet2" && c.after == Some(Value::from(4.0))));
+
+        let wallet_history = core.get_entity_history(EntityType::Wallet, "wallet2").unwrap();
+        let actions: Vec<AuditAction> = wallet_history.iter().map(|e| e.action.clone()).collect();
+        assert_eq!(actions, vec![AuditAction::Create, AuditAction::Transfer]);
+
+        let in_range = core
+            .query_audit_log(&AuditQuery {
+                from: Some(150),
+                to: Some(250),
+                ..AuditQuery::default()
+            })
+            .unwrap();
+        assert_eq!(in_range.len(), 3);
+    }
+
+    #[test]
+    fn test_verifier_detects_tampering() {
+        let clock = ManualClock::new(100);
+        let core = setup(&clock);
+        core.mint_to_wallet("wallet1", "asset1", 10.0).unwrap();
+        assert!(core.verify_audit_log().unwrap().is_ok());
+
+        core.audit_log.write().unwrap().entries[1].actor = "someone-else".to_string();
+        let violation = core.verify_audit_log().unwrap().unwrap_err();
+        assert_eq!(violation.sequence, 1);
+
+        // Recomputing the tampered entry's hash still breaks the link to its successor
+        {
+            let mut log = core.audit_log.write().unwrap();
+            let hash = log.entries[1].compute_hash();
+            log.entries[1].hash = hash;
+        }
+        assert_eq!(core.verify_audit_log().unwrap().unwrap_err().sequence, 2);
+
+        core.audit_log.write().unwrap().entries.remove(0);
+        assert_eq!(core.verify_audit_log().unwrap().unwrap_err().sequence, 1);
+    }
+}
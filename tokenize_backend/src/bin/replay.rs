@@ -0,0 +1,52 @@
+//! Rebuild the core ledgers from an event store directory
+//!
+//! Usage: `replay <event-store-dir> [--at <timestamp>] [--snapshot]`
+//!
+//! Replays the whole stream from the first event, ignoring existing snapshots, and
+//! prints the resulting ledgers as JSON. With `--at` only events up to that
+//! timestamp are applied. With `--snapshot` the rebuilt state of the full stream is
+//! appended to the store as a new snapshot.
+
+use tokenize_backend::events::{replay_events, EventStore, FileEventStore, LedgerState};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let dir = args
+        .next()
+        .ok_or("Usage: replay <event-store-dir> [--at <timestamp>] [--snapshot]")?;
+    let mut at = None;
+    let mut snapshot = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--at" => at = Some(args.next().ok_or("--at needs a timestamp")?.parse::<u64>()?),
+            "--snapshot" => snapshot = true,
+            other => return Err(format!("Unknown argument: {}", other).into()),
+        }
+    }
+
+    let store = FileEventStore::open(&dir)?;
+    let state = match at {
+        Some(at) => {
+            let mut state = LedgerState::default();
+            for event in store.events_after(0)?.iter().take_while(|e| e.timestamp <= at) {
+                state.apply(event);
+            }
+            state
+        }
+        None => replay_events(&store)?,
+    };
+    eprintln!(
+        "Replayed {} events: {} assets, {} wallets",
+        state.sequence,
+        state.assets.len(),
+        state.wallets.len()
+    );
+    println!("{}", serde_json::to_string_pretty(&state)?);
+
+    if snapshot {
+        let full = if at.is_some() { replay_events(&store)? } else { state };
+        store.save_snapshot(&full)?;
+        eprintln!("Saved snapshot at sequence {}", full.sequence);
+    }
+    Ok(())
+}
@@ -0,0 +1,481 @@
+//! Debt instruments: coupon schedules, interest accrual and redemption
+//!
+//! A bond is registered against an `AssetType::Debt` asset. Units of the asset each
+//! represent `face_value` of principal. Coupon periods are generated backwards from
+//! maturity (any stub goes at the front) with payment dates rolled by the bond's
+//! business-day calendar, and coupons are paid through the corporate actions engine.
+
+use crate::access_control::Permission;
+use crate::audit::{AuditAction, EntityType};
+use crate::calendar::{BusinessCalendar, BusinessDayConvention, Date};
+use crate::core_component::{AssetType, TokenizationCore, QUANTITY_EPSILON};
+use crate::corporate_actions::{CorporateAction, CorporateActionKind};
+use crate::events::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+/// Number of coupon payments per year
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CouponFrequency {
+    Annual,
+    SemiAnnual,
+    Quarterly,
+    Monthly,
+}
+
+impl CouponFrequency {
+    pub fn months_per_period(self) -> i32 {
+        match self {
+            CouponFrequency::Annual => 12,
+            CouponFrequency::SemiAnnual => 6,
+            CouponFrequency::Quarterly => 3,
+            CouponFrequency::Monthly => 1,
+        }
+    }
+}
+
+/// Day-count convention used to turn a date range into a year fraction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DayCountConvention {
+    /// 30/360 bond basis
+    Thirty360,
+    Act360,
+    /// Actual/365 fixed
+    Act365,
+    /// Actual/Actual ISDA
+    ActAct,
+}
+
+impl DayCountConvention {
+    /// Year fraction between two dates
+    pub fn year_fraction(self, start: Date, end: Date) -> f64 {
+        if end <= start {
+            return 0.0;
+        }
+        match self {
+            DayCountConvention::Thirty360 => {
+                let d1 = start.day.min(30) as i64;
+                let d2 = if end.day == 31 && d1 == 30 { 30 } else { end.day as i64 };
+                let days = 360 * (end.year - start.year) as i64
+                    + 30 * (end.month as i64 - start.month as i64)
+                    + (d2 - d1);
+                days as f64 / 360.0
+            }
+            DayCountConvention::Act360 => start.days_until(end) as f64 / 360.0,
+            DayCountConvention::Act365 => start.days_until(end) as f64 / 365.0,
+            DayCountConvention::ActAct => {
+                let mut fraction = 0.0;
+                let mut cursor = start;
+                while cursor < end {
+                    let next_year = Date {
+                        year: cursor.year + 1,
+                        month: 1,
+                        day: 1,
+                    };
+                    let segment_end = if next_year < end { next_year } else { end };
+                    let year_days = if crate::calendar::is_leap_year(cursor.year) { 366.0 } else { 365.0 };
+                    fraction += cursor.days_until(segment_end) as f64 / year_days;
+                    cursor = segment_end;
+                }
+                fraction
+            }
+        }
+    }
+}
+
+/// Contractual terms of a bond
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BondTerms {
+    pub asset_id: String,
+    /// Principal represented by one unit of the asset
+    pub face_value: f64,
+    /// Annual coupon rate, e.g. 0.05 for 5%
+    pub coupon_rate: f64,
+    pub frequency: CouponFrequency,
+    pub day_count: DayCountConvention,
+    pub issue_date: Date,
+    pub maturity_date: Date,
+    pub business_day_convention: BusinessDayConvention,
+    pub calendar: BusinessCalendar,
+}
+
+impl BondTerms {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.face_value <= 0.0 {
+            return Err("Face value must be positive".to_string());
+        }
+        if self.coupon_rate < 0.0 {
+            return Err("Coupon rate must not be negative".to_string());
+        }
+        if self.maturity_date <= self.issue_date {
+            return Err("Maturity must be after issue date".to_string());
+        }
+        // Every payment date must fall on a reachable business day
+        self.coupon_schedule()?;
+        self.redemption_date()?;
+        Ok(())
+    }
+
+    /// Generate the coupon schedule from issue to maturity
+    pub fn coupon_schedule(&self) -> Result<Vec<CouponPeriod>, String> {
+        let months = self.frequency.months_per_period();
+        let mut period_ends = Vec::new();
+        let mut k = 0;
+        loop {
+            let end = self.maturity_date.add_months(-months * k);
+            if end <= self.issue_date {
+                break;
+            }
+            period_ends.push(end);
+            k += 1;
+        }
+        period_ends.reverse();
+
+        let mut start = self.issue_date;
+        period_ends
+            .into_iter()
+            .enumerate()
+            .map(|(index, end)| {
+                let year_fraction = self.day_count.year_fraction(start, end);
+                let period = CouponPeriod {
+                    index: index + 1,
+                    accrual_start: start,
+                    accrual_end: end,
+                    payment_date: self.calendar.adjust(end, self.business_day_convention)?,
+                    year_fraction,
+                    amount_per_unit: self.face_value * self.coupon_rate * year_fraction,
+                };
+                start = end;
+                Ok(period)
+            })
+            .collect()
+    }
+
+    /// Interest accrued per unit since the last coupon date
+    pub fn accrued_interest(&self, on: Date) -> Result<f64, String> {
+        if on <= self.issue_date || on >= self.maturity_date {
+            return Ok(0.0);
+        }
+        Ok(self
+            .coupon_schedule()?
+            .iter()
+            .find(|p| p.accrual_start <= on && on < p.accrual_end)
+            .map(|p| self.face_value * self.coupon_rate * self.day_count.year_fraction(p.accrual_start, on))
+            .unwrap_or(0.0))
+    }
+
+    /// Business day on which principal is repaid
+    pub fn redemption_date(&self) -> Result<Date, String> {
+        self.calendar.adjust(self.maturity_date, self.business_day_convention)
+    }
+}
+
+/// A single coupon accrual period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CouponPeriod {
+    pub index: usize,
+    pub accrual_start: Date,
+    pub accrual_end: Date,
+    pub payment_date: Date,
+    pub year_fraction: f64,
+    pub amount_per_unit: f64,
+}
+
+/// Lifecycle state of a bond
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BondStatus {
+    Outstanding,
+    Redeemed,
+}
+
+/// Principal repaid to one holder at maturity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedemptionPayout {
+    pub wallet_id: String,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+/// Record of a bond's redemption
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedemptionRecord {
+    pub redeemed_at: u64,
+    pub payouts: Vec<RedemptionPayout>,
+    pub total_amount: f64,
+}
+
+/// Registered bond with its lifecycle state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BondRecord {
+    pub terms: BondTerms,
+    pub status: BondStatus,
+    pub redemption: Option<RedemptionRecord>,
+}
+
+impl TokenizationCore {
+    /// Register bond terms for an existing debt asset
+    pub fn register_bond(&self, terms: BondTerms) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, &terms.asset_id)?;
+        terms.validate()?;
+        self.ensure_ids_not_frozen(&[], &[&terms.asset_id])?;
+        let asset = self.get_asset(&terms.asset_id)?.ok_or("Asset not found")?;
+        if asset.asset_type != AssetType::Debt {
+            return Err("Bond terms can only be attached to debt assets".to_string());
+        }
+        let mut bonds = self.bonds.write().map_err(|_| "Failed to acquire write lock")?;
+        if bonds.contains_key(&terms.asset_id) {
+            return Err("Bond already registered".to_string());
+        }
+        let record = BondRecord {
+            terms,
+            status: BondStatus::Outstanding,
+            redemption: None,
+        };
+        self.record_audit(AuditAction::Create, EntityType::Bond, &record.terms.asset_id, None::<&BondRecord>, Some(&record))?;
+        bonds.insert(record.terms.asset_id.clone(), record);
+        Ok(())
+    }
+
+    /// Get a registered bond by asset ID
+    pub fn get_bond(&self, asset_id: &str) -> Result<Option<BondRecord>, String> {
+        let bonds = self.bonds.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(bonds.get(asset_id).cloned())
+    }
+
+    /// Generate the coupon schedule of a registered bond
+    pub fn get_coupon_schedule(&self, asset_id: &str) -> Result<Vec<CouponPeriod>, String> {
+        let bond = self.get_bond(asset_id)?.ok_or("Bond not found")?;
+        bond.terms.coupon_schedule()
+    }
+
+    /// Interest accrued per unit of a bond on a date
+    pub fn get_accrued_interest(&self, asset_id: &str, on: Date) -> Result<f64, String> {
+        let bond = self.get_bond(asset_id)?.ok_or("Bond not found")?;
+        if bond.status == BondStatus::Redeemed {
+            return Ok(0.0);
+        }
+        bond.terms.accrued_interest(on)
+    }
+
+    /// Interest accrued on a wallet's whole holding of a bond on a date
+    pub fn get_accrued_interest_for_wallet(&self, wallet_id: &str, asset_id: &str, on: Date) -> Result<f64, String> {
+        let per_unit = self.get_accrued_interest(asset_id, on)?;
+        Ok(per_unit * self.get_holding(wallet_id, asset_id)?)
+    }
+
+    /// Announce a coupon corporate action for every period of the bond's schedule
+    ///
+    /// Holders are recorded at the end of each accrual period and paid on the
+    /// adjusted payment date. Coupons that were already announced are skipped.
+    pub fn announce_coupon_payments(&self, asset_id: &str) -> Result<Vec<String>, String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        let schedule = self.get_coupon_schedule(asset_id)?;
+        let mut announced = Vec::new();
+        for period in schedule {
+            if period.amount_per_unit <= 0.0 {
+                continue;
+            }
+            let action_id = format!("{}-coupon-{}", asset_id, period.index);
+            if self.get_corporate_action(&action_id)?.is_some() {
+                continue;
+            }
+            self.announce_corporate_action(CorporateAction {
+                id: action_id.clone(),
+                asset_id: asset_id.to_string(),
+                kind: CorporateActionKind::Coupon {
+                    amount_per_unit: period.amount_per_unit,
+                },
+                record_date: period.accrual_end.to_timestamp(),
+                payment_date: period.payment_date.to_timestamp(),
+            })?;
+            announced.push(action_id);
+        }
+        Ok(announced)
+    }
+
+    /// Repay principal to every holder and retire the bond's units
+    ///
+    /// Only allowed on or after the adjusted maturity date. Redeeming an already
+    /// redeemed bond returns the original redemption record. Units held by deleted
+    /// or archived wallets are not paid and remain in issue.
+    pub fn redeem_bond(&self, asset_id: &str) -> Result<RedemptionRecord, String> {
+        self.authorize_asset(Permission::Burn, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut bonds = self.bonds.write().map_err(|_| "Failed to acquire write lock")?;
+        let bond = bonds.get_mut(asset_id).ok_or("Bond not found")?;
+
+        if let Some(redemption) = &bond.redemption {
+            return Ok(redemption.clone());
+        }
+        let redemption_date = bond.terms.redemption_date()?;
+        if now < redemption_date.to_timestamp() {
+            return Err(format!("Bond cannot be redeemed before {}", redemption_date));
+        }
+
+        let before = bond.clone();
+        // Holdings of deleted or archived wallets stay outstanding until they can be paid
+        let mut payouts = Vec::new();
+        for (wallet_id, positions) in holdings.iter().filter(|(wallet_id, _)| wallets.contains_key(*wallet_id)) {
+            if let Some(quantity) = positions.get(asset_id).filter(|q| **q > QUANTITY_EPSILON) {
+                payouts.push(RedemptionPayout {
+                    wallet_id: wallet_id.clone(),
+                    quantity: *quantity,
+                    amount: quantity * bond.terms.face_value,
+                });
+            }
+        }
+        payouts.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+
+        for payout in &payouts {
+            if let Some(wallet) = wallets.get_mut(&payout.wallet_id) {
+                wallet.balance += payout.amount;
+                Self::debit_holding(&mut holdings, wallet, asset_id, payout.quantity);
+                wallet.updated_at = now;
+                self.emit_event(DomainEvent::WalletBalanceChanged {
+                    wallet_id: payout.wallet_id.clone(),
+                    amount: payout.amount,
+                })?;
+                self.emit_event(DomainEvent::AssetBurned {
+                    wallet_id: payout.wallet_id.clone(),
+                    asset_id: asset_id.to_string(),
+                    quantity: payout.quantity,
+                })?;
+            }
+        }
+        let redeemed: f64 = payouts.iter().map(|p| p.quantity).sum();
+        if let Some(issued) = supply.get_mut(asset_id) {
+            *issued = (*issued - redeemed).max(0.0);
+        }
+
+        let record = RedemptionRecord {
+            redeemed_at: now,
+            total_amount: payouts.iter().map(|p| p.amount).sum(),
+            payouts,
+        };
+        bond.status = BondStatus::Redeemed;
+        bond.redemption = Some(record.clone());
+        self.record_audit(AuditAction::Process, EntityType::Bond, asset_id, Some(&before), Some(&*bond))?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{ComplianceStatus, DeletePolicy, DigitalWallet, TokenizedAsset, WalletType};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    fn terms(day_count: DayCountConvention) -> BondTerms {
+        BondTerms {
+            asset_id: "bond1".to_string(),
+            face_value: 1_000.0,
+            coupon_rate: 0.06,
+            frequency: CouponFrequency::SemiAnnual,
+            day_count,
+            issue_date: date(2024, 1, 15),
+            maturity_date: date(2026, 1, 15),
+            business_day_convention: BusinessDayConvention::Following,
+            calendar: BusinessCalendar::default(),
+        }
+    }
+
+    #[test]
+    fn test_day_count_conventions() {
+        let start = date(2024, 1, 31);
+        let end = date(2024, 7, 31);
+        assert_eq!(DayCountConvention::Thirty360.year_fraction(start, end), 0.5);
+        assert_eq!(DayCountConvention::Act360.year_fraction(start, end), 182.0 / 360.0);
+        assert_eq!(DayCountConvention::Act365.year_fraction(start, end), 182.0 / 365.0);
+
+        let across = DayCountConvention::ActAct.year_fraction(date(2023, 7, 1), date(2024, 7, 1));
+        assert!((across - (184.0 / 365.0 + 182.0 / 366.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_coupon_schedule_and_accrual() {
+        let bond = terms(DayCountConvention::Thirty360);
+        let schedule = bond.coupon_schedule().unwrap();
+        assert_eq!(schedule.len(), 4);
+        assert_eq!(schedule[0].accrual_start, date(2024, 1, 15));
+        assert_eq!(schedule[0].accrual_end, date(2024, 7, 15));
+        assert_eq!(schedule[0].amount_per_unit, 30.0);
+        // 2025-01-15 and 2025-07-15 fall on weekdays; 2026-01-15 is a Thursday
+        assert_eq!(schedule[3].payment_date, date(2026, 1, 15));
+
+        // Two months into the first period: 60/360 * 6% * 1000
+        assert!((bond.accrued_interest(date(2024, 3, 15)).unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(bond.accrued_interest(date(2026, 1, 15)), Ok(0.0));
+    }
+
+    #[test]
+    fn test_coupons_and_redemption_credit_holders() {
+        let clock = ManualClock::new(date(2024, 1, 15).to_timestamp());
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            id: "bond1".to_string(),
+            name: "6% Notes 2026".to_string(),
+            asset_type: AssetType::Debt,
+            value: 1_000.0,
+            owner: "issuer".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        })
+        .unwrap();
+        core.create_wallet(DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "investor".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        })
+        .unwrap();
+        core.register_bond(terms(DayCountConvention::Thirty360)).unwrap();
+        core.mint_to_wallet("wallet1", "bond1", 10.0).unwrap();
+
+        let announced = core.announce_coupon_payments("bond1").unwrap();
+        assert_eq!(announced.len(), 4);
+        assert!(core.announce_coupon_payments("bond1").unwrap().is_empty());
+        assert!(core.redeem_bond("bond1").is_err());
+
+        clock.set(date(2024, 7, 15).to_timestamp());
+        core.record_corporate_action("bond1-coupon-1").unwrap();
+        core.process_corporate_action("bond1-coupon-1").unwrap();
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 300.0);
+
+        // An archived holder cannot be paid, so its units stay in issue
+        core.create_wallet(DigitalWallet {
+            id: "wallet2".to_string(),
+            owner: "investor2".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        })
+        .unwrap();
+        core.mint_to_wallet("wallet2", "bond1", 5.0).unwrap();
+        core.delete_wallet("wallet2", DeletePolicy::SoftDelete).unwrap();
+
+        clock.set(date(2026, 1, 15).to_timestamp());
+        let redemption = core.redeem_bond("bond1").unwrap();
+        assert_eq!(redemption.total_amount, 10_000.0);
+        assert_eq!(redemption.payouts.len(), 1);
+        assert_eq!(core.get_holding("wallet1", "bond1").unwrap(), 0.0);
+        assert_eq!(core.get_issued_supply("bond1").unwrap(), 5.0);
+        assert_eq!(core.redeem_bond("bond1").unwrap(), redemption);
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 10_300.0);
+    }
+}
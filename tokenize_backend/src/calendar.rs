@@ -0,0 +1,234 @@
+//! Calendar dates and business-day calendars
+//!
+//! The core stores times as Unix timestamps; schedule-driven instruments need civil
+//! dates instead. `Date` converts between the two (UTC, proleptic Gregorian) and
+//! `BusinessCalendar` rolls dates that fall on weekends or holidays.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A civil calendar date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Create a date, checking that it exists in the calendar
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Self, String> {
+        if !(1..=12).contains(&month) {
+            return Err(format!("Invalid month {}", month));
+        }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(format!("Invalid day {} for {}-{:02}", day, year, month));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Date containing the given Unix timestamp (UTC)
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        Self::from_days(timestamp as i64 / SECONDS_PER_DAY)
+    }
+
+    /// Unix timestamp of midnight UTC on this date
+    pub fn to_timestamp(self) -> u64 {
+        (self.to_days() * SECONDS_PER_DAY).max(0) as u64
+    }
+
+    /// Days since 1970-01-01
+    pub fn to_days(self) -> i64 {
+        let y = if self.month <= 2 { self.year - 1 } else { self.year } as i64;
+        let era = if y >= 0 { y } else { y - 399 } / 400;
+        let yoe = y - era * 400;
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// Date from days since 1970-01-01
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Self { year, month, day }
+    }
+
+    /// Number of days from `self` to `other` (negative if `other` is earlier)
+    pub fn days_until(self, other: Date) -> i64 {
+        other.to_days() - self.to_days()
+    }
+
+    /// Shift by a number of days
+    pub fn add_days(self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// Shift by a number of months, clamping the day to the end of the target month
+    pub fn add_months(self, months: i32) -> Self {
+        let total = self.year * 12 + self.month as i32 - 1 + months;
+        let year = total.div_euclid(12);
+        let month = total.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Self { year, month, day }
+    }
+
+    /// Day of week, 0 = Monday .. 6 = Sunday
+    pub fn weekday(self) -> u32 {
+        // 1970-01-01 was a Thursday
+        (self.to_days() + 3).rem_euclid(7) as u32
+    }
+
+    pub fn is_weekend(self) -> bool {
+        self.weekday() >= 5
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Parse an ISO 8601 date, `YYYY-MM-DD`
+    fn from_str(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid date {}, expected YYYY-MM-DD", s);
+        let mut parts = s.splitn(3, '-');
+        let mut next = || parts.next().filter(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit())).ok_or_else(invalid);
+        let year = next()?.parse().map_err(|_| invalid())?;
+        let month = next()?.parse().map_err(|_| invalid())?;
+        let day = next()?.parse().map_err(|_| invalid())?;
+        Date::new(year, month, day)
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Longest run of non-business days a date is rolled across before giving up
+pub const MAX_ROLL_DAYS: i64 = 366;
+
+/// Rule for moving a date that falls on a non-business day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BusinessDayConvention {
+    Unadjusted,
+    Following,
+    ModifiedFollowing,
+    Preceding,
+}
+
+/// Weekend-aware calendar with an explicit holiday list
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BusinessCalendar {
+    pub name: String,
+    pub holidays: BTreeSet<Date>,
+}
+
+impl BusinessCalendar {
+    pub fn new(name: &str, holidays: impl IntoIterator<Item = Date>) -> Self {
+        Self {
+            name: name.to_string(),
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    pub fn is_business_day(&self, date: Date) -> bool {
+        !date.is_weekend() && !self.holidays.contains(&date)
+    }
+
+    /// Roll a date onto a business day according to the convention
+    pub fn adjust(&self, date: Date, convention: BusinessDayConvention) -> Result<Date, String> {
+        match convention {
+            BusinessDayConvention::Unadjusted => Ok(date),
+            BusinessDayConvention::Following => self.roll(date, 1),
+            BusinessDayConvention::Preceding => self.roll(date, -1),
+            BusinessDayConvention::ModifiedFollowing => {
+                let following = self.roll(date, 1)?;
+                if following.month != date.month {
+                    self.roll(date, -1)
+                } else {
+                    Ok(following)
+                }
+            }
+        }
+    }
+
+    fn roll(&self, date: Date, step: i64) -> Result<Date, String> {
+        (0..=MAX_ROLL_DAYS)
+            .map(|days| date.add_days(step * days))
+            .find(|candidate| self.is_business_day(*candidate))
+            .ok_or_else(|| format!("Calendar {} has no business day within {} days of {}", self.name, MAX_ROLL_DAYS, date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_timestamp_round_trip() {
+        let date = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(Date::from_timestamp(date.to_timestamp()), date);
+        assert_eq!(Date::from_timestamp(0), Date::new(1970, 1, 1).unwrap());
+        assert_eq!(Date::new(2000, 3, 1).unwrap().to_days(), 11_017);
+        assert!(Date::new(2023, 2, 29).is_err());
+        assert_eq!("2024-02-29".parse::<Date>().unwrap().to_string(), "2024-02-29");
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2024-02".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn test_add_months_clamps_to_month_end() {
+        let date = Date::new(2024, 8, 31).unwrap();
+        assert_eq!(date.add_months(-6), Date::new(2024, 2, 29).unwrap());
+        assert_eq!(date.add_months(6), Date::new(2025, 2, 28).unwrap());
+    }
+
+    #[test]
+    fn test_business_day_adjustment() {
+        // 2024-03-30 is a Saturday, 2024-04-01 a Monday holiday
+        let calendar = BusinessCalendar::new("test", [Date::new(2024, 4, 1).unwrap()]);
+        let saturday = Date::new(2024, 3, 30).unwrap();
+
+        assert_eq!(
+            calendar.adjust(saturday, BusinessDayConvention::Following),
+            Ok(Date::new(2024, 4, 2).unwrap())
+        );
+        assert_eq!(
+            calendar.adjust(saturday, BusinessDayConvention::ModifiedFollowing),
+            Ok(Date::new(2024, 3, 29).unwrap())
+        );
+        assert_eq!(calendar.adjust(saturday, BusinessDayConvention::Unadjusted), Ok(saturday));
+
+        // A calendar closed for over a year fails instead of rolling forever
+        let closed = BusinessCalendar::new("closed", (0..400).map(|d| saturday.add_days(d)));
+        assert!(closed.adjust(saturday, BusinessDayConvention::Following).is_err());
+        assert!(closed.adjust(saturday, BusinessDayConvention::Preceding).is_ok());
+    }
+}
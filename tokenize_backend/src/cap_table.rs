@@ -0,0 +1,332 @@
+//! Cap tables and ledger integrity checks
+//!
+//! The cap table of an asset is built from wallet holdings and, for fully diluted
+//! figures, from registered dilutive instruments (option pools, warrants,
+//! convertibles). The integrity check proves that holdings add up to issued supply
+//! and that no wallet or holding refers to an asset that no longer exists.
+//! Soft-deleted assets and archived wallets still count as existing.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, QUANTITY_EPSILON};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kind of instrument that can convert into units of an asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DilutiveKind {
+    /// Unallocated employee option pool
+    OptionPool,
+    Option,
+    Warrant,
+    Convertible,
+}
+
+/// Units that may be issued in the future on exercise or conversion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DilutiveInstrument {
+    pub id: String,
+    pub asset_id: String,
+    pub kind: DilutiveKind,
+    /// Wallet entitled to the units; `None` for unallocated pools
+    pub holder_wallet_id: Option<String>,
+    pub quantity: f64,
+}
+
+/// One line of a cap table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapTableEntry {
+    pub wallet_id: String,
+    pub owner: String,
+    pub quantity: f64,
+    pub ownership_pct: f64,
+    pub fully_diluted_quantity: f64,
+    pub fully_diluted_pct: f64,
+}
+
+/// Ownership of an asset across all wallets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapTable {
+    pub asset_id: String,
+    pub as_of: u64,
+    pub issued_supply: f64,
+    pub fully_diluted_supply: f64,
+    pub entries: Vec<CapTableEntry>,
+    /// Dilutive units not allocated to any wallet
+    pub unallocated_dilutive: f64,
+}
+
+/// A single integrity violation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum IntegrityIssue {
+    /// Holdings of an asset do not add up to its issued supply
+    SupplyMismatch { asset_id: String, issued: f64, held: f64 },
+    /// A wallet lists an asset that does not exist
+    DanglingAssetReference { wallet_id: String, asset_id: String },
+    /// Units are recorded for an asset or wallet that does not exist
+    OrphanHolding { wallet_id: String, asset_id: String, quantity: f64 },
+}
+
+/// Result of an integrity check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_at: u64,
+    pub assets_checked: usize,
+    pub wallets_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl TokenizationCore {
+    /// Register an option pool, warrant or convertible against an asset
+    pub fn add_dilutive_instrument(&self, instrument: DilutiveInstrument) -> Result<(), String> {
+        if instrument.quantity <= 0.0 {
+            return Err("Dilutive quantity must be positive".to_string());
+        }
+        if self.get_asset(&instrument.asset_id)?.is_none() {
+            return Err("Asset not found".to_string());
+        }
+        self.ensure_ids_not_frozen(&[], &[&instrument.asset_id])?;
+        if let Some(wallet_id) = &instrument.holder_wallet_id
+            && self.get_wallet(wallet_id)?.is_none()
+        {
+            return Err("Wallet not found".to_string());
+        }
+        let mut instruments = self.dilutive_instruments.write().map_err(|_| "Failed to acquire write lock")?;
+        if instruments.iter().any(|i| i.id == instrument.id) {
+            return Err("Dilutive instrument already exists".to_string());
+        }
+        self.record_audit(AuditAction::Create, EntityType::DilutiveInstrument, &instrument.id, None::<&DilutiveInstrument>, Some(&instrument))?;
+        instruments.push(instrument);
+        Ok(())
+    }
+
+    /// Remove a dilutive instrument, e.g. once exercised or expired
+    pub fn remove_dilutive_instrument(&self, instrument_id: &str) -> Result<(), String> {
+        // Assets before instruments, the order delete_asset_with_policy takes them in
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut instruments = self.dilutive_instruments.write().map_err(|_| "Failed to acquire write lock")?;
+        let index = instruments
+            .iter()
+            .position(|i| i.id == instrument_id)
+            .ok_or("Dilutive instrument not found")?;
+        self.ensure_not_frozen(&[], assets.get(&instruments[index].asset_id).as_slice())?;
+        let removed = instruments.remove(index);
+        self.record_audit(AuditAction::Delete, EntityType::DilutiveInstrument, instrument_id, Some(&removed), None::<&DilutiveInstrument>)?;
+        Ok(())
+    }
+
+    /// Build the cap table of an asset from current wallet holdings
+    pub fn get_cap_table(&self, asset_id: &str) -> Result<CapTable, String> {
+        self.authorize_asset(Permission::ReadReports, asset_id)?;
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        let supply = self.supply.read().map_err(|_| "Failed to acquire read lock")?;
+        let instruments = self.dilutive_instruments.read().map_err(|_| "Failed to acquire read lock")?;
+
+        if !assets.contains_key(asset_id) {
+            return Err("Asset not found".to_string());
+        }
+        let issued_supply = supply.get(asset_id).copied().unwrap_or(0.0);
+
+        let mut dilutive_by_wallet: HashMap<&str, f64> = HashMap::new();
+        let mut unallocated_dilutive = 0.0;
+        for instrument in instruments.iter().filter(|i| i.asset_id == asset_id) {
+            match &instrument.holder_wallet_id {
+                Some(wallet_id) => *dilutive_by_wallet.entry(wallet_id.as_str()).or_insert(0.0) += instrument.quantity,
+                None => unallocated_dilutive += instrument.quantity,
+            }
+        }
+        let fully_diluted_supply =
+            issued_supply + unallocated_dilutive + dilutive_by_wallet.values().sum::<f64>();
+
+        let mut wallet_ids: Vec<&String> = holdings
+            .iter()
+            .filter(|(_, positions)| positions.get(asset_id).is_some_and(|q| *q > QUANTITY_EPSILON))
+            .map(|(wallet_id, _)| wallet_id)
+            .collect();
+        for wallet_id in dilutive_by_wallet.keys() {
+            if let Some((id, _)) = wallets.get_key_value(*wallet_id)
+                && !wallet_ids.contains(&id)
+            {
+                wallet_ids.push(id);
+            }
+        }
+
+        let pct = |quantity: f64, total: f64| if total > QUANTITY_EPSILON { quantity / total * 100.0 } else { 0.0 };
+        let mut entries: Vec<CapTableEntry> = wallet_ids
+            .into_iter()
+            .map(|wallet_id| {
+                let quantity = holdings
+                    .get(wallet_id)
+                    .and_then(|p| p.get(asset_id))
+                    .copied()
+                    .unwrap_or(0.0);
+                let fully_diluted_quantity = quantity + dilutive_by_wallet.get(wallet_id.as_str()).copied().unwrap_or(0.0);
+                CapTableEntry {
+                    wallet_id: wallet_id.clone(),
+                    owner: wallets.get(wallet_id).map(|w| w.owner.clone()).unwrap_or_default(),
+                    quantity,
+                    ownership_pct: pct(quantity, issued_supply),
+                    fully_diluted_quantity,
+                    fully_diluted_pct: pct(fully_diluted_quantity, fully_diluted_supply),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.quantity.total_cmp(&a.quantity).then(a.wallet_id.cmp(&b.wallet_id)));
+
+        Ok(CapTable {
+            asset_id: asset_id.to_string(),
+            as_of: self.now(),
+            issued_supply,
+            fully_diluted_supply,
+            entries,
+            unallocated_dilutive,
+        })
+    }
+
+    /// Check that holdings match issued supply and that nothing refers to a missing asset
+    pub fn check_integrity(&self) -> Result<IntegrityReport, String> {
+        authorize_current(Permission::ReadReports, None)?;
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        let supply = self.supply.read().map_err(|_| "Failed to acquire read lock")?;
+        let tombstones = self.asset_tombstones.read().map_err(|_| "Failed to acquire read lock")?;
+        let archived = self.archived_wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let asset_known = |id: &String| assets.contains_key(id) || tombstones.contains_key(id);
+        let wallet_known = |id: &String| wallets.contains_key(id) || archived.contains_key(id);
+        let mut issues = Vec::new();
+
+        let mut held_by_asset: HashMap<&str, f64> = HashMap::new();
+        for (wallet_id, positions) in holdings.iter() {
+            for (asset_id, quantity) in positions {
+                if !asset_known(asset_id) || !wallet_known(wallet_id) {
+                    issues.push(IntegrityIssue::OrphanHolding {
+                        wallet_id: wallet_id.clone(),
+                        asset_id: asset_id.clone(),
+                        quantity: *quantity,
+                    });
+                }
+                *held_by_asset.entry(asset_id.as_str()).or_insert(0.0) += quantity;
+            }
+        }
+
+        let mut asset_ids: Vec<&String> = assets.keys().chain(supply.keys()).collect();
+        asset_ids.sort();
+        asset_ids.dedup();
+        for asset_id in asset_ids {
+            let issued = supply.get(asset_id).copied().unwrap_or(0.0);
+            let held = held_by_asset.get(asset_id.as_str()).copied().unwrap_or(0.0);
+            if (issued - held).abs() > QUANTITY_EPSILON * issued.max(1.0) {
+                issues.push(IntegrityIssue::SupplyMismatch {
+                    asset_id: asset_id.clone(),
+                    issued,
+                    held,
+                });
+            }
+        }
+
+        for wallet in wallets.values() {
+            for asset_id in &wallet.assets {
+                if !asset_known(asset_id) {
+                    issues.push(IntegrityIssue::DanglingAssetReference {
+                        wallet_id: wallet.id.clone(),
+                        asset_id: asset_id.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(IntegrityReport {
+            checked_at: self.now(),
+            assets_checked: assets.len(),
+            wallets_checked: wallets.len(),
+            issues,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+
+    fn setup() -> TokenizationCore {
+        let core = TokenizationCore::new();
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for id in ["founder", "investor", "employee"] {
+            core.create_wallet(wallet(id, &format!("{}-owner", id), WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("founder", "asset1", 600.0).unwrap();
+        core.mint_to_wallet("investor", "asset1", 200.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_cap_table_with_fully_diluted_figures() {
+        let core = setup();
+        core.add_dilutive_instrument(DilutiveInstrument {
+            id: "pool".to_string(),
+            asset_id: "asset1".to_string(),
+            kind: DilutiveKind::OptionPool,
+            holder_wallet_id: None,
+            quantity: 100.0,
+        })
+        .unwrap();
+        core.add_dilutive_instrument(DilutiveInstrument {
+            id: "grant1".to_string(),
+            asset_id: "asset1".to_string(),
+            kind: DilutiveKind::Option,
+            holder_wallet_id: Some("employee".to_string()),
+            quantity: 100.0,
+        })
+        .unwrap();
+
+        let table = core.get_cap_table("asset1").unwrap();
+        assert_eq!(table.issued_supply, 800.0);
+        assert_eq!(table.fully_diluted_supply, 1_000.0);
+        assert_eq!(table.entries.len(), 3);
+        assert_eq!(table.entries[0].wallet_id, "founder");
+        assert_eq!(table.entries[0].ownership_pct, 75.0);
+        assert_eq!(table.entries[0].fully_diluted_pct, 60.0);
+        let employee = table.entries.iter().find(|e| e.wallet_id == "employee").unwrap();
+        assert_eq!(employee.quantity, 0.0);
+        assert_eq!(employee.fully_diluted_pct, 10.0);
+    }
+
+    #[test]
+    fn test_integrity_check_passes_for_consistent_ledger() {
+        let core = setup();
+        core.transfer_asset("founder", "employee", "asset1", 50.0).unwrap();
+        assert!(core.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    fn test_integrity_check_detects_dangling_references() {
+        let core = setup();
+        core.assets.write().unwrap().remove("asset1");
+
+        let report = core.check_integrity().unwrap();
+        assert!(!report.is_consistent());
+        assert!(report.issues.contains(&IntegrityIssue::DanglingAssetReference {
+            wallet_id: "founder".to_string(),
+            asset_id: "asset1".to_string(),
+        }));
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| matches!(i, IntegrityIssue::OrphanHolding { wallet_id, .. } if wallet_id == "investor")));
+    }
+}
@@ -3,12 +3,38 @@
 //! This module provides the main functionality for managing tokenized assets and digital wallets.
 //! It includes core operations such as asset tokenization, wallet management, and compliance checks.
 
+use crate::access_control::{authorize_current, Permission};
+use crate::approvals::{ApprovalRule, OperationKind, PendingAction};
+use crate::audit::{quantity_change, AuditAction, AuditLog, EntityType};
+use crate::bonds::BondRecord;
+use crate::cap_table::DilutiveInstrument;
+use crate::clock::{Clock, SystemClock};
+use crate::corporate_actions::CorporateActionRecord;
+use crate::custody::{self, CustodyModel, SigningRequest, SignerRole};
+use crate::events::{DomainEvent, EventStore, InMemoryEventStore, DEFAULT_SNAPSHOT_INTERVAL};
+use crate::freezes::{Freeze, FreezeTarget};
+use crate::fund_orders::{DealingTerms, FundOrder};
+use crate::issuance::OfferingRecord;
+use crate::key_management::{InMemoryKeyStore, KeyStore};
+use crate::multisig::{Proposal, SignerPolicy};
+use crate::nav::{FundProfile, NavRecord};
+use crate::notifications::NotificationHub;
+use crate::policy::{PolicyActor, PolicyEngine, PolicyInput, PolicyOperation, PolicyOperationKind};
+use crate::reconciliation::Reconciliation;
+use crate::recovery::{GuardianSet, RecoveryRequest};
+use crate::reserves::StoredReserveReport;
+use crate::signatures::WalletKey;
+use crate::spending_limits::{LimitTarget, SpendRecord, SpendingLimits};
+use crate::vesting::HoldingRestrictions;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{Arc, RwLock};
 
+/// Tolerance used when comparing token quantities
+pub const QUANTITY_EPSILON: f64 = 1e-9;
+
 /// Represents a tokenized asset with all its properties
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenizedAsset {
     pub id: String,
     pub name: String,
@@ -22,7 +48,7 @@ pub struct TokenizedAsset {
 }
 
 /// Enum representing different types of tokenized assets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AssetType {
     Equity,
     Debt,
@@ -36,7 +62,7 @@ pub enum AssetType {
 }
 
 /// Enum representing compliance status of an asset
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ComplianceStatus {
     Pending,
     Approved,
@@ -45,7 +71,7 @@ pub enum ComplianceStatus {
 }
 
 /// Represents a digital wallet for holding tokenized assets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DigitalWallet {
     pub id: String,
     pub owner: String,
@@ -57,90 +83,481 @@ pub struct DigitalWallet {
 }
 
 /// Enum representing different types of wallets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WalletType {
     Custodial,
     NonCustodial,
     Hybrid,
 }
 
+/// How a delete treats records that still reference the deleted entity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DeletePolicy {
+    /// Refuse to delete while holdings, balances or wallet references exist
+    Reject,
+    /// Remove every reference, adjusting balances and issued supply
+    Cascade,
+    /// Keep the record as a tombstone so existing references stay resolvable
+    SoftDelete,
+}
+
+/// A soft-deleted asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetTombstone {
+    pub asset: TokenizedAsset,
+    pub deleted_at: u64,
+}
+
+/// A closed or soft-deleted wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedWallet {
+    pub wallet: DigitalWallet,
+    pub holdings: HashMap<String, f64>,
+    pub archived_at: u64,
+}
+
 /// Core component that manages tokenized assets and wallets
 pub struct TokenizationCore {
-    assets: Arc<RwLock<HashMap<String, TokenizedAsset>>>,
-    wallets: Arc<RwLock<HashMap<String, DigitalWallet>>>,
+    pub(crate) assets: Arc<RwLock<HashMap<String, TokenizedAsset>>>,
+    pub(crate) wallets: Arc<RwLock<HashMap<String, DigitalWallet>>>,
+    /// Token quantities keyed by wallet ID, then asset ID
+    pub(crate) holdings: Arc<RwLock<HashMap<String, HashMap<String, f64>>>>,
+    /// Issued supply keyed by asset ID
+    pub(crate) supply: Arc<RwLock<HashMap<String, f64>>>,
+    /// Vesting schedules and holding-period clocks keyed by (wallet ID, asset ID)
+    pub(crate) restrictions: Arc<RwLock<HashMap<(String, String), HoldingRestrictions>>>,
+    /// Corporate action records keyed by action ID
+    pub(crate) corporate_actions: Arc<RwLock<HashMap<String, CorporateActionRecord>>>,
+    /// Bond terms and lifecycle keyed by asset ID
+    pub(crate) bonds: Arc<RwLock<HashMap<String, BondRecord>>>,
+    /// Fund portfolios and fee set-up keyed by asset ID
+    pub(crate) funds: Arc<RwLock<HashMap<String, FundProfile>>>,
+    /// NAV history keyed by asset ID, oldest first
+    pub(crate) nav_history: Arc<RwLock<HashMap<String, Vec<NavRecord>>>>,
+    /// Fund dealing terms keyed by asset ID
+    pub(crate) fund_dealing: Arc<RwLock<HashMap<String, DealingTerms>>>,
+    /// Fund subscription and redemption orders keyed by order ID
+    pub(crate) fund_orders: Arc<RwLock<HashMap<String, FundOrder>>>,
+    /// Primary offerings keyed by offering ID
+    pub(crate) offerings: Arc<RwLock<HashMap<String, OfferingRecord>>>,
+    /// Option pools, warrants and convertibles counted in fully diluted figures
+    pub(crate) dilutive_instruments: Arc<RwLock<Vec<DilutiveInstrument>>>,
+    /// Soft-deleted assets keyed by asset ID
+    pub(crate) asset_tombstones: Arc<RwLock<HashMap<String, AssetTombstone>>>,
+    /// Closed and soft-deleted wallets keyed by wallet ID
+    pub(crate) archived_wallets: Arc<RwLock<HashMap<String, ArchivedWallet>>>,
+    /// Append-only, hash-chained record of every mutation
+    pub(crate) audit_log: Arc<RwLock<AuditLog>>,
+    /// Stream of domain events the asset and wallet ledgers can be rebuilt from
+    pub(crate) event_store: Arc<dyn EventStore>,
+    /// Number of events between automatic snapshots; zero disables them
+    pub(crate) snapshot_interval: u64,
+    /// Outbound notification fan-out fed by the event stream
+    pub(crate) notifications: NotificationHub,
+    /// Four-eyes approval rules keyed by the operation kind they cover
+    pub(crate) approval_rules: Arc<RwLock<HashMap<OperationKind, ApprovalRule>>>,
+    /// Operations awaiting or having gone through approval, keyed by action ID
+    pub(crate) pending_actions: Arc<RwLock<HashMap<String, PendingAction>>>,
+    /// Policy bundle consulted before transfers, wallet creation and issuance
+    pub(crate) policies: PolicyEngine,
+    /// Spending limits keyed by the wallet or owner they apply to
+    pub(crate) spending_limits: Arc<RwLock<HashMap<LimitTarget, SpendingLimits>>>,
+    /// Outgoing transfer values of limited wallets and owners over the last week
+    pub(crate) spend_log: Arc<RwLock<HashMap<LimitTarget, Vec<SpendRecord>>>>,
+    /// Wallet, asset, owner and platform freezes keyed by target
+    pub(crate) freezes: Arc<RwLock<HashMap<FreezeTarget, Freeze>>>,
+    /// Custody model chosen for custodial wallets; unlisted ones are segregated
+    pub(crate) custody_models: Arc<RwLock<HashMap<String, CustodyModel>>>,
+    /// Outgoing transfers collecting co-signatures
+    pub(crate) signing_requests: Arc<RwLock<HashMap<String, SigningRequest>>>,
+    /// Public keys wallets sign operations with, by key ID
+    pub(crate) wallet_keys: Arc<RwLock<HashMap<String, WalletKey>>>,
+    /// Last nonce accepted from each wallet
+    pub(crate) wallet_nonces: Arc<RwLock<HashMap<String, u64>>>,
+    /// Platform signing keys
+    pub(crate) key_store: Arc<dyn KeyStore>,
+    /// Threshold signers keyed by the wallet they control
+    pub(crate) wallet_signers: Arc<RwLock<HashMap<String, SignerPolicy>>>,
+    /// Operations on threshold wallets collecting approvals, keyed by proposal ID
+    pub(crate) proposals: Arc<RwLock<HashMap<String, Proposal>>>,
+    /// Guardians and inheritance terms keyed by the wallet they protect
+    pub(crate) wallet_guardians: Arc<RwLock<HashMap<String, GuardianSet>>>,
+    /// Recoveries and inheritance claims, keyed by recovery ID
+    pub(crate) recoveries: Arc<RwLock<HashMap<String, RecoveryRequest>>>,
+    /// Proof of liabilities reports and their leaves, keyed by report ID
+    pub(crate) reserve_reports: Arc<RwLock<HashMap<String, StoredReserveReport>>>,
+    /// Statement reconciliation reports, keyed by reconciliation ID
+    pub(crate) reconciliations: Arc<RwLock<HashMap<String, Reconciliation>>>,
+    pub(crate) clock: Arc<dyn Clock>,
 }
 
 impl TokenizationCore {
     /// Create a new TokenizationCore instance
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    /// Create a new TokenizationCore instance that reads time from the given clock
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             assets: Arc::new(RwLock::new(HashMap::new())),
             wallets: Arc::new(RwLock::new(HashMap::new())),
+            holdings: Arc::new(RwLock::new(HashMap::new())),
+            supply: Arc::new(RwLock::new(HashMap::new())),
+            restrictions: Arc::new(RwLock::new(HashMap::new())),
+            corporate_actions: Arc::new(RwLock::new(HashMap::new())),
+            bonds: Arc::new(RwLock::new(HashMap::new())),
+            funds: Arc::new(RwLock::new(HashMap::new())),
+            nav_history: Arc::new(RwLock::new(HashMap::new())),
+            fund_dealing: Arc::new(RwLock::new(HashMap::new())),
+            fund_orders: Arc::new(RwLock::new(HashMap::new())),
+            offerings: Arc::new(RwLock::new(HashMap::new())),
+            dilutive_instruments: Arc::new(RwLock::new(Vec::new())),
+            asset_tombstones: Arc::new(RwLock::new(HashMap::new())),
+            archived_wallets: Arc::new(RwLock::new(HashMap::new())),
+            audit_log: Arc::new(RwLock::new(AuditLog::default())),
+            event_store: Arc::new(InMemoryEventStore::default()),
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+            notifications: NotificationHub::new(),
+            approval_rules: Arc::new(RwLock::new(HashMap::new())),
+            pending_actions: Arc::new(RwLock::new(HashMap::new())),
+            policies: PolicyEngine::new(),
+            spending_limits: Arc::new(RwLock::new(HashMap::new())),
+            spend_log: Arc::new(RwLock::new(HashMap::new())),
+            freezes: Arc::new(RwLock::new(HashMap::new())),
+            custody_models: Arc::new(RwLock::new(HashMap::new())),
+            signing_requests: Arc::new(RwLock::new(HashMap::new())),
+            wallet_keys: Arc::new(RwLock::new(HashMap::new())),
+            wallet_nonces: Arc::new(RwLock::new(HashMap::new())),
+            key_store: Arc::new(InMemoryKeyStore::default()),
+            wallet_signers: Arc::new(RwLock::new(HashMap::new())),
+            proposals: Arc::new(RwLock::new(HashMap::new())),
+            wallet_guardians: Arc::new(RwLock::new(HashMap::new())),
+            recoveries: Arc::new(RwLock::new(HashMap::new())),
+            reserve_reports: Arc::new(RwLock::new(HashMap::new())),
+            reconciliations: Arc::new(RwLock::new(HashMap::new())),
+            clock,
         }
     }
 
+    /// Current time according to the core's clock
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    /// Hub that publishes every domain event of this core to registered sinks
+    pub fn notifications(&self) -> NotificationHub {
+        self.notifications.clone()
+    }
+
     /// Create a new tokenized asset
     pub fn create_asset(&self, asset: TokenizedAsset) -> Result<String, String> {
         let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
+        let tombstones = self.asset_tombstones.read().map_err(|_| "Failed to acquire read lock")?;
+        if tombstones.contains_key(&asset.id) {
+            return Err("Asset ID belongs to a deleted asset".to_string());
+        }
+        if assets.contains_key(&asset.id) {
+            return Err("Asset already exists".to_string());
+        }
+        authorize_current(Permission::CreateAsset, Some(&asset.owner))?;
+        self.enforce_policies(PolicyInput {
+            actor: PolicyActor::current(),
+            operation: PolicyOperation {
+                kind: PolicyOperationKind::IssueAsset,
+                quantity: None,
+                to_wallet: None,
+            },
+            wallet: None,
+            asset: Some(asset.clone()),
+        })?;
+        self.ensure_not_frozen(&[], &[&asset])?;
         let asset_id = asset.id.clone();
-        assets.insert(asset_id.clone(), asset);
+        assets.insert(asset_id.clone(), asset.clone());
+        self.record_audit(AuditAction::Create, EntityType::Asset, &asset_id, None::<&TokenizedAsset>, Some(&asset))?;
+        self.emit_event(DomainEvent::AssetCreated { asset })?;
         Ok(asset_id)
     }
 
     /// Get an asset by ID
     pub fn get_asset(&self, asset_id: &str) -> Result<Option<TokenizedAsset>, String> {
         let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
-        Ok(assets.get(asset_id).cloned())
+        let asset = assets.get(asset_id).cloned();
+        if let Some(asset) = &asset {
+            authorize_current(Permission::ReadAsset, Some(&asset.owner))?;
+        }
+        Ok(asset)
     }
 
     /// Update an asset
-    pub fn update_asset(&self, asset_id: &str, updated_asset: TokenizedAsset) -> Result<(), String> {
+    ///
+    /// The owner and compliance status are kept from the stored asset; compliance
+    /// decisions go through `perform_compliance_check` and `override_compliance_status`.
+    pub fn update_asset(&self, asset_id: &str, mut updated_asset: TokenizedAsset) -> Result<(), String> {
         let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
-        if assets.contains_key(asset_id) {
-            assets.insert(asset_id.to_string(), updated_asset);
+        if let Some(existing) = assets.get(asset_id) {
+            authorize_current(Permission::UpdateAsset, Some(&existing.owner))?;
+            updated_asset.owner = existing.owner.clone();
+            updated_asset.compliance_status = existing.compliance_status.clone();
+            self.ensure_not_frozen(&[], &[existing, &updated_asset])?;
+            let before = assets.insert(asset_id.to_string(), updated_asset.clone());
+            self.record_audit(AuditAction::Update, EntityType::Asset, asset_id, before.as_ref(), Some(&updated_asset))?;
+            self.emit_event(DomainEvent::AssetUpdated { asset: updated_asset })?;
             Ok(())
         } else {
             Err("Asset not found".to_string())
         }
     }
 
-    /// Delete an asset
+    /// Delete an asset, refusing while any wallet still references it
     pub fn delete_asset(&self, asset_id: &str) -> Result<(), String> {
+        self.delete_asset_with_policy(asset_id, DeletePolicy::Reject)
+    }
+
+    /// Delete an asset using the given policy for wallets that still reference it
+    ///
+    /// `Cascade` removes the asset from every wallet's asset list, then drops its
+    /// holdings, supply, restrictions and dilutive instruments. Wallets that listed the
+    /// asset without holding units got it from `add_asset_to_wallet`, which credited its
+    /// value to their balance; that credit is reversed. Minting never credited balances.
+    /// `SoftDelete` moves the asset to a tombstone and leaves holdings in place.
+    pub fn delete_asset_with_policy(&self, asset_id: &str, policy: DeletePolicy) -> Result<(), String> {
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
         let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
-        if assets.remove(asset_id).is_some() {
-            Ok(())
-        } else {
-            Err("Asset not found".to_string())
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut restrictions = self.restrictions.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut tombstones = self.asset_tombstones.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut instruments = self.dilutive_instruments.write().map_err(|_| "Failed to acquire write lock")?;
+
+        let asset = assets.get(asset_id).cloned().ok_or("Asset not found")?;
+        authorize_current(Permission::DeleteAsset, Some(&asset.owner))?;
+        self.check_four_eyes(OperationKind::DeleteAsset, None)?;
+        self.ensure_not_frozen(&[], &[&asset])?;
+        let referencing: BTreeSet<String> = wallets
+            .values()
+            .filter(|w| w.assets.iter().any(|id| id == asset_id))
+            .map(|w| w.id.clone())
+            .chain(
+                holdings
+                    .iter()
+                    .filter(|(_, positions)| positions.get(asset_id).is_some_and(|q| *q > QUANTITY_EPSILON))
+                    .map(|(wallet_id, _)| wallet_id.clone()),
+            )
+            .collect();
+
+        match policy {
+            DeletePolicy::Reject => {
+                if !referencing.is_empty() {
+                    return Err(format!("Asset is still held by {} wallet(s)", referencing.len()));
+                }
+                assets.remove(asset_id);
+                supply.remove(asset_id);
+            }
+            DeletePolicy::Cascade => {
+                for wallet in wallets.values_mut() {
+                    if let Some(index) = wallet.assets.iter().position(|id| id == asset_id) {
+                        wallet.assets.remove(index);
+                        let held = holdings.get(&wallet.id).and_then(|p| p.get(asset_id)).copied().unwrap_or(0.0);
+                        if held <= QUANTITY_EPSILON {
+                            wallet.balance -= asset.value;
+                        }
+                        wallet.updated_at = now;
+                    }
+                }
+                for positions in holdings.values_mut() {
+                    positions.remove(asset_id);
+                }
+                restrictions.retain(|(_, restricted_asset), _| restricted_asset != asset_id);
+                instruments.retain(|i| i.asset_id != asset_id);
+                assets.remove(asset_id);
+                supply.remove(asset_id);
+            }
+            DeletePolicy::SoftDelete => {
+                assets.remove(asset_id);
+                tombstones.insert(
+                    asset_id.to_string(),
+                    AssetTombstone {
+                        asset: asset.clone(),
+                        deleted_at: now,
+                    },
+                );
+            }
         }
+        self.record_audit(AuditAction::Delete, EntityType::Asset, asset_id, Some(&asset), None::<&TokenizedAsset>)?;
+        self.emit_event(DomainEvent::AssetDeleted {
+            asset_id: asset_id.to_string(),
+            policy,
+        })?;
+        Ok(())
+    }
+
+    /// Get a soft-deleted asset
+    pub fn get_asset_tombstone(&self, asset_id: &str) -> Result<Option<AssetTombstone>, String> {
+        let tombstones = self.asset_tombstones.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(tombstones.get(asset_id).cloned())
+    }
+
+    /// Bring a soft-deleted asset back
+    pub fn restore_asset(&self, asset_id: &str) -> Result<(), String> {
+        let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut tombstones = self.asset_tombstones.write().map_err(|_| "Failed to acquire write lock")?;
+        let deleted = tombstones.get(asset_id).map(|t| &t.asset).ok_or("Deleted asset not found")?;
+        authorize_current(Permission::DeleteAsset, Some(&deleted.owner))?;
+        self.ensure_not_frozen(&[], &[deleted])?;
+        let tombstone = tombstones.remove(asset_id).ok_or("Deleted asset not found")?;
+        assets.insert(asset_id.to_string(), tombstone.asset.clone());
+        self.record_audit(AuditAction::Restore, EntityType::Asset, asset_id, None::<&TokenizedAsset>, Some(&tombstone.asset))?;
+        self.emit_event(DomainEvent::AssetRestored {
+            asset_id: asset_id.to_string(),
+        })?;
+        Ok(())
     }
 
     /// Create a new digital wallet
+    ///
+    /// Only a caller who may create wallets for anyone can open one with a cash
+    /// balance or assets already in it.
     pub fn create_wallet(&self, wallet: DigitalWallet) -> Result<String, String> {
         let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        if wallets.contains_key(&wallet.id) {
+            return Err("Wallet already exists".to_string());
+        }
+        authorize_current(Permission::CreateWallet, Some(&wallet.owner))?;
+        if wallet.balance.abs() > QUANTITY_EPSILON || !wallet.assets.is_empty() {
+            authorize_current(Permission::CreateWallet, None)?;
+        }
+        self.enforce_policies(PolicyInput {
+            actor: PolicyActor::current(),
+            operation: PolicyOperation {
+                kind: PolicyOperationKind::CreateWallet,
+                quantity: None,
+                to_wallet: None,
+            },
+            wallet: Some(wallet.clone()),
+            asset: None,
+        })?;
+        self.ensure_not_frozen(&[&wallet], &[])?;
         let wallet_id = wallet.id.clone();
-        wallets.insert(wallet_id.clone(), wallet);
+        wallets.insert(wallet_id.clone(), wallet.clone());
+        self.record_audit(AuditAction::Create, EntityType::Wallet, &wallet_id, None::<&DigitalWallet>, Some(&wallet))?;
+        self.emit_event(DomainEvent::WalletCreated { wallet })?;
         Ok(wallet_id)
     }
 
     /// Get a wallet by ID
     pub fn get_wallet(&self, wallet_id: &str) -> Result<Option<DigitalWallet>, String> {
         let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
-        Ok(wallets.get(wallet_id).cloned())
+        let wallet = wallets.get(wallet_id).cloned();
+        if let Some(wallet) = &wallet {
+            authorize_current(Permission::ReadWallet, Some(&wallet.owner))?;
+        }
+        Ok(wallet)
+    }
+
+    /// Close a wallet with a zero balance and no holdings, keeping it in the archive
+    pub fn close_wallet(&self, wallet_id: &str) -> Result<(), String> {
+        let wallet = self.get_wallet(wallet_id)?.ok_or("Wallet not found")?;
+        if wallet.balance.abs() > QUANTITY_EPSILON {
+            return Err("Wallet balance must be zero to close".to_string());
+        }
+        if !wallet.assets.is_empty() || !self.get_wallet_holdings(wallet_id)?.is_empty() {
+            return Err("Wallet still holds assets".to_string());
+        }
+        self.delete_wallet(wallet_id, DeletePolicy::SoftDelete)
+    }
+
+    /// Delete a wallet using the given policy for its holdings
+    ///
+    /// `Reject` requires a zero balance and no holdings. `Cascade` burns the wallet's
+    /// holdings, reducing issued supply, but still refuses a non-zero cash balance.
+    /// `SoftDelete` archives the wallet together with its holdings.
+    pub fn delete_wallet(&self, wallet_id: &str, policy: DeletePolicy) -> Result<(), String> {
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut restrictions = self.restrictions.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut archived = self.archived_wallets.write().map_err(|_| "Failed to acquire write lock")?;
+
+        let wallet = wallets.get(wallet_id).cloned().ok_or("Wallet not found")?;
+        authorize_current(Permission::DeleteWallet, Some(&wallet.owner))?;
+        self.check_four_eyes(OperationKind::DeleteWallet, None)?;
+        self.ensure_not_frozen(&[&wallet], &[])?;
+        let positions = holdings.get(wallet_id).cloned().unwrap_or_default();
+        let has_holdings = positions.values().any(|q| *q > QUANTITY_EPSILON) || !wallet.assets.is_empty();
+        let has_balance = wallet.balance.abs() > QUANTITY_EPSILON;
+
+        match policy {
+            DeletePolicy::Reject => {
+                if has_holdings || has_balance {
+                    return Err("Wallet still has holdings or a balance".to_string());
+                }
+                wallets.remove(wallet_id);
+                holdings.remove(wallet_id);
+            }
+            DeletePolicy::Cascade => {
+                if has_balance {
+                    return Err("Wallet balance must be withdrawn before deleting".to_string());
+                }
+                for (asset_id, quantity) in &positions {
+                    if let Some(issued) = supply.get_mut(asset_id) {
+                        *issued = (*issued - quantity).max(0.0);
+                    }
+                }
+                restrictions.retain(|(restricted_wallet, _), _| restricted_wallet != wallet_id);
+                wallets.remove(wallet_id);
+                holdings.remove(wallet_id);
+            }
+            DeletePolicy::SoftDelete => {
+                if let Some(wallet) = wallets.remove(wallet_id) {
+                    archived.insert(
+                        wallet_id.to_string(),
+                        ArchivedWallet {
+                            wallet,
+                            holdings: positions,
+                            archived_at: now,
+                        },
+                    );
+                }
+            }
+        }
+        self.record_audit(AuditAction::Delete, EntityType::Wallet, wallet_id, Some(&wallet), None::<&DigitalWallet>)?;
+        self.emit_event(DomainEvent::WalletDeleted {
+            wallet_id: wallet_id.to_string(),
+            policy,
+        })?;
+        Ok(())
+    }
+
+    /// Get a closed or soft-deleted wallet
+    pub fn get_archived_wallet(&self, wallet_id: &str) -> Result<Option<ArchivedWallet>, String> {
+        let archived = self.archived_wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(archived.get(wallet_id).cloned())
     }
 
     /// Add an asset to a wallet
     pub fn add_asset_to_wallet(&self, wallet_id: &str, asset_id: &str) -> Result<(), String> {
         let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
-        let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
         
         if let Some(wallet) = wallets.get_mut(wallet_id) {
+            authorize_current(Permission::UpdateWallet, Some(&wallet.owner))?;
+            self.ensure_not_frozen(&[&*wallet], assets.get(asset_id).as_slice())?;
             if assets.contains_key(asset_id) {
                 if !wallet.assets.contains(&asset_id.to_string()) {
+                    let before = wallet.clone();
                     wallet.assets.push(asset_id.to_string());
                     // Update wallet balance based on asset value
                     if let Some(asset) = assets.get(asset_id) {
                         wallet.balance += asset.value;
                     }
+                    self.record_audit(AuditAction::Update, EntityType::Wallet, wallet_id, Some(&before), Some(&*wallet))?;
+                    self.emit_event(DomainEvent::AssetAddedToWallet {
+                        wallet_id: wallet_id.to_string(),
+                        asset_id: asset_id.to_string(),
+                    })?;
                     Ok(())
                 } else {
                     Err("Asset already in wallet".to_string())
@@ -156,15 +573,23 @@ impl TokenizationCore {
     /// Remove an asset from a wallet
     pub fn remove_asset_from_wallet(&self, wallet_id: &str, asset_id: &str) -> Result<(), String> {
         let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
-        let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
         
         if let Some(wallet) = wallets.get_mut(wallet_id) {
+            authorize_current(Permission::UpdateWallet, Some(&wallet.owner))?;
+            self.ensure_not_frozen(&[&*wallet], assets.get(asset_id).as_slice())?;
             if let Some(index) = wallet.assets.iter().position(|x| x == asset_id) {
+                let before = wallet.clone();
                 wallet.assets.remove(index);
                 // Update wallet balance based on asset value
                 if let Some(asset) = assets.get(asset_id) {
                     wallet.balance -= asset.value;
                 }
+                self.record_audit(AuditAction::Update, EntityType::Wallet, wallet_id, Some(&before), Some(&*wallet))?;
+                self.emit_event(DomainEvent::AssetRemovedFromWallet {
+                    wallet_id: wallet_id.to_string(),
+                    asset_id: asset_id.to_string(),
+                })?;
                 Ok(())
             } else {
                 Err("Asset not in wallet".to_string())
@@ -181,19 +606,44 @@ impl TokenizationCore {
         let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
         
         if let Some(asset) = assets.get_mut(asset_id) {
+            authorize_current(Permission::ComplianceCheck, Some(&asset.owner))?;
+            self.check_four_eyes(OperationKind::ComplianceOverride, None)?;
+            self.ensure_not_frozen(&[], &[&*asset])?;
+            let before = asset.clone();
             asset.compliance_status = ComplianceStatus::Approved;
-            asset.updated_at = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+            asset.updated_at = self.now();
+            self.record_audit(AuditAction::ComplianceDecision, EntityType::Asset, asset_id, Some(&before), Some(&*asset))?;
+            self.emit_event(DomainEvent::ComplianceDecided {
+                asset_id: asset_id.to_string(),
+                status: ComplianceStatus::Approved,
+            })?;
             Ok(ComplianceStatus::Approved)
         } else {
             Err("Asset not found".to_string())
         }
     }
 
+    /// Set an asset's compliance status by hand, overriding the automated check
+    pub fn override_compliance_status(&self, asset_id: &str, status: ComplianceStatus) -> Result<(), String> {
+        let mut assets = self.assets.write().map_err(|_| "Failed to acquire write lock")?;
+        let asset = assets.get_mut(asset_id).ok_or("Asset not found")?;
+        authorize_current(Permission::ComplianceCheck, Some(&asset.owner))?;
+        self.check_four_eyes(OperationKind::ComplianceOverride, None)?;
+        self.ensure_not_frozen(&[], &[&*asset])?;
+        let before = asset.clone();
+        asset.compliance_status = status.clone();
+        asset.updated_at = self.now();
+        self.record_audit(AuditAction::ComplianceDecision, EntityType::Asset, asset_id, Some(&before), Some(&*asset))?;
+        self.emit_event(DomainEvent::ComplianceDecided {
+            asset_id: asset_id.to_string(),
+            status,
+        })?;
+        Ok(())
+    }
+
     /// Get all assets of a specific type
     pub fn get_assets_by_type(&self, asset_type: AssetType) -> Result<Vec<TokenizedAsset>, String> {
+        authorize_current(Permission::ReadAsset, None)?;
         let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
         let filtered_assets: Vec<TokenizedAsset> = assets
             .values()
@@ -209,6 +659,7 @@ impl TokenizationCore {
         let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
         
         if let Some(wallet) = wallets.get(wallet_id) {
+            authorize_current(Permission::ReadWallet, Some(&wallet.owner))?;
             let mut total_value = 0.0;
             for asset_id in &wallet.assets {
                 if let Some(asset) = assets.get(asset_id) {
@@ -220,6 +671,263 @@ impl TokenizationCore {
             Err("Wallet not found".to_string())
         }
     }
+
+    /// Issue new units of an asset into a wallet
+    pub fn mint_to_wallet(&self, wallet_id: &str, asset_id: &str, quantity: f64) -> Result<(), String> {
+        if quantity <= 0.0 {
+            return Err("Quantity must be positive".to_string());
+        }
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+
+        let asset = assets.get(asset_id).ok_or("Asset not found")?;
+        authorize_current(Permission::Mint, Some(&asset.owner))?;
+        self.check_four_eyes(OperationKind::Mint, Some(quantity))?;
+        self.ensure_not_frozen(wallets.get(wallet_id).as_slice(), &[asset])?;
+        self.enforce_policies(PolicyInput {
+            actor: PolicyActor::current(),
+            operation: PolicyOperation {
+                kind: PolicyOperationKind::Mint,
+                quantity: Some(quantity),
+                to_wallet: None,
+            },
+            wallet: Some(wallets.get(wallet_id).cloned().ok_or("Wallet not found")?),
+            asset: Some(asset.clone()),
+        })?;
+        let wallet = wallets.get_mut(wallet_id).ok_or("Wallet not found")?;
+
+        let position = holdings
+            .entry(wallet_id.to_string())
+            .or_default()
+            .entry(asset_id.to_string())
+            .or_insert(0.0);
+        let held_before = *position;
+        *position += quantity;
+        let held_after = *position;
+        let issued = supply.entry(asset_id.to_string()).or_insert(0.0);
+        let issued_before = *issued;
+        *issued += quantity;
+        let issued_after = *issued;
+        if !wallet.assets.iter().any(|id| id == asset_id) {
+            wallet.assets.push(asset_id.to_string());
+        }
+        wallet.updated_at = self.now();
+        self.record_audit_changes(
+            AuditAction::Mint,
+            EntityType::Asset,
+            asset_id,
+            vec![
+                quantity_change(format!("holdings.{}", wallet_id), held_before, held_after),
+                quantity_change("supply".to_string(), issued_before, issued_after),
+            ],
+        )?;
+        self.emit_event(DomainEvent::AssetMinted {
+            wallet_id: wallet_id.to_string(),
+            asset_id: asset_id.to_string(),
+            quantity,
+        })?;
+        Ok(())
+    }
+
+    /// Cancel units of an asset held in a wallet, reducing issued supply
+    pub fn burn_from_wallet(&self, wallet_id: &str, asset_id: &str, quantity: f64) -> Result<(), String> {
+        if quantity <= 0.0 {
+            return Err("Quantity must be positive".to_string());
+        }
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+
+        authorize_current(Permission::Burn, assets.get(asset_id).map(|a| a.owner.as_str()))?;
+        self.ensure_not_frozen(wallets.get(wallet_id).as_slice(), assets.get(asset_id).as_slice())?;
+        let wallet = wallets.get_mut(wallet_id).ok_or("Wallet not found")?;
+        let held = holdings
+            .get(wallet_id)
+            .and_then(|h| h.get(asset_id))
+            .copied()
+            .unwrap_or(0.0);
+        if held + QUANTITY_EPSILON < quantity {
+            return Err("Insufficient holding".to_string());
+        }
+
+        Self::debit_holding(&mut holdings, wallet, asset_id, quantity);
+        let mut changes = vec![quantity_change(format!("holdings.{}", wallet_id), held, held - quantity)];
+        if let Some(issued) = supply.get_mut(asset_id) {
+            let issued_before = *issued;
+            *issued = (*issued - quantity).max(0.0);
+            changes.push(quantity_change("supply".to_string(), issued_before, *issued));
+        }
+        wallet.updated_at = self.now();
+        self.record_audit_changes(AuditAction::Burn, EntityType::Asset, asset_id, changes)?;
+        self.emit_event(DomainEvent::AssetBurned {
+            wallet_id: wallet_id.to_string(),
+            asset_id: asset_id.to_string(),
+            quantity,
+        })?;
+        Ok(())
+    }
+
+    /// Transfer units of an asset between two wallets
+    ///
+    /// Unvested units and units still under a holding-period clock cannot leave the
+    /// sending wallet, and the sender's spending limits must allow the transfer.
+    pub fn transfer_asset(
+        &self,
+        from_wallet_id: &str,
+        to_wallet_id: &str,
+        asset_id: &str,
+        quantity: f64,
+    ) -> Result<(), String> {
+        self.transfer_signed(from_wallet_id, to_wallet_id, asset_id, quantity, None)
+    }
+
+    /// Transfer with the signatures a signing request collected, or the caller's own when `None`
+    pub(crate) fn transfer_signed(
+        &self,
+        from_wallet_id: &str,
+        to_wallet_id: &str,
+        asset_id: &str,
+        quantity: f64,
+        signatures: Option<&[SignerRole]>,
+    ) -> Result<(), String> {
+        if quantity <= 0.0 {
+            return Err("Quantity must be positive".to_string());
+        }
+        if from_wallet_id == to_wallet_id {
+            return Err("Cannot transfer to the same wallet".to_string());
+        }
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let restrictions = self.restrictions.read().map_err(|_| "Failed to acquire read lock")?;
+
+        if !assets.contains_key(asset_id) {
+            return Err("Asset not found".to_string());
+        }
+        if !wallets.contains_key(from_wallet_id) || !wallets.contains_key(to_wallet_id) {
+            return Err("Wallet not found".to_string());
+        }
+        authorize_current(Permission::Transfer, wallets.get(from_wallet_id).map(|w| w.owner.as_str()))?;
+        self.check_four_eyes(OperationKind::Transfer, Some(quantity))?;
+        let parties: Vec<&DigitalWallet> = [from_wallet_id, to_wallet_id].iter().filter_map(|id| wallets.get(*id)).collect();
+        self.ensure_not_frozen(&parties, assets.get(asset_id).as_slice())?;
+        if let [sender, receiver] = parties[..] {
+            let signed = match signatures {
+                Some(signatures) => signatures.to_vec(),
+                None => self.caller_signatures(sender)?,
+            };
+            custody::check_signatures(sender, receiver, &signed)?;
+        }
+        self.enforce_policies(PolicyInput {
+            actor: PolicyActor::current(),
+            operation: PolicyOperation {
+                kind: PolicyOperationKind::Transfer,
+                quantity: Some(quantity),
+                to_wallet: wallets.get(to_wallet_id).cloned(),
+            },
+            wallet: wallets.get(from_wallet_id).cloned(),
+            asset: assets.get(asset_id).cloned(),
+        })?;
+
+        let held = holdings
+            .get(from_wallet_id)
+            .and_then(|h| h.get(asset_id))
+            .copied()
+            .unwrap_or(0.0);
+        if held + QUANTITY_EPSILON < quantity {
+            return Err("Insufficient holding".to_string());
+        }
+        if let Some(restriction) = restrictions.get(&(from_wallet_id.to_string(), asset_id.to_string())) {
+            restriction.check_transfer(held, quantity, now)?;
+        }
+        let value = quantity * assets.get(asset_id).map(|a| a.value).unwrap_or(0.0);
+        let limited = match (wallets.get(from_wallet_id), wallets.get(to_wallet_id)) {
+            (Some(sender), Some(receiver)) => self.check_spending_limits(sender, receiver, value, now)?,
+            _ => Vec::new(),
+        };
+
+        if let Some(sender) = wallets.get_mut(from_wallet_id) {
+            Self::debit_holding(&mut holdings, sender, asset_id, quantity);
+            sender.updated_at = now;
+        }
+        let received = holdings
+            .entry(to_wallet_id.to_string())
+            .or_default()
+            .entry(asset_id.to_string())
+            .or_insert(0.0);
+        let received_before = *received;
+        *received += quantity;
+        let received_after = *received;
+        if let Some(receiver) = wallets.get_mut(to_wallet_id) {
+            if !receiver.assets.iter().any(|id| id == asset_id) {
+                receiver.assets.push(asset_id.to_string());
+            }
+            receiver.updated_at = now;
+        }
+        self.record_audit_changes(
+            AuditAction::Transfer,
+            EntityType::Asset,
+            asset_id,
+            vec![
+                quantity_change(format!("holdings.{}", from_wallet_id), held, held - quantity),
+                quantity_change(format!("holdings.{}", to_wallet_id), received_before, received_after),
+            ],
+        )?;
+        self.record_spend(limited, value, now)?;
+        self.emit_event(DomainEvent::AssetTransferred {
+            from_wallet_id: from_wallet_id.to_string(),
+            to_wallet_id: to_wallet_id.to_string(),
+            asset_id: asset_id.to_string(),
+            quantity,
+        })?;
+        Ok(())
+    }
+
+    /// Get the quantity of an asset held in a wallet
+    pub fn get_holding(&self, wallet_id: &str, asset_id: &str) -> Result<f64, String> {
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(holdings
+            .get(wallet_id)
+            .and_then(|h| h.get(asset_id))
+            .copied()
+            .unwrap_or(0.0))
+    }
+
+    /// Get all asset quantities held in a wallet
+    pub fn get_wallet_holdings(&self, wallet_id: &str) -> Result<HashMap<String, f64>, String> {
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(holdings.get(wallet_id).cloned().unwrap_or_default())
+    }
+
+    /// Get the issued supply of an asset
+    pub fn get_issued_supply(&self, asset_id: &str) -> Result<f64, String> {
+        authorize_current(Permission::ReadAsset, None)?;
+        let supply = self.supply.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(supply.get(asset_id).copied().unwrap_or(0.0))
+    }
+
+    /// Reduce a wallet's holding, dropping the asset from the wallet once it reaches zero
+    pub(crate) fn debit_holding(
+        holdings: &mut HashMap<String, HashMap<String, f64>>,
+        wallet: &mut DigitalWallet,
+        asset_id: &str,
+        quantity: f64,
+    ) {
+        let wallet_holdings = holdings.entry(wallet.id.clone()).or_default();
+        let remaining = wallet_holdings.get(asset_id).copied().unwrap_or(0.0) - quantity;
+        if remaining <= QUANTITY_EPSILON {
+            wallet_holdings.remove(asset_id);
+            wallet.assets.retain(|id| id != asset_id);
+        } else {
+            wallet_holdings.insert(asset_id.to_string(), remaining);
+        }
+    }
 }
 
 impl Default for TokenizationCore {
@@ -319,4 +1027,154 @@ mod tests {
         assert!(wallet.assets.contains(&"asset1".to_string()));
         assert_eq!(wallet.balance, 100.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mint_and_transfer_asset() {
+        let core = TokenizationCore::new();
+
+        let asset = TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Test Asset".to_string(),
+            asset_type: AssetType::Equity,
+            value: 100.0,
+            owner: "user1".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Pending,
+            created_at: 0,
+            updated_at: 0,
+        };
+        core.create_asset(asset).unwrap();
+
+        for id in ["wallet1", "wallet2"] {
+            let wallet = DigitalWallet {
+                id: id.to_string(),
+                owner: "user1".to_string(),
+                assets: vec![],
+                balance: 0.0,
+                wallet_type: WalletType::Custodial,
+                created_at: 0,
+                updated_at: 0,
+            };
+            core.create_wallet(wallet).unwrap();
+        }
+
+        core.mint_to_wallet("wallet1", "asset1", 10.0).unwrap();
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 10.0);
+
+        // Cannot transfer more than is held
+        assert!(core.transfer_asset("wallet1", "wallet2", "asset1", 11.0).is_err());
+
+        core.transfer_asset("wallet1", "wallet2", "asset1", 10.0).unwrap();
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 0.0);
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 10.0);
+        assert!(core.get_wallet("wallet1").unwrap().unwrap().assets.is_empty());
+
+        core.burn_from_wallet("wallet2", "asset1", 4.0).unwrap();
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 6.0);
+    }
+
+    fn setup_held_asset() -> TokenizationCore {
+        let core = TokenizationCore::new();
+
+        let asset = TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Test Asset".to_string(),
+            asset_type: AssetType::Equity,
+            value: 100.0,
+            owner: "user1".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Pending,
+            created_at: 0,
+            updated_at: 0,
+        };
+        core.create_asset(asset).unwrap();
+
+        let wallet = DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "user1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        };
+        core.create_wallet(wallet).unwrap();
+        core.mint_to_wallet("wallet1", "asset1", 5.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_delete_asset_rejects_while_held() {
+        let core = setup_held_asset();
+
+        assert_eq!(core.delete_asset("asset1").unwrap_err(), "Asset is still held by 1 wallet(s)");
+        assert!(core.get_asset("asset1").unwrap().is_some());
+
+        core.burn_from_wallet("wallet1", "asset1", 5.0).unwrap();
+        core.delete_asset("asset1").unwrap();
+        assert!(core.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    fn test_delete_asset_cascade_removes_references() {
+        let core = setup_held_asset();
+
+        let mut wallet2 = core.get_wallet("wallet1").unwrap().unwrap();
+        wallet2.id = "wallet2".to_string();
+        wallet2.assets.clear();
+        core.create_wallet(wallet2).unwrap();
+        core.add_asset_to_wallet("wallet2", "asset1").unwrap();
+        assert_eq!(core.get_wallet("wallet2").unwrap().unwrap().balance, 100.0);
+
+        core.delete_asset_with_policy("asset1", DeletePolicy::Cascade).unwrap();
+        let wallet = core.get_wallet("wallet1").unwrap().unwrap();
+        assert!(wallet.assets.is_empty());
+        assert_eq!(wallet.balance, 0.0);
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 0.0);
+        let wallet2 = core.get_wallet("wallet2").unwrap().unwrap();
+        assert!(wallet2.assets.is_empty());
+        assert_eq!(wallet2.balance, 0.0);
+        assert!(core.check_integrity().unwrap().is_consistent());
+    }
+
+    #[test]
+    fn test_delete_asset_soft_delete_keeps_tombstone() {
+        let core = setup_held_asset();
+
+        core.delete_asset_with_policy("asset1", DeletePolicy::SoftDelete).unwrap();
+        assert!(core.get_asset("asset1").unwrap().is_none());
+        assert!(core.get_asset_tombstone("asset1").unwrap().is_some());
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 5.0);
+        assert!(core.check_integrity().unwrap().is_consistent());
+
+        core.restore_asset("asset1").unwrap();
+        assert!(core.get_asset("asset1").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_close_and_delete_wallet_policies() {
+        let core = setup_held_asset();
+
+        assert!(core.close_wallet("wallet1").is_err());
+        assert!(core.delete_wallet("wallet1", DeletePolicy::Reject).is_err());
+
+        core.delete_wallet("wallet1", DeletePolicy::Cascade).unwrap();
+        assert!(core.get_wallet("wallet1").unwrap().is_none());
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 0.0);
+        assert!(core.check_integrity().unwrap().is_consistent());
+
+        let wallet = DigitalWallet {
+            id: "wallet2".to_string(),
+            owner: "user1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        };
+        core.create_wallet(wallet).unwrap();
+        core.close_wallet("wallet2").unwrap();
+        assert!(core.get_wallet("wallet2").unwrap().is_none());
+        assert!(core.get_archived_wallet("wallet2").unwrap().is_some());
+    }
+}
@@ -0,0 +1,538 @@
+//! Corporate actions engine
+//!
+//! Handles dividends, coupons, pro-rata distributions and (reverse) splits on a
+//! tokenized asset. An action is announced, its holders are snapshotted once the
+//! record date is reached, and processing then credits cash to wallet balances or
+//! rescales holdings. Every step is appended to the action's audit history, and
+//! processing is idempotent: each entitlement is applied at most once.
+
+use crate::access_control::Permission;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, QUANTITY_EPSILON};
+use crate::events::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+/// The economic effect of a corporate action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CorporateActionKind {
+    /// Cash dividend paid per unit held
+    CashDividend { amount_per_unit: f64 },
+    /// Coupon paid per unit held
+    Coupon { amount_per_unit: f64 },
+    /// Fixed cash pool shared pro-rata between holders
+    Distribution { total_amount: f64 },
+    /// Every `denominator` units become `numerator` units; a reverse split has `numerator < denominator`
+    Split { numerator: u32, denominator: u32 },
+}
+
+impl CorporateActionKind {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            CorporateActionKind::CashDividend { amount_per_unit }
+            | CorporateActionKind::Coupon { amount_per_unit } => {
+                if *amount_per_unit <= 0.0 {
+                    return Err("Amount per unit must be positive".to_string());
+                }
+            }
+            CorporateActionKind::Distribution { total_amount } => {
+                if *total_amount <= 0.0 {
+                    return Err("Distribution amount must be positive".to_string());
+                }
+            }
+            CorporateActionKind::Split { numerator, denominator } => {
+                if *numerator == 0 || *denominator == 0 {
+                    return Err("Split ratio terms must be non-zero".to_string());
+                }
+                if numerator == denominator {
+                    return Err("Split ratio must change the number of units".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the action pays cash rather than changing holdings
+    pub fn is_cash(&self) -> bool {
+        !matches!(self, CorporateActionKind::Split { .. })
+    }
+}
+
+/// Lifecycle state of a corporate action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CorporateActionStatus {
+    Announced,
+    Recorded,
+    Processed,
+    Cancelled,
+}
+
+/// A corporate action announced against an asset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorporateAction {
+    pub id: String,
+    pub asset_id: String,
+    pub kind: CorporateActionKind,
+    pub record_date: u64,
+    pub payment_date: u64,
+}
+
+/// A holder's position captured at the record date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HolderPosition {
+    pub wallet_id: String,
+    pub quantity: f64,
+}
+
+/// What a single holder receives from an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entitlement {
+    pub wallet_id: String,
+    pub quantity_held: f64,
+    /// Cash credited to the wallet balance
+    pub cash_amount: f64,
+    /// Holding after a split has been applied
+    pub quantity_after: Option<f64>,
+    pub applied: bool,
+}
+
+/// One entry in an action's audit history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorporateActionEvent {
+    pub at: u64,
+    pub status: CorporateActionStatus,
+    pub note: String,
+}
+
+/// Full record of a corporate action: terms, snapshot, entitlements and history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorporateActionRecord {
+    pub action: CorporateAction,
+    pub status: CorporateActionStatus,
+    pub snapshot: Vec<HolderPosition>,
+    pub entitlements: Vec<Entitlement>,
+    pub history: Vec<CorporateActionEvent>,
+}
+
+impl CorporateActionRecord {
+    fn log(&mut self, at: u64, status: CorporateActionStatus, note: String) {
+        self.status = status.clone();
+        self.history.push(CorporateActionEvent { at, status, note });
+    }
+
+    /// Total cash paid out, or to be paid out, by the action
+    pub fn total_cash(&self) -> f64 {
+        self.entitlements.iter().map(|e| e.cash_amount).sum()
+    }
+}
+
+/// Compute cash entitlements for a holder snapshot
+pub fn compute_cash_entitlements(kind: &CorporateActionKind, snapshot: &[HolderPosition]) -> Vec<Entitlement> {
+    let total_units: f64 = snapshot.iter().map(|p| p.quantity).sum();
+    snapshot
+        .iter()
+        .map(|position| {
+            let cash_amount = match kind {
+                CorporateActionKind::CashDividend { amount_per_unit }
+                | CorporateActionKind::Coupon { amount_per_unit } => amount_per_unit * position.quantity,
+                CorporateActionKind::Distribution { total_amount } => {
+                    if total_units > 0.0 {
+                        total_amount * position.quantity / total_units
+                    } else {
+                        0.0
+                    }
+                }
+                CorporateActionKind::Split { .. } => 0.0,
+            };
+            Entitlement {
+                wallet_id: position.wallet_id.clone(),
+                quantity_held: position.quantity,
+                cash_amount,
+                quantity_after: None,
+                applied: false,
+            }
+        })
+        .collect()
+}
+
+impl TokenizationCore {
+    /// Announce a corporate action against an existing asset
+    pub fn announce_corporate_action(&self, action: CorporateAction) -> Result<String, String> {
+        self.authorize_asset(Permission::UpdateAsset, &action.asset_id)?;
+        action.kind.validate()?;
+        if action.payment_date < action.record_date {
+            return Err("Payment date must not precede record date".to_string());
+        }
+        if self.get_asset(&action.asset_id)?.is_none() {
+            return Err("Asset not found".to_string());
+        }
+        self.ensure_ids_not_frozen(&[], &[&action.asset_id])?;
+        let now = self.now();
+        let mut actions = self.corporate_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        if actions.contains_key(&action.id) {
+            return Err("Corporate action already exists".to_string());
+        }
+        let action_id = action.id.clone();
+        let mut record = CorporateActionRecord {
+            action,
+            status: CorporateActionStatus::Announced,
+            snapshot: Vec::new(),
+            entitlements: Vec::new(),
+            history: Vec::new(),
+        };
+        record.log(now, CorporateActionStatus::Announced, "Action announced".to_string());
+        self.record_audit(AuditAction::Create, EntityType::CorporateAction, &action_id, None::<&CorporateActionRecord>, Some(&record))?;
+        actions.insert(action_id.clone(), record);
+        Ok(action_id)
+    }
+
+    /// Snapshot the holders of the action's asset once the record date has been reached
+    pub fn record_corporate_action(&self, action_id: &str) -> Result<CorporateActionRecord, String> {
+        self.authorize_action(action_id)?;
+        self.ensure_action_not_frozen(action_id)?;
+        let now = self.now();
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut actions = self.corporate_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        let record = actions.get_mut(action_id).ok_or("Corporate action not found")?;
+
+        if record.status != CorporateActionStatus::Announced {
+            return Ok(record.clone());
+        }
+        if now < record.action.record_date {
+            return Err(format!("Record date {} has not been reached", record.action.record_date));
+        }
+
+        let before = record.clone();
+        let asset_id = &record.action.asset_id;
+        let mut snapshot: Vec<HolderPosition> = holdings
+            .iter()
+            .filter_map(|(wallet_id, positions)| {
+                positions
+                    .get(asset_id)
+                    .filter(|q| **q > QUANTITY_EPSILON)
+                    .map(|q| HolderPosition {
+                        wallet_id: wallet_id.clone(),
+                        quantity: *q,
+                    })
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+
+        if record.action.kind.is_cash() {
+            record.entitlements = compute_cash_entitlements(&record.action.kind, &snapshot);
+        }
+        let note = format!("Snapshot of {} holders taken", snapshot.len());
+        record.snapshot = snapshot;
+        record.log(now, CorporateActionStatus::Recorded, note);
+        self.record_audit(AuditAction::Update, EntityType::CorporateAction, action_id, Some(&before), Some(&*record))?;
+        Ok(record.clone())
+    }
+
+    /// Apply a recorded corporate action
+    ///
+    /// Cash actions credit each entitled wallet's balance; splits rescale every
+    /// current holding of the asset, its issued supply and any attached vesting or
+    /// holding-period quantities. Entitlements of wallets that no longer exist stay
+    /// outstanding and the action stays `Recorded`, so a later run pays them once the
+    /// wallet is back. Processing an already processed action is a no-op.
+    pub fn process_corporate_action(&self, action_id: &str) -> Result<CorporateActionRecord, String> {
+        self.authorize_action(action_id)?;
+        self.ensure_action_not_frozen(action_id)?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut holdings = self.holdings.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut supply = self.supply.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut restrictions = self.restrictions.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut actions = self.corporate_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        let record = actions.get_mut(action_id).ok_or("Corporate action not found")?;
+
+        match record.status {
+            CorporateActionStatus::Processed => return Ok(record.clone()),
+            CorporateActionStatus::Cancelled => return Err("Corporate action was cancelled".to_string()),
+            CorporateActionStatus::Announced => return Err("Corporate action has not been recorded".to_string()),
+            CorporateActionStatus::Recorded => {}
+        }
+        if now < record.action.payment_date {
+            return Err(format!("Payment date {} has not been reached", record.action.payment_date));
+        }
+
+        let before = record.clone();
+        let asset_id = record.action.asset_id.clone();
+        match record.action.kind.clone() {
+            CorporateActionKind::Split { numerator, denominator } => {
+                let factor = numerator as f64 / denominator as f64;
+                let mut entitlements = Vec::new();
+                for (wallet_id, positions) in holdings.iter_mut() {
+                    if let Some(quantity) = positions.get_mut(&asset_id) {
+                        let before = *quantity;
+                        *quantity = before * factor;
+                        entitlements.push(Entitlement {
+                            wallet_id: wallet_id.clone(),
+                            quantity_held: before,
+                            cash_amount: 0.0,
+                            quantity_after: Some(*quantity),
+                            applied: true,
+                        });
+                        if let Some(wallet) = wallets.get_mut(wallet_id) {
+                            wallet.updated_at = now;
+                        }
+                    }
+                }
+                entitlements.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+                if let Some(issued) = supply.get_mut(&asset_id) {
+                    *issued *= factor;
+                }
+                self.emit_event(DomainEvent::HoldingsRescaled {
+                    asset_id: asset_id.clone(),
+                    factor,
+                })?;
+                for ((_, restricted_asset), restriction) in restrictions.iter_mut() {
+                    if *restricted_asset == asset_id {
+                        restriction.scale(factor);
+                    }
+                }
+                record.entitlements = entitlements;
+                let note = format!("Applied {}-for-{} split to {} holdings", numerator, denominator, record.entitlements.len());
+                record.log(now, CorporateActionStatus::Processed, note);
+            }
+            _ => {
+                let mut credited = 0;
+                for entitlement in record.entitlements.iter_mut().filter(|e| !e.applied) {
+                    let Some(wallet) = wallets.get_mut(&entitlement.wallet_id) else {
+                        continue;
+                    };
+                    wallet.balance += entitlement.cash_amount;
+                    wallet.updated_at = now;
+                    entitlement.applied = true;
+                    self.emit_event(DomainEvent::WalletBalanceChanged {
+                        wallet_id: entitlement.wallet_id.clone(),
+                        amount: entitlement.cash_amount,
+                    })?;
+                    credited += 1;
+                }
+                let outstanding = record.entitlements.iter().filter(|e| !e.applied).count();
+                let note = format!("Credited {} wallets, {} outstanding", credited, outstanding);
+                let status = if outstanding == 0 {
+                    CorporateActionStatus::Processed
+                } else {
+                    CorporateActionStatus::Recorded
+                };
+                record.log(now, status, note);
+            }
+        }
+        self.record_audit(AuditAction::Process, EntityType::CorporateAction, action_id, Some(&before), Some(&*record))?;
+        Ok(record.clone())
+    }
+
+    /// Cancel an action that has only been announced
+    ///
+    /// Once the holders are snapshotted the action may already have paid some of
+    /// them, so it can no longer be withdrawn.
+    pub fn cancel_corporate_action(&self, action_id: &str, reason: &str) -> Result<(), String> {
+        self.authorize_action(action_id)?;
+        self.ensure_action_not_frozen(action_id)?;
+        let now = self.now();
+        let mut actions = self.corporate_actions.write().map_err(|_| "Failed to acquire write lock")?;
+        let record = actions.get_mut(action_id).ok_or("Corporate action not found")?;
+        if record.status != CorporateActionStatus::Announced {
+            return Err(format!("{:?} corporate actions cannot be cancelled", record.status));
+        }
+        let before = record.clone();
+        record.log(now, CorporateActionStatus::Cancelled, reason.to_string());
+        self.record_audit(AuditAction::Cancel, EntityType::CorporateAction, action_id, Some(&before), Some(&*record))?;
+        Ok(())
+    }
+
+    /// Only those who may update the asset run its corporate actions
+    fn authorize_action(&self, action_id: &str) -> Result<(), String> {
+        match self.get_corporate_action(action_id)? {
+            Some(record) => self.authorize_asset(Permission::UpdateAsset, &record.action.asset_id),
+            None => Ok(()),
+        }
+    }
+
+    fn ensure_action_not_frozen(&self, action_id: &str) -> Result<(), String> {
+        match self.get_corporate_action(action_id)? {
+            Some(record) => self.ensure_ids_not_frozen(&[], &[&record.action.asset_id]),
+            None => self.ensure_ids_not_frozen(&[], &[]),
+        }
+    }
+
+    /// Get a corporate action record by ID
+    pub fn get_corporate_action(&self, action_id: &str) -> Result<Option<CorporateActionRecord>, String> {
+        let actions = self.corporate_actions.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(actions.get(action_id).cloned())
+    }
+
+    /// Get all corporate actions for an asset, ordered by record date
+    pub fn get_corporate_actions_for_asset(&self, asset_id: &str) -> Result<Vec<CorporateActionRecord>, String> {
+        let actions = self.corporate_actions.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut records: Vec<CorporateActionRecord> = actions
+            .values()
+            .filter(|r| r.action.asset_id == asset_id)
+            .cloned()
+            .collect();
+        records.sort_by_key(|r| r.action.record_date);
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject, PERMISSION_DENIED};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, DeletePolicy, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use crate::vesting::VestingSchedule;
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            value: 10.0,
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for id in ["wallet1", "wallet2"] {
+            core.create_wallet(wallet(id, id, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 75.0).unwrap();
+        core.mint_to_wallet("wallet2", "asset1", 25.0).unwrap();
+        core
+    }
+
+    fn action(id: &str, kind: CorporateActionKind) -> CorporateAction {
+        CorporateAction {
+            id: id.to_string(),
+            asset_id: "asset1".to_string(),
+            kind,
+            record_date: 100,
+            payment_date: 110,
+        }
+    }
+
+    #[test]
+    fn test_distribution_is_pro_rata_and_idempotent() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.announce_corporate_action(action("ca1", CorporateActionKind::Distribution { total_amount: 1_000.0 }))
+            .unwrap();
+
+        assert!(core.record_corporate_action("ca1").is_err());
+        clock.set(100);
+        let recorded = core.record_corporate_action("ca1").unwrap();
+        assert_eq!(recorded.snapshot.len(), 2);
+
+        // Holdings moving after the record date do not change entitlements
+        core.transfer_asset("wallet1", "wallet2", "asset1", 75.0).unwrap();
+
+        clock.set(110);
+        let processed = core.process_corporate_action("ca1").unwrap();
+        assert_eq!(processed.total_cash(), 1_000.0);
+        core.process_corporate_action("ca1").unwrap();
+
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 750.0);
+        assert_eq!(core.get_wallet("wallet2").unwrap().unwrap().balance, 250.0);
+        assert_eq!(processed.history.len(), 3);
+    }
+
+    #[test]
+    fn test_split_and_reverse_split_rescale_holdings() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.add_vesting_schedule(
+            "wallet2",
+            "asset1",
+            VestingSchedule::Cliff {
+                total: 25.0,
+                cliff_at: 1_000,
+            },
+        )
+        .unwrap();
+        core.announce_corporate_action(action("split", CorporateActionKind::Split { numerator: 2, denominator: 1 }))
+            .unwrap();
+
+        clock.set(110);
+        core.record_corporate_action("split").unwrap();
+        core.process_corporate_action("split").unwrap();
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 150.0);
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 200.0);
+        assert_eq!(core.unvested_quantity("wallet2", "asset1", 110).unwrap(), 50.0);
+
+        let mut reverse = action("reverse", CorporateActionKind::Split { numerator: 1, denominator: 10 });
+        reverse.record_date = 110;
+        core.announce_corporate_action(reverse).unwrap();
+        core.record_corporate_action("reverse").unwrap();
+        core.process_corporate_action("reverse").unwrap();
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 5.0);
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 20.0);
+    }
+
+    #[test]
+    fn test_cancelled_action_cannot_be_processed() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.announce_corporate_action(action("ca1", CorporateActionKind::CashDividend { amount_per_unit: 0.5 }))
+            .unwrap();
+        core.cancel_corporate_action("ca1", "Board withdrew dividend").unwrap();
+
+        clock.set(110);
+        assert!(core.process_corporate_action("ca1").is_err());
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 0.0);
+
+        // Only announced actions can be withdrawn
+        core.announce_corporate_action(action("ca2", CorporateActionKind::CashDividend { amount_per_unit: 0.5 }))
+            .unwrap();
+        core.record_corporate_action("ca2").unwrap();
+        assert!(core.cancel_corporate_action("ca2", "Too late").is_err());
+        assert_eq!(core.get_corporate_action("ca2").unwrap().unwrap().status, CorporateActionStatus::Recorded);
+    }
+
+    #[test]
+    fn test_entitlements_of_missing_wallets_stay_outstanding() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.announce_corporate_action(action("ca1", CorporateActionKind::CashDividend { amount_per_unit: 2.0 }))
+            .unwrap();
+        clock.set(110);
+        core.record_corporate_action("ca1").unwrap();
+        let wallet2 = core.get_wallet("wallet2").unwrap().unwrap();
+        core.transfer_asset("wallet2", "wallet1", "asset1", 25.0).unwrap();
+        core.delete_wallet("wallet2", DeletePolicy::Reject).unwrap();
+
+        let partial = core.process_corporate_action("ca1").unwrap();
+        assert_eq!(partial.status, CorporateActionStatus::Recorded);
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 150.0);
+        assert!(partial.entitlements.iter().any(|e| e.wallet_id == "wallet2" && !e.applied));
+
+        core.create_wallet(wallet2).unwrap();
+        let processed = core.process_corporate_action("ca1").unwrap();
+        assert_eq!(processed.status, CorporateActionStatus::Processed);
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 150.0);
+        assert_eq!(core.get_wallet("wallet2").unwrap().unwrap().balance, 50.0);
+    }
+
+    #[test]
+    fn test_only_the_issuer_runs_corporate_actions() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        let holder = Subject::new("wallet1", &[Role::Investor]);
+        let other_issuer = Subject::new("other", &[Role::Issuer]);
+        let issuer = Subject::new("issuer", &[Role::Issuer]);
+        let dividend = || action("ca1", CorporateActionKind::CashDividend { amount_per_unit: 1.0 });
+
+        let error = core.as_subject(&holder, || core.announce_corporate_action(dividend())).unwrap_err();
+        assert!(error.starts_with(PERMISSION_DENIED), "{}", error);
+        assert!(core.as_subject(&other_issuer, || core.announce_corporate_action(dividend())).is_err());
+        core.as_subject(&issuer, || core.announce_corporate_action(dividend())).unwrap();
+
+        clock.set(110);
+        core.record_corporate_action("ca1").unwrap();
+        assert!(core.as_subject(&other_issuer, || core.process_corporate_action("ca1")).is_err());
+        assert!(core.as_subject(&holder, || core.cancel_corporate_action("ca1", "no")).is_err());
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 0.0);
+        core.as_subject(&issuer, || core.process_corporate_action("ca1")).unwrap();
+        assert_eq!(core.get_wallet("wallet1").unwrap().unwrap().balance, 75.0);
+    }
+}
@@ -0,0 +1,640 @@
+//! Custody arrangements and signing requirements driven by `WalletType`
+//!
+//! Custodial wallets are held by the platform, either pooled with other wallets as
+//! sub-ledgers of an omnibus account or in a segregated account of their own. The
+//! platform moves assets between custodial wallets on any authorized instruction,
+//! but a withdrawal out of custody, i.e. a transfer to a non-custodial or hybrid
+//! wallet, needs the platform's approval. Non-custodial wallets are controlled by
+//! the client alone, so every outgoing transfer needs the client's signature, and
+//! hybrid wallets need both the client and the platform to co-sign.
+//!
+//! A direct call counts as signed by its caller: the wallet owner signs as the
+//! client and a subject holding `ManageCustody` signs as the platform. Calls made
+//! outside `as_subject` are the platform itself, and also the client when running
+//! on the owner's behalf. Once a wallet has registered keys, only an operation
+//! signed with one of them counts as the client's signature, and once it has
+//! signers only their quorum does (see `multisig`). Transfers needing more than
+//! the caller's signature go through a `SigningRequest`, which runs once every
+//! required party has signed. A request runs as the subject who opened it, so
+//! their transfer permission and any four-eyes rule apply to it as to a direct
+//! transfer; transfers needing approval are refused when requested and must be
+//! submitted for approval instead.
+
+use crate::access_control::{authorize_current, current_subject, without_subject, Permission, Subject, PERMISSION_DENIED};
+use crate::approvals::OperationKind;
+use crate::audit::{current_actor, AuditAction, EntityType};
+use crate::core_component::{DigitalWallet, TokenizationCore, WalletType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prefix of every rejection caused by a missing signature
+pub const SIGNATURE_REQUIRED: &str = "Signature required";
+
+/// Party whose signature an outgoing transfer may need
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SignerRole {
+    /// The wallet owner
+    Client,
+    Platform,
+}
+
+impl WalletType {
+    /// Parties that must sign a transfer out of a wallet of this type into one of type `destination`
+    pub fn required_signers(&self, destination: &WalletType) -> Vec<SignerRole> {
+        match (self, destination) {
+            (WalletType::Custodial, WalletType::Custodial) => Vec::new(),
+            (WalletType::Custodial, _) => vec![SignerRole::Platform],
+            (WalletType::NonCustodial, _) => vec![SignerRole::Client],
+            (WalletType::Hybrid, _) => vec![SignerRole::Client, SignerRole::Platform],
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            WalletType::Custodial => "custodial",
+            WalletType::NonCustodial => "non-custodial",
+            WalletType::Hybrid => "hybrid",
+        }
+    }
+}
+
+/// How the platform holds a custodial wallet's assets
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CustodyModel {
+    /// Pooled with other wallets in a shared account, each wallet being a sub-ledger
+    Omnibus { account_id: String },
+    /// In an account of its own, identified by the wallet ID
+    #[default]
+    Segregated,
+}
+
+/// One wallet's share of a custody account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubLedger {
+    pub wallet_id: String,
+    pub owner: String,
+    pub holdings: HashMap<String, f64>,
+}
+
+/// A custody account with its sub-ledgers and the total it holds per asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyAccount {
+    pub account_id: String,
+    pub model: CustodyModel,
+    pub sub_ledgers: Vec<SubLedger>,
+    pub totals: HashMap<String, f64>,
+}
+
+/// A transfer waiting for its signatures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutgoingTransfer {
+    pub from_wallet_id: String,
+    pub to_wallet_id: String,
+    pub asset_id: String,
+    pub quantity: f64,
+}
+
+/// A party's signature on a signing request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoSignature {
+    pub role: SignerRole,
+    pub signer: String,
+    pub signed_at: u64,
+}
+
+/// Where a signing request stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SigningStatus {
+    Pending,
+    /// Fully approved and running
+    Executing,
+    Executed,
+    /// Fully signed, but the transfer itself failed
+    Failed { error: String },
+    Cancelled,
+}
+
+/// An outgoing transfer collecting the signatures its sending wallet's type requires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigningRequest {
+    pub id: String,
+    pub transfer: OutgoingTransfer,
+    pub required: Vec<SignerRole>,
+    pub signatures: Vec<CoSignature>,
+    pub status: SigningStatus,
+    pub requested_by: String,
+    pub requested_at: u64,
+    pub closed_at: Option<u64>,
+    /// Subject the transfer runs as; `None` for requests opened by the platform itself
+    #[serde(skip)]
+    pub(crate) requester: Option<Subject>,
+}
+
+impl SigningRequest {
+    /// Required roles nobody has signed for yet
+    pub fn missing(&self) -> Vec<SignerRole> {
+        self.required
+            .iter()
+            .filter(|role| !self.signatures.iter().any(|s| s.role == **role))
+            .copied()
+            .collect()
+    }
+}
+
+/// Refuse a transfer from `from` to `to` unless `signed` covers every required signer
+pub(crate) fn check_signatures(from: &DigitalWallet, to: &DigitalWallet, signed: &[SignerRole]) -> Result<(), String> {
+    let missing: Vec<&str> = from
+        .wallet_type
+        .required_signers(&to.wallet_type)
+        .into_iter()
+        .filter(|role| !signed.contains(role))
+        .map(|role| match role {
+            SignerRole::Client => "the client's signature",
+            SignerRole::Platform => "the platform's signature",
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "{}: transfers out of {} wallet {} need {}; request signatures instead",
+        SIGNATURE_REQUIRED,
+        from.wallet_type.label(),
+        from.id,
+        missing.join(" and ")
+    ))
+}
+
+/// Whether an error from the core is a missing signature
+pub fn is_signature_required(error: &str) -> bool {
+    error.starts_with(SIGNATURE_REQUIRED)
+}
+
+impl TokenizationCore {
+    /// Roles the current caller signs for on operations of `wallet`
+    pub(crate) fn caller_signatures(&self, wallet: &DigitalWallet) -> Result<Vec<SignerRole>, String> {
+        // Owners of wallets with keys sign with them rather than by calling, and
+        // wallets with signers are signed for by their quorum
+        let client = !self.has_wallet_keys(&wallet.id)? && !self.has_wallet_signers(&wallet.id)?;
+        Ok(match current_subject() {
+            Some(subject) if subject.id == wallet.owner => if client { vec![SignerRole::Client] } else { Vec::new() },
+            Some(subject) if subject.may(Permission::ManageCustody) => vec![SignerRole::Platform],
+            Some(_) => Vec::new(),
+            None if client && current_actor() == wallet.owner => vec![SignerRole::Client, SignerRole::Platform],
+            None => vec![SignerRole::Platform],
+        })
+    }
+
+    /// Choose how the platform holds a custodial wallet
+    pub fn set_custody_model(&self, wallet_id: &str, model: CustodyModel) -> Result<(), String> {
+        authorize_current(Permission::ManageCustody, None)?;
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let wallet = wallets.get(wallet_id).ok_or("Wallet not found")?;
+        if wallet.wallet_type != WalletType::Custodial {
+            return Err("Only custodial wallets have a custody model".to_string());
+        }
+        if let CustodyModel::Omnibus { account_id } = &model {
+            if account_id.is_empty() {
+                return Err("Omnibus account ID must not be empty".to_string());
+            }
+            // Segregated accounts are named after their wallet
+            if wallets.contains_key(account_id) {
+                return Err("Omnibus account ID must not be a wallet ID".to_string());
+            }
+        }
+        let mut models = self.custody_models.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = models.insert(wallet_id.to_string(), model.clone());
+        let action = if before.is_some() { AuditAction::Update } else { AuditAction::Create };
+        self.record_audit(action, EntityType::Custody, wallet_id, before.as_ref(), Some(&model))
+    }
+
+    /// How the platform holds a wallet, or `None` if it is not custodial
+    pub fn get_custody_model(&self, wallet_id: &str) -> Result<Option<CustodyModel>, String> {
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let wallet = wallets.get(wallet_id).ok_or("Wallet not found")?;
+        if wallet.wallet_type != WalletType::Custodial {
+            return Ok(None);
+        }
+        let models = self.custody_models.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(Some(models.get(wallet_id).cloned().unwrap_or_default()))
+    }
+
+    /// Every custody account with the sub-ledgers of the custodial wallets it pools
+    pub fn get_custody_accounts(&self) -> Result<Vec<CustodyAccount>, String> {
+        authorize_current(Permission::ReadReports, None)?;
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+        let models = self.custody_models.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut accounts: HashMap<String, CustodyAccount> = HashMap::new();
+        for wallet in wallets.values().filter(|w| w.wallet_type == WalletType::Custodial) {
+            let model = models.get(&wallet.id).cloned().unwrap_or_default();
+            let account_id = match &model {
+                CustodyModel::Omnibus { account_id } => account_id.clone(),
+                CustodyModel::Segregated => wallet.id.clone(),
+            };
+            let account = accounts.entry(account_id.clone()).or_insert_with(|| CustodyAccount {
+                account_id,
+                model,
+                sub_ledgers: Vec::new(),
+                totals: HashMap::new(),
+            });
+            let held = holdings.get(&wallet.id).cloned().unwrap_or_default();
+            for (asset_id, quantity) in &held {
+                *account.totals.entry(asset_id.clone()).or_insert(0.0) += quantity;
+            }
+            account.sub_ledgers.push(SubLedger {
+                wallet_id: wallet.id.clone(),
+                owner: wallet.owner.clone(),
+                holdings: held,
+            });
+        }
+        let mut accounts: Vec<CustodyAccount> = accounts.into_values().collect();
+        for account in &mut accounts {
+            account.sub_ledgers.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+        }
+        accounts.sort_by(|a, b| a.account_id.cmp(&b.account_id));
+        Ok(accounts)
+    }
+
+    /// A custody account by ID
+    pub fn get_custody_account(&self, account_id: &str) -> Result<Option<CustodyAccount>, String> {
+        Ok(self.get_custody_accounts()?.into_iter().find(|a| a.account_id == account_id))
+    }
+
+    /// Open a signing request for a transfer, signed by the caller; it runs at once
+    /// if the caller's signature is all the sending wallet needs
+    pub fn request_transfer_signatures(&self, transfer: OutgoingTransfer) -> Result<SigningRequest, String> {
+        if transfer.quantity <= 0.0 {
+            return Err("Quantity must be positive".to_string());
+        }
+        let (sender, receiver) = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            match (wallets.get(&transfer.from_wallet_id), wallets.get(&transfer.to_wallet_id)) {
+                (Some(sender), Some(receiver)) => (sender.clone(), receiver.clone()),
+                _ => return Err("Wallet not found".to_string()),
+            }
+        };
+        let signer = current_actor();
+        let roles = self.caller_signatures(&sender)?;
+        if roles.is_empty() && signer != sender.owner {
+            return Err(format!("{}: {} may not sign for wallet {}", PERMISSION_DENIED, signer, sender.id));
+        }
+        // Signatures do not replace the checks a direct transfer goes through
+        authorize_current(Permission::Transfer, Some(&sender.owner))?;
+        self.check_four_eyes(OperationKind::Transfer, Some(transfer.quantity))?;
+        let now = self.now();
+        let required = sender.wallet_type.required_signers(&receiver.wallet_type);
+        let signatures = required
+            .iter()
+            .filter(|role| roles.contains(role))
+            .map(|role| CoSignature {
+                role: *role,
+                signer: signer.clone(),
+                signed_at: now,
+            })
+            .collect();
+        self.open_signing_request(transfer, required, signer, signatures)
+    }
+
+    /// Record a signing request with the signatures it already has; it runs at once if they are all it needs
+    pub(crate) fn open_signing_request(
+        &self,
+        transfer: OutgoingTransfer,
+        required: Vec<SignerRole>,
+        requested_by: String,
+        signatures: Vec<CoSignature>,
+    ) -> Result<SigningRequest, String> {
+        let request = {
+            let mut requests = self.signing_requests.write().map_err(|_| "Failed to acquire write lock")?;
+            let request = SigningRequest {
+                id: format!("signing-{}", requests.len() + 1),
+                signatures,
+                transfer,
+                required,
+                status: SigningStatus::Pending,
+                requested_by,
+                requested_at: self.now(),
+                closed_at: None,
+                requester: current_subject(),
+            };
+            requests.insert(request.id.clone(), request.clone());
+            request
+        };
+        self.record_audit(AuditAction::Create, EntityType::SigningRequest, &request.id, None::<&SigningRequest>, Some(&request))?;
+        if request.missing().is_empty() {
+            return self.execute_signing_request(&request.id);
+        }
+        Ok(request)
+    }
+
+    /// Add the caller's signature to a request, running the transfer once it is fully signed
+    pub fn sign_request(&self, request_id: &str) -> Result<SigningRequest, String> {
+        let sender = {
+            let wallet_id = self.get_signing_request(request_id)?.ok_or("Signing request not found")?.transfer.from_wallet_id;
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            wallets.get(&wallet_id).cloned().ok_or("Wallet not found")?
+        };
+        let roles = self.caller_signatures(&sender)?;
+        self.add_signature(request_id, current_actor(), &roles)
+    }
+
+    /// Sign a request as `signer` in the first missing role out of `roles`
+    pub(crate) fn add_signature(&self, request_id: &str, signer: String, roles: &[SignerRole]) -> Result<SigningRequest, String> {
+        let now = self.now();
+        let (before, request) = {
+            let mut requests = self.signing_requests.write().map_err(|_| "Failed to acquire write lock")?;
+            let request = requests.get_mut(request_id).ok_or("Signing request not found")?;
+            if request.status != SigningStatus::Pending {
+                return Err(format!("Signing request is already {:?}", request.status));
+            }
+            // Co-signers must be distinct parties
+            if request.signatures.iter().any(|s| s.signer == signer) {
+                return Err(format!("{} has already signed this request", signer));
+            }
+            let Some(role) = request.missing().into_iter().find(|role| roles.contains(role)) else {
+                return Err(format!("{}: {} has no signature outstanding on this request", PERMISSION_DENIED, signer));
+            };
+            let before = request.clone();
+            request.signatures.push(CoSignature {
+                role,
+                signer,
+                signed_at: now,
+            });
+            (before, request.clone())
+        };
+        self.record_audit(AuditAction::Approve, EntityType::SigningRequest, request_id, Some(&before), Some(&request))?;
+        if request.missing().is_empty() {
+            return self.execute_signing_request(request_id);
+        }
+        Ok(request)
+    }
+
+    fn execute_signing_request(&self, request_id: &str) -> Result<SigningRequest, String> {
+        let request = self.get_signing_request(request_id)?.ok_or("Signing request not found")?;
+        let signed: Vec<SignerRole> = request.signatures.iter().map(|s| s.role).collect();
+        let transfer = &request.transfer;
+        let run = || {
+            self.transfer_signed(&transfer.from_wallet_id, &transfer.to_wallet_id, &transfer.asset_id, transfer.quantity, Some(&signed))
+        };
+        // The signatures stand in for the caller's, not for the requester's permissions:
+        // run as the requester so that their authorization and four-eyes rules are checked again
+        let outcome = match &request.requester {
+            Some(requester) => self.as_subject(requester, run),
+            None => without_subject(|| self.with_actor(&request.requested_by, run)),
+        };
+        let mut requests = self.signing_requests.write().map_err(|_| "Failed to acquire write lock")?;
+        let stored = requests.get_mut(request_id).ok_or("Signing request not found")?;
+        let before = stored.clone();
+        stored.status = match outcome {
+            Ok(()) => SigningStatus::Executed,
+            Err(error) => SigningStatus::Failed { error },
+        };
+        stored.closed_at = Some(self.now());
+        let request = stored.clone();
+        drop(requests);
+        self.record_audit(AuditAction::Process, EntityType::SigningRequest, request_id, Some(&before), Some(&request))?;
+        Ok(request)
+    }
+
+    /// Withdraw a pending request; open to its requester and to anyone who could sign it
+    pub fn cancel_signing_request(&self, request_id: &str) -> Result<SigningRequest, String> {
+        let actor = current_actor();
+        let existing = self.get_signing_request(request_id)?.ok_or("Signing request not found")?;
+        let sender = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            wallets.get(&existing.transfer.from_wallet_id).cloned()
+        };
+        let may_cancel = match &sender {
+            _ if actor == existing.requested_by => true,
+            Some(wallet) => actor == wallet.owner || !self.caller_signatures(wallet)?.is_empty(),
+            None => false,
+        };
+        if !may_cancel {
+            return Err(format!("{}: {} may not cancel this request", PERMISSION_DENIED, actor));
+        }
+        let mut requests = self.signing_requests.write().map_err(|_| "Failed to acquire write lock")?;
+        let request = requests.get_mut(request_id).ok_or("Signing request not found")?;
+        if request.status != SigningStatus::Pending {
+            return Err(format!("Signing request is already {:?}", request.status));
+        }
+        let before = request.clone();
+        request.status = SigningStatus::Cancelled;
+        request.closed_at = Some(self.now());
+        let request = request.clone();
+        drop(requests);
+        self.record_audit(AuditAction::Cancel, EntityType::SigningRequest, request_id, Some(&before), Some(&request))?;
+        Ok(request)
+    }
+
+    /// Get a signing request with the signatures collected so far
+    pub fn get_signing_request(&self, request_id: &str) -> Result<Option<SigningRequest>, String> {
+        let requests = self.signing_requests.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(requests.get(request_id).cloned())
+    }
+
+    /// Signing requests the caller may see, optionally only those sent from one wallet, oldest first
+    pub fn get_signing_requests(&self, wallet_id: Option<&str>) -> Result<Vec<SigningRequest>, String> {
+        let mut found: Vec<SigningRequest> = {
+            let requests = self.signing_requests.read().map_err(|_| "Failed to acquire read lock")?;
+            requests
+                .values()
+                .filter(|r| wallet_id.is_none_or(|id| r.transfer.from_wallet_id == id))
+                .cloned()
+                .collect()
+        };
+        found.retain(|r| self.authorize_wallet(Permission::ReadWallet, &r.transfer.from_wallet_id).is_ok());
+        // IDs are numbered in request order
+        found.sort_by_key(|r| r.id.trim_start_matches("signing-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::Role;
+    use crate::approvals::ApprovalRule;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset};
+    use crate::test_support::{asset, wallet};
+
+    fn setup(wallets: &[(&str, &str, WalletType)]) -> TokenizationCore {
+        let core = TokenizationCore::new();
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Commodity)
+        })
+        .unwrap();
+        for (id, owner, wallet_type) in wallets {
+            core.create_wallet(wallet(id, owner, wallet_type.clone())).unwrap();
+        }
+        core
+    }
+
+    fn transfer(from: &str, to: &str, quantity: f64) -> OutgoingTransfer {
+        OutgoingTransfer {
+            from_wallet_id: from.to_string(),
+            to_wallet_id: to.to_string(),
+            asset_id: "asset1".to_string(),
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_custodial_sub_ledgers_and_platform_approved_withdrawals() {
+        let core = setup(&[
+            ("alice-c", "alice", WalletType::Custodial),
+            ("bob-c", "bob", WalletType::Custodial),
+            ("carol-c", "carol", WalletType::Custodial),
+            ("alice-own", "alice", WalletType::NonCustodial),
+        ]);
+        core.mint_to_wallet("alice-c", "asset1", 100.0).unwrap();
+        core.mint_to_wallet("bob-c", "asset1", 40.0).unwrap();
+        core.mint_to_wallet("carol-c", "asset1", 5.0).unwrap();
+        let omnibus = CustodyModel::Omnibus {
+            account_id: "omnibus-1".to_string(),
+        };
+        core.set_custody_model("alice-c", omnibus.clone()).unwrap();
+        core.set_custody_model("bob-c", omnibus.clone()).unwrap();
+        assert!(core.set_custody_model("alice-own", omnibus.clone()).is_err());
+
+        let accounts = core.get_custody_accounts().unwrap();
+        assert_eq!(accounts.iter().map(|a| a.account_id.as_str()).collect::<Vec<_>>(), ["carol-c", "omnibus-1"]);
+        assert_eq!(accounts[0].model, CustodyModel::Segregated);
+        let pooled = core.get_custody_account("omnibus-1").unwrap().unwrap();
+        assert_eq!(pooled.totals["asset1"], 140.0);
+        assert_eq!(pooled.sub_ledgers.iter().map(|l| l.holdings["asset1"]).collect::<Vec<_>>(), [100.0, 40.0]);
+
+        // Moves inside custody only need the client's instruction
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        core.as_subject(&alice, || core.transfer_asset("alice-c", "bob-c", "asset1", 10.0)).unwrap();
+        assert_eq!(core.get_custody_account("omnibus-1").unwrap().unwrap().totals["asset1"], 140.0);
+
+        // Leaving custody needs the platform to approve
+        let error = core.as_subject(&alice, || core.transfer_asset("alice-c", "alice-own", "asset1", 30.0)).unwrap_err();
+        assert!(is_signature_required(&error), "{}", error);
+        let request = core
+            .as_subject(&alice, || core.request_transfer_signatures(transfer("alice-c", "alice-own", 30.0)))
+            .unwrap();
+        assert_eq!(request.status, SigningStatus::Pending);
+        assert_eq!(request.missing(), [SignerRole::Platform]);
+        assert!(core.as_subject(&alice, || core.sign_request(&request.id)).is_err());
+        let signed = core.as_subject(&agent, || core.sign_request(&request.id)).unwrap();
+        assert_eq!(signed.status, SigningStatus::Executed);
+        assert_eq!(signed.signatures.iter().map(|s| s.signer.as_str()).collect::<Vec<_>>(), ["agent"]);
+        assert_eq!(core.get_holding("alice-own", "asset1").unwrap(), 30.0);
+        assert_eq!(core.get_custody_account("omnibus-1").unwrap().unwrap().totals["asset1"], 110.0);
+
+        // A platform operator's own withdrawal is approved by making it
+        core.as_subject(&agent, || core.transfer_asset("alice-c", "alice-own", "asset1", 5.0)).unwrap();
+    }
+
+    #[test]
+    fn test_non_custodial_transfers_need_the_client_signature() {
+        let core = setup(&[
+            ("alice-own", "alice", WalletType::NonCustodial),
+            ("bob-c", "bob", WalletType::Custodial),
+        ]);
+        core.mint_to_wallet("alice-own", "asset1", 50.0).unwrap();
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+
+        // The platform holds no keys to the wallet
+        let error = core.transfer_asset("alice-own", "bob-c", "asset1", 1.0).unwrap_err();
+        assert_eq!(
+            error,
+            "Signature required: transfers out of non-custodial wallet alice-own need the client's signature; request signatures instead"
+        );
+        assert!(core.as_subject(&agent, || core.transfer_asset("alice-own", "bob-c", "asset1", 1.0)).is_err());
+
+        core.as_subject(&alice, || core.transfer_asset("alice-own", "bob-c", "asset1", 10.0)).unwrap();
+
+        // A transfer prepared by the platform waits for the client
+        let request = core
+            .as_subject(&agent, || core.request_transfer_signatures(transfer("alice-own", "bob-c", 15.0)))
+            .unwrap();
+        assert!(request.signatures.is_empty());
+        let signed = core.as_subject(&alice, || core.sign_request(&request.id)).unwrap();
+        assert_eq!(signed.status, SigningStatus::Executed);
+        assert_eq!(core.get_holding("alice-own", "asset1").unwrap(), 25.0);
+        assert_eq!(core.get_holding("bob-c", "asset1").unwrap(), 25.0);
+        assert_eq!(core.get_custody_model("alice-own").unwrap(), None);
+    }
+
+    #[test]
+    fn test_signing_requests_do_not_bypass_four_eyes_rules() {
+        let core = setup(&[
+            ("alice-c", "alice", WalletType::Custodial),
+            ("bob-c", "bob", WalletType::Custodial),
+            ("alice-own", "alice", WalletType::NonCustodial),
+        ]);
+        core.mint_to_wallet("alice-c", "asset1", 1_000.0).unwrap();
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let mallory = Subject::new("mallory", &[Role::Investor]);
+
+        // A request opened before the rule is checked again when it runs
+        let early = core
+            .as_subject(&alice, || core.request_transfer_signatures(transfer("alice-c", "alice-own", 100.0)))
+            .unwrap();
+        core.configure_approval_rule(ApprovalRule::new(OperationKind::Transfer, 2, vec![])).unwrap();
+
+        let direct = core.as_subject(&alice, || core.transfer_asset("alice-c", "bob-c", "asset1", 900.0)).unwrap_err();
+        let requested = core
+            .as_subject(&alice, || core.request_transfer_signatures(transfer("alice-c", "bob-c", 900.0)))
+            .unwrap_err();
+        assert_eq!(requested, direct);
+        let signed = core.as_subject(&agent, || core.sign_request(&early.id)).unwrap();
+        assert_eq!(signed.status, SigningStatus::Failed { error: direct });
+        assert_eq!(core.get_holding("alice-c", "asset1").unwrap(), 1_000.0);
+
+        // Only the owner or a subject who may transfer from the wallet can request
+        core.remove_approval_rule(OperationKind::Transfer).unwrap();
+        let error = core
+            .as_subject(&mallory, || core.request_transfer_signatures(transfer("alice-c", "bob-c", 1.0)))
+            .unwrap_err();
+        assert!(error.starts_with(PERMISSION_DENIED), "{}", error);
+    }
+
+    #[test]
+    fn test_hybrid_transfers_need_client_and_platform_cosigning() {
+        let core = setup(&[
+            ("alice-co", "alice", WalletType::Hybrid),
+            ("bob-c", "bob", WalletType::Custodial),
+        ]);
+        core.mint_to_wallet("alice-co", "asset1", 50.0).unwrap();
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let mallory = Subject::new("mallory", &[Role::Investor]);
+
+        for subject in [&alice, &agent] {
+            let error = core.as_subject(subject, || core.transfer_asset("alice-co", "bob-c", "asset1", 1.0)).unwrap_err();
+            assert!(is_signature_required(&error), "{}", error);
+        }
+
+        let request = core
+            .as_subject(&alice, || core.request_transfer_signatures(transfer("alice-co", "bob-c", 20.0)))
+            .unwrap();
+        assert_eq!(request.missing(), [SignerRole::Platform]);
+        assert!(core.as_subject(&alice, || core.sign_request(&request.id)).is_err());
+        assert!(core.as_subject(&mallory, || core.sign_request(&request.id)).is_err());
+        assert!(core.as_subject(&mallory, || core.cancel_signing_request(&request.id)).is_err());
+        let signed = core.as_subject(&agent, || core.sign_request(&request.id)).unwrap();
+        assert_eq!(signed.status, SigningStatus::Executed);
+        assert_eq!(core.get_holding("bob-c", "asset1").unwrap(), 20.0);
+
+        // Either party can call off a request the other has not signed yet
+        let request = core
+            .as_subject(&agent, || core.request_transfer_signatures(transfer("alice-co", "bob-c", 5.0)))
+            .unwrap();
+        assert_eq!(request.missing(), [SignerRole::Client]);
+        let cancelled = core.as_subject(&alice, || core.cancel_signing_request(&request.id)).unwrap();
+        assert_eq!(cancelled.status, SigningStatus::Cancelled);
+        assert!(core.as_subject(&alice, || core.sign_request(&request.id)).is_err());
+        assert_eq!(core.get_holding("alice-co", "asset1").unwrap(), 30.0);
+        assert_eq!(core.as_subject(&mallory, || core.get_signing_requests(None)).unwrap(), []);
+        assert_eq!(core.as_subject(&alice, || core.get_signing_requests(Some("alice-co"))).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,270 @@
+//! Emergency freezes and the global kill switch
+//!
+//! A freeze on a wallet, an asset or an owner identity blocks every mutating
+//! operation of `TokenizationCore` that touches it: a frozen owner's wallets and
+//! assets are frozen too. A global freeze stops all mutations. Freezes carry a
+//! reason code, the principal that set them and an optional expiry, and both
+//! freezing and unfreezing are audited. Reads are never blocked, and freezes can
+//! always be lifted.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{current_actor, AuditAction, EntityType, SYSTEM_ACTOR};
+use crate::core_component::{DigitalWallet, TokenizationCore, TokenizedAsset};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Prefix of every error returned by the core for a frozen entity
+pub const FROZEN: &str = "Frozen";
+
+/// How often the server lifts expired freezes
+pub const FREEZE_EXPIRY_INTERVAL_SECS: u64 = 60;
+
+/// What a freeze applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum FreezeTarget {
+    Wallet(String),
+    Asset(String),
+    /// Every wallet and asset owned by the identity
+    Owner(String),
+    /// Kill switch for every mutation
+    Global,
+}
+
+impl fmt::Display for FreezeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FreezeTarget::Wallet(id) => write!(f, "wallet {}", id),
+            FreezeTarget::Asset(id) => write!(f, "asset {}", id),
+            FreezeTarget::Owner(id) => write!(f, "owner {}", id),
+            FreezeTarget::Global => write!(f, "platform"),
+        }
+    }
+}
+
+/// Why something was frozen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreezeReason {
+    SuspectedFraud,
+    Sanctions,
+    CourtOrder,
+    SecurityIncident,
+    RegulatoryHold,
+    Other,
+}
+
+/// An active freeze
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Freeze {
+    pub target: FreezeTarget,
+    pub reason: FreezeReason,
+    pub note: Option<String>,
+    /// Principal that set the freeze
+    pub frozen_by: String,
+    pub frozen_at: u64,
+    /// The freeze lifts itself at this time, if set
+    pub expires_at: Option<u64>,
+}
+
+impl Freeze {
+    pub fn is_active_at(&self, at: u64) -> bool {
+        self.expires_at.is_none_or(|expires_at| at < expires_at)
+    }
+}
+
+/// Whether an error from the core was caused by a freeze
+pub fn is_frozen(error: &str) -> bool {
+    error.starts_with(FROZEN)
+}
+
+impl TokenizationCore {
+    /// Freeze a wallet, an asset, an owner or the whole platform, replacing any
+    /// existing freeze on the same target
+    pub fn freeze(
+        &self,
+        target: FreezeTarget,
+        reason: FreezeReason,
+        note: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Result<Freeze, String> {
+        authorize_current(Permission::ManageFreezes, None)?;
+        let now = self.now();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err("Freeze expiry must be in the future".to_string());
+        }
+        let freeze = Freeze {
+            target: target.clone(),
+            reason,
+            note,
+            frozen_by: current_actor(),
+            frozen_at: now,
+            expires_at,
+        };
+        let mut freezes = self.freezes.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = freezes.insert(target.clone(), freeze.clone());
+        self.record_audit(AuditAction::Freeze, EntityType::Freeze, &target.to_string(), before.as_ref(), Some(&freeze))?;
+        Ok(freeze)
+    }
+
+    /// Lift the freeze on a target
+    pub fn unfreeze(&self, target: &FreezeTarget) -> Result<Freeze, String> {
+        authorize_current(Permission::ManageFreezes, None)?;
+        let mut freezes = self.freezes.write().map_err(|_| "Failed to acquire write lock")?;
+        let freeze = freezes.remove(target).ok_or_else(|| format!("{} is not frozen", target))?;
+        self.record_audit(AuditAction::Unfreeze, EntityType::Freeze, &target.to_string(), Some(&freeze), None::<&Freeze>)?;
+        Ok(freeze)
+    }
+
+    /// Remove freezes past their expiry, auditing each as unfrozen by the system
+    pub fn release_expired_freezes(&self) -> Result<Vec<FreezeTarget>, String> {
+        let now = self.now();
+        let mut freezes = self.freezes.write().map_err(|_| "Failed to acquire write lock")?;
+        let expired: Vec<Freeze> = freezes.values().filter(|f| !f.is_active_at(now)).cloned().collect();
+        self.with_actor(SYSTEM_ACTOR, || {
+            for freeze in &expired {
+                freezes.remove(&freeze.target);
+                self.record_audit(AuditAction::Unfreeze, EntityType::Freeze, &freeze.target.to_string(), Some(freeze), None::<&Freeze>)?;
+            }
+            Ok::<(), String>(())
+        })?;
+        Ok(expired.into_iter().map(|f| f.target).collect())
+    }
+
+    /// Freezes currently in force
+    pub fn get_freezes(&self) -> Result<Vec<Freeze>, String> {
+        let now = self.now();
+        let freezes = self.freezes.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut active: Vec<Freeze> = freezes.values().filter(|f| f.is_active_at(now)).cloned().collect();
+        active.sort_by_key(|f| (f.frozen_at, f.target.to_string()));
+        Ok(active)
+    }
+
+    /// Refuse a mutation touching any of the given wallets or assets, or their owners,
+    /// while one of them or the platform is frozen
+    pub(crate) fn ensure_not_frozen(&self, wallets: &[&DigitalWallet], assets: &[&TokenizedAsset]) -> Result<(), String> {
+        let now = self.now();
+        let freezes = self.freezes.read().map_err(|_| "Failed to acquire read lock")?;
+        if freezes.is_empty() {
+            return Ok(());
+        }
+        let targets = std::iter::once(FreezeTarget::Global)
+            .chain(wallets.iter().flat_map(|w| [FreezeTarget::Wallet(w.id.clone()), FreezeTarget::Owner(w.owner.clone())]))
+            .chain(assets.iter().flat_map(|a| [FreezeTarget::Asset(a.id.clone()), FreezeTarget::Owner(a.owner.clone())]));
+        for target in targets {
+            if let Some(freeze) = freezes.get(&target).filter(|f| f.is_active_at(now)) {
+                return Err(format!("{}: {} was frozen by {} ({:?})", FROZEN, target, freeze.frozen_by, freeze.reason));
+            }
+        }
+        Ok(())
+    }
+
+    /// `ensure_not_frozen` by ID, for callers not yet holding the wallet or asset locks
+    pub(crate) fn ensure_ids_not_frozen(&self, wallet_ids: &[&str], asset_ids: &[&str]) -> Result<(), String> {
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+        let wallets: Vec<&DigitalWallet> = wallet_ids.iter().filter_map(|id| wallets.get(*id)).collect();
+        let assets: Vec<&TokenizedAsset> = asset_ids.iter().filter_map(|id| assets.get(*id)).collect();
+        self.ensure_not_frozen(&wallets, &assets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        for (id, owner) in [("asset1", "issuer-1"), ("asset2", "issuer-2")] {
+            core.create_asset(TokenizedAsset {
+                compliance_status: ComplianceStatus::Approved,
+                ..asset(id, owner, AssetType::Equity)
+            })
+            .unwrap();
+        }
+        for (id, owner) in [("wallet1", "alice"), ("wallet2", "bob"), ("wallet3", "carol")] {
+            core.create_wallet(wallet(id, owner, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 100.0).unwrap();
+        core.mint_to_wallet("wallet1", "asset2", 100.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_freezes_block_mutations_until_lifted_or_expired() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(&clock);
+        let officer = Subject::new("officer-1", &[Role::ComplianceOfficer]);
+        let investor = Subject::new("alice", &[Role::Investor]);
+
+        assert!(core.as_subject(&investor, || core.freeze(FreezeTarget::Global, FreezeReason::Other, None, None)).is_err());
+        let freeze = core
+            .as_subject(&officer, || {
+                core.freeze(FreezeTarget::Wallet("wallet2".to_string()), FreezeReason::Sanctions, None, Some(2_000))
+            })
+            .unwrap();
+        assert_eq!(freeze.frozen_by, "officer-1");
+
+        // Both sending and receiving wallets are checked
+        let error = core.transfer_asset("wallet1", "wallet2", "asset1", 10.0).unwrap_err();
+        assert!(is_frozen(&error));
+        assert_eq!(error, "Frozen: wallet wallet2 was frozen by officer-1 (Sanctions)");
+        core.transfer_asset("wallet1", "wallet3", "asset1", 10.0).unwrap();
+
+        // An asset freeze blocks issuance and transfers of that asset only
+        core.as_subject(&officer, || {
+            core.freeze(FreezeTarget::Asset("asset1".to_string()), FreezeReason::CourtOrder, None, None)
+        })
+        .unwrap();
+        assert!(core.mint_to_wallet("wallet3", "asset1", 1.0).is_err());
+        assert!(core.burn_from_wallet("wallet1", "asset1", 1.0).is_err());
+        core.transfer_asset("wallet1", "wallet3", "asset2", 10.0).unwrap();
+
+        // An owner freeze covers the owner's wallets and the assets they issued
+        core.as_subject(&officer, || {
+            core.freeze(FreezeTarget::Owner("issuer-2".to_string()), FreezeReason::SuspectedFraud, None, None)
+        })
+        .unwrap();
+        assert!(core.transfer_asset("wallet1", "wallet3", "asset2", 1.0).is_err());
+        let mut renamed = core.get_asset("asset2").unwrap().unwrap();
+        renamed.name = "Renamed".to_string();
+        assert!(core.update_asset("asset2", renamed).is_err());
+
+        // Expired freezes stop applying and are released by the system
+        clock.set(2_000);
+        assert_eq!(core.get_freezes().unwrap().len(), 2);
+        assert_eq!(core.release_expired_freezes().unwrap(), vec![FreezeTarget::Wallet("wallet2".to_string())]);
+        core.as_subject(&officer, || core.unfreeze(&FreezeTarget::Asset("asset1".to_string()))).unwrap();
+        core.transfer_asset("wallet1", "wallet2", "asset1", 10.0).unwrap();
+
+        let entries = core.get_audit_log().unwrap();
+        let unfreezes: Vec<_> = entries.iter().filter(|e| e.action == AuditAction::Unfreeze).map(|e| e.actor.as_str()).collect();
+        assert_eq!(unfreezes, [SYSTEM_ACTOR, "officer-1"]);
+        assert_eq!(entries.iter().filter(|e| e.action == AuditAction::Freeze).count(), 3);
+    }
+
+    #[test]
+    fn test_kill_switch_stops_every_mutation() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(&clock);
+        core.freeze(FreezeTarget::Global, FreezeReason::SecurityIncident, Some("key compromise".to_string()), None)
+            .unwrap();
+
+        assert!(core.transfer_asset("wallet1", "wallet2", "asset1", 1.0).is_err());
+        assert!(core.mint_to_wallet("wallet2", "asset2", 1.0).is_err());
+        assert!(core.delete_wallet("wallet3", crate::core_component::DeletePolicy::Reject).is_err());
+        assert!(core.perform_compliance_check("asset1").is_err());
+        let error = core.create_wallet(wallet("wallet4", "dave", WalletType::Custodial)).unwrap_err();
+        assert!(error.starts_with("Frozen: platform"), "{}", error);
+
+        // Reads still work and the switch can be turned off
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 100.0);
+        core.unfreeze(&FreezeTarget::Global).unwrap();
+        core.transfer_asset("wallet1", "wallet2", "asset1", 1.0).unwrap();
+    }
+}
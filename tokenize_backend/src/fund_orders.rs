@@ -0,0 +1,707 @@
+//! Subscription and redemption order flow for fund assets
+//!
+//! Orders are forward priced: each order is assigned to the first dealing point
+//! whose cut-off it meets, and every order in a batch is filled at the NAV struck
+//! when that batch is dealt. Subscription cash is taken from the wallet balance
+//! on submission and held against the order. Redemptions beyond the fund's gate
+//! threshold are pro-rated, and the unfilled remainder rolls to the next dealing point.
+//! A dealing never pays out more than the fund holds in cash.
+
+use crate::access_control::{without_subject, Permission};
+use crate::approvals::OperationKind;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, QUANTITY_EPSILON};
+use crate::events::DomainEvent;
+use crate::nav::NavRecord;
+use serde::{Deserialize, Serialize};
+
+/// Dealing calendar, fees and liquidity gate of a fund
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DealingTerms {
+    pub asset_id: String,
+    /// Timestamp of a reference dealing point
+    pub anchor: u64,
+    /// Seconds between dealing points
+    pub dealing_interval_secs: u64,
+    /// Orders must be received this many seconds before a dealing point
+    pub cutoff_lead_secs: u64,
+    /// Fee on subscription amounts, e.g. 0.02 for 2%
+    pub entry_fee_rate: f64,
+    /// Fee on gross redemption proceeds
+    pub exit_fee_rate: f64,
+    /// Maximum share of net assets redeemable per dealing point; `None` disables the gate
+    pub gate_threshold: Option<f64>,
+}
+
+impl DealingTerms {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.dealing_interval_secs == 0 {
+            return Err("Dealing interval must be positive".to_string());
+        }
+        if self.cutoff_lead_secs >= self.dealing_interval_secs {
+            return Err("Cut-off lead must be shorter than the dealing interval".to_string());
+        }
+        if !(0.0..1.0).contains(&self.entry_fee_rate) || !(0.0..1.0).contains(&self.exit_fee_rate) {
+            return Err("Fee rates must be between 0 and 1".to_string());
+        }
+        if let Some(gate) = self.gate_threshold
+            && (gate <= 0.0 || gate > 1.0)
+        {
+            return Err("Gate threshold must be in (0, 1]".to_string());
+        }
+        Ok(())
+    }
+
+    /// First dealing point whose cut-off has not passed at `at`
+    pub fn next_dealing_point(&self, at: u64) -> u64 {
+        let earliest = at + self.cutoff_lead_secs;
+        if earliest <= self.anchor {
+            let periods_back = (self.anchor - earliest) / self.dealing_interval_secs;
+            return self.anchor - periods_back * self.dealing_interval_secs;
+        }
+        let periods = (earliest - self.anchor).div_ceil(self.dealing_interval_secs);
+        self.anchor + periods * self.dealing_interval_secs
+    }
+}
+
+/// Direction and size of an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FundOrderSide {
+    /// Invest a cash amount, including the entry fee
+    Subscribe { amount: f64 },
+    /// Redeem a number of units
+    Redeem { units: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FundOrderStatus {
+    Pending,
+    /// Claimed by a dealing that is settling it
+    Processing,
+    /// Gated redemption with units still to be filled at a later dealing point
+    PartiallySettled,
+    Settled,
+    Cancelled,
+    Rejected(String),
+}
+
+/// Execution of (part of) an order at a dealing point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub dealing_time: u64,
+    pub nav_per_unit: f64,
+    pub units: f64,
+    pub gross_amount: f64,
+    pub fee: f64,
+    pub net_amount: f64,
+}
+
+/// A subscription or redemption order against a fund asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundOrder {
+    pub id: String,
+    pub asset_id: String,
+    pub wallet_id: String,
+    pub side: FundOrderSide,
+    pub submitted_at: u64,
+    pub dealing_time: u64,
+    pub status: FundOrderStatus,
+    pub fills: Vec<OrderFill>,
+}
+
+impl FundOrder {
+    /// Units still to be redeemed; zero for subscriptions
+    pub fn remaining_units(&self) -> f64 {
+        match self.side {
+            FundOrderSide::Redeem { units } => (units - self.fills.iter().map(|f| f.units).sum::<f64>()).max(0.0),
+            FundOrderSide::Subscribe { .. } => 0.0,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.status, FundOrderStatus::Pending | FundOrderStatus::PartiallySettled)
+    }
+}
+
+/// Outcome of dealing one batch of orders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DealingResult {
+    pub nav: NavRecord,
+    pub orders: Vec<FundOrder>,
+    /// Share of requested redemptions filled when the gate was triggered
+    pub gate_factor: Option<f64>,
+}
+
+impl TokenizationCore {
+    /// Configure the dealing calendar, fees and gate of a registered fund
+    pub fn configure_fund_dealing(&self, terms: DealingTerms) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, &terms.asset_id)?;
+        terms.validate()?;
+        self.ensure_ids_not_frozen(&[], &[&terms.asset_id])?;
+        if self.get_fund(&terms.asset_id)?.is_none() {
+            return Err("Fund not found".to_string());
+        }
+        let mut dealing = self.fund_dealing.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = dealing.insert(terms.asset_id.clone(), terms.clone());
+        self.record_audit(AuditAction::Update, EntityType::Fund, &terms.asset_id, before.as_ref(), Some(&terms))?;
+        Ok(())
+    }
+
+    /// Get the dealing terms of a fund
+    pub fn get_fund_dealing(&self, asset_id: &str) -> Result<Option<DealingTerms>, String> {
+        let dealing = self.fund_dealing.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(dealing.get(asset_id).cloned())
+    }
+
+    /// Submit a subscription, taking the cash amount from the wallet balance
+    pub fn submit_subscription(&self, order_id: &str, asset_id: &str, wallet_id: &str, amount: f64) -> Result<FundOrder, String> {
+        if amount <= 0.0 {
+            return Err("Subscription amount must be positive".to_string());
+        }
+        self.authorize_wallet(Permission::Transfer, wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[asset_id])?;
+        let terms = self.get_fund_dealing(asset_id)?.ok_or("Fund dealing terms not configured")?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut orders = self.fund_orders.write().map_err(|_| "Failed to acquire write lock")?;
+        if orders.contains_key(order_id) {
+            return Err("Order already exists".to_string());
+        }
+        let wallet = wallets.get_mut(wallet_id).ok_or("Wallet not found")?;
+        if wallet.balance + QUANTITY_EPSILON < amount {
+            return Err("Insufficient wallet balance".to_string());
+        }
+        let before = wallet.clone();
+        wallet.balance -= amount;
+        wallet.updated_at = now;
+        self.record_audit(AuditAction::Update, EntityType::Wallet, wallet_id, Some(&before), Some(&*wallet))?;
+        self.emit_event(DomainEvent::WalletBalanceChanged {
+            wallet_id: wallet_id.to_string(),
+            amount: -amount,
+        })?;
+
+        let order = FundOrder {
+            id: order_id.to_string(),
+            asset_id: asset_id.to_string(),
+            wallet_id: wallet_id.to_string(),
+            side: FundOrderSide::Subscribe { amount },
+            submitted_at: now,
+            dealing_time: terms.next_dealing_point(now),
+            status: FundOrderStatus::Pending,
+            fills: Vec::new(),
+        };
+        self.record_audit(AuditAction::Create, EntityType::FundOrder, order_id, None::<&FundOrder>, Some(&order))?;
+        orders.insert(order.id.clone(), order.clone());
+        Ok(order)
+    }
+
+    /// Submit a redemption of units held in the wallet
+    pub fn submit_redemption(&self, order_id: &str, asset_id: &str, wallet_id: &str, units: f64) -> Result<FundOrder, String> {
+        if units <= 0.0 {
+            return Err("Redemption units must be positive".to_string());
+        }
+        self.authorize_wallet(Permission::Transfer, wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[asset_id])?;
+        let terms = self.get_fund_dealing(asset_id)?.ok_or("Fund dealing terms not configured")?;
+        let now = self.now();
+        if self.get_wallet(wallet_id)?.is_none() {
+            return Err("Wallet not found".to_string());
+        }
+        let mut orders = self.fund_orders.write().map_err(|_| "Failed to acquire write lock")?;
+        if orders.contains_key(order_id) {
+            return Err("Order already exists".to_string());
+        }
+        let already_pending: f64 = orders
+            .values()
+            .filter(|o| o.wallet_id == wallet_id && o.asset_id == asset_id)
+            .filter(|o| o.is_open() || o.status == FundOrderStatus::Processing)
+            .map(|o| o.remaining_units())
+            .sum();
+        let transferable = self.transferable_quantity(wallet_id, asset_id, now)?;
+        if already_pending + units > transferable + QUANTITY_EPSILON {
+            return Err("Redemption exceeds redeemable units".to_string());
+        }
+
+        let order = FundOrder {
+            id: order_id.to_string(),
+            asset_id: asset_id.to_string(),
+            wallet_id: wallet_id.to_string(),
+            side: FundOrderSide::Redeem { units },
+            submitted_at: now,
+            dealing_time: terms.next_dealing_point(now),
+            status: FundOrderStatus::Pending,
+            fills: Vec::new(),
+        };
+        self.record_audit(AuditAction::Create, EntityType::FundOrder, order_id, None::<&FundOrder>, Some(&order))?;
+        orders.insert(order.id.clone(), order.clone());
+        Ok(order)
+    }
+
+    /// Cancel an order before its cut-off, refunding any subscription cash
+    pub fn cancel_fund_order(&self, order_id: &str) -> Result<(), String> {
+        match self.get_fund_order(order_id)? {
+            Some(order) => {
+                self.authorize_wallet(Permission::Transfer, &order.wallet_id)?;
+                self.ensure_ids_not_frozen(&[&order.wallet_id], &[&order.asset_id])?
+            }
+            None => self.ensure_ids_not_frozen(&[], &[])?,
+        }
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let dealing = self.fund_dealing.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut orders = self.fund_orders.write().map_err(|_| "Failed to acquire write lock")?;
+        let order = orders.get_mut(order_id).ok_or("Order not found")?;
+
+        if order.status != FundOrderStatus::Pending || !order.fills.is_empty() {
+            return Err("Only unfilled pending orders can be cancelled".to_string());
+        }
+        let lead = dealing.get(&order.asset_id).map(|t| t.cutoff_lead_secs).unwrap_or(0);
+        if now + lead > order.dealing_time {
+            return Err("Cut-off has passed".to_string());
+        }
+        if let FundOrderSide::Subscribe { amount } = order.side
+            && let Some(wallet) = wallets.get_mut(&order.wallet_id)
+        {
+            let before = wallet.clone();
+            wallet.balance += amount;
+            wallet.updated_at = now;
+            self.record_audit(AuditAction::Update, EntityType::Wallet, &wallet.id, Some(&before), Some(&*wallet))?;
+            self.emit_event(DomainEvent::WalletBalanceChanged {
+                wallet_id: wallet.id.clone(),
+                amount,
+            })?;
+        }
+        let before = order.clone();
+        order.status = FundOrderStatus::Cancelled;
+        self.record_audit(AuditAction::Cancel, EntityType::FundOrder, order_id, Some(&before), Some(&*order))?;
+        Ok(())
+    }
+
+    /// Get an order by ID
+    pub fn get_fund_order(&self, order_id: &str) -> Result<Option<FundOrder>, String> {
+        let orders = self.fund_orders.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(orders.get(order_id).cloned())
+    }
+
+    /// Get all orders against a fund, oldest first
+    pub fn get_fund_orders(&self, asset_id: &str) -> Result<Vec<FundOrder>, String> {
+        let orders = self.fund_orders.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut result: Vec<FundOrder> = orders.values().filter(|o| o.asset_id == asset_id).cloned().collect();
+        result.sort_by(|a, b| (a.submitted_at, &a.id).cmp(&(b.submitted_at, &b.id)));
+        Ok(result)
+    }
+
+    /// Strike the NAV and settle every open order whose dealing point has been reached
+    ///
+    /// Subscriptions mint units into the investor's wallet and add the whole amount to
+    /// fund cash, booking the entry fee as owed to the manager. Redemptions burn units
+    /// and credit net proceeds to the wallet balance; when their total value exceeds
+    /// the gate, each is filled pro rata and the rest moves to the next dealing point. Subscriptions settle before redemptions, and
+    /// the dealing is refused up front if the batch would overdraw fund cash. The
+    /// batch is claimed before anything moves, so a concurrent dealing or a
+    /// cancellation cannot settle the same orders again.
+    pub fn run_dealing(&self, asset_id: &str) -> Result<DealingResult, String> {
+        self.authorize_asset(Permission::Mint, asset_id)?;
+        self.authorize_asset(Permission::Burn, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let terms = self.get_fund_dealing(asset_id)?.ok_or("Fund dealing terms not configured")?;
+        let now = self.now();
+        let nav = self.calculate_nav(asset_id)?;
+        let price = nav.nav_per_unit;
+        if price <= 0.0 {
+            return Err("NAV per unit must be positive to deal".to_string());
+        }
+        let mut batch: Vec<FundOrder> = {
+            let mut orders = self.fund_orders.write().map_err(|_| "Failed to acquire write lock")?;
+            orders
+                .values_mut()
+                .filter(|o| o.asset_id == asset_id && o.is_open() && o.dealing_time <= now)
+                .map(|order| {
+                    let claimed = order.clone();
+                    order.status = FundOrderStatus::Processing;
+                    claimed
+                })
+                .collect()
+        };
+        batch.sort_by(|a, b| (a.submitted_at, &a.id).cmp(&(b.submitted_at, &b.id)));
+        let result = self.settle_dealing(asset_id, &terms, nav, &mut batch);
+        // Whatever happened, the claimed orders leave Processing
+        let mut orders = self.fund_orders.write().map_err(|_| "Failed to acquire write lock")?;
+        for order in &batch {
+            let before = orders.insert(order.id.clone(), order.clone());
+            if result.is_ok() {
+                self.record_audit(AuditAction::Process, EntityType::FundOrder, &order.id, before.as_ref(), Some(order))?;
+            }
+        }
+        drop(orders);
+        let (nav, gate_factor) = result?;
+        Ok(DealingResult {
+            nav,
+            orders: batch,
+            gate_factor,
+        })
+    }
+
+    /// Price and settle a claimed batch, leaving it untouched if the batch cannot settle
+    fn settle_dealing(
+        &self,
+        asset_id: &str,
+        terms: &DealingTerms,
+        nav: NavRecord,
+        batch: &mut [FundOrder],
+    ) -> Result<(NavRecord, Option<f64>), String> {
+        let now = self.now();
+        let price = nav.nav_per_unit;
+        let opening_cash = self.get_fund(asset_id)?.ok_or("Fund not found")?.cash;
+
+        let requested_redemptions: f64 = batch.iter().map(|o| o.remaining_units() * price).sum();
+        let gate_factor = terms.gate_threshold.and_then(|gate| {
+            let capacity = gate * nav.net_assets.max(0.0);
+            (requested_redemptions > capacity + QUANTITY_EPSILON).then(|| capacity / requested_redemptions)
+        });
+
+        // Price every order and check that it can settle before anything moves
+        let fills: Vec<Result<OrderFill, String>> = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+            batch
+                .iter()
+                .map(|order| {
+                    let (units, gross_amount, fee) = match order.side {
+                        FundOrderSide::Subscribe { amount } => {
+                            let fee = amount * terms.entry_fee_rate;
+                            ((amount - fee) / price, amount, fee)
+                        }
+                        FundOrderSide::Redeem { .. } => {
+                            let units = order.remaining_units() * gate_factor.unwrap_or(1.0);
+                            let gross_amount = units * price;
+                            (units, gross_amount, gross_amount * terms.exit_fee_rate)
+                        }
+                    };
+                    if !wallets.contains_key(&order.wallet_id) {
+                        return Err("Wallet not found".to_string());
+                    }
+                    let held = holdings.get(&order.wallet_id).and_then(|h| h.get(asset_id)).copied().unwrap_or(0.0);
+                    if matches!(order.side, FundOrderSide::Redeem { .. }) && held + QUANTITY_EPSILON < units {
+                        return Err("Insufficient holding".to_string());
+                    }
+                    Ok(OrderFill {
+                        dealing_time: now,
+                        nav_per_unit: price,
+                        units,
+                        gross_amount,
+                        fee,
+                        net_amount: gross_amount - fee,
+                    })
+                })
+                .collect()
+        };
+        // A mint approval rule gates the dealing as a whole: submit it as `RunDealing`
+        for (order, fill) in batch.iter().zip(&fills) {
+            if let (FundOrderSide::Subscribe { .. }, Ok(fill)) = (&order.side, fill) {
+                self.check_four_eyes(OperationKind::Mint, Some(fill.units))?;
+            }
+        }
+        let net_flow: f64 = batch
+            .iter()
+            .zip(&fills)
+            .filter_map(|(order, fill)| {
+                let fill = fill.as_ref().ok()?;
+                Some(match order.side {
+                    FundOrderSide::Subscribe { .. } => fill.gross_amount,
+                    FundOrderSide::Redeem { .. } => -fill.net_amount,
+                })
+            })
+            .sum();
+        if opening_cash + net_flow < -QUANTITY_EPSILON {
+            return Err(format!(
+                "Fund cash of {} cannot cover the redemptions due; {} short",
+                opening_cash,
+                -(opening_cash + net_flow)
+            ));
+        }
+
+        let mut cash = opening_cash;
+        let mut entry_fees = 0.0;
+        for redemptions in [false, true] {
+            for (order, fill) in batch.iter_mut().zip(&fills) {
+                if matches!(order.side, FundOrderSide::Redeem { .. }) != redemptions {
+                    continue;
+                }
+                let result = fill.clone().and_then(|fill| {
+                    match order.side {
+                        FundOrderSide::Subscribe { .. } => {
+                            self.mint_to_wallet(&order.wallet_id, asset_id, fill.units)?;
+                            cash += fill.gross_amount;
+                            entry_fees += fill.fee;
+                        }
+                        FundOrderSide::Redeem { .. } => {
+                            // A subscription that failed to settle may have left less cash than planned
+                            if cash + QUANTITY_EPSILON < fill.net_amount {
+                                return Err("Insufficient fund cash".to_string());
+                            }
+                            self.settle_redemption(&order.wallet_id, asset_id, fill.units, fill.net_amount)?;
+                            cash -= fill.net_amount;
+                        }
+                    }
+                    Ok(fill)
+                });
+
+                match result {
+                    Ok(fill) => {
+                        order.fills.push(fill);
+                        if order.remaining_units() > QUANTITY_EPSILON {
+                            order.status = FundOrderStatus::PartiallySettled;
+                            order.dealing_time = terms.next_dealing_point(now);
+                        } else {
+                            order.status = FundOrderStatus::Settled;
+                        }
+                    }
+                    Err(e) => {
+                        // Subscription cash goes back to the investor while their wallet exists
+                        let wallet_exists = self
+                            .wallets
+                            .read()
+                            .map_err(|_| "Failed to acquire read lock")?
+                            .contains_key(&order.wallet_id);
+                        if let FundOrderSide::Subscribe { amount } = order.side
+                            && wallet_exists
+                        {
+                            self.credit_wallet_balance(&order.wallet_id, amount)?;
+                        }
+                        order.status = FundOrderStatus::Rejected(e);
+                    }
+                }
+            }
+        }
+
+        {
+            let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+            if let Some(fund) = funds.get_mut(asset_id) {
+                let before = fund.clone();
+                fund.cash += cash - opening_cash;
+                fund.accrued_fees += entry_fees;
+                self.record_audit(AuditAction::Update, EntityType::Fund, asset_id, Some(&before), Some(&*fund))?;
+            }
+        }
+        Ok((nav, gate_factor))
+    }
+
+    /// Burn redeemed units and pay out their proceeds, or do neither
+    fn settle_redemption(&self, wallet_id: &str, asset_id: &str, units: f64, proceeds: f64) -> Result<(), String> {
+        self.burn_from_wallet(wallet_id, asset_id, units)?;
+        if let Err(error) = self.credit_wallet_balance(wallet_id, proceeds) {
+            without_subject(|| self.mint_to_wallet(wallet_id, asset_id, units))?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Add cash to a wallet balance
+    pub(crate) fn credit_wallet_balance(&self, wallet_id: &str, amount: f64) -> Result<(), String> {
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let wallet = wallets.get_mut(wallet_id).ok_or("Wallet not found")?;
+        let before = wallet.clone();
+        wallet.balance += amount;
+        wallet.updated_at = now;
+        self.record_audit(AuditAction::Update, EntityType::Wallet, wallet_id, Some(&before), Some(&*wallet))?;
+        self.emit_event(DomainEvent::WalletBalanceChanged {
+            wallet_id: wallet_id.to_string(),
+            amount,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, DigitalWallet, TokenizedAsset, WalletType};
+    use crate::access_control::{Role, Subject, PERMISSION_DENIED};
+    use crate::approvals::{ActionStatus, ApprovalOperation, ApprovalRule, Submission};
+    use crate::nav::{FundProfile, UnderlyingPosition};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    const DAY: u64 = 86_400;
+
+    fn setup(clock: &ManualClock, gate_threshold: Option<f64>) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            value: 0.0,
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("fund1", "manager", AssetType::Fund)
+        })
+        .unwrap();
+        for id in ["alice", "bob"] {
+            core.create_wallet(DigitalWallet {
+                balance: 1_000.0,
+                ..wallet(id, id, WalletType::Custodial)
+            })
+            .unwrap();
+        }
+        core.register_fund(FundProfile::new("fund1", 10.0, vec![], 0)).unwrap();
+        core.configure_fund_dealing(DealingTerms {
+            asset_id: "fund1".to_string(),
+            anchor: DAY,
+            dealing_interval_secs: DAY,
+            cutoff_lead_secs: 3_600,
+            entry_fee_rate: 0.02,
+            exit_fee_rate: 0.01,
+            gate_threshold,
+        })
+        .unwrap();
+        core
+    }
+
+    #[test]
+    fn test_orders_after_cutoff_roll_to_next_dealing_point() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        assert_eq!(core.submit_subscription("o1", "fund1", "alice", 100.0).unwrap().dealing_time, DAY);
+
+        clock.set(DAY - 1_800);
+        assert_eq!(core.submit_subscription("o2", "fund1", "bob", 100.0).unwrap().dealing_time, 2 * DAY);
+        assert!(core.cancel_fund_order("o1").is_err());
+        core.cancel_fund_order("o2").unwrap();
+        assert_eq!(core.get_wallet("bob").unwrap().unwrap().balance, 1_000.0);
+    }
+
+    #[test]
+    fn test_subscription_mints_units_net_of_entry_fee() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        core.submit_subscription("o1", "fund1", "alice", 1_000.0).unwrap();
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 0.0);
+
+        clock.set(DAY);
+        let result = core.run_dealing("fund1").unwrap();
+        assert_eq!(result.nav.nav_per_unit, 10.0);
+        assert_eq!(core.get_holding("alice", "fund1").unwrap(), 98.0);
+        // The entry fee stays in fund cash as owed to the manager, so the NAV is unchanged
+        let fund = core.get_fund("fund1").unwrap().unwrap();
+        assert_eq!((fund.cash, fund.accrued_fees), (1_000.0, 20.0));
+        assert_eq!(core.calculate_nav("fund1").unwrap().nav_per_unit, 10.0);
+        assert_eq!(core.get_fund_order("o1").unwrap().unwrap().status, FundOrderStatus::Settled);
+    }
+
+    #[test]
+    fn test_claimed_orders_are_dealt_once() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        core.submit_subscription("o1", "fund1", "alice", 1_000.0).unwrap();
+
+        // An order another dealing is settling is neither dealt again nor cancellable
+        core.fund_orders.write().unwrap().get_mut("o1").unwrap().status = FundOrderStatus::Processing;
+        assert!(core.cancel_fund_order("o1").is_err());
+        clock.set(DAY);
+        assert!(core.run_dealing("fund1").unwrap().orders.is_empty());
+        assert_eq!(core.get_holding("alice", "fund1").unwrap(), 0.0);
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 0.0);
+    }
+
+    #[test]
+    fn test_redemption_gate_pro_rates_and_carries_remainder() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, Some(0.25));
+        core.submit_subscription("s1", "fund1", "alice", 1_000.0).unwrap();
+        core.submit_subscription("s2", "fund1", "bob", 1_000.0).unwrap();
+        clock.set(DAY);
+        core.run_dealing("fund1").unwrap();
+
+        // 196 units outstanding at NAV 10; both investors ask for 49 units (50% of the fund)
+        core.submit_redemption("r1", "fund1", "alice", 49.0).unwrap();
+        core.submit_redemption("r2", "fund1", "bob", 49.0).unwrap();
+        clock.set(2 * DAY);
+        let result = core.run_dealing("fund1").unwrap();
+        assert!((result.gate_factor.unwrap() - 0.5).abs() < 1e-9);
+
+        let r1 = core.get_fund_order("r1").unwrap().unwrap();
+        assert_eq!(r1.status, FundOrderStatus::PartiallySettled);
+        assert!((r1.remaining_units() - 24.5).abs() < 1e-9);
+        assert_eq!(r1.dealing_time, 3 * DAY);
+        // 24.5 units * 10 less 1% exit fee
+        assert!((core.get_wallet("alice").unwrap().unwrap().balance - 242.55).abs() < 1e-9);
+
+        // Lifting the gate lets the carried remainder settle in full
+        let mut terms = core.get_fund_dealing("fund1").unwrap().unwrap();
+        terms.gate_threshold = None;
+        core.configure_fund_dealing(terms).unwrap();
+        clock.set(3 * DAY);
+        core.run_dealing("fund1").unwrap();
+        assert_eq!(core.get_fund_order("r2").unwrap().unwrap().status, FundOrderStatus::Settled);
+        assert!((core.get_holding("bob", "fund1").unwrap() - 49.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dealing_never_overdraws_fund_cash() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        core.submit_subscription("s1", "fund1", "alice", 1_000.0).unwrap();
+        clock.set(DAY);
+        core.run_dealing("fund1").unwrap();
+
+        // Most of the fund is invested, leaving 100 in cash for 960 of net assets
+        let bonds = UnderlyingPosition {
+            instrument: "bond".to_string(),
+            quantity: 88.0,
+            price: 10.0,
+        };
+        core.update_fund_portfolio("fund1", vec![bonds], 100.0).unwrap();
+        core.submit_redemption("r1", "fund1", "alice", 49.0).unwrap();
+        clock.set(2 * DAY);
+        let error = core.run_dealing("fund1").unwrap_err();
+        assert!(error.starts_with("Fund cash of 100 cannot cover"), "{}", error);
+        assert_eq!(core.get_holding("alice", "fund1").unwrap(), 98.0);
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 0.0);
+        assert_eq!(core.get_fund_order("r1").unwrap().unwrap().status, FundOrderStatus::Pending);
+
+        // New subscriptions in the same batch count towards the cash available
+        core.submit_subscription("s2", "fund1", "bob", 1_000.0).unwrap();
+        clock.set(3 * DAY);
+        core.run_dealing("fund1").unwrap();
+        assert_eq!(core.get_fund_order("r1").unwrap().unwrap().status, FundOrderStatus::Settled);
+        assert!((core.get_fund("fund1").unwrap().unwrap().cash - (100.0 + 1_000.0 - 475.2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orders_are_placed_from_own_wallets_only() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let error = core
+            .as_subject(&alice, || core.submit_subscription("o1", "fund1", "bob", 100.0))
+            .unwrap_err();
+        assert!(error.starts_with(PERMISSION_DENIED), "{}", error);
+        core.as_subject(&alice, || core.submit_subscription("o1", "fund1", "alice", 100.0)).unwrap();
+        assert!(core.as_subject(&alice, || core.run_dealing("fund1")).is_err());
+    }
+
+    #[test]
+    fn test_mint_approval_rule_gates_the_dealing_as_a_whole() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock, None);
+        core.submit_subscription("o1", "fund1", "alice", 1_000.0).unwrap();
+        core.configure_approval_rule(ApprovalRule::new(OperationKind::Mint, 1, vec!["ops".to_string()])).unwrap();
+        let manager = Subject::new("manager", &[Role::Issuer]);
+        let ops = Subject::new("ops", &[Role::TransferAgent]);
+
+        clock.set(DAY);
+        let error = core.as_subject(&manager, || core.run_dealing("fund1")).unwrap_err();
+        assert!(error.contains("submit it for approval"), "{}", error);
+        assert_eq!(core.get_fund_order("o1").unwrap().unwrap().status, FundOrderStatus::Pending);
+
+        let dealing = ApprovalOperation::RunDealing {
+            asset_id: "fund1".to_string(),
+        };
+        let Submission::Pending(action) = core.as_subject(&manager, || core.submit_operation(dealing)).unwrap() else {
+            panic!("expected a pending action");
+        };
+        let executed = core.as_subject(&ops, || core.approve_action(&action.id, None)).unwrap();
+        assert_eq!(executed.status, ActionStatus::Executed);
+        assert_eq!(core.get_holding("alice", "fund1").unwrap(), 98.0);
+        assert_eq!(core.get_fund_order("o1").unwrap().unwrap().status, FundOrderStatus::Settled);
+    }
+}
@@ -0,0 +1,744 @@
+//! Primary issuance: bookbuilding and allocation
+//!
+//! An offering attached to a tokenized asset collects bids during its window.
+//! Each bid reserves `quantity * price` from the investor's wallet balance. At
+//! close, an `AllocationStrategy` decides how much of each bid is filled; filled
+//! units are minted into investor wallets, the reserved cash for the fill is paid
+//! to the issuer wallet and the rest is refunded. Closing claims the book first
+//! and checks every allocation before any unit or cash moves.
+
+use crate::access_control::{authorize_current, without_subject, Permission};
+use crate::approvals::OperationKind;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{DigitalWallet, TokenizationCore, WalletType, QUANTITY_EPSILON};
+use crate::events::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+/// Who may bid into an offering
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EligibilityCriteria {
+    /// Wallet types allowed to bid; empty allows all
+    pub wallet_types: Vec<WalletType>,
+    /// Wallet owners allowed to bid, e.g. accredited investors; `None` allows all
+    pub allowed_owners: Option<Vec<String>>,
+    /// Largest quantity a single wallet may bid for across its bids
+    pub max_quantity_per_wallet: Option<f64>,
+}
+
+/// Terms of a primary offering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfferingTerms {
+    pub id: String,
+    pub asset_id: String,
+    /// Wallet that receives the proceeds
+    pub issuer_wallet_id: String,
+    pub opens_at: u64,
+    pub closes_at: u64,
+    pub quantity_offered: f64,
+    /// Offer price, or the floor price when bids carry a limit price
+    pub price: f64,
+    /// Smallest bid value (quantity times price) accepted
+    pub min_ticket: f64,
+    pub eligibility: EligibilityCriteria,
+}
+
+impl OfferingTerms {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.closes_at <= self.opens_at {
+            return Err("Offering must close after it opens".to_string());
+        }
+        if self.quantity_offered <= 0.0 || self.price <= 0.0 {
+            return Err("Offered quantity and price must be positive".to_string());
+        }
+        if self.min_ticket < 0.0 {
+            return Err("Minimum ticket must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// An investor's bid into an offering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bid {
+    pub id: String,
+    pub wallet_id: String,
+    pub quantity: f64,
+    /// Highest price the investor will pay; `None` accepts the offer price
+    pub limit_price: Option<f64>,
+    pub submitted_at: u64,
+    /// Arrival order within the book
+    pub sequence: u64,
+}
+
+impl Bid {
+    /// Price used to reserve cash and rank the bid
+    pub fn effective_price(&self, offer_price: f64) -> f64 {
+        self.limit_price.unwrap_or(offer_price)
+    }
+}
+
+/// Quantity and price allotted to one bid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub bid_id: String,
+    pub wallet_id: String,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+/// Pluggable rule for splitting the offered quantity between bids
+pub trait AllocationStrategy {
+    fn name(&self) -> &str;
+
+    /// Allocate the offering across bids; bids left out receive nothing
+    fn allocate(&self, terms: &OfferingTerms, bids: &[Bid]) -> Vec<Allocation>;
+}
+
+/// Every bid is filled by the same fraction when the book is oversubscribed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProRataAllocation;
+
+impl AllocationStrategy for ProRataAllocation {
+    fn name(&self) -> &str {
+        "pro-rata"
+    }
+
+    fn allocate(&self, terms: &OfferingTerms, bids: &[Bid]) -> Vec<Allocation> {
+        let demand: f64 = bids.iter().map(|b| b.quantity).sum();
+        let fill = if demand > terms.quantity_offered {
+            terms.quantity_offered / demand
+        } else {
+            1.0
+        };
+        bids.iter()
+            .map(|bid| Allocation {
+                bid_id: bid.id.clone(),
+                wallet_id: bid.wallet_id.clone(),
+                quantity: bid.quantity * fill,
+                price: terms.price,
+            })
+            .collect()
+    }
+}
+
+/// Bids are filled in arrival order until the offering is exhausted
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstComeAllocation;
+
+impl AllocationStrategy for FirstComeAllocation {
+    fn name(&self) -> &str {
+        "first-come"
+    }
+
+    fn allocate(&self, terms: &OfferingTerms, bids: &[Bid]) -> Vec<Allocation> {
+        let mut ordered: Vec<&Bid> = bids.iter().collect();
+        ordered.sort_by_key(|b| b.sequence);
+        fill_in_order(&ordered, terms.quantity_offered, |_| terms.price)
+    }
+}
+
+/// Highest limit prices are filled first and every filled bid pays the clearing
+/// price, i.e. the lowest limit price that received units
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PricePriorityAllocation;
+
+impl AllocationStrategy for PricePriorityAllocation {
+    fn name(&self) -> &str {
+        "price-priority"
+    }
+
+    fn allocate(&self, terms: &OfferingTerms, bids: &[Bid]) -> Vec<Allocation> {
+        let mut ordered: Vec<&Bid> = bids.iter().collect();
+        ordered.sort_by(|a, b| {
+            b.effective_price(terms.price)
+                .total_cmp(&a.effective_price(terms.price))
+                .then(a.sequence.cmp(&b.sequence))
+        });
+        let mut allocations = fill_in_order(&ordered, terms.quantity_offered, |b| b.effective_price(terms.price));
+        let clearing_price = allocations
+            .iter()
+            .map(|a| a.price)
+            .fold(f64::INFINITY, f64::min);
+        for allocation in &mut allocations {
+            allocation.price = clearing_price;
+        }
+        allocations
+    }
+}
+
+/// Built-in allocation strategy with the given name, e.g. `pro-rata`
+pub fn allocation_strategy(name: &str) -> Option<Box<dyn AllocationStrategy>> {
+    match name {
+        "pro-rata" => Some(Box::new(ProRataAllocation)),
+        "first-come" => Some(Box::new(FirstComeAllocation)),
+        "price-priority" => Some(Box::new(PricePriorityAllocation)),
+        _ => None,
+    }
+}
+
+fn fill_in_order(bids: &[&Bid], available: f64, price: impl Fn(&Bid) -> f64) -> Vec<Allocation> {
+    let mut remaining = available;
+    let mut allocations = Vec::new();
+    for bid in bids {
+        if remaining <= QUANTITY_EPSILON {
+            break;
+        }
+        let quantity = bid.quantity.min(remaining);
+        remaining -= quantity;
+        allocations.push(Allocation {
+            bid_id: bid.id.clone(),
+            wallet_id: bid.wallet_id.clone(),
+            quantity,
+            price: price(bid),
+        });
+    }
+    allocations
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OfferingStatus {
+    Open,
+    /// Allocation is being settled; the book accepts no changes
+    Closing,
+    Closed,
+    Cancelled,
+}
+
+/// An offering with its order book and, once closed, its allocations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OfferingRecord {
+    pub terms: OfferingTerms,
+    pub status: OfferingStatus,
+    pub bids: Vec<Bid>,
+    pub allocations: Vec<Allocation>,
+    /// Name of the strategy used at close
+    pub strategy: Option<String>,
+    pub closed_at: Option<u64>,
+    /// Sequence given to the next bid; never reused after a withdrawal
+    #[serde(default)]
+    pub next_sequence: u64,
+}
+
+impl OfferingRecord {
+    pub fn total_demand(&self) -> f64 {
+        self.bids.iter().map(|b| b.quantity).sum()
+    }
+
+    pub fn total_allocated(&self) -> f64 {
+        self.allocations.iter().map(|a| a.quantity).sum()
+    }
+}
+
+impl TokenizationCore {
+    /// Attach a new offering to an existing asset
+    pub fn create_offering(&self, terms: OfferingTerms) -> Result<String, String> {
+        self.authorize_asset(Permission::Mint, &terms.asset_id)?;
+        terms.validate()?;
+        self.ensure_ids_not_frozen(&[&terms.issuer_wallet_id], &[&terms.asset_id])?;
+        if self.get_asset(&terms.asset_id)?.is_none() {
+            return Err("Asset not found".to_string());
+        }
+        if self.get_wallet(&terms.issuer_wallet_id)?.is_none() {
+            return Err("Issuer wallet not found".to_string());
+        }
+        let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+        if offerings.contains_key(&terms.id) {
+            return Err("Offering already exists".to_string());
+        }
+        let offering_id = terms.id.clone();
+        let record = OfferingRecord {
+            terms,
+            status: OfferingStatus::Open,
+            bids: Vec::new(),
+            allocations: Vec::new(),
+            strategy: None,
+            closed_at: None,
+            next_sequence: 0,
+        };
+        self.record_audit(AuditAction::Create, EntityType::Offering, &offering_id, None::<&OfferingRecord>, Some(&record))?;
+        offerings.insert(offering_id.clone(), record);
+        Ok(offering_id)
+    }
+
+    /// Only those who may mint the asset run its offering
+    pub(crate) fn authorize_offering(&self, offering_id: &str) -> Result<(), String> {
+        match self.get_offering(offering_id)? {
+            Some(offering) => self.authorize_asset(Permission::Mint, &offering.terms.asset_id),
+            None => Ok(()),
+        }
+    }
+
+    fn ensure_offering_not_frozen(&self, offering_id: &str, wallet_ids: &[&str]) -> Result<(), String> {
+        match self.get_offering(offering_id)? {
+            Some(offering) => self.ensure_ids_not_frozen(wallet_ids, &[&offering.terms.asset_id]),
+            None => self.ensure_ids_not_frozen(wallet_ids, &[]),
+        }
+    }
+
+    /// Get an offering by ID
+    pub fn get_offering(&self, offering_id: &str) -> Result<Option<OfferingRecord>, String> {
+        let offerings = self.offerings.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(offerings.get(offering_id).cloned())
+    }
+
+    /// Place a bid, reserving its full value from the wallet balance
+    pub fn submit_bid(
+        &self,
+        offering_id: &str,
+        bid_id: &str,
+        wallet_id: &str,
+        quantity: f64,
+        limit_price: Option<f64>,
+    ) -> Result<Bid, String> {
+        if quantity <= 0.0 {
+            return Err("Bid quantity must be positive".to_string());
+        }
+        self.authorize_wallet(Permission::Transfer, wallet_id)?;
+        self.ensure_offering_not_frozen(offering_id, &[wallet_id])?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+        let offering = offerings.get_mut(offering_id).ok_or("Offering not found")?;
+        let terms = &offering.terms;
+
+        if offering.status != OfferingStatus::Open || now < terms.opens_at || now >= terms.closes_at {
+            return Err("Offering is not accepting bids".to_string());
+        }
+        if offering.bids.iter().any(|b| b.id == bid_id) {
+            return Err("Bid already exists".to_string());
+        }
+        if limit_price.is_some_and(|p| p < terms.price) {
+            return Err(format!("Limit price is below the floor price {}", terms.price));
+        }
+        let price = limit_price.unwrap_or(terms.price);
+        if quantity * price + QUANTITY_EPSILON < terms.min_ticket {
+            return Err(format!("Bid value is below the minimum ticket {}", terms.min_ticket));
+        }
+
+        let wallet = wallets.get_mut(wallet_id).ok_or("Wallet not found")?;
+        let eligibility = &terms.eligibility;
+        if !eligibility.wallet_types.is_empty() && !eligibility.wallet_types.contains(&wallet.wallet_type) {
+            return Err("Wallet type is not eligible for this offering".to_string());
+        }
+        if let Some(allowed) = &eligibility.allowed_owners
+            && !allowed.contains(&wallet.owner)
+        {
+            return Err("Investor is not eligible for this offering".to_string());
+        }
+        if let Some(max) = eligibility.max_quantity_per_wallet {
+            let existing: f64 = offering.bids.iter().filter(|b| b.wallet_id == wallet_id).map(|b| b.quantity).sum();
+            if existing + quantity > max + QUANTITY_EPSILON {
+                return Err(format!("Bids from one wallet may not exceed {} units", max));
+            }
+        }
+
+        let reserved = quantity * price;
+        if wallet.balance + QUANTITY_EPSILON < reserved {
+            return Err("Insufficient wallet balance".to_string());
+        }
+        let wallet_before = wallet.clone();
+        wallet.balance -= reserved;
+        wallet.updated_at = now;
+        self.record_audit(AuditAction::Update, EntityType::Wallet, wallet_id, Some(&wallet_before), Some(&*wallet))?;
+        self.emit_event(DomainEvent::WalletBalanceChanged {
+            wallet_id: wallet_id.to_string(),
+            amount: -reserved,
+        })?;
+
+        let bid = Bid {
+            id: bid_id.to_string(),
+            wallet_id: wallet_id.to_string(),
+            quantity,
+            limit_price,
+            submitted_at: now,
+            sequence: offering.next_sequence,
+        };
+        let before = offering.clone();
+        offering.next_sequence += 1;
+        offering.bids.push(bid.clone());
+        self.record_audit(AuditAction::Update, EntityType::Offering, offering_id, Some(&before), Some(&*offering))?;
+        Ok(bid)
+    }
+
+    /// Withdraw a bid while the offering is open, refunding its reservation
+    pub fn withdraw_bid(&self, offering_id: &str, bid_id: &str) -> Result<(), String> {
+        let bidder = self
+            .get_offering(offering_id)?
+            .and_then(|o| o.bids.into_iter().find(|b| b.id == bid_id))
+            .map(|b| b.wallet_id);
+        if let Some(wallet_id) = &bidder {
+            self.authorize_wallet(Permission::Transfer, wallet_id)?;
+        }
+        self.ensure_offering_not_frozen(offering_id, &[])?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+        let offering = offerings.get_mut(offering_id).ok_or("Offering not found")?;
+        if offering.status != OfferingStatus::Open || now >= offering.terms.closes_at {
+            return Err("Offering is closed".to_string());
+        }
+        let index = offering.bids.iter().position(|b| b.id == bid_id).ok_or("Bid not found")?;
+        let before = offering.clone();
+        let bid = offering.bids.remove(index);
+        if let Some(wallet) = wallets.get_mut(&bid.wallet_id) {
+            let wallet_before = wallet.clone();
+            let refund = bid.quantity * bid.effective_price(offering.terms.price);
+            wallet.balance += refund;
+            wallet.updated_at = now;
+            self.record_audit(AuditAction::Update, EntityType::Wallet, &bid.wallet_id, Some(&wallet_before), Some(&*wallet))?;
+            self.emit_event(DomainEvent::WalletBalanceChanged {
+                wallet_id: bid.wallet_id.clone(),
+                amount: refund,
+            })?;
+        }
+        self.record_audit(AuditAction::Update, EntityType::Offering, offering_id, Some(&before), Some(&*offering))?;
+        Ok(())
+    }
+
+    /// Close the book once the window has ended and allocate with the given strategy
+    pub fn close_offering(&self, offering_id: &str, strategy: &dyn AllocationStrategy) -> Result<OfferingRecord, String> {
+        self.authorize_offering(offering_id)?;
+        self.ensure_offering_not_frozen(offering_id, &[])?;
+        let now = self.now();
+        // Claim the book so that no bid, withdrawal, cancellation or second close interleaves
+        let record = {
+            let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+            let offering = offerings.get_mut(offering_id).ok_or("Offering not found")?;
+            if offering.status != OfferingStatus::Open {
+                return Err("Offering is not open".to_string());
+            }
+            if now < offering.terms.closes_at {
+                return Err("Offering window has not ended".to_string());
+            }
+            offering.status = OfferingStatus::Closing;
+            offering.clone()
+        };
+
+        match self.settle_offering(record, strategy, now) {
+            Ok(closed) => Ok(closed),
+            Err(error) => {
+                let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+                if let Some(offering) = offerings.get_mut(offering_id) {
+                    offering.status = OfferingStatus::Open;
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Allocate a claimed offering, mint the fills and move the reserved cash
+    fn settle_offering(&self, mut record: OfferingRecord, strategy: &dyn AllocationStrategy, now: u64) -> Result<OfferingRecord, String> {
+        let terms = &record.terms;
+        let mut allocations: Vec<Allocation> = strategy
+            .allocate(terms, &record.bids)
+            .into_iter()
+            .filter(|a| a.quantity > QUANTITY_EPSILON)
+            .collect();
+        if allocations.iter().map(|a| a.quantity).sum::<f64>() > terms.quantity_offered + QUANTITY_EPSILON {
+            return Err(format!("Strategy {} over-allocated the offering", strategy.name()));
+        }
+        for allocation in &mut allocations {
+            let bid = record
+                .bids
+                .iter()
+                .find(|b| b.id == allocation.bid_id && b.wallet_id == allocation.wallet_id)
+                .ok_or_else(|| format!("Strategy {} allocated to unknown bid {}", strategy.name(), allocation.bid_id))?;
+            if allocation.quantity > bid.quantity + QUANTITY_EPSILON {
+                return Err(format!("Strategy {} allocated more than bid {} asked for", strategy.name(), bid.id));
+            }
+            // A strategy may not charge more than the investor reserved
+            allocation.price = allocation.price.min(bid.effective_price(terms.price));
+        }
+        let mut allocated_bids: Vec<&str> = allocations.iter().map(|a| a.bid_id.as_str()).collect();
+        allocated_bids.sort_unstable();
+        if allocated_bids.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(format!("Strategy {} allocated a bid twice", strategy.name()));
+        }
+
+        {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            let assets = self.assets.read().map_err(|_| "Failed to acquire read lock")?;
+            let asset = assets.get(&terms.asset_id).ok_or("Asset not found")?;
+            if !allocations.is_empty() {
+                authorize_current(Permission::Mint, Some(&asset.owner))?;
+            }
+            for allocation in &allocations {
+                self.check_four_eyes(OperationKind::Mint, Some(allocation.quantity))?;
+            }
+            let mut involved: Vec<&DigitalWallet> = vec![wallets.get(&terms.issuer_wallet_id).ok_or("Issuer wallet not found")?];
+            for bid in &record.bids {
+                involved.push(wallets.get(&bid.wallet_id).ok_or_else(|| format!("Wallet {} not found", bid.wallet_id))?);
+            }
+            self.ensure_not_frozen(&involved, &[asset])?;
+        }
+
+        let mut minted: Vec<&Allocation> = Vec::new();
+        for allocation in &allocations {
+            if let Err(error) = self.mint_to_wallet(&allocation.wallet_id, &terms.asset_id, allocation.quantity) {
+                // Mint policies are only known at mint time: take back what was issued so far
+                without_subject(|| {
+                    minted
+                        .iter()
+                        .try_for_each(|m| self.burn_from_wallet(&m.wallet_id, &terms.asset_id, m.quantity))
+                })?;
+                return Err(error);
+            }
+            minted.push(allocation);
+        }
+
+        let mut proceeds = 0.0;
+        for bid in &record.bids {
+            let reserved = bid.quantity * bid.effective_price(terms.price);
+            let cost = allocations
+                .iter()
+                .find(|a| a.bid_id == bid.id)
+                .map_or(0.0, |a| a.quantity * a.price);
+            proceeds += cost;
+            if reserved - cost > QUANTITY_EPSILON {
+                self.credit_wallet_balance(&bid.wallet_id, reserved - cost)?;
+            }
+        }
+        if proceeds > 0.0 {
+            self.credit_wallet_balance(&terms.issuer_wallet_id, proceeds)?;
+        }
+
+        let mut before = record.clone();
+        before.status = OfferingStatus::Open;
+        record.status = OfferingStatus::Closed;
+        record.allocations = allocations;
+        record.strategy = Some(strategy.name().to_string());
+        record.closed_at = Some(now);
+        let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+        offerings.insert(record.terms.id.clone(), record.clone());
+        self.record_audit(AuditAction::Process, EntityType::Offering, &record.terms.id, Some(&before), Some(&record))?;
+        Ok(record)
+    }
+
+    /// Cancel an open offering and refund every bid
+    pub fn cancel_offering(&self, offering_id: &str) -> Result<(), String> {
+        self.authorize_offering(offering_id)?;
+        self.ensure_offering_not_frozen(offering_id, &[])?;
+        let now = self.now();
+        let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut offerings = self.offerings.write().map_err(|_| "Failed to acquire write lock")?;
+        let offering = offerings.get_mut(offering_id).ok_or("Offering not found")?;
+        if offering.status != OfferingStatus::Open {
+            return Err("Offering is not open".to_string());
+        }
+        for bid in &offering.bids {
+            if let Some(wallet) = wallets.get_mut(&bid.wallet_id) {
+                let wallet_before = wallet.clone();
+                let refund = bid.quantity * bid.effective_price(offering.terms.price);
+                wallet.balance += refund;
+                wallet.updated_at = now;
+                self.record_audit(AuditAction::Update, EntityType::Wallet, &bid.wallet_id, Some(&wallet_before), Some(&*wallet))?;
+                self.emit_event(DomainEvent::WalletBalanceChanged {
+                    wallet_id: bid.wallet_id.clone(),
+                    amount: refund,
+                })?;
+            }
+        }
+        let before = offering.clone();
+        offering.status = OfferingStatus::Cancelled;
+        self.record_audit(AuditAction::Cancel, EntityType::Offering, offering_id, Some(&before), Some(&*offering))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::approvals::{ActionStatus, ApprovalOperation, ApprovalRule, Submission};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, DigitalWallet, TokenizedAsset};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for (id, wallet_type) in [
+            ("issuer", WalletType::Custodial),
+            ("alice", WalletType::Custodial),
+            ("bob", WalletType::Custodial),
+            ("carol", WalletType::NonCustodial),
+        ] {
+            core.create_wallet(DigitalWallet {
+                balance: 1_000.0,
+                ..wallet(id, id, wallet_type)
+            })
+            .unwrap();
+        }
+        core.create_offering(OfferingTerms {
+            id: "ipo".to_string(),
+            asset_id: "asset1".to_string(),
+            issuer_wallet_id: "issuer".to_string(),
+            opens_at: 10,
+            closes_at: 20,
+            quantity_offered: 100.0,
+            price: 2.0,
+            min_ticket: 20.0,
+            eligibility: EligibilityCriteria {
+                wallet_types: vec![WalletType::Custodial],
+                allowed_owners: None,
+                max_quantity_per_wallet: Some(150.0),
+            },
+        })
+        .unwrap();
+        core
+    }
+
+    #[test]
+    fn test_bids_enforce_window_ticket_and_eligibility() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        assert!(core.submit_bid("ipo", "b1", "alice", 50.0, None).is_err());
+
+        clock.set(10);
+        assert!(core.submit_bid("ipo", "b1", "alice", 5.0, None).unwrap_err().contains("minimum ticket"));
+        assert!(core.submit_bid("ipo", "b1", "carol", 50.0, None).is_err());
+        assert!(core.submit_bid("ipo", "b1", "alice", 200.0, None).is_err());
+        core.submit_bid("ipo", "b1", "alice", 50.0, Some(3.0)).unwrap();
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 850.0);
+
+        core.withdraw_bid("ipo", "b1").unwrap();
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 1_000.0);
+    }
+
+    #[test]
+    fn test_pro_rata_close_mints_and_refunds() {
+        let clock = ManualClock::new(10);
+        let core = setup(&clock);
+        core.submit_bid("ipo", "b1", "alice", 150.0, None).unwrap();
+        core.submit_bid("ipo", "b2", "bob", 50.0, None).unwrap();
+
+        assert!(core.close_offering("ipo", &ProRataAllocation).is_err());
+        clock.set(20);
+        let record = core.close_offering("ipo", &ProRataAllocation).unwrap();
+        assert_eq!(record.total_allocated(), 100.0);
+        assert_eq!(core.get_holding("alice", "asset1").unwrap(), 75.0);
+        assert_eq!(core.get_holding("bob", "asset1").unwrap(), 25.0);
+        // Alice reserved 300 and paid 150
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 850.0);
+        assert_eq!(core.get_wallet("issuer").unwrap().unwrap().balance, 1_200.0);
+        assert_eq!(core.get_issued_supply("asset1").unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_mint_approval_rule_gates_the_close_as_a_whole() {
+        let clock = ManualClock::new(10);
+        let core = setup(&clock);
+        core.submit_bid("ipo", "b1", "alice", 60.0, None).unwrap();
+        core.submit_bid("ipo", "b2", "bob", 40.0, None).unwrap();
+        core.configure_approval_rule(ApprovalRule::new(OperationKind::Mint, 1, vec!["ops".to_string()])).unwrap();
+        let issuer = Subject::new("issuer", &[Role::Issuer]);
+        let ops = Subject::new("ops", &[Role::TransferAgent]);
+
+        clock.set(20);
+        let error = core.as_subject(&issuer, || core.close_offering("ipo", &ProRataAllocation)).unwrap_err();
+        assert!(error.contains("submit it for approval"), "{}", error);
+        assert_eq!(core.get_offering("ipo").unwrap().unwrap().status, OfferingStatus::Open);
+
+        let close = |strategy: &str| ApprovalOperation::CloseOffering {
+            offering_id: "ipo".to_string(),
+            strategy: strategy.to_string(),
+        };
+        assert!(core.as_subject(&issuer, || core.submit_operation(close("lottery"))).is_err());
+        let Submission::Pending(action) = core.as_subject(&issuer, || core.submit_operation(close("pro-rata"))).unwrap() else {
+            panic!("expected a pending action");
+        };
+        let executed = core.as_subject(&ops, || core.approve_action(&action.id, None)).unwrap();
+        assert_eq!(executed.status, ActionStatus::Executed);
+        assert_eq!(core.get_offering("ipo").unwrap().unwrap().status, OfferingStatus::Closed);
+        assert_eq!(core.get_holding("alice", "asset1").unwrap(), 60.0);
+        assert_eq!(core.get_holding("bob", "asset1").unwrap(), 40.0);
+    }
+
+    #[test]
+    fn test_first_come_and_price_priority_strategies() {
+        let terms = OfferingTerms {
+            id: "o".to_string(),
+            asset_id: "a".to_string(),
+            issuer_wallet_id: "i".to_string(),
+            opens_at: 0,
+            closes_at: 1,
+            quantity_offered: 100.0,
+            price: 1.0,
+            min_ticket: 0.0,
+            eligibility: EligibilityCriteria::default(),
+        };
+        let bid = |id: &str, quantity: f64, limit_price: Option<f64>, sequence: u64| Bid {
+            id: id.to_string(),
+            wallet_id: id.to_string(),
+            quantity,
+            limit_price,
+            submitted_at: sequence,
+            sequence,
+        };
+        let bids = vec![
+            bid("early", 80.0, Some(1.0), 0),
+            bid("mid", 40.0, Some(1.5), 1),
+            bid("late", 40.0, Some(2.0), 2),
+        ];
+
+        let first_come = FirstComeAllocation.allocate(&terms, &bids);
+        assert_eq!(first_come.len(), 2);
+        assert_eq!(first_come[1].quantity, 20.0);
+
+        let by_price = PricePriorityAllocation.allocate(&terms, &bids);
+        assert_eq!(by_price[0].bid_id, "late");
+        assert_eq!(by_price[2].quantity, 20.0);
+        assert!(by_price.iter().all(|a| a.price == 1.0));
+    }
+
+    /// Splits the offering evenly between bids, whatever each asked for
+    struct GreedyAllocation;
+
+    impl AllocationStrategy for GreedyAllocation {
+        fn name(&self) -> &str {
+            "greedy"
+        }
+
+        fn allocate(&self, terms: &OfferingTerms, bids: &[Bid]) -> Vec<Allocation> {
+            bids.iter()
+                .map(|bid| Allocation {
+                    bid_id: bid.id.clone(),
+                    wallet_id: bid.wallet_id.clone(),
+                    quantity: terms.quantity_offered / bids.len() as f64,
+                    price: terms.price,
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_close_validates_allocations_before_settling() {
+        let clock = ManualClock::new(10);
+        let core = setup(&clock);
+        core.submit_bid("ipo", "b1", "alice", 20.0, None).unwrap();
+        core.submit_bid("ipo", "b2", "bob", 20.0, None).unwrap();
+        core.withdraw_bid("ipo", "b1").unwrap();
+        let rebid = core.submit_bid("ipo", "b3", "alice", 80.0, None).unwrap();
+        // Sequences are not reused after a withdrawal
+        assert_eq!(rebid.sequence, 2);
+
+        clock.set(20);
+        // Bob asked for 20 but would be given 50: nothing moves and the book stays open
+        let error = core.close_offering("ipo", &GreedyAllocation).unwrap_err();
+        assert!(error.contains("more than bid b2"), "{}", error);
+        assert_eq!(core.get_offering("ipo").unwrap().unwrap().status, OfferingStatus::Open);
+        assert_eq!(core.get_holding("alice", "asset1").unwrap(), 0.0);
+        assert_eq!(core.get_wallet("alice").unwrap().unwrap().balance, 840.0);
+        assert_eq!(core.get_wallet("issuer").unwrap().unwrap().balance, 1_000.0);
+
+        let record = core.close_offering("ipo", &FirstComeAllocation).unwrap();
+        assert_eq!(record.status, OfferingStatus::Closed);
+        assert_eq!(core.get_holding("alice", "asset1").unwrap(), 80.0);
+        assert!(core.close_offering("ipo", &FirstComeAllocation).is_err());
+    }
+}
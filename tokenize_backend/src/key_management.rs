@@ -0,0 +1,664 @@
+//! Platform key management
+//!
+//! Keys the platform signs with live in a `KeyStore` and never leave it: callers
+//! get public keys and signatures, never private keys. Each key is a series of
+//! versions. Rotating a key adds a version that signs from then on and marks the
+//! previous one superseded, keeping its public key so earlier signatures can still
+//! be checked; retiring a key stops it signing altogether.
+//!
+//! `InMemoryKeyStore` keeps keys for the life of the process and suits tests.
+//! `FileKeyStore` keeps them in one file encrypted with AES-256-GCM under a key
+//! derived from a password with Argon2id, rewritten whole on every change. The
+//! core records an audit entry for every generation, rotation, retirement and
+//! signature.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{AuditAction, EntityType, FieldChange};
+use crate::core_component::TokenizationCore;
+use crate::signatures::KeyAlgorithm;
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use ed25519_dalek::Signer as _;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use zeroize::Zeroizing;
+
+/// Format tag of encrypted key store files, also bound into their ciphertext
+pub const KEY_STORE_FORMAT: &str = "tokenize-key-store:v1";
+
+/// Lifecycle of one version of a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyVersionState {
+    /// Signs new messages
+    Active,
+    /// Replaced by a later version; kept to check earlier signatures
+    Superseded,
+    Retired,
+}
+
+/// Public half of one version of a key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyVersion {
+    /// Starting at 1
+    pub version: u32,
+    /// Hex-encoded
+    pub public_key: String,
+    pub created_at: u64,
+    pub state: KeyVersionState,
+}
+
+/// A platform key and its versions, without private material
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManagedKey {
+    pub id: String,
+    pub algorithm: KeyAlgorithm,
+    pub created_at: u64,
+    pub retired_at: Option<u64>,
+    /// Oldest first
+    pub versions: Vec<KeyVersion>,
+}
+
+impl ManagedKey {
+    /// The version new signatures are made with, unless the key is retired
+    pub fn active_version(&self) -> Option<&KeyVersion> {
+        self.versions.iter().find(|v| v.state == KeyVersionState::Active)
+    }
+}
+
+/// A signature made by a managed key
+///
+/// Checkable with `signatures::verify_signature` against the version's public key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeySignature {
+    pub key_id: String,
+    pub version: u32,
+    pub algorithm: KeyAlgorithm,
+    /// Hex-encoded
+    pub public_key: String,
+    /// Hex-encoded
+    pub signature: String,
+}
+
+/// Storage for platform keys that signs without handing out private keys
+pub trait KeyStore: Send + Sync {
+    /// Generate the first version of a new key
+    fn generate(&self, key_id: &str, algorithm: KeyAlgorithm, now: u64) -> Result<ManagedKey, String>;
+
+    /// Add a version that signs from now on, superseding the active one
+    fn rotate(&self, key_id: &str, now: u64) -> Result<ManagedKey, String>;
+
+    /// Stop a key signing; its public keys stay available
+    fn retire(&self, key_id: &str, now: u64) -> Result<ManagedKey, String>;
+
+    /// Sign `message` with the key's active version
+    fn sign(&self, key_id: &str, message: &[u8]) -> Result<KeySignature, String>;
+
+    /// Every key, in no particular order
+    fn keys(&self) -> Result<Vec<ManagedKey>, String>;
+}
+
+type SecretKey = Zeroizing<[u8; 32]>;
+
+/// A key with the private half of each version, by version index
+#[derive(Clone)]
+struct KeyEntry {
+    key: ManagedKey,
+    secrets: Vec<SecretKey>,
+}
+
+impl fmt::Debug for KeyEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyEntry").field("key", &self.key).finish_non_exhaustive()
+    }
+}
+
+fn generate_secret(algorithm: KeyAlgorithm) -> SecretKey {
+    loop {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(secret.as_mut());
+        // Almost every 32-byte string is a valid secp256k1 scalar; draw again if not
+        if algorithm == KeyAlgorithm::Ed25519 || k256::ecdsa::SigningKey::from_slice(secret.as_ref()).is_ok() {
+            return secret;
+        }
+    }
+}
+
+fn public_key_hex(algorithm: KeyAlgorithm, secret: &SecretKey) -> Result<String, String> {
+    match algorithm {
+        KeyAlgorithm::Ed25519 => Ok(hex::encode(ed25519_dalek::SigningKey::from_bytes(secret).verifying_key().to_bytes())),
+        KeyAlgorithm::Secp256k1 => {
+            let key = k256::ecdsa::SigningKey::from_slice(secret.as_ref()).map_err(|_| "Invalid secp256k1 key")?;
+            Ok(hex::encode(key.verifying_key().to_sec1_bytes()))
+        }
+    }
+}
+
+fn sign_message(algorithm: KeyAlgorithm, secret: &SecretKey, message: &[u8]) -> Result<Vec<u8>, String> {
+    match algorithm {
+        KeyAlgorithm::Ed25519 => Ok(ed25519_dalek::SigningKey::from_bytes(secret).sign(message).to_bytes().to_vec()),
+        KeyAlgorithm::Secp256k1 => {
+            let key = k256::ecdsa::SigningKey::from_slice(secret.as_ref()).map_err(|_| "Invalid secp256k1 key")?;
+            let signature: k256::ecdsa::Signature = key.sign(message);
+            Ok(signature.to_bytes().to_vec())
+        }
+    }
+}
+
+fn new_version(algorithm: KeyAlgorithm, version: u32, now: u64) -> Result<(KeyVersion, SecretKey), String> {
+    let secret = generate_secret(algorithm);
+    let version = KeyVersion {
+        version,
+        public_key: public_key_hex(algorithm, &secret)?,
+        created_at: now,
+        state: KeyVersionState::Active,
+    };
+    Ok((version, secret))
+}
+
+fn generate_entry(
+    entries: &mut HashMap<String, KeyEntry>,
+    key_id: &str,
+    algorithm: KeyAlgorithm,
+    now: u64,
+) -> Result<ManagedKey, String> {
+    if key_id.trim().is_empty() {
+        return Err("Key ID is required".to_string());
+    }
+    if entries.contains_key(key_id) {
+        return Err(format!("Key {} already exists", key_id));
+    }
+    let (version, secret) = new_version(algorithm, 1, now)?;
+    let key = ManagedKey {
+        id: key_id.to_string(),
+        algorithm,
+        created_at: now,
+        retired_at: None,
+        versions: vec![version],
+    };
+    entries.insert(key_id.to_string(), KeyEntry {
+        key: key.clone(),
+        secrets: vec![secret],
+    });
+    Ok(key)
+}
+
+fn rotate_entry(entries: &mut HashMap<String, KeyEntry>, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+    let entry = entries.get_mut(key_id).ok_or("Key not found")?;
+    if entry.key.retired_at.is_some() {
+        return Err("Key is retired".to_string());
+    }
+    let (version, secret) = new_version(entry.key.algorithm, entry.key.versions.len() as u32 + 1, now)?;
+    for previous in entry.key.versions.iter_mut().filter(|v| v.state == KeyVersionState::Active) {
+        previous.state = KeyVersionState::Superseded;
+    }
+    entry.key.versions.push(version);
+    entry.secrets.push(secret);
+    Ok(entry.key.clone())
+}
+
+fn retire_entry(entries: &mut HashMap<String, KeyEntry>, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+    let entry = entries.get_mut(key_id).ok_or("Key not found")?;
+    if entry.key.retired_at.is_some() {
+        return Err("Key is already retired".to_string());
+    }
+    entry.key.retired_at = Some(now);
+    for version in &mut entry.key.versions {
+        version.state = KeyVersionState::Retired;
+    }
+    Ok(entry.key.clone())
+}
+
+fn sign_entry(entries: &HashMap<String, KeyEntry>, key_id: &str, message: &[u8]) -> Result<KeySignature, String> {
+    let entry = entries.get(key_id).ok_or("Key not found")?;
+    let version = entry.key.active_version().ok_or("Key is retired")?;
+    let secret = &entry.secrets[version.version as usize - 1];
+    Ok(KeySignature {
+        key_id: key_id.to_string(),
+        version: version.version,
+        algorithm: entry.key.algorithm,
+        public_key: version.public_key.clone(),
+        signature: hex::encode(sign_message(entry.key.algorithm, secret, message)?),
+    })
+}
+
+/// Key store that keeps everything in memory
+#[derive(Debug, Default)]
+pub struct InMemoryKeyStore {
+    entries: RwLock<HashMap<String, KeyEntry>>,
+}
+
+impl KeyStore for InMemoryKeyStore {
+    fn generate(&self, key_id: &str, algorithm: KeyAlgorithm, now: u64) -> Result<ManagedKey, String> {
+        let mut entries = self.entries.write().map_err(|_| "Failed to acquire write lock")?;
+        generate_entry(&mut entries, key_id, algorithm, now)
+    }
+
+    fn rotate(&self, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+        let mut entries = self.entries.write().map_err(|_| "Failed to acquire write lock")?;
+        rotate_entry(&mut entries, key_id, now)
+    }
+
+    fn retire(&self, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+        let mut entries = self.entries.write().map_err(|_| "Failed to acquire write lock")?;
+        retire_entry(&mut entries, key_id, now)
+    }
+
+    fn sign(&self, key_id: &str, message: &[u8]) -> Result<KeySignature, String> {
+        let entries = self.entries.read().map_err(|_| "Failed to acquire read lock")?;
+        sign_entry(&entries, key_id, message)
+    }
+
+    fn keys(&self) -> Result<Vec<ManagedKey>, String> {
+        let entries = self.entries.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(entries.values().map(|e| e.key.clone()).collect())
+    }
+}
+
+/// Argon2id cost parameters used to derive a key store's encryption key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: argon2::Params::DEFAULT_M_COST,
+            iterations: argon2::Params::DEFAULT_T_COST,
+            parallelism: argon2::Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// On-disk layout of an encrypted key store
+#[derive(Serialize, Deserialize)]
+struct KeyStoreFile {
+    format: String,
+    kdf: KdfParams,
+    /// Hex-encoded
+    salt: String,
+    /// Hex-encoded, fresh for every write
+    nonce: String,
+    /// Hex-encoded AES-256-GCM encryption of the keys as JSON
+    ciphertext: String,
+}
+
+/// A key as held inside the encrypted payload
+#[derive(Serialize, Deserialize)]
+struct SealedKey {
+    key: ManagedKey,
+    /// Hex-encoded private key of each version
+    secrets: Vec<Zeroizing<String>>,
+}
+
+fn derive_key(password: &str, salt: &[u8], kdf: KdfParams) -> Result<SecretKey, String> {
+    if password.is_empty() {
+        return Err("Key store password is required".to_string());
+    }
+    let params = argon2::Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("Failed to derive key store key: {}", e))?;
+    Ok(key)
+}
+
+/// Key store persisted as a password-encrypted file
+///
+/// Keys are decrypted into memory when the store is opened. Every change writes a
+/// freshly encrypted copy next to the file and renames it over the original, so
+/// the file always holds either the old or the new set of keys.
+#[derive(Debug)]
+pub struct FileKeyStore {
+    path: PathBuf,
+    kdf: KdfParams,
+    salt: [u8; 16],
+    cipher_key: SecretKey,
+    cache: InMemoryKeyStore,
+}
+
+impl FileKeyStore {
+    /// Open the store at `path`, creating it with default key derivation costs if needed
+    pub fn open(path: impl AsRef<Path>, password: &str) -> Result<Self, String> {
+        Self::open_with(path, password, KdfParams::default())
+    }
+
+    /// Open the store at `path`, creating it with the given key derivation costs if
+    /// needed; an existing file keeps the costs it was created with
+    pub fn open_with(path: impl AsRef<Path>, password: &str, kdf: KdfParams) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if !path.exists() {
+            let mut salt = [0u8; 16];
+            rand::rngs::OsRng.fill_bytes(&mut salt);
+            let store = Self {
+                cipher_key: derive_key(password, &salt, kdf)?,
+                path,
+                kdf,
+                salt,
+                cache: InMemoryKeyStore::default(),
+            };
+            if let Some(dir) = store.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create key store directory: {}", e))?;
+            }
+            store.save(&HashMap::new())?;
+            return Ok(store);
+        }
+
+        let contents = fs::read_to_string(&path).map_err(|e| format!("Failed to read key store: {}", e))?;
+        let file: KeyStoreFile = serde_json::from_str(&contents).map_err(|e| format!("Invalid key store file: {}", e))?;
+        if file.format != KEY_STORE_FORMAT {
+            return Err(format!("Unsupported key store format {}", file.format));
+        }
+        let salt: [u8; 16] = hex::decode(&file.salt)
+            .ok()
+            .and_then(|s| s.try_into().ok())
+            .ok_or("Invalid key store salt")?;
+        let nonce: [u8; 12] = hex::decode(&file.nonce)
+            .ok()
+            .and_then(|n| n.try_into().ok())
+            .ok_or("Invalid key store nonce")?;
+        let ciphertext = hex::decode(&file.ciphertext).map_err(|_| "Invalid key store ciphertext")?;
+        let cipher_key = derive_key(password, &salt, file.kdf)?;
+        let cipher = Aes256Gcm::new_from_slice(cipher_key.as_ref()).map_err(|e| e.to_string())?;
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(&Nonce::from(nonce), Payload {
+                    msg: &ciphertext,
+                    aad: KEY_STORE_FORMAT.as_bytes(),
+                })
+                .map_err(|_| "Wrong key store password or corrupted key store")?,
+        );
+        let sealed: Vec<SealedKey> = serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid key store contents: {}", e))?;
+        let mut entries = HashMap::new();
+        for SealedKey { key, secrets } in sealed {
+            let secrets = secrets
+                .iter()
+                .map(|s| {
+                    let bytes = Zeroizing::new(hex::decode(s.as_str()).map_err(|_| "Invalid private key in key store")?);
+                    let mut secret = Zeroizing::new([0u8; 32]);
+                    if bytes.len() != secret.len() {
+                        return Err("Invalid private key in key store".to_string());
+                    }
+                    secret.copy_from_slice(&bytes);
+                    Ok(secret)
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            if secrets.len() != key.versions.len() {
+                return Err(format!("Key {} is missing private keys", key.id));
+            }
+            entries.insert(key.id.clone(), KeyEntry { key, secrets });
+        }
+        Ok(Self {
+            path,
+            kdf: file.kdf,
+            salt,
+            cipher_key,
+            cache: InMemoryKeyStore {
+                entries: RwLock::new(entries),
+            },
+        })
+    }
+
+    fn save(&self, entries: &HashMap<String, KeyEntry>) -> Result<(), String> {
+        let sealed: Vec<SealedKey> = entries
+            .values()
+            .map(|e| SealedKey {
+                key: e.key.clone(),
+                secrets: e.secrets.iter().map(|s| Zeroizing::new(hex::encode(s.as_ref()))).collect(),
+            })
+            .collect();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&sealed).map_err(|e| e.to_string())?);
+        let mut nonce = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let cipher = Aes256Gcm::new_from_slice(self.cipher_key.as_ref()).map_err(|e| e.to_string())?;
+        let ciphertext = cipher
+            .encrypt(&Nonce::from(nonce), Payload {
+                msg: &plaintext,
+                aad: KEY_STORE_FORMAT.as_bytes(),
+            })
+            .map_err(|_| "Failed to encrypt key store")?;
+        let file = KeyStoreFile {
+            format: KEY_STORE_FORMAT.to_string(),
+            kdf: self.kdf,
+            salt: hex::encode(self.salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        let contents = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        let temp = self.path.with_extension("tmp");
+        fs::write(&temp, contents).map_err(|e| format!("Failed to write key store: {}", e))?;
+        fs::rename(&temp, &self.path).map_err(|e| format!("Failed to write key store: {}", e))
+    }
+
+    /// Apply a change to a copy of the keys and keep it only once it is on disk
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, KeyEntry>) -> Result<ManagedKey, String>) -> Result<ManagedKey, String> {
+        let mut entries = self.cache.entries.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut updated = entries.clone();
+        let key = change(&mut updated)?;
+        self.save(&updated)?;
+        *entries = updated;
+        Ok(key)
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn generate(&self, key_id: &str, algorithm: KeyAlgorithm, now: u64) -> Result<ManagedKey, String> {
+        self.update(|entries| generate_entry(entries, key_id, algorithm, now))
+    }
+
+    fn rotate(&self, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+        self.update(|entries| rotate_entry(entries, key_id, now))
+    }
+
+    fn retire(&self, key_id: &str, now: u64) -> Result<ManagedKey, String> {
+        self.update(|entries| retire_entry(entries, key_id, now))
+    }
+
+    fn sign(&self, key_id: &str, message: &[u8]) -> Result<KeySignature, String> {
+        self.cache.sign(key_id, message)
+    }
+
+    fn keys(&self) -> Result<Vec<ManagedKey>, String> {
+        self.cache.keys()
+    }
+}
+
+impl TokenizationCore {
+    /// Keep platform keys in the given store instead of in memory
+    pub fn with_key_store(mut self, key_store: Arc<dyn KeyStore>) -> Self {
+        self.key_store = key_store;
+        self
+    }
+
+    /// Generate a platform key
+    pub fn generate_managed_key(&self, key_id: &str, algorithm: KeyAlgorithm) -> Result<ManagedKey, String> {
+        authorize_current(Permission::ManageKeys, None)?;
+        let key = self.key_store.generate(key_id, algorithm, self.now())?;
+        self.record_audit(AuditAction::Create, EntityType::ManagedKey, key_id, None::<&ManagedKey>, Some(&key))?;
+        Ok(key)
+    }
+
+    /// Rotate a platform key to a new version
+    pub fn rotate_managed_key(&self, key_id: &str) -> Result<ManagedKey, String> {
+        authorize_current(Permission::ManageKeys, None)?;
+        let before = self.find_managed_key(key_id)?;
+        let key = self.key_store.rotate(key_id, self.now())?;
+        self.record_audit(AuditAction::Rotate, EntityType::ManagedKey, key_id, before.as_ref(), Some(&key))?;
+        Ok(key)
+    }
+
+    /// Retire a platform key so that it no longer signs
+    pub fn retire_managed_key(&self, key_id: &str) -> Result<ManagedKey, String> {
+        authorize_current(Permission::ManageKeys, None)?;
+        let before = self.find_managed_key(key_id)?;
+        let key = self.key_store.retire(key_id, self.now())?;
+        self.record_audit(AuditAction::Retire, EntityType::ManagedKey, key_id, before.as_ref(), Some(&key))?;
+        Ok(key)
+    }
+
+    /// Get every platform key, by ID
+    pub fn get_managed_keys(&self) -> Result<Vec<ManagedKey>, String> {
+        authorize_current(Permission::UseKeys, None)?;
+        let mut keys = self.key_store.keys()?;
+        keys.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(keys)
+    }
+
+    /// Get a platform key by ID
+    pub fn get_managed_key(&self, key_id: &str) -> Result<Option<ManagedKey>, String> {
+        authorize_current(Permission::UseKeys, None)?;
+        self.find_managed_key(key_id)
+    }
+
+    /// Sign a message with a platform key's active version
+    ///
+    /// The audit entry records the SHA-256 digest of the message and either the
+    /// version used or, for refused attempts such as a denied caller or a retired
+    /// key, the error.
+    pub fn sign_with_managed_key(&self, key_id: &str, message: &[u8]) -> Result<KeySignature, String> {
+        let result = authorize_current(Permission::UseKeys, None).and_then(|_| self.key_store.sign(key_id, message));
+        let outcome = match &result {
+            Ok(signature) => FieldChange {
+                field: "version".to_string(),
+                before: None,
+                after: Some(Value::from(signature.version)),
+            },
+            Err(error) => FieldChange {
+                field: "error".to_string(),
+                before: None,
+                after: Some(Value::String(error.clone())),
+            },
+        };
+        let digest = FieldChange {
+            field: "message_sha256".to_string(),
+            before: None,
+            after: Some(Value::String(hex::encode(Sha256::digest(message)))),
+        };
+        self.record_audit_changes(AuditAction::Sign, EntityType::ManagedKey, key_id, vec![outcome, digest])?;
+        result
+    }
+
+    fn find_managed_key(&self, key_id: &str) -> Result<Option<ManagedKey>, String> {
+        Ok(self.key_store.keys()?.into_iter().find(|k| k.id == key_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::audit::AuditQuery;
+    use crate::signatures::verify_signature;
+
+    fn fast_kdf() -> KdfParams {
+        KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tokenize-keys-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("keys.json")
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_versions_verifiable() {
+        let core = TokenizationCore::new();
+        let v1 = core.generate_managed_key("platform", KeyAlgorithm::Ed25519).unwrap();
+        let first = core.sign_with_managed_key("platform", b"first").unwrap();
+        assert_eq!(first.version, 1);
+
+        let rotated = core.rotate_managed_key("platform").unwrap();
+        assert_eq!(rotated.versions[0].state, KeyVersionState::Superseded);
+        assert_eq!(rotated.active_version().unwrap().version, 2);
+        let second = core.sign_with_managed_key("platform", b"second").unwrap();
+        assert_eq!(second.version, 2);
+        assert_ne!(second.public_key, v1.versions[0].public_key);
+        verify_signature(KeyAlgorithm::Ed25519, &first.public_key, b"first", &first.signature).unwrap();
+        verify_signature(KeyAlgorithm::Ed25519, &second.public_key, b"second", &second.signature).unwrap();
+
+        core.retire_managed_key("platform").unwrap();
+        assert_eq!(core.sign_with_managed_key("platform", b"third").unwrap_err(), "Key is retired");
+        assert_eq!(core.rotate_managed_key("platform").unwrap_err(), "Key is retired");
+
+        // Generation, rotation, retirement and every signing attempt are audited; a failed
+        // rotation changes nothing and is not
+        let entries = core
+            .query_audit_log(&AuditQuery {
+                entity_type: Some(EntityType::ManagedKey),
+                ..Default::default()
+            })
+            .unwrap();
+        let actions: Vec<AuditAction> = entries.iter().map(|e| e.action.clone()).collect();
+        assert_eq!(actions, vec![
+            AuditAction::Create,
+            AuditAction::Sign,
+            AuditAction::Rotate,
+            AuditAction::Sign,
+            AuditAction::Retire,
+            AuditAction::Sign
+        ]);
+        let digest = Value::String(hex::encode(Sha256::digest(b"first")));
+        assert!(entries[1].changes.iter().any(|c| c.field == "message_sha256" && c.after == Some(digest.clone())));
+        let refused = Value::String("Key is retired".to_string());
+        assert!(entries[5].changes.iter().any(|c| c.field == "error" && c.after == Some(refused.clone())));
+        assert!(!serde_json::to_string(&entries).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn test_file_store_is_encrypted_and_reopens() {
+        let path = temp_path("reopen");
+        let store = FileKeyStore::open_with(&path, "correct horse", fast_kdf()).unwrap();
+        let key = store.generate("platform", KeyAlgorithm::Secp256k1, 10).unwrap();
+        store.rotate("platform", 20).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("platform"));
+        assert!(!contents.contains(&key.versions[0].public_key));
+
+        assert!(FileKeyStore::open(&path, "wrong password").unwrap_err().starts_with("Wrong key store password"));
+        let reopened = FileKeyStore::open(&path, "correct horse").unwrap();
+        let keys = reopened.keys().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].versions.len(), 2);
+        assert_eq!(keys[0].versions[0].public_key, key.versions[0].public_key);
+        let signature = reopened.sign("platform", b"payload").unwrap();
+        assert_eq!(signature.version, 2);
+        verify_signature(KeyAlgorithm::Secp256k1, &signature.public_key, b"payload", &signature.signature).unwrap();
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_only_key_custodians_use_keys() {
+        let core = TokenizationCore::new().with_key_store(Arc::new(InMemoryKeyStore::default()));
+        let admin = Subject::new("root", &[Role::Admin]);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let investor = Subject::new("alice", &[Role::Investor]);
+        core.as_subject(&admin, || core.generate_managed_key("platform", KeyAlgorithm::Ed25519)).unwrap();
+
+        assert!(core.as_subject(&agent, || core.rotate_managed_key("platform")).is_err());
+        let signature = core.as_subject(&agent, || core.sign_with_managed_key("platform", b"hello")).unwrap();
+        assert_eq!(signature.key_id, "platform");
+        assert!(core.as_subject(&investor, || core.get_managed_keys()).is_err());
+        assert!(core.as_subject(&investor, || core.sign_with_managed_key("platform", b"hello")).is_err());
+
+        // The refused attempt is on record against the caller
+        let last = core.get_audit_log().unwrap().pop().unwrap();
+        assert_eq!((last.action, last.actor.as_str()), (AuditAction::Sign, "alice"));
+        assert!(last.changes.iter().any(|c| c.field == "error"));
+    }
+}
@@ -0,0 +1,42 @@
+//! Tokenization Platform backend library
+//!
+//! Exposes the component catalog API and the tokenization core so they can be
+//! shared between the server binary and tests.
+
+pub mod access_control;
+pub mod approvals;
+pub mod audit;
+pub mod auth;
+pub mod bonds;
+pub mod calendar;
+pub mod cap_table;
+pub mod clock;
+pub mod core_component;
+pub mod corporate_actions;
+pub mod csv_parser;
+pub mod custody;
+pub mod events;
+pub mod freezes;
+pub mod fund_orders;
+pub mod issuance;
+pub mod key_management;
+pub mod live_feed;
+pub mod models;
+pub mod multisig;
+pub mod nav;
+pub mod notifications;
+pub mod policy;
+pub mod reconciliation;
+pub mod recovery;
+pub mod reserves;
+pub mod routes;
+pub mod signatures;
+pub mod spending_limits;
+pub mod vesting;
+pub mod webhooks;
+
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
@@ -0,0 +1,260 @@
+//! Live feed of change notifications for streaming clients
+//!
+//! `LiveFeed` is a `NotificationSink` that keeps the most recent notifications in
+//! a bounded buffer and broadcasts new ones to connected clients. A client that
+//! reconnects with the ID of the last notification it saw first receives
+//! everything it missed from the buffer, then continues live. If the buffer no
+//! longer reaches back that far the client is told about the gap so it can
+//! refetch instead. A client resuming from an ID the feed has not reached yet,
+//! for example after a server restart, is told to reset and continues from the
+//! current head.
+
+use crate::notifications::{event_type_matches, Notification, NotificationSink};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Number of recent notifications kept for resuming clients
+pub const DEFAULT_REPLAY_CAPACITY: usize = 1_000;
+
+/// Event type sent when a resuming client has missed notifications that are no longer buffered
+pub const GAP_EVENT_TYPE: &str = "stream.gap";
+
+/// Event type sent when a client resumes from an ID newer than anything the feed has published
+pub const RESET_EVENT_TYPE: &str = "stream.reset";
+
+/// Topic filter from a comma-separated list such as `asset,compliance` or `wallet.created`
+///
+/// Plain names match a notification's topic; entries with a dot are event type
+/// patterns. An empty filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TopicFilter(Vec<String>);
+
+impl TopicFilter {
+    pub fn parse(topics: Option<&str>) -> Self {
+        Self(
+            topics
+                .unwrap_or_default()
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        )
+    }
+
+    /// Whether the filter is limited to notifications of `topic`
+    pub fn is_within(&self, topic: &str) -> bool {
+        !self.0.is_empty()
+            && self.0.iter().all(|entry| {
+                entry == topic || entry.split_once('.').is_some_and(|(prefix, _)| prefix == topic)
+            })
+    }
+
+    pub fn matches(&self, notification: &Notification) -> bool {
+        self.0.is_empty()
+            || self.0.iter().any(|entry| {
+                if entry.contains('.') || entry == "*" {
+                    event_type_matches(entry, &notification.event_type)
+                } else {
+                    entry == notification.topic()
+                }
+            })
+    }
+}
+
+/// Something to send to a streaming client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FeedItem {
+    Event(Notification),
+    /// Notifications after `requested_after` and before `oldest_available` were dropped
+    Gap {
+        event_type: String,
+        requested_after: u64,
+        oldest_available: u64,
+    },
+    /// `requested_after` is past the feed's `latest` ID; streaming restarts from there
+    Reset {
+        event_type: String,
+        requested_after: u64,
+        latest: u64,
+    },
+}
+
+/// Buffer and broadcast channel shared by every connected client
+pub struct LiveFeed {
+    recent: RwLock<VecDeque<Notification>>,
+    sender: broadcast::Sender<Notification>,
+    capacity: usize,
+}
+
+impl LiveFeed {
+    pub fn new(capacity: usize) -> Arc<Self> {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Arc::new(Self {
+            recent: RwLock::new(VecDeque::with_capacity(capacity)),
+            sender,
+            capacity,
+        })
+    }
+
+    /// Start a client subscription, replaying buffered notifications after `last_event_id`
+    pub fn subscribe(&self, last_event_id: Option<u64>, filter: TopicFilter) -> Result<FeedSubscription, String> {
+        // Holding the buffer lock while subscribing means nothing published in between is lost
+        let recent = self.recent.read().map_err(|_| "Failed to acquire read lock")?;
+        let receiver = self.sender.subscribe();
+        let mut pending = VecDeque::new();
+        let last_sent = recent.back().map(|n| n.id).unwrap_or(0);
+        if let Some(after) = last_event_id.filter(|&after| after > last_sent) {
+            // Remembering `after` would skip every notification up to it
+            pending.push_back(FeedItem::Reset {
+                event_type: RESET_EVENT_TYPE.to_string(),
+                requested_after: after,
+                latest: last_sent,
+            });
+        } else if let Some(after) = last_event_id {
+            let oldest = recent.front().map(|n| n.id);
+            if let Some(oldest) = oldest
+                && oldest > after + 1
+            {
+                pending.push_back(FeedItem::Gap {
+                    event_type: GAP_EVENT_TYPE.to_string(),
+                    requested_after: after,
+                    oldest_available: oldest,
+                });
+            }
+            pending.extend(
+                recent
+                    .iter()
+                    .filter(|n| n.id > after && filter.matches(n))
+                    .cloned()
+                    .map(FeedItem::Event),
+            );
+        }
+        Ok(FeedSubscription {
+            pending,
+            receiver,
+            last_sent,
+            filter,
+        })
+    }
+}
+
+impl NotificationSink for LiveFeed {
+    fn notify(&self, notification: &Notification) {
+        let Ok(mut recent) = self.recent.write() else {
+            return;
+        };
+        if recent.len() == self.capacity {
+            recent.pop_front();
+        }
+        if self.capacity > 0 {
+            recent.push_back(notification.clone());
+        }
+        // No receivers is not an error; the notification stays buffered
+        let _ = self.sender.send(notification.clone());
+    }
+}
+
+/// One client's position in the feed
+pub struct FeedSubscription {
+    pending: VecDeque<FeedItem>,
+    receiver: broadcast::Receiver<Notification>,
+    last_sent: u64,
+    filter: TopicFilter,
+}
+
+impl FeedSubscription {
+    /// Next item for the client; `None` once the feed closes or the client fell too
+    /// far behind, in which case it should reconnect with its last event ID
+    pub async fn next(&mut self) -> Option<FeedItem> {
+        if let Some(item) = self.pending.pop_front() {
+            return Some(item);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(notification) => {
+                    if notification.id <= self.last_sent {
+                        continue;
+                    }
+                    self.last_sent = notification.id;
+                    if self.filter.matches(&notification) {
+                        return Some(FeedItem::Event(notification));
+                    }
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationHub;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_resume_filters_and_reports_gaps() {
+        let hub = NotificationHub::new();
+        let feed = LiveFeed::new(3);
+        hub.subscribe(feed.clone()).unwrap();
+        for event_type in ["asset.created", "wallet.created", "compliance.decided", "asset.minted"] {
+            hub.publish(event_type, 100, Value::Null).unwrap();
+        }
+
+        // Resume after id 2: ids 3 and 4 are still buffered
+        let mut subscription = feed.subscribe(Some(2), TopicFilter::parse(Some("asset,compliance"))).unwrap();
+        let Some(FeedItem::Event(first)) = subscription.next().await else { panic!("expected event") };
+        assert_eq!(first.id, 3);
+        let Some(FeedItem::Event(second)) = subscription.next().await else { panic!("expected event") };
+        assert_eq!(second.event_type, "asset.minted");
+
+        hub.publish("wallet.deleted", 101, Value::Null).unwrap();
+        hub.publish("asset.burned", 101, Value::Null).unwrap();
+        let Some(FeedItem::Event(live)) = subscription.next().await else { panic!("expected event") };
+        assert_eq!((live.id, live.event_type.as_str()), (6, "asset.burned"));
+
+        // Id 1 and 2 have been evicted, so resuming after 0 reports a gap first
+        let mut subscription = feed.subscribe(Some(0), TopicFilter::default()).unwrap();
+        assert_eq!(
+            subscription.next().await,
+            Some(FeedItem::Gap {
+                event_type: GAP_EVENT_TYPE.to_string(),
+                requested_after: 0,
+                oldest_available: 4,
+            })
+        );
+
+        // A fresh client without a last event id only sees new notifications
+        let mut subscription = feed.subscribe(None, TopicFilter::parse(Some("wallet.*"))).unwrap();
+        hub.publish("asset.created", 102, Value::Null).unwrap();
+        hub.publish("wallet.created", 102, Value::Null).unwrap();
+        let Some(FeedItem::Event(live)) = subscription.next().await else { panic!("expected event") };
+        assert_eq!(live.id, 8);
+    }
+
+    #[tokio::test]
+    async fn test_resume_past_latest_resets_to_head() {
+        let hub = NotificationHub::new();
+        let feed = LiveFeed::new(10);
+        hub.subscribe(feed.clone()).unwrap();
+        hub.publish("asset.created", 100, Value::Null).unwrap();
+        hub.publish("asset.minted", 100, Value::Null).unwrap();
+
+        // The client saw ids from an earlier run that went further than this one
+        let mut subscription = feed.subscribe(Some(50), TopicFilter::default()).unwrap();
+        assert_eq!(
+            subscription.next().await,
+            Some(FeedItem::Reset {
+                event_type: RESET_EVENT_TYPE.to_string(),
+                requested_after: 50,
+                latest: 2,
+            })
+        );
+        hub.publish("asset.burned", 101, Value::Null).unwrap();
+        let Some(FeedItem::Event(live)) = subscription.next().await else { panic!("expected event") };
+        assert_eq!((live.id, live.event_type.as_str()), (3, "asset.burned"));
+    }
+}
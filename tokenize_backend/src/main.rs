@@ -1,12 +1,19 @@
-mod models;
-mod csv_parser;
-mod routes;
-
-use tokio;
 use warp::Filter;
 use std::sync::{Arc, RwLock};
-use models::{InMemoryDatabase, TokenizationComponent};
-use csv_parser::parse_csv;
+use tokenize_backend::auth::{self, Authenticator, NewApiKey, ADMIN_ROLE};
+use tokenize_backend::clock::SystemClock;
+use tokenize_backend::core_component::TokenizationCore;
+use tokenize_backend::events::{FileEventStore, DEFAULT_SNAPSHOT_INTERVAL};
+use tokenize_backend::approvals::APPROVAL_EXPIRY_INTERVAL_SECS;
+use tokenize_backend::freezes::FREEZE_EXPIRY_INTERVAL_SECS;
+use tokenize_backend::key_management::FileKeyStore;
+use tokenize_backend::live_feed::{LiveFeed, DEFAULT_REPLAY_CAPACITY};
+use tokenize_backend::models::InMemoryDatabase;
+use tokenize_backend::policy::DEFAULT_RELOAD_INTERVAL_SECS;
+use tokenize_backend::recovery::RECOVERY_INTERVAL_SECS;
+use tokenize_backend::csv_parser::parse_csv;
+use tokenize_backend::routes;
+use tokenize_backend::webhooks::{HttpTransport, RetryPolicy, WebhookDispatcher};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -25,37 +32,128 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     
     // Insert components into database
-    for (i, component) in new_components.iter().enumerate() {
-        let id = i as u32 + 1;
-        let token_component = TokenizationComponent {
-            id,
-            main_type: component.main_type.clone(),
-            sub_type: component.sub_type.clone(),
-            components: component.components.clone(),
-        };
-        
-        // Add to components list
-        db.components.push(token_component);
-        
-        // Update indexes
-        let main_type = component.main_type.clone();
-        db.main_type_index.entry(main_type).or_insert_with(Vec::new).push(i);
-        
-        let sub_type_key = (component.main_type.clone(), component.sub_type.clone());
-        db.sub_type_index.entry(sub_type_key).or_insert_with(Vec::new).push(i);
-        
-        println!("Inserted component with ID: {}", id);
+    for component in new_components {
+        let inserted = db.insert_component(component);
+        println!("Inserted component with ID: {}", inserted.id);
     }
     
     let db = Arc::new(RwLock::new(db));
+    let core = match std::env::var("EVENT_STORE_DIR") {
+        Ok(dir) => {
+            println!("Recording core events in {}", dir);
+            let store = Arc::new(FileEventStore::open(&dir)?);
+            TokenizationCore::with_event_store(Arc::new(SystemClock), store, DEFAULT_SNAPSHOT_INTERVAL)?
+        }
+        Err(_) => TokenizationCore::new(),
+    };
+    // Keep platform keys in an encrypted file instead of in memory
+    let core = match std::env::var("KEY_STORE_PATH") {
+        Ok(path) => {
+            let password = std::env::var("KEY_STORE_PASSWORD").map_err(|_| "KEY_STORE_PASSWORD must be set with KEY_STORE_PATH")?;
+            println!("Opening key store {}", path);
+            core.with_key_store(Arc::new(FileKeyStore::open(&path, &password)?))
+        }
+        Err(_) => core,
+    };
+    let core = Arc::new(core);
+
+    // Load policy documents and pick up changes to them while running
+    if let Ok(dir) = std::env::var("POLICY_BUNDLE_DIR") {
+        let loaded = core.policies().load_dir(&dir)?;
+        println!("Loaded {} policies from {}", loaded, dir);
+        core.policies().watch(std::time::Duration::from_secs(DEFAULT_RELOAD_INTERVAL_SECS));
+    }
+
+    // Lift freezes once they expire
+    let expiry_core = core.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(FREEZE_EXPIRY_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = expiry_core.release_expired_freezes() {
+                eprintln!("Failed to release expired freezes: {}", e);
+            }
+        }
+    });
+
+    // Close pending approvals once their deadline passes
+    let approvals_core = core.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(APPROVAL_EXPIRY_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = approvals_core.expire_pending_actions() {
+                eprintln!("Failed to expire pending actions: {}", e);
+            }
+        }
+    });
+
+    // Run recoveries once their veto period has passed
+    let recovery_core = core.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(RECOVERY_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = recovery_core.execute_due_recoveries() {
+                eprintln!("Failed to run due recoveries: {}", e);
+            }
+        }
+    });
+
+    // Deliver core and catalog notifications to registered webhooks
+    let webhooks = WebhookDispatcher::new(
+        Arc::new(HttpTransport::default()),
+        RetryPolicy::default(),
+        Arc::new(SystemClock),
+    );
+    webhooks.start();
+    core.notifications().subscribe(webhooks.clone())?;
+
+    // Push the same notifications to SSE and WebSocket clients
+    let live_feed = LiveFeed::new(DEFAULT_REPLAY_CAPACITY);
+    core.notifications().subscribe(live_feed.clone())?;
     
+    let auth = Arc::new(authenticator_from_env()?);
+    if auth.get_api_keys()?.is_empty() {
+        let issued = auth.issue_api_key(
+            NewApiKey {
+                subject: "admin".to_string(),
+                roles: vec![ADMIN_ROLE.to_string()],
+            },
+            "bootstrap",
+        )?;
+        println!("No API keys configured; issued bootstrap admin key {}", issued.key);
+    }
+
+    // Statement files that reconciliations may read
+    let statements_dir = std::path::PathBuf::from(std::env::var("STATEMENTS_DIR").unwrap_or_else(|_| "statements".to_string()));
+
     // Create API routes
+    let allowed_origins = std::env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_else(|_| "http://127.0.0.1:3030,http://localhost:3030".to_string());
     let cors = warp::cors()
-        .allow_any_origin()
+        .allow_origins(allowed_origins.split(',').map(str::trim).filter(|o| !o.is_empty()))
         .allow_methods(vec!["GET", "POST", "PUT", "DELETE"])
-        .allow_headers(vec!["Content-Type"]);
+        .allow_headers(vec!["Content-Type", "Authorization", "X-API-Key", "Last-Event-ID"]);
     
     let api_routes = routes::components_routes(db.clone())
+        .or(routes::catalog_routes(db.clone(), core.notifications(), auth.clone()))
+        .or(routes::core_routes(core.clone(), auth.clone()))
+        .or(routes::approval_routes(core.clone(), auth.clone()))
+        .or(routes::policy_routes(core.clone(), auth.clone()))
+        .or(routes::limit_routes(core.clone(), auth.clone()))
+        .or(routes::freeze_routes(core.clone(), auth.clone()))
+        .or(routes::custody_routes(core.clone(), auth.clone()))
+        .or(routes::signature_routes(core.clone(), auth.clone()))
+        .or(routes::multisig_routes(core.clone(), auth.clone()))
+        .or(routes::recovery_routes(core.clone(), auth.clone()))
+        .or(routes::reserve_routes(core.clone(), auth.clone()))
+        .or(routes::reconciliation_routes(core.clone(), auth.clone(), statements_dir))
+        .or(routes::key_routes(core.clone(), auth.clone()))
+        .or(routes::webhook_routes(webhooks.clone(), auth.clone()))
+        .or(routes::stream_routes(live_feed.clone(), auth.clone()))
+        .or(routes::auth_routes(auth.clone()))
+        .recover(auth::handle_rejection)
         .with(cors);
     
     // Serve static files (frontend)
@@ -75,10 +173,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Server starting on http://127.0.0.1:3030");
     println!("Frontend available at http://127.0.0.1:3030/");
     println!("API endpoints available at http://127.0.0.1:3030/api/components");
+    println!("Core API endpoints available at http://127.0.0.1:3030/api/core");
+    println!("Webhook API endpoints available at http://127.0.0.1:3030/api/webhooks");
+    println!("Live stream available at http://127.0.0.1:3030/api/stream (SSE or WebSocket)");
     
     warp::serve(routes)
         .run(([127, 0, 0, 1], 3030))
         .await;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Build the authenticator from API_KEYS_FILE, JWT_HS256_SECRET, JWT_RS256_PUBLIC_KEY_FILE,
+/// JWT_ISSUER and JWT_AUDIENCE
+fn authenticator_from_env() -> Result<Authenticator, Box<dyn std::error::Error>> {
+    let mut auth = Authenticator::new(Arc::new(SystemClock));
+    if let Ok(path) = std::env::var("API_KEYS_FILE") {
+        auth = auth.with_api_key_file(path)?;
+    }
+    if let Ok(secret) = std::env::var("JWT_HS256_SECRET") {
+        auth = auth.with_hs256_secret(None, secret.as_bytes());
+    }
+    if let Ok(path) = std::env::var("JWT_RS256_PUBLIC_KEY_FILE") {
+        auth = auth.with_rs256_public_key(None, &std::fs::read(path)?)?;
+    }
+    if let Ok(issuer) = std::env::var("JWT_ISSUER") {
+        auth = auth.with_issuer(issuer);
+    }
+    if let Ok(audience) = std::env::var("JWT_AUDIENCE") {
+        auth = auth.with_audience(audience);
+    }
+    Ok(auth)
+}
@@ -28,4 +28,27 @@ pub struct InMemoryDatabase {
     pub components: Vec<TokenizationComponent>,
     pub main_type_index: HashMap<String, Vec<usize>>,
     pub sub_type_index: HashMap<(String, String), Vec<usize>>,
-}
\ No newline at end of file
+}
+
+impl InMemoryDatabase {
+    /// Add a component with the next free ID and index it
+    pub fn insert_component(&mut self, component: NewTokenizationComponent) -> TokenizationComponent {
+        let index = self.components.len();
+        let token_component = TokenizationComponent {
+            id: self.components.iter().map(|c| c.id).max().unwrap_or(0) + 1,
+            main_type: component.main_type,
+            sub_type: component.sub_type,
+            components: component.components,
+        };
+        self.main_type_index
+            .entry(token_component.main_type.clone())
+            .or_default()
+            .push(index);
+        self.sub_type_index
+            .entry((token_component.main_type.clone(), token_component.sub_type.clone()))
+            .or_default()
+            .push(index);
+        self.components.push(token_component.clone());
+        token_component
+    }
+}
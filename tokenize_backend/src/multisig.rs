@@ -0,0 +1,702 @@
+//! Threshold signing for wallets controlled by several parties
+//!
+//! A wallet can be given N signers and a threshold M. From then on nothing leaves
+//! it on one party's say: transfers are proposed, collect approvals from signers
+//! and run once M of the current signers have approved. Adding, removing and
+//! rotating signers and changing the threshold are proposals under the same quorum.
+//!
+//! A signer is a subject who approves by calling or, when bound to one of the
+//! wallet's keys, only with an `ApproveProposal` operation signed by that key.
+//! Proposing counts as the proposer's approval unless they sign with a key.
+//! Approvals are counted against the signer set as it stands, so those of signers
+//! since removed or rotated no longer count.
+//!
+//! The quorum stands in for the client's signature, and no single caller can give
+//! that signature for the wallet any more. A hybrid wallet's transfer still needs
+//! the platform, so once approved it moves on to a signing request for the
+//! platform to co-sign. Custodial wallets are controlled by the platform and take
+//! no signers.
+
+use crate::access_control::{without_subject, Permission, PERMISSION_DENIED};
+use crate::approvals::OperationKind;
+use crate::audit::{current_actor, AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, WalletType};
+use crate::custody::{CoSignature, OutgoingTransfer, SignerRole, SigningStatus, SIGNATURE_REQUIRED};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A party whose approval counts towards a wallet's threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletSigner {
+    /// Subject ID
+    pub id: String,
+    /// Wallet key the signer approves with; without one they approve by calling
+    #[serde(default)]
+    pub key_id: Option<String>,
+}
+
+/// A wallet's signers and how many of them must approve
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerPolicy {
+    pub wallet_id: String,
+    pub signers: Vec<WalletSigner>,
+    /// Between 1 and the number of signers
+    pub threshold: usize,
+    pub updated_at: u64,
+}
+
+impl SignerPolicy {
+    /// The signer with the given subject ID
+    pub fn signer(&self, id: &str) -> Option<&WalletSigner> {
+        self.signers.iter().find(|s| s.id == id)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.signers.is_empty() {
+            return Err("At least one signer is required".to_string());
+        }
+        if self.threshold == 0 || self.threshold > self.signers.len() {
+            return Err(format!("Threshold must be between 1 and {}, the number of signers", self.signers.len()));
+        }
+        let mut ids = HashSet::new();
+        let mut keys = HashSet::new();
+        for signer in &self.signers {
+            if signer.id.trim().is_empty() {
+                return Err("Signer ID must not be empty".to_string());
+            }
+            if !ids.insert(signer.id.as_str()) {
+                return Err(format!("{} is listed as a signer more than once", signer.id));
+            }
+            if let Some(key_id) = &signer.key_id
+                && !keys.insert(key_id.as_str())
+            {
+                return Err(format!("Key {} is bound to more than one signer", key_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// The policy after a change to its signers or threshold, which must leave it valid
+    pub fn apply(&self, operation: &MultisigOperation, now: u64) -> Result<SignerPolicy, String> {
+        let mut changed = self.clone();
+        match operation {
+            MultisigOperation::Transfer { .. } => return Err("Transfers do not change the signers".to_string()),
+            MultisigOperation::AddSigner { signer } => changed.signers.push(signer.clone()),
+            MultisigOperation::RemoveSigner { signer_id } => {
+                self.signer(signer_id).ok_or_else(|| format!("{} is not a signer", signer_id))?;
+                changed.signers.retain(|s| s.id != *signer_id);
+            }
+            MultisigOperation::RotateSigner { signer_id, replacement } => {
+                let slot = changed
+                    .signers
+                    .iter_mut()
+                    .find(|s| s.id == *signer_id)
+                    .ok_or_else(|| format!("{} is not a signer", signer_id))?;
+                if slot == replacement {
+                    return Err("Replacement is the same as the signer".to_string());
+                }
+                *slot = replacement.clone();
+            }
+            MultisigOperation::SetThreshold { threshold } => changed.threshold = *threshold,
+        }
+        changed.updated_at = now;
+        changed.validate()?;
+        Ok(changed)
+    }
+}
+
+/// What a proposal does once approved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MultisigOperation {
+    /// Transfer out of the wallet
+    Transfer {
+        to_wallet_id: String,
+        asset_id: String,
+        quantity: f64,
+    },
+    AddSigner { signer: WalletSigner },
+    RemoveSigner { signer_id: String },
+    /// Replace a signer, e.g. to bind them to a new key or hand their seat to someone else
+    RotateSigner { signer_id: String, replacement: WalletSigner },
+    SetThreshold { threshold: usize },
+}
+
+/// A signer's approval of a proposal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalApproval {
+    pub signer: String,
+    /// Key the approval was signed with, if any
+    pub key_id: Option<String>,
+    pub approved_at: u64,
+}
+
+/// An operation on a threshold wallet collecting approvals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proposal {
+    pub id: String,
+    pub wallet_id: String,
+    pub operation: MultisigOperation,
+    pub approvals: Vec<ProposalApproval>,
+    pub status: SigningStatus,
+    pub proposed_by: String,
+    pub proposed_at: u64,
+    pub closed_at: Option<u64>,
+    /// Signing request an approved transfer moved to for the platform's co-signature
+    pub signing_request_id: Option<String>,
+}
+
+impl Proposal {
+    /// Approvals that count under `policy`: those of its signers, made the way they sign now
+    pub fn approval_count(&self, policy: &SignerPolicy) -> usize {
+        self.approvals
+            .iter()
+            .filter(|a| policy.signer(&a.signer).is_some_and(|s| s.key_id == a.key_id))
+            .count()
+    }
+}
+
+impl TokenizationCore {
+    /// Whether the wallet is controlled by a threshold of signers
+    pub(crate) fn has_wallet_signers(&self, wallet_id: &str) -> Result<bool, String> {
+        let policies = self.wallet_signers.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(policies.contains_key(wallet_id))
+    }
+
+    fn signer_policy(&self, wallet_id: &str) -> Result<SignerPolicy, String> {
+        let policies = self.wallet_signers.read().map_err(|_| "Failed to acquire read lock")?;
+        policies.get(wallet_id).cloned().ok_or_else(|| "Wallet has no signers".to_string())
+    }
+
+    fn validate_signer_keys(&self, policy: &SignerPolicy) -> Result<(), String> {
+        let keys = self.wallet_keys.read().map_err(|_| "Failed to acquire read lock")?;
+        for key_id in policy.signers.iter().filter_map(|s| s.key_id.as_ref()) {
+            if !keys.get(key_id).is_some_and(|k| k.wallet_id == policy.wallet_id && k.revoked_at.is_none()) {
+                return Err(format!("Key {} is not an active key of wallet {}", key_id, policy.wallet_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Signers of a wallet may see its policy and proposals; others need to be able to read it
+    fn authorize_signer_or_reader(&self, wallet_id: &str) -> Result<(), String> {
+        let policies = self.wallet_signers.read().map_err(|_| "Failed to acquire read lock")?;
+        if policies.get(wallet_id).is_some_and(|p| p.signer(&current_actor()).is_some()) {
+            return Ok(());
+        }
+        drop(policies);
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)
+    }
+
+    /// Put a wallet under the control of a threshold of signers
+    ///
+    /// Only the owner can do this, and only once; later changes to the signers are
+    /// proposals. Transfers proposed afterwards are still subject to four-eyes rules.
+    pub fn set_wallet_signers(&self, wallet_id: &str, signers: Vec<WalletSigner>, threshold: usize) -> Result<SignerPolicy, String> {
+        self.authorize_wallet(Permission::UpdateWallet, wallet_id)?;
+        self.authorize_wallet_owner(wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[])?;
+        {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            let wallet = wallets.get(wallet_id).ok_or("Wallet not found")?;
+            if wallet.wallet_type == WalletType::Custodial {
+                return Err("Custodial wallets are controlled by the platform and take no signers".to_string());
+            }
+        }
+        let policy = SignerPolicy {
+            wallet_id: wallet_id.to_string(),
+            signers,
+            threshold,
+            updated_at: self.now(),
+        };
+        policy.validate()?;
+        self.validate_signer_keys(&policy)?;
+        let mut policies = self.wallet_signers.write().map_err(|_| "Failed to acquire write lock")?;
+        if policies.contains_key(wallet_id) {
+            return Err("Wallet already has signers; propose changes to them instead".to_string());
+        }
+        policies.insert(wallet_id.to_string(), policy.clone());
+        drop(policies);
+        self.record_audit(AuditAction::Create, EntityType::SignerPolicy, wallet_id, None::<&SignerPolicy>, Some(&policy))?;
+        Ok(policy)
+    }
+
+    /// A wallet's signers and threshold, if it has any
+    pub fn get_wallet_signers(&self, wallet_id: &str) -> Result<Option<SignerPolicy>, String> {
+        self.authorize_signer_or_reader(wallet_id)?;
+        let policies = self.wallet_signers.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(policies.get(wallet_id).cloned())
+    }
+
+    /// Propose an operation on a threshold wallet as one of its signers
+    pub fn propose_wallet_operation(&self, wallet_id: &str, operation: MultisigOperation) -> Result<Proposal, String> {
+        let now = self.now();
+        let policy = self.signer_policy(wallet_id)?;
+        let actor = current_actor();
+        let signer = policy
+            .signer(&actor)
+            .cloned()
+            .ok_or_else(|| format!("{}: {} is not a signer of wallet {}", PERMISSION_DENIED, actor, wallet_id))?;
+        match &operation {
+            MultisigOperation::Transfer { quantity, .. } if *quantity <= 0.0 => {
+                return Err("Quantity must be positive".to_string());
+            }
+            MultisigOperation::Transfer { quantity, .. } => self.check_four_eyes(OperationKind::Transfer, Some(*quantity))?,
+            change => self.validate_signer_keys(&policy.apply(change, now)?)?,
+        }
+        let proposal = {
+            let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+            let proposal = Proposal {
+                id: format!("proposal-{}", proposals.len() + 1),
+                wallet_id: wallet_id.to_string(),
+                operation,
+                approvals: match signer.key_id {
+                    Some(_) => Vec::new(),
+                    None => vec![ProposalApproval {
+                        signer: actor.clone(),
+                        key_id: None,
+                        approved_at: now,
+                    }],
+                },
+                status: SigningStatus::Pending,
+                proposed_by: actor,
+                proposed_at: now,
+                closed_at: None,
+                signing_request_id: None,
+            };
+            proposals.insert(proposal.id.clone(), proposal.clone());
+            proposal
+        };
+        self.record_audit(AuditAction::Create, EntityType::Proposal, &proposal.id, None::<&Proposal>, Some(&proposal))?;
+        self.execute_if_approved(proposal)
+    }
+
+    /// Approve a proposal as the calling signer; it runs once the threshold is met
+    pub fn approve_proposal(&self, proposal_id: &str) -> Result<Proposal, String> {
+        let wallet_id = self.find_proposal(proposal_id)?.wallet_id;
+        let policy = self.signer_policy(&wallet_id)?;
+        let actor = current_actor();
+        let signer = policy
+            .signer(&actor)
+            .ok_or_else(|| format!("{}: {} is not a signer of wallet {}", PERMISSION_DENIED, actor, wallet_id))?;
+        if let Some(key_id) = &signer.key_id {
+            return Err(format!("{}: {} approves with a signature from key {}", SIGNATURE_REQUIRED, actor, key_id));
+        }
+        self.add_proposal_approval(proposal_id, signer.clone())
+    }
+
+    /// Approve a proposal as the signer bound to `key_id`, whose signature has been verified
+    pub(crate) fn approve_proposal_with_key(&self, proposal_id: &str, wallet_id: &str, key_id: &str) -> Result<Proposal, String> {
+        if self.find_proposal(proposal_id)?.wallet_id != wallet_id {
+            return Err(format!("Proposal is not for wallet {}", wallet_id));
+        }
+        let policy = self.signer_policy(wallet_id)?;
+        let signer = policy
+            .signers
+            .iter()
+            .find(|s| s.key_id.as_deref() == Some(key_id))
+            .cloned()
+            .ok_or_else(|| format!("{}: key {} belongs to no signer of wallet {}", PERMISSION_DENIED, key_id, wallet_id))?;
+        self.add_proposal_approval(proposal_id, signer)
+    }
+
+    fn add_proposal_approval(&self, proposal_id: &str, signer: WalletSigner) -> Result<Proposal, String> {
+        let now = self.now();
+        let (before, proposal) = {
+            let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+            let proposal = proposals.get_mut(proposal_id).ok_or("Proposal not found")?;
+            if proposal.status != SigningStatus::Pending {
+                return Err(format!("Proposal is already {:?}", proposal.status));
+            }
+            if proposal.approvals.iter().any(|a| a.signer == signer.id && a.key_id == signer.key_id) {
+                return Err(format!("{} has already approved this proposal", signer.id));
+            }
+            let before = proposal.clone();
+            // An approval made before the signer was rotated to a new key no longer counts
+            proposal.approvals.retain(|a| a.signer != signer.id);
+            proposal.approvals.push(ProposalApproval {
+                signer: signer.id,
+                key_id: signer.key_id,
+                approved_at: now,
+            });
+            (before, proposal.clone())
+        };
+        self.record_audit(AuditAction::Approve, EntityType::Proposal, proposal_id, Some(&before), Some(&proposal))?;
+        self.execute_if_approved(proposal)
+    }
+
+    fn execute_if_approved(&self, proposal: Proposal) -> Result<Proposal, String> {
+        let policy = self.signer_policy(&proposal.wallet_id)?;
+        if proposal.approval_count(&policy) < policy.threshold {
+            return Ok(proposal);
+        }
+        // Claim the proposal so that an approval arriving after quorum cannot run it again
+        let proposal = {
+            let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+            let stored = proposals.get_mut(&proposal.id).ok_or("Proposal not found")?;
+            if stored.status != SigningStatus::Pending {
+                // Another approval reached quorum first and runs it
+                return Ok(stored.clone());
+            }
+            stored.status = SigningStatus::Executing;
+            stored.clone()
+        };
+        // The quorum stands in for the caller: run as the proposer, outside its permissions
+        let outcome = without_subject(|| {
+            self.with_actor(&proposal.proposed_by, || match &proposal.operation {
+                MultisigOperation::Transfer {
+                    to_wallet_id,
+                    asset_id,
+                    quantity,
+                } => self.execute_quorum_transfer(&proposal, to_wallet_id, asset_id, *quantity),
+                change => self.change_signers(&proposal.wallet_id, change).map(|_| None),
+            })
+        });
+        let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+        let stored = proposals.get_mut(&proposal.id).ok_or("Proposal not found")?;
+        let before = stored.clone();
+        match outcome {
+            Ok(signing_request_id) => {
+                stored.status = SigningStatus::Executed;
+                stored.signing_request_id = signing_request_id;
+            }
+            Err(error) => stored.status = SigningStatus::Failed { error },
+        }
+        stored.closed_at = Some(self.now());
+        let proposal = stored.clone();
+        drop(proposals);
+        self.record_audit(AuditAction::Process, EntityType::Proposal, &proposal.id, Some(&before), Some(&proposal))?;
+        Ok(proposal)
+    }
+
+    /// Run an approved transfer, or hand it to a signing request when the platform must co-sign
+    fn execute_quorum_transfer(&self, proposal: &Proposal, to_wallet_id: &str, asset_id: &str, quantity: f64) -> Result<Option<String>, String> {
+        let required = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            match (wallets.get(&proposal.wallet_id), wallets.get(to_wallet_id)) {
+                (Some(sender), Some(receiver)) => sender.wallet_type.required_signers(&receiver.wallet_type),
+                _ => return Err("Wallet not found".to_string()),
+            }
+        };
+        if required.contains(&SignerRole::Platform) {
+            let transfer = OutgoingTransfer {
+                from_wallet_id: proposal.wallet_id.clone(),
+                to_wallet_id: to_wallet_id.to_string(),
+                asset_id: asset_id.to_string(),
+                quantity,
+            };
+            let quorum = CoSignature {
+                role: SignerRole::Client,
+                signer: proposal.id.clone(),
+                signed_at: self.now(),
+            };
+            let request = self.open_signing_request(transfer, required, proposal.proposed_by.clone(), vec![quorum])?;
+            return Ok(Some(request.id));
+        }
+        self.transfer_signed(&proposal.wallet_id, to_wallet_id, asset_id, quantity, Some(&[SignerRole::Client]))?;
+        Ok(None)
+    }
+
+    fn change_signers(&self, wallet_id: &str, change: &MultisigOperation) -> Result<(), String> {
+        self.ensure_ids_not_frozen(&[wallet_id], &[])?;
+        let changed = self.signer_policy(wallet_id)?.apply(change, self.now())?;
+        self.validate_signer_keys(&changed)?;
+        let mut policies = self.wallet_signers.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = policies.insert(wallet_id.to_string(), changed.clone());
+        drop(policies);
+        self.record_audit(AuditAction::Update, EntityType::SignerPolicy, wallet_id, before.as_ref(), Some(&changed))
+    }
+
+    /// Take a wallet out of its signers' control, cancelling their pending proposals
+    pub(crate) fn clear_wallet_signers(&self, wallet_id: &str) -> Result<(), String> {
+        let removed = {
+            let mut policies = self.wallet_signers.write().map_err(|_| "Failed to acquire write lock")?;
+            policies.remove(wallet_id)
+        };
+        if let Some(policy) = removed {
+            self.record_audit(AuditAction::Delete, EntityType::SignerPolicy, wallet_id, Some(&policy), None::<&SignerPolicy>)?;
+        }
+        let now = self.now();
+        let cancelled: Vec<(Proposal, Proposal)> = {
+            let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+            proposals
+                .values_mut()
+                .filter(|p| p.wallet_id == wallet_id && p.status == SigningStatus::Pending)
+                .map(|proposal| {
+                    let before = proposal.clone();
+                    proposal.status = SigningStatus::Cancelled;
+                    proposal.closed_at = Some(now);
+                    (before, proposal.clone())
+                })
+                .collect()
+        };
+        for (before, proposal) in cancelled {
+            self.record_audit(AuditAction::Cancel, EntityType::Proposal, &proposal.id, Some(&before), Some(&proposal))?;
+        }
+        Ok(())
+    }
+
+    /// Withdraw a pending proposal; open to its proposer and the wallet's owner
+    pub fn cancel_proposal(&self, proposal_id: &str) -> Result<Proposal, String> {
+        let actor = current_actor();
+        let existing = self.find_proposal(proposal_id)?;
+        let owner = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            wallets.get(&existing.wallet_id).map(|w| w.owner.clone())
+        };
+        if actor != existing.proposed_by && owner.as_deref() != Some(actor.as_str()) {
+            return Err(format!("{}: {} may not cancel this proposal", PERMISSION_DENIED, actor));
+        }
+        let mut proposals = self.proposals.write().map_err(|_| "Failed to acquire write lock")?;
+        let proposal = proposals.get_mut(proposal_id).ok_or("Proposal not found")?;
+        if proposal.status != SigningStatus::Pending {
+            return Err(format!("Proposal is already {:?}", proposal.status));
+        }
+        let before = proposal.clone();
+        proposal.status = SigningStatus::Cancelled;
+        proposal.closed_at = Some(self.now());
+        let proposal = proposal.clone();
+        drop(proposals);
+        self.record_audit(AuditAction::Cancel, EntityType::Proposal, proposal_id, Some(&before), Some(&proposal))?;
+        Ok(proposal)
+    }
+
+    fn find_proposal(&self, proposal_id: &str) -> Result<Proposal, String> {
+        let proposals = self.proposals.read().map_err(|_| "Failed to acquire read lock")?;
+        proposals.get(proposal_id).cloned().ok_or_else(|| "Proposal not found".to_string())
+    }
+
+    /// Get a proposal with the approvals collected so far
+    pub fn get_proposal(&self, proposal_id: &str) -> Result<Option<Proposal>, String> {
+        let proposal = {
+            let proposals = self.proposals.read().map_err(|_| "Failed to acquire read lock")?;
+            proposals.get(proposal_id).cloned()
+        };
+        if let Some(proposal) = &proposal {
+            self.authorize_signer_or_reader(&proposal.wallet_id)?;
+        }
+        Ok(proposal)
+    }
+
+    /// Proposals the caller may see, optionally only those on one wallet, oldest first
+    pub fn get_proposals(&self, wallet_id: Option<&str>) -> Result<Vec<Proposal>, String> {
+        let mut found: Vec<Proposal> = {
+            let proposals = self.proposals.read().map_err(|_| "Failed to acquire read lock")?;
+            proposals
+                .values()
+                .filter(|p| wallet_id.is_none_or(|id| p.wallet_id == id))
+                .cloned()
+                .collect()
+        };
+        found.retain(|p| self.authorize_signer_or_reader(&p.wallet_id).is_ok());
+        // IDs are numbered in proposal order
+        found.sort_by_key(|p| p.id.trim_start_matches("proposal-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{is_permission_denied, Role, Subject};
+    use crate::approvals::ApprovalRule;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset};
+    use crate::freezes::{is_frozen, FreezeReason, FreezeTarget};
+    use crate::signatures::{KeyAlgorithm, OperationPayload, SignedOperation, WalletOperation};
+    use crate::test_support::{asset, wallet};
+    use ed25519_dalek::Signer as _;
+
+    fn setup(wallet_type: WalletType) -> TokenizationCore {
+        let core = TokenizationCore::new();
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Commodity)
+        })
+        .unwrap();
+        for (id, owner, wallet_type) in [("w1", "alice", wallet_type), ("w2", "bob", WalletType::NonCustodial)] {
+            core.create_wallet(wallet(id, owner, wallet_type)).unwrap();
+        }
+        core.mint_to_wallet("w1", "asset1", 100.0).unwrap();
+        core
+    }
+
+    fn signer(id: &str) -> WalletSigner {
+        WalletSigner {
+            id: id.to_string(),
+            key_id: None,
+        }
+    }
+
+    fn investor(id: &str) -> Subject {
+        Subject::new(id, &[Role::Investor])
+    }
+
+    fn transfer(quantity: f64) -> MultisigOperation {
+        MultisigOperation::Transfer {
+            to_wallet_id: "w2".to_string(),
+            asset_id: "asset1".to_string(),
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_transfers_run_once_the_threshold_is_met() {
+        let core = setup(WalletType::NonCustodial);
+        let (alice, carol, dave) = (investor("alice"), investor("carol"), investor("dave"));
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let error = core.as_subject(&agent, || core.set_wallet_signers("w1", vec![signer("agent")], 1)).unwrap_err();
+        assert!(is_permission_denied(&error), "{}", error);
+        core.as_subject(&alice, || core.set_wallet_signers("w1", vec![signer("alice"), signer("carol"), signer("dave")], 2))
+            .unwrap();
+        assert!(core.set_wallet_signers("w1", vec![signer("alice")], 1).is_err());
+
+        // The owner alone can no longer move assets
+        let error = core.as_subject(&alice, || core.transfer_asset("w1", "w2", "asset1", 10.0)).unwrap_err();
+        assert!(error.starts_with(SIGNATURE_REQUIRED), "{}", error);
+        assert!(core.as_subject(&investor("mallory"), || core.propose_wallet_operation("w1", transfer(10.0))).is_err());
+
+        let proposal = core.as_subject(&carol, || core.propose_wallet_operation("w1", transfer(10.0))).unwrap();
+        assert_eq!(proposal.status, SigningStatus::Pending);
+        assert!(core.as_subject(&carol, || core.approve_proposal(&proposal.id)).is_err());
+        let proposal = core.as_subject(&dave, || core.approve_proposal(&proposal.id)).unwrap();
+        assert_eq!(proposal.status, SigningStatus::Executed);
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 10.0);
+        assert!(core.as_subject(&alice, || core.approve_proposal(&proposal.id)).is_err());
+
+        // A signer approving while the quorum's approval is running cannot run it a second time
+        let second = core.as_subject(&carol, || core.propose_wallet_operation("w1", transfer(10.0))).unwrap();
+        core.proposals.write().unwrap().get_mut(&second.id).unwrap().status = SigningStatus::Executing;
+        let error = core.as_subject(&dave, || core.approve_proposal(&second.id)).unwrap_err();
+        assert_eq!(error, "Proposal is already Executing");
+        core.proposals.write().unwrap().get_mut(&second.id).unwrap().status = SigningStatus::Pending;
+        core.as_subject(&dave, || core.approve_proposal(&second.id)).unwrap();
+        assert!(core.as_subject(&alice, || core.approve_proposal(&second.id)).is_err());
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 20.0);
+
+        // A signer can see proposals on the wallet without owning it
+        assert_eq!(core.as_subject(&dave, || core.get_proposals(Some("w1"))).unwrap().len(), 2);
+        assert!(core.as_subject(&investor("bob"), || core.get_proposals(Some("w1"))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_large_transfers_still_need_four_eyes_approval() {
+        let core = setup(WalletType::NonCustodial);
+        let carol = investor("carol");
+        core.set_wallet_signers("w1", vec![signer("alice"), signer("carol")], 1).unwrap();
+        core.configure_approval_rule(ApprovalRule {
+            operation: OperationKind::Transfer,
+            min_quantity: Some(50.0),
+            required_approvals: 2,
+            approvers: vec![],
+            expires_after_secs: 3_600,
+        })
+        .unwrap();
+
+        let error = core.as_subject(&carol, || core.propose_wallet_operation("w1", transfer(60.0))).unwrap_err();
+        assert_eq!(error, "Transfer requires 2 approvals; submit it for approval");
+        assert!(core.get_proposals(Some("w1")).unwrap().is_empty());
+        let proposal = core.as_subject(&carol, || core.propose_wallet_operation("w1", transfer(10.0))).unwrap();
+        assert_eq!(proposal.status, SigningStatus::Executed);
+
+        // Signer changes stop while the wallet is frozen
+        core.freeze(FreezeTarget::Wallet("w1".to_string()), FreezeReason::CourtOrder, None, None).unwrap();
+        let removal = MultisigOperation::RemoveSigner { signer_id: "alice".to_string() };
+        let removal = core.as_subject(&carol, || core.propose_wallet_operation("w1", removal)).unwrap();
+        assert!(matches!(removal.status, SigningStatus::Failed { error } if is_frozen(&error)));
+        assert_eq!(core.get_wallet_signers("w1").unwrap().unwrap().signers.len(), 2);
+    }
+
+    #[test]
+    fn test_signer_changes_need_the_same_quorum() {
+        let core = setup(WalletType::NonCustodial);
+        let (alice, carol, erin) = (investor("alice"), investor("carol"), investor("erin"));
+        core.set_wallet_signers("w1", vec![signer("alice"), signer("carol")], 2).unwrap();
+
+        let pending = core.as_subject(&carol, || core.propose_wallet_operation("w1", transfer(5.0))).unwrap();
+        let rotate = MultisigOperation::RotateSigner {
+            signer_id: "carol".to_string(),
+            replacement: signer("erin"),
+        };
+        let rotation = core.as_subject(&alice, || core.propose_wallet_operation("w1", rotate)).unwrap();
+        assert_eq!(rotation.status, SigningStatus::Pending);
+        let rotation = core.as_subject(&carol, || core.approve_proposal(&rotation.id)).unwrap();
+        assert_eq!(rotation.status, SigningStatus::Executed);
+        let policy = core.get_wallet_signers("w1").unwrap().unwrap();
+        assert_eq!(policy.signers.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), ["alice", "erin"]);
+
+        // Carol's approval of the older transfer no longer counts
+        assert!(core.as_subject(&carol, || core.approve_proposal(&pending.id)).is_err());
+        let pending = core.as_subject(&alice, || core.approve_proposal(&pending.id)).unwrap();
+        assert_eq!(pending.status, SigningStatus::Pending);
+        let pending = core.as_subject(&erin, || core.approve_proposal(&pending.id)).unwrap();
+        assert_eq!(pending.status, SigningStatus::Executed);
+
+        // Changes that would leave the policy unusable are refused up front
+        let error = core
+            .as_subject(&alice, || core.propose_wallet_operation("w1", MultisigOperation::SetThreshold { threshold: 3 }))
+            .unwrap_err();
+        assert_eq!(error, "Threshold must be between 1 and 2, the number of signers");
+        let lower = core
+            .as_subject(&alice, || core.propose_wallet_operation("w1", MultisigOperation::SetThreshold { threshold: 1 }))
+            .unwrap();
+        core.as_subject(&erin, || core.approve_proposal(&lower.id)).unwrap();
+        let removal = MultisigOperation::RemoveSigner { signer_id: "erin".to_string() };
+        let removal = core.as_subject(&alice, || core.propose_wallet_operation("w1", removal)).unwrap();
+        assert_eq!(removal.status, SigningStatus::Executed);
+        assert_eq!(core.get_wallet_signers("w1").unwrap().unwrap().signers.len(), 1);
+    }
+
+    #[test]
+    fn test_key_bound_signers_approve_by_signature() {
+        let core = setup(WalletType::Hybrid);
+        let (alice, carol) = (investor("alice"), investor("carol"));
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[3; 32]);
+        let key = core
+            .as_subject(&alice, || {
+                core.register_wallet_key("w1", KeyAlgorithm::Ed25519, &hex::encode(signing_key.verifying_key().to_bytes()))
+            })
+            .unwrap();
+        let carol_signer = WalletSigner {
+            id: "carol".to_string(),
+            key_id: Some(key.id.clone()),
+        };
+        core.set_wallet_signers("w1", vec![signer("alice"), carol_signer], 2).unwrap();
+
+        let proposal = core.as_subject(&alice, || core.propose_wallet_operation("w1", transfer(30.0))).unwrap();
+        let error = core.as_subject(&carol, || core.approve_proposal(&proposal.id)).unwrap_err();
+        assert!(error.starts_with(SIGNATURE_REQUIRED), "{}", error);
+
+        // A signed transfer from the key bypasses nothing: it is refused outright
+        let sign = |nonce: u64, operation: WalletOperation| {
+            let payload = OperationPayload {
+                wallet_id: "w1".to_string(),
+                nonce,
+                operation,
+            };
+            let signature = signing_key.sign(&payload.signing_bytes().unwrap());
+            SignedOperation {
+                payload,
+                key_id: key.id.clone(),
+                signature: hex::encode(signature.to_bytes()),
+            }
+        };
+        let direct = WalletOperation::Transfer {
+            to_wallet_id: "w2".to_string(),
+            asset_id: "asset1".to_string(),
+            quantity: 30.0,
+        };
+        assert!(core.as_subject(&carol, || core.submit_signed_operation(sign(1, direct))).is_err());
+
+        // Approved, the hybrid wallet's transfer waits for the platform's co-signature
+        let approve = WalletOperation::ApproveProposal { proposal_id: proposal.id.clone() };
+        core.as_subject(&carol, || core.submit_signed_operation(sign(2, approve))).unwrap();
+        let proposal = core.get_proposal(&proposal.id).unwrap().unwrap();
+        assert_eq!(proposal.status, SigningStatus::Executed);
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 0.0);
+        let request_id = proposal.signing_request_id.unwrap();
+        let request = core.as_subject(&agent, || core.sign_request(&request_id)).unwrap();
+        assert_eq!(request.status, SigningStatus::Executed);
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 30.0);
+    }
+}
@@ -0,0 +1,337 @@
+//! NAV calculation engine for fund-type assets
+//!
+//! A fund asset carries a portfolio of underlying positions, cash, liabilities and
+//! annual fee rates. Each valuation accrues fees since the previous valuation point,
+//! computes net assets and divides them by the units outstanding. Results are kept
+//! as a NAV history so that orders can be priced at any past valuation point.
+
+use crate::access_control::Permission;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{AssetType, TokenizationCore, QUANTITY_EPSILON};
+use serde::{Deserialize, Serialize};
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 86_400;
+
+/// A position in an instrument held by the fund
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnderlyingPosition {
+    pub instrument: String,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+impl UnderlyingPosition {
+    pub fn market_value(&self) -> f64 {
+        self.quantity * self.price
+    }
+}
+
+/// A fixed liability of the fund, e.g. payables or borrowings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Liability {
+    pub description: String,
+    pub amount: f64,
+}
+
+/// Fee charged as an annual rate on gross assets, accrued daily pro rata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeRate {
+    pub name: String,
+    pub annual_rate: f64,
+}
+
+/// Portfolio and fee set-up of a fund asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundProfile {
+    pub asset_id: String,
+    /// NAV per unit used while no units are outstanding
+    pub initial_nav_per_unit: f64,
+    pub positions: Vec<UnderlyingPosition>,
+    pub cash: f64,
+    pub liabilities: Vec<Liability>,
+    pub fees: Vec<FeeRate>,
+    /// Fees accrued but not yet paid
+    pub accrued_fees: f64,
+    /// Time up to which fees have been accrued
+    pub fees_accrued_to: u64,
+}
+
+impl FundProfile {
+    /// Create a fund profile with no portfolio yet
+    pub fn new(asset_id: &str, initial_nav_per_unit: f64, fees: Vec<FeeRate>, start: u64) -> Self {
+        Self {
+            asset_id: asset_id.to_string(),
+            initial_nav_per_unit,
+            positions: Vec::new(),
+            cash: 0.0,
+            liabilities: Vec::new(),
+            fees,
+            accrued_fees: 0.0,
+            fees_accrued_to: start,
+        }
+    }
+
+    pub fn gross_assets(&self) -> f64 {
+        self.cash + self.positions.iter().map(|p| p.market_value()).sum::<f64>()
+    }
+
+    pub fn total_liabilities(&self) -> f64 {
+        self.liabilities.iter().map(|l| l.amount).sum()
+    }
+
+    /// Fees that would accrue between the last accrual point and `at`
+    pub fn fees_due(&self, at: u64) -> f64 {
+        let elapsed = at.saturating_sub(self.fees_accrued_to) as f64 / SECONDS_PER_YEAR as f64;
+        let annual_rate: f64 = self.fees.iter().map(|f| f.annual_rate).sum();
+        self.gross_assets() * annual_rate * elapsed
+    }
+}
+
+/// A NAV computed at a valuation point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavRecord {
+    pub asset_id: String,
+    pub valuation_time: u64,
+    pub gross_assets: f64,
+    pub liabilities: f64,
+    pub accrued_fees: f64,
+    pub net_assets: f64,
+    pub units_outstanding: f64,
+    pub nav_per_unit: f64,
+}
+
+impl TokenizationCore {
+    /// Register a fund profile for an existing fund asset
+    pub fn register_fund(&self, profile: FundProfile) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, &profile.asset_id)?;
+        if profile.initial_nav_per_unit <= 0.0 {
+            return Err("Initial NAV per unit must be positive".to_string());
+        }
+        self.ensure_ids_not_frozen(&[], &[&profile.asset_id])?;
+        let asset = self.get_asset(&profile.asset_id)?.ok_or("Asset not found")?;
+        if asset.asset_type != AssetType::Fund {
+            return Err("NAV can only be calculated for fund assets".to_string());
+        }
+        let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+        if funds.contains_key(&profile.asset_id) {
+            return Err("Fund already registered".to_string());
+        }
+        self.record_audit(AuditAction::Create, EntityType::Fund, &profile.asset_id, None::<&FundProfile>, Some(&profile))?;
+        funds.insert(profile.asset_id.clone(), profile);
+        Ok(())
+    }
+
+    /// Get a fund profile by asset ID
+    pub fn get_fund(&self, asset_id: &str) -> Result<Option<FundProfile>, String> {
+        let funds = self.funds.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(funds.get(asset_id).cloned())
+    }
+
+    /// Replace a fund's underlying positions and cash
+    ///
+    /// Fees are accrued up to now on the old portfolio before it is replaced.
+    pub fn update_fund_portfolio(
+        &self,
+        asset_id: &str,
+        positions: Vec<UnderlyingPosition>,
+        cash: f64,
+    ) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let now = self.now();
+        let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+        let fund = funds.get_mut(asset_id).ok_or("Fund not found")?;
+        let before = fund.clone();
+        fund.accrued_fees += fund.fees_due(now);
+        fund.fees_accrued_to = fund.fees_accrued_to.max(now);
+        fund.positions = positions;
+        fund.cash = cash;
+        self.record_audit(AuditAction::Update, EntityType::Fund, asset_id, Some(&before), Some(&*fund))?;
+        Ok(())
+    }
+
+    /// Replace a fund's liabilities
+    pub fn set_fund_liabilities(&self, asset_id: &str, liabilities: Vec<Liability>) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+        let fund = funds.get_mut(asset_id).ok_or("Fund not found")?;
+        let before = fund.clone();
+        fund.liabilities = liabilities;
+        self.record_audit(AuditAction::Update, EntityType::Fund, asset_id, Some(&before), Some(&*fund))?;
+        Ok(())
+    }
+
+    /// Pay accrued fees out of the fund's cash
+    pub fn pay_fund_fees(&self, asset_id: &str, amount: f64) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+        let fund = funds.get_mut(asset_id).ok_or("Fund not found")?;
+        if amount <= 0.0 || amount > fund.accrued_fees + QUANTITY_EPSILON {
+            return Err("Fee payment must be positive and not exceed accrued fees".to_string());
+        }
+        if amount > fund.cash + QUANTITY_EPSILON {
+            return Err("Insufficient fund cash".to_string());
+        }
+        let before = fund.clone();
+        fund.accrued_fees -= amount;
+        fund.cash -= amount;
+        self.record_audit(AuditAction::Update, EntityType::Fund, asset_id, Some(&before), Some(&*fund))?;
+        Ok(())
+    }
+
+    /// Compute the NAV at the current time and append it to the fund's NAV history
+    pub fn calculate_nav(&self, asset_id: &str) -> Result<NavRecord, String> {
+        self.authorize_asset(Permission::ReadReports, asset_id)?;
+        self.ensure_ids_not_frozen(&[], &[asset_id])?;
+        let now = self.now();
+        let units_outstanding = self.get_issued_supply(asset_id)?;
+        let mut funds = self.funds.write().map_err(|_| "Failed to acquire write lock")?;
+        let mut history = self.nav_history.write().map_err(|_| "Failed to acquire write lock")?;
+        let fund = funds.get_mut(asset_id).ok_or("Fund not found")?;
+
+        let entries = history.entry(asset_id.to_string()).or_default();
+        if entries.last().is_some_and(|last| last.valuation_time > now) {
+            return Err("Valuation point precedes the latest NAV".to_string());
+        }
+
+        let before = fund.clone();
+        fund.accrued_fees += fund.fees_due(now);
+        fund.fees_accrued_to = fund.fees_accrued_to.max(now);
+
+        let gross_assets = fund.gross_assets();
+        let liabilities = fund.total_liabilities();
+        let net_assets = gross_assets - liabilities - fund.accrued_fees;
+        let nav_per_unit = if units_outstanding > QUANTITY_EPSILON {
+            net_assets / units_outstanding
+        } else {
+            fund.initial_nav_per_unit
+        };
+
+        let record = NavRecord {
+            asset_id: asset_id.to_string(),
+            valuation_time: now,
+            gross_assets,
+            liabilities,
+            accrued_fees: fund.accrued_fees,
+            net_assets,
+            units_outstanding,
+            nav_per_unit,
+        };
+        // A second valuation at the same instant supersedes the first
+        if entries.last().is_some_and(|last| last.valuation_time == now) {
+            entries.pop();
+        }
+        entries.push(record.clone());
+        self.record_audit(AuditAction::Process, EntityType::Fund, asset_id, Some(&before), Some(&*fund))?;
+        Ok(record)
+    }
+
+    /// Get the full NAV history of a fund, oldest first
+    pub fn get_nav_history(&self, asset_id: &str) -> Result<Vec<NavRecord>, String> {
+        self.authorize_asset(Permission::ReadReports, asset_id)?;
+        let history = self.nav_history.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(history.get(asset_id).cloned().unwrap_or_default())
+    }
+
+    /// Get the latest NAV struck at or before `at`
+    pub fn get_nav_at(&self, asset_id: &str, at: u64) -> Result<Option<NavRecord>, String> {
+        self.authorize_asset(Permission::ReadReports, asset_id)?;
+        let history = self.nav_history.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(history
+            .get(asset_id)
+            .and_then(|entries| entries.iter().rev().find(|r| r.valuation_time <= at))
+            .cloned())
+    }
+
+    /// Get the first NAV struck at or after `at`
+    pub fn get_next_nav(&self, asset_id: &str, at: u64) -> Result<Option<NavRecord>, String> {
+        let history = self.nav_history.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(history
+            .get(asset_id)
+            .and_then(|entries| entries.iter().find(|r| r.valuation_time >= at))
+            .cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            value: 0.0,
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("fund1", "manager", AssetType::Fund)
+        })
+        .unwrap();
+        core.create_wallet(wallet("wallet1", "investor", WalletType::Custodial)).unwrap();
+        let fees = vec![FeeRate {
+            name: "management".to_string(),
+            annual_rate: 0.01,
+        }];
+        core.register_fund(FundProfile::new("fund1", 10.0, fees, 0)).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_nav_uses_initial_price_until_units_exist() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        assert_eq!(core.calculate_nav("fund1").unwrap().nav_per_unit, 10.0);
+    }
+
+    #[test]
+    fn test_nav_deducts_liabilities_and_accrued_fees() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.mint_to_wallet("wallet1", "fund1", 100.0).unwrap();
+        core.update_fund_portfolio(
+            "fund1",
+            vec![UnderlyingPosition {
+                instrument: "BOND-A".to_string(),
+                quantity: 9.0,
+                price: 100.0,
+            }],
+            100.0,
+        )
+        .unwrap();
+        core.set_fund_liabilities(
+            "fund1",
+            vec![Liability {
+                description: "Payables".to_string(),
+                amount: 50.0,
+            }],
+        )
+        .unwrap();
+
+        clock.set(SECONDS_PER_YEAR);
+        let nav = core.calculate_nav("fund1").unwrap();
+        assert_eq!(nav.gross_assets, 1_000.0);
+        assert!((nav.accrued_fees - 10.0).abs() < 1e-9);
+        assert!((nav.nav_per_unit - 9.4).abs() < 1e-9);
+
+        core.pay_fund_fees("fund1", 10.0).unwrap();
+        assert_eq!(core.get_fund("fund1").unwrap().unwrap().cash, 90.0);
+    }
+
+    #[test]
+    fn test_nav_history_lookup_by_time() {
+        let clock = ManualClock::new(100);
+        let core = setup(&clock);
+        core.calculate_nav("fund1").unwrap();
+        clock.set(200);
+        core.calculate_nav("fund1").unwrap();
+
+        assert!(core.get_nav_at("fund1", 99).unwrap().is_none());
+        assert_eq!(core.get_nav_at("fund1", 150).unwrap().unwrap().valuation_time, 100);
+        assert_eq!(core.get_next_nav("fund1", 150).unwrap().unwrap().valuation_time, 200);
+        assert_eq!(core.get_nav_history("fund1").unwrap().len(), 2);
+    }
+}
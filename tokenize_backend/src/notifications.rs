@@ -0,0 +1,164 @@
+//! Fan-out of change notifications to outbound integrations
+//!
+//! Core domain events and component catalog changes are turned into
+//! `Notification`s with a dotted event type such as `asset.transferred` or
+//! `catalog.component_added`, numbered in publication order and handed to every
+//! registered `NotificationSink`. Sinks are called synchronously, often while the
+//! core still holds its ledger locks, so they must only enqueue work.
+
+use crate::events::{DomainEvent, StoredEvent};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Topic of component catalog changes
+pub const CATALOG_TOPIC: &str = "catalog";
+
+/// Event type published when a component is added to the catalog
+pub const COMPONENT_ADDED_EVENT: &str = "catalog.component_added";
+
+/// A change published to outbound integrations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Unique, increasing identifier assigned at publication
+    pub id: u64,
+    pub event_type: String,
+    pub occurred_at: u64,
+    pub data: Value,
+}
+
+impl Notification {
+    /// Topic of the notification, i.e. the part of the event type before the first dot
+    pub fn topic(&self) -> &str {
+        self.event_type.split('.').next().unwrap_or_default()
+    }
+}
+
+/// Receiver of published notifications
+pub trait NotificationSink: Send + Sync {
+    fn notify(&self, notification: &Notification);
+}
+
+/// Dotted event type of a domain event
+pub fn event_type_of(event: &DomainEvent) -> &'static str {
+    match event {
+        DomainEvent::AssetCreated { .. } => "asset.created",
+        DomainEvent::AssetUpdated { .. } => "asset.updated",
+        DomainEvent::AssetDeleted { .. } => "asset.deleted",
+        DomainEvent::AssetRestored { .. } => "asset.restored",
+        DomainEvent::AssetMinted { .. } => "asset.minted",
+        DomainEvent::AssetBurned { .. } => "asset.burned",
+        DomainEvent::AssetTransferred { .. } => "asset.transferred",
+        DomainEvent::HoldingsRescaled { .. } => "asset.rescaled",
+        DomainEvent::WalletCreated { .. } => "wallet.created",
+        DomainEvent::WalletDeleted { .. } => "wallet.deleted",
+        DomainEvent::WalletOwnerChanged { .. } => "wallet.owner_changed",
+        DomainEvent::AssetAddedToWallet { .. } => "wallet.asset_added",
+        DomainEvent::AssetRemovedFromWallet { .. } => "wallet.asset_removed",
+        DomainEvent::WalletBalanceChanged { .. } => "wallet.balance_changed",
+        DomainEvent::ComplianceDecided { .. } => "compliance.decided",
+    }
+}
+
+/// Whether an event type matches a filter such as `asset.transferred`, `asset.*` or `*`
+pub fn event_type_matches(filter: &str, event_type: &str) -> bool {
+    match filter.strip_suffix(".*") {
+        Some(prefix) => event_type.split('.').next() == Some(prefix),
+        None => filter == "*" || filter == event_type,
+    }
+}
+
+/// Shared registry of sinks; clones publish to the same sinks and share numbering
+#[derive(Clone, Default)]
+pub struct NotificationHub {
+    sinks: Arc<RwLock<Vec<Arc<dyn NotificationSink>>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl NotificationHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a sink for every notification published from now on
+    pub fn subscribe(&self, sink: Arc<dyn NotificationSink>) -> Result<(), String> {
+        let mut sinks = self.sinks.write().map_err(|_| "Failed to acquire write lock")?;
+        sinks.push(sink);
+        Ok(())
+    }
+
+    /// Number and publish a notification to every sink
+    pub fn publish(&self, event_type: &str, occurred_at: u64, data: Value) -> Result<Notification, String> {
+        let notification = Notification {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst) + 1,
+            event_type: event_type.to_string(),
+            occurred_at,
+            data,
+        };
+        let sinks = self.sinks.read().map_err(|_| "Failed to acquire read lock")?;
+        for sink in sinks.iter() {
+            sink.notify(&notification);
+        }
+        Ok(notification)
+    }
+
+    /// Publish a persisted domain event
+    ///
+    /// The payload is the event's fields plus its stream `sequence`.
+    pub fn publish_domain_event(&self, stored: &StoredEvent) -> Result<Notification, String> {
+        let mut data = serde_json::to_value(&stored.event)
+            .ok()
+            .and_then(|v| v.as_object().and_then(|o| o.values().next().cloned()))
+            .unwrap_or(Value::Null);
+        if let Value::Object(fields) = &mut data {
+            fields.insert("sequence".to_string(), Value::from(stored.sequence));
+        }
+        self.publish(event_type_of(&stored.event), stored.timestamp, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Collector(RwLock<Vec<Notification>>);
+
+    impl NotificationSink for Collector {
+        fn notify(&self, notification: &Notification) {
+            self.0.write().unwrap().push(notification.clone());
+        }
+    }
+
+    #[test]
+    fn test_publish_numbers_and_fans_out() {
+        let hub = NotificationHub::new();
+        let collector = Arc::new(Collector(RwLock::new(Vec::new())));
+        hub.subscribe(collector.clone()).unwrap();
+
+        hub.publish_domain_event(&StoredEvent {
+            sequence: 7,
+            timestamp: 100,
+            event: DomainEvent::ComplianceDecided {
+                asset_id: "asset1".to_string(),
+                status: crate::core_component::ComplianceStatus::Approved,
+            },
+        })
+        .unwrap();
+        hub.clone().publish("catalog.component_added", 101, Value::Null).unwrap();
+
+        let received = collector.0.read().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].id, 1);
+        assert_eq!(received[0].event_type, "compliance.decided");
+        assert_eq!(received[0].topic(), "compliance");
+        assert_eq!(received[0].data["asset_id"], "asset1");
+        assert_eq!(received[0].data["sequence"], 7);
+        assert_eq!(received[1].id, 2);
+
+        assert!(event_type_matches("asset.*", "asset.minted"));
+        assert!(event_type_matches("*", "wallet.created"));
+        assert!(!event_type_matches("asset.*", "wallet.created"));
+        assert!(!event_type_matches("asset.created", "asset.updated"));
+    }
+}
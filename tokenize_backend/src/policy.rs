@@ -0,0 +1,597 @@
+//! Embedded policy evaluation
+//!
+//! Policies are JSON documents of allow and deny rules in the spirit of OPA: each
+//! rule names the operations it covers and a list of conditions on the input
+//! document, which describes the actor, the wallet, the asset and the proposed
+//! operation. A document denies when any of its deny rules match, or when its
+//! default is `deny` and none of its allow rules match. An operation is allowed
+//! only if no document denies it, and every decision carries the rules that
+//! produced it.
+//!
+//! A bundle is a directory of `*.json` policy documents. It can be reloaded while
+//! the server runs; a bundle that fails to parse leaves the previous one in force.
+//! With no documents loaded every operation is allowed.
+
+use crate::access_control::{current_subject, Role};
+use crate::audit::current_actor;
+use crate::core_component::{DigitalWallet, TokenizationCore, TokenizedAsset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Prefix of every error returned by the core when a policy denies an operation
+pub const POLICY_DENIED: &str = "Denied by policy";
+
+/// Seconds between checks of the bundle directory for changed files
+pub const DEFAULT_RELOAD_INTERVAL_SECS: u64 = 5;
+
+/// Outcome a rule or a document's default contributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// Comparison a condition applies to the value found at its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operator {
+    Eq,
+    Ne,
+    /// The value is an array containing the input value
+    In,
+    NotIn,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// The input value is an array containing the value, or a string containing it
+    Contains,
+    /// The path is present and not null
+    Exists,
+    Absent,
+}
+
+/// A test on one value of the input document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    /// Dotted path into the input, e.g. `asset.metadata.jurisdiction`
+    pub path: String,
+    pub op: Operator,
+    #[serde(default)]
+    pub value: Value,
+    /// Compare against the value at another input path instead of `value`
+    #[serde(default)]
+    pub value_from: Option<String>,
+}
+
+impl Condition {
+    pub fn holds(&self, input: &Value) -> bool {
+        let actual = lookup(input, &self.path);
+        let expected = match &self.value_from {
+            Some(path) => lookup(input, path).unwrap_or(&Value::Null),
+            None => &self.value,
+        };
+        let found = actual.filter(|v| !v.is_null());
+        let actual = actual.unwrap_or(&Value::Null);
+        match self.op {
+            Operator::Eq => values_equal(actual, expected),
+            Operator::Ne => !values_equal(actual, expected),
+            Operator::In => expected.as_array().is_some_and(|items| items.iter().any(|v| values_equal(actual, v))),
+            Operator::NotIn => !expected.as_array().is_some_and(|items| items.iter().any(|v| values_equal(actual, v))),
+            Operator::Gt => compare(actual, expected) == Some(Ordering::Greater),
+            Operator::Gte => matches!(compare(actual, expected), Some(Ordering::Greater | Ordering::Equal)),
+            Operator::Lt => compare(actual, expected) == Some(Ordering::Less),
+            Operator::Lte => matches!(compare(actual, expected), Some(Ordering::Less | Ordering::Equal)),
+            Operator::Contains => match (actual, expected) {
+                (Value::Array(items), _) => items.iter().any(|v| values_equal(v, expected)),
+                (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+                _ => false,
+            },
+            Operator::Exists => found.is_some(),
+            Operator::Absent => found.is_none(),
+        }
+    }
+}
+
+/// Value at a dotted path; numeric segments index arrays
+fn lookup<'a>(input: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|s| !s.is_empty()).try_fold(input, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+/// JSON equality that treats `10` and `10.0` as the same number
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+/// An allow or deny rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    pub effect: Effect,
+    /// Operation kinds the rule covers, e.g. `transfer`; empty covers all of them
+    #[serde(default)]
+    pub operations: Vec<String>,
+    /// Conditions that must all hold for the rule to match
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    /// Explanation reported when the rule matches
+    pub message: String,
+}
+
+impl PolicyRule {
+    pub fn matches(&self, input: &Value) -> bool {
+        let kind = lookup(input, "operation.kind").and_then(Value::as_str);
+        (self.operations.is_empty() || self.operations.iter().any(|op| Some(op.as_str()) == kind))
+            && self.when.iter().all(|condition| condition.holds(input))
+    }
+}
+
+/// A named set of rules, typically one file of a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDocument {
+    pub name: String,
+    /// Outcome when no rule matches
+    #[serde(default)]
+    pub default: Effect,
+    pub rules: Vec<PolicyRule>,
+}
+
+impl PolicyDocument {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Policy name must not be empty".to_string());
+        }
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            if !ids.insert(rule.id.as_str()) {
+                return Err(format!("Policy {} has duplicate rule {}", self.name, rule.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the document allows the input, with the rules behind that outcome
+    pub fn evaluate(&self, input: &Value) -> (Effect, Vec<Explanation>) {
+        let matched: Vec<&PolicyRule> = self.rules.iter().filter(|rule| rule.matches(input)).collect();
+        let explain = |effect: Effect| {
+            matched
+                .iter()
+                .filter(|rule| rule.effect == effect)
+                .map(|rule| Explanation {
+                    policy: self.name.clone(),
+                    rule: Some(rule.id.clone()),
+                    effect,
+                    message: rule.message.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let denials = explain(Effect::Deny);
+        if !denials.is_empty() {
+            return (Effect::Deny, denials);
+        }
+        let allowances = explain(Effect::Allow);
+        if allowances.is_empty() && self.default == Effect::Deny {
+            let default = Explanation {
+                policy: self.name.clone(),
+                rule: None,
+                effect: Effect::Deny,
+                message: "No allow rule matched".to_string(),
+            };
+            return (Effect::Deny, vec![default]);
+        }
+        (Effect::Allow, allowances)
+    }
+}
+
+/// Why a document reached its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub policy: String,
+    /// Matching rule, or `None` when the document's default applied
+    pub rule: Option<String>,
+    pub effect: Effect,
+    pub message: String,
+}
+
+/// Result of evaluating every document against an input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    pub explanations: Vec<Explanation>,
+}
+
+impl PolicyDecision {
+    /// Messages of the explanations that denied the operation
+    pub fn denial_reasons(&self) -> Vec<String> {
+        self.explanations
+            .iter()
+            .filter(|e| e.effect == Effect::Deny)
+            .map(|e| format!("{}: {}", e.policy, e.message))
+            .collect()
+    }
+}
+
+/// Evaluate an input against a set of documents
+pub fn evaluate_documents(documents: &[PolicyDocument], input: &Value) -> PolicyDecision {
+    let mut allowed = true;
+    let mut explanations = Vec::new();
+    for document in documents {
+        let (effect, reasons) = document.evaluate(input);
+        allowed &= effect == Effect::Allow;
+        explanations.extend(reasons);
+    }
+    PolicyDecision { allowed, explanations }
+}
+
+/// Whether an error from the core is a policy denial
+pub fn is_policy_denied(error: &str) -> bool {
+    error.starts_with(POLICY_DENIED)
+}
+
+/// Operations the core consults policies for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOperationKind {
+    Transfer,
+    CreateWallet,
+    /// Creation of a new tokenized asset
+    IssueAsset,
+    Mint,
+}
+
+/// Who is acting; platform-internal calls have no roles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyActor {
+    pub id: String,
+    pub roles: Vec<Role>,
+}
+
+impl PolicyActor {
+    /// The current audit actor with the roles of the current subject, if any
+    pub fn current() -> Self {
+        Self {
+            id: current_actor(),
+            roles: current_subject().map(|s| s.roles).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyOperation {
+    pub kind: PolicyOperationKind,
+    pub quantity: Option<f64>,
+    /// Receiving wallet of a transfer
+    pub to_wallet: Option<DigitalWallet>,
+}
+
+/// Input document the policies are evaluated against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyInput {
+    pub actor: PolicyActor,
+    pub operation: PolicyOperation,
+    /// Wallet created, or the sending or receiving wallet of a transfer or mint
+    pub wallet: Option<DigitalWallet>,
+    pub asset: Option<TokenizedAsset>,
+}
+
+/// Loaded policy documents and where they came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyBundle {
+    pub documents: Vec<PolicyDocument>,
+    /// Directory the documents were loaded from
+    pub source: Option<PathBuf>,
+    /// Incremented on every successful load
+    pub revision: u64,
+    /// File names, sizes and modification times at the last load
+    #[serde(skip)]
+    fingerprint: Vec<(String, u64, Option<SystemTime>)>,
+}
+
+/// Shared, reloadable policy bundle; clones evaluate and reload the same bundle
+#[derive(Clone, Default)]
+pub struct PolicyEngine {
+    bundle: Arc<RwLock<PolicyBundle>>,
+}
+
+impl PolicyEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `*.json` document in a directory, replacing the current bundle
+    pub fn load_dir(&self, dir: impl AsRef<Path>) -> Result<usize, String> {
+        let dir = dir.as_ref();
+        let fingerprint = fingerprint(dir)?;
+        let mut documents = Vec::new();
+        for (name, _, _) in &fingerprint {
+            let path = dir.join(name);
+            let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let document: PolicyDocument =
+                serde_json::from_str(&text).map_err(|e| format!("Invalid policy {}: {}", path.display(), e))?;
+            document.validate()?;
+            documents.push(document);
+        }
+        let count = documents.len();
+        let mut bundle = self.bundle.write().map_err(|_| "Failed to acquire write lock")?;
+        bundle.documents = documents;
+        bundle.source = Some(dir.to_path_buf());
+        bundle.revision += 1;
+        bundle.fingerprint = fingerprint;
+        Ok(count)
+    }
+
+    /// Replace the bundle with documents held in memory
+    pub fn set_documents(&self, documents: Vec<PolicyDocument>) -> Result<(), String> {
+        for document in &documents {
+            document.validate()?;
+        }
+        let mut bundle = self.bundle.write().map_err(|_| "Failed to acquire write lock")?;
+        bundle.documents = documents;
+        bundle.source = None;
+        bundle.revision += 1;
+        bundle.fingerprint.clear();
+        Ok(())
+    }
+
+    /// Reload the bundle from its directory
+    pub fn reload(&self) -> Result<usize, String> {
+        let source = self.bundle()?.source.ok_or("Policy bundle was not loaded from a directory")?;
+        self.load_dir(source)
+    }
+
+    /// Reload the bundle if any file in its directory was added, removed or changed
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        let (source, loaded) = {
+            let bundle = self.bundle.read().map_err(|_| "Failed to acquire read lock")?;
+            match &bundle.source {
+                Some(source) => (source.clone(), bundle.fingerprint.clone()),
+                None => return Ok(false),
+            }
+        };
+        if fingerprint(&source)? == loaded {
+            return Ok(false);
+        }
+        self.load_dir(source)?;
+        Ok(true)
+    }
+
+    /// Poll the bundle directory for changes until the runtime shuts down
+    pub fn watch(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match engine.reload_if_changed() {
+                    Ok(true) => println!("Reloaded policy bundle"),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("Keeping previous policy bundle: {}", e),
+                }
+            }
+        })
+    }
+
+    pub fn bundle(&self) -> Result<PolicyBundle, String> {
+        let bundle = self.bundle.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(bundle.clone())
+    }
+
+    /// Evaluate an input document against the loaded bundle
+    pub fn evaluate(&self, input: &Value) -> Result<PolicyDecision, String> {
+        let bundle = self.bundle.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(evaluate_documents(&bundle.documents, input))
+    }
+
+    fn is_empty(&self) -> Result<bool, String> {
+        let bundle = self.bundle.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(bundle.documents.is_empty())
+    }
+}
+
+/// Sorted names, sizes and modification times of the `*.json` files in a directory
+fn fingerprint(dir: &Path) -> Result<Vec<(String, u64, Option<SystemTime>)>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.ends_with(".json") {
+            continue;
+        }
+        let metadata = entry.metadata().map_err(|e| e.to_string())?;
+        if metadata.is_file() {
+            files.push((name, metadata.len(), metadata.modified().ok()));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl TokenizationCore {
+    /// Policy engine consulted before transfers, wallet creation and issuance
+    pub fn policies(&self) -> PolicyEngine {
+        self.policies.clone()
+    }
+
+    /// Refuse the operation if a loaded policy denies it
+    pub(crate) fn enforce_policies(&self, input: PolicyInput) -> Result<(), String> {
+        if self.policies.is_empty()? {
+            return Ok(());
+        }
+        let input = serde_json::to_value(&input).map_err(|e| e.to_string())?;
+        let decision = self.policies.evaluate(&input)?;
+        if decision.allowed {
+            Ok(())
+        } else {
+            Err(format!("{}: {}", POLICY_DENIED, decision.denial_reasons().join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::Subject;
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, WalletType};
+    use crate::test_support::{asset, wallet};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn setup(clock: ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock));
+        core.create_asset(TokenizedAsset {
+            metadata: HashMap::from([("jurisdiction".to_string(), "US".to_string())]),
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for (id, owner) in [("wallet1", "alice"), ("wallet2", "bob")] {
+            core.create_wallet(wallet(id, owner, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 1_000.0).unwrap();
+        core
+    }
+
+    fn transfer_limits() -> PolicyDocument {
+        serde_json::from_value(json!({
+            "name": "transfer-limits",
+            "rules": [
+                {
+                    "id": "max-ticket",
+                    "effect": "deny",
+                    "operations": ["transfer"],
+                    "when": [{"path": "operation.quantity", "op": "gt", "value": 500}],
+                    "message": "Transfers are capped at 500 units"
+                },
+                {
+                    "id": "no-self-custody-receivers",
+                    "effect": "deny",
+                    "operations": ["transfer"],
+                    "when": [{"path": "operation.to_wallet.wallet_type", "op": "eq", "value": "NonCustodial"}],
+                    "message": "Only custodial wallets may receive"
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_policies_gate_core_operations_with_explanations() {
+        let core = setup(ManualClock::new(100));
+        core.policies().set_documents(vec![transfer_limits()]).unwrap();
+
+        core.transfer_asset("wallet1", "wallet2", "asset1", 100.0).unwrap();
+        let error = core.transfer_asset("wallet1", "wallet2", "asset1", 600.0).unwrap_err();
+        assert!(is_policy_denied(&error));
+        assert!(error.contains("Transfers are capped at 500 units"));
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 100.0);
+
+        // Only custodial wallets opened by their owner, unless the caller is an admin
+        let mut custodial_wallets: PolicyDocument = serde_json::from_value(json!({
+            "name": "custodial-wallets",
+            "default": "deny",
+            "rules": [
+                {
+                    "id": "own-custodial",
+                    "effect": "allow",
+                    "operations": ["create_wallet"],
+                    "when": [
+                        {"path": "wallet.owner", "op": "eq", "value_from": "actor.id"},
+                        {"path": "wallet.wallet_type", "op": "eq", "value": "Custodial"}
+                    ],
+                    "message": "Custodial wallet opened by its owner"
+                },
+                {
+                    "id": "admins",
+                    "effect": "allow",
+                    "when": [{"path": "actor.roles", "op": "contains", "value": "admin"}],
+                    "message": "Admins may do anything"
+                }
+            ]
+        }))
+        .unwrap();
+        custodial_wallets.rules.push(PolicyRule {
+            id: "other-operations".to_string(),
+            effect: Effect::Allow,
+            operations: vec!["transfer".to_string(), "issue_asset".to_string(), "mint".to_string()],
+            when: vec![],
+            message: "Not covered".to_string(),
+        });
+        core.policies().set_documents(vec![transfer_limits(), custodial_wallets]).unwrap();
+        let carol = Subject::new("carol", &[Role::Investor]);
+        let admin = Subject::new("root", &[Role::Admin]);
+        let mut self_custody = wallet("wallet4", "carol", WalletType::Custodial);
+        self_custody.wallet_type = WalletType::NonCustodial;
+        assert!(core.as_subject(&carol, || core.create_wallet(wallet("wallet3", "carol", WalletType::Custodial))).is_ok());
+        let error = core.as_subject(&carol, || core.create_wallet(self_custody.clone())).unwrap_err();
+        assert!(error.contains("custodial-wallets: No allow rule matched"), "{}", error);
+        assert!(core.as_subject(&admin, || core.create_wallet(self_custody)).is_ok());
+
+        // Receiving wallets are checked too
+        let error = core.transfer_asset("wallet1", "wallet4", "asset1", 10.0).unwrap_err();
+        assert!(error.contains("Only custodial wallets may receive"));
+    }
+
+    #[test]
+    fn test_dry_run_evaluation_reports_every_matching_rule() {
+        let documents = vec![transfer_limits()];
+        let input = json!({
+            "actor": {"id": "alice", "roles": []},
+            "operation": {"kind": "transfer", "quantity": 900, "to_wallet": {"wallet_type": "NonCustodial"}}
+        });
+        let decision = evaluate_documents(&documents, &input);
+        assert!(!decision.allowed);
+        let rules: Vec<_> = decision.explanations.iter().filter_map(|e| e.rule.as_deref()).collect();
+        assert_eq!(rules, ["max-ticket", "no-self-custody-receivers"]);
+
+        let input = json!({"operation": {"kind": "mint", "quantity": 900}});
+        assert!(evaluate_documents(&documents, &input).allowed);
+    }
+
+    #[test]
+    fn test_bundle_hot_reload_keeps_last_good_bundle() {
+        let dir = std::env::temp_dir().join(format!("policy-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let engine = PolicyEngine::new();
+        std::fs::write(dir.join("limits.json"), serde_json::to_string(&transfer_limits()).unwrap()).unwrap();
+        std::fs::write(dir.join("README.md"), "not a policy").unwrap();
+        assert_eq!(engine.load_dir(&dir), Ok(1));
+        assert_eq!(engine.reload_if_changed(), Ok(false));
+
+        std::fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        assert!(engine.reload_if_changed().is_err());
+        assert_eq!(engine.bundle().unwrap().documents, vec![transfer_limits()]);
+
+        let mut stricter = transfer_limits();
+        stricter.name = "stricter".to_string();
+        std::fs::write(dir.join("broken.json"), serde_json::to_string(&stricter).unwrap()).unwrap();
+        assert_eq!(engine.reload_if_changed(), Ok(true));
+        let bundle = engine.bundle().unwrap();
+        assert_eq!(bundle.documents.len(), 2);
+        assert_eq!(bundle.revision, 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,684 @@
+//! Reconciliation of the internal ledger against external statements
+//!
+//! A custodian's or bank's statement for a wallet is loaded from a local file,
+//! either CSV or ISO 20022 camt.053 XML, and its lines matched to the wallet's
+//! movements in the event stream: mints, burns and transfers of one asset, or cash
+//! balance changes when no asset is given. Movements are referenced as
+//! `event-{sequence}`, the reference the platform quotes on its instructions.
+//!
+//! A line and a movement in the same direction are paired when their references
+//! agree or their amount and date fall within the tolerance rules. A pair that
+//! agrees on everything is matched; one that differs in amount, date or reference
+//! is a partial match and its differences are reported as breaks. Lines and
+//! movements left over are unmatched. Only movements within the statement's period,
+//! widened by the date tolerance, are considered.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{current_actor, AuditAction, EntityType};
+use crate::calendar::Date;
+use crate::core_component::TokenizationCore;
+use crate::events::DomainEvent;
+use csv::{ReaderBuilder, Trim, Writer};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Amount differences below this are rounding noise rather than breaks
+const AMOUNT_EPSILON: f64 = 1e-9;
+
+/// File format of an external statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatementFormat {
+    Csv,
+    Camt053,
+}
+
+/// One entry of an external statement; credits are positive, debits negative
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatementLine {
+    /// Position in the statement, starting at 1
+    pub line: usize,
+    pub date: Date,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub reference: Option<String>,
+    pub description: Option<String>,
+}
+
+/// An external statement as loaded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Statement {
+    pub format: StatementFormat,
+    pub account: Option<String>,
+    /// Period the statement covers, when it says
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+    pub lines: Vec<StatementLine>,
+}
+
+/// A movement of the wallet in the event stream; credits are positive, debits negative
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerMovement {
+    pub reference: String,
+    pub sequence: u64,
+    pub timestamp: u64,
+    pub date: Date,
+    pub amount: f64,
+}
+
+/// How far a statement line may stray from a movement and still match it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToleranceRules {
+    /// Largest absolute amount difference
+    pub amount: f64,
+    /// Largest number of days between booking dates
+    pub days: i64,
+}
+
+impl Default for ToleranceRules {
+    fn default() -> Self {
+        Self { amount: 0.01, days: 2 }
+    }
+}
+
+/// Outcome for a statement line or movement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchStatus {
+    Matched,
+    PartiallyMatched,
+    /// On the statement but not in the ledger
+    UnmatchedStatement,
+    /// In the ledger but not on the statement
+    UnmatchedLedger,
+}
+
+/// How a partially matched line differs from its movement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Break {
+    /// Statement amount less ledger amount
+    Amount { difference: f64 },
+    /// Days from the ledger date to the statement date
+    Date { days: i64 },
+    Reference { statement: String, ledger: String },
+}
+
+/// A line of the reconciliation report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationItem {
+    pub status: MatchStatus,
+    pub statement_line: Option<StatementLine>,
+    pub movement: Option<LedgerMovement>,
+    pub breaks: Vec<Break>,
+}
+
+/// Counts of each outcome
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationSummary {
+    pub matched: usize,
+    pub partially_matched: usize,
+    pub unmatched_statement: usize,
+    pub unmatched_ledger: usize,
+}
+
+/// A statement reconciled against a wallet's ledger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reconciliation {
+    pub id: String,
+    pub wallet_id: String,
+    /// Asset reconciled, or `None` for the wallet's cash balance
+    pub asset_id: Option<String>,
+    pub format: StatementFormat,
+    pub account: Option<String>,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+    pub tolerance: ToleranceRules,
+    pub run_at: u64,
+    pub run_by: String,
+    pub summary: ReconciliationSummary,
+    pub items: Vec<ReconciliationItem>,
+}
+
+impl StatementFormat {
+    /// Format implied by a file's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(StatementFormat::Csv),
+            "xml" => Some(StatementFormat::Camt053),
+            _ => None,
+        }
+    }
+}
+
+/// Path of a statement file inside the statements directory, refusing names that leave it
+pub fn resolve_statement_path(dir: &Path, file: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(file);
+    if file.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Statement file {} must be a relative path inside the statements directory", file));
+    }
+    Ok(dir.join(relative))
+}
+
+/// Read and parse a statement file, taking the format from its extension if not given
+pub fn load_statement(path: &Path, format: Option<StatementFormat>) -> Result<Statement, String> {
+    let format = format
+        .or_else(|| StatementFormat::from_path(path))
+        .ok_or_else(|| format!("Cannot tell the format of {}; give csv or camt.053", path.display()))?;
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_statement(format, &content)
+}
+
+/// Parse a statement's contents
+pub fn parse_statement(format: StatementFormat, content: &str) -> Result<Statement, String> {
+    match format {
+        StatementFormat::Csv => parse_csv_statement(content),
+        StatementFormat::Camt053 => parse_camt053(content),
+    }
+}
+
+/// Parse a CSV statement with a header row naming `date` (YYYY-MM-DD) and signed
+/// `amount` columns, and optionally `reference`, `description` and `currency`
+fn parse_csv_statement(content: &str) -> Result<Statement, String> {
+    let mut reader = ReaderBuilder::new().has_headers(true).trim(Trim::All).from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Invalid CSV header: {}", e))?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let date = column("date").ok_or("CSV statement needs a date column")?;
+    let amount = column("amount").ok_or("CSV statement needs an amount column")?;
+    let (reference, description, currency) = (column("reference"), column("description"), column("currency"));
+
+    let mut lines = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = index + 1;
+        let record = record.map_err(|e| format!("Line {}: {}", line, e))?;
+        let text = |column: Option<usize>| column.and_then(|c| record.get(c)).filter(|v| !v.is_empty()).map(str::to_string);
+        lines.push(StatementLine {
+            line,
+            date: record.get(date).unwrap_or("").parse().map_err(|e| format!("Line {}: {}", line, e))?,
+            amount: record
+                .get(amount)
+                .unwrap_or("")
+                .parse()
+                .map_err(|_| format!("Line {}: invalid amount", line))?,
+            currency: text(currency),
+            reference: text(reference),
+            description: text(description),
+        });
+    }
+    Ok(Statement {
+        format: StatementFormat::Csv,
+        account: None,
+        from: None,
+        to: None,
+        lines,
+    })
+}
+
+type XmlNode<'a> = roxmltree::Node<'a, 'a>;
+
+fn child<'a>(node: XmlNode<'a>, name: &str) -> Option<XmlNode<'a>> {
+    node.children().find(|c| c.is_element() && c.tag_name().name() == name)
+}
+
+/// Text of the element at a path of child names below a node
+fn text_at<'a>(node: XmlNode<'a>, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(node, |node, name| child(node, name))?
+        .text()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Date of a `Dt` or `DtTm` element below a node
+fn date_at(node: XmlNode<'_>, path: &[&str]) -> Result<Option<Date>, String> {
+    let element = match path.iter().try_fold(node, |node, name| child(node, name)) {
+        Some(element) => element,
+        None => return Ok(None),
+    };
+    let text = text_at(element, &["Dt"])
+        .or_else(|| text_at(element, &["DtTm"]))
+        .or_else(|| element.text().map(str::trim).filter(|t| !t.is_empty()));
+    text.map(|t| t.get(..10).unwrap_or(t).parse()).transpose()
+}
+
+/// Parse the booked entries of the first statement of a camt.053 document
+fn parse_camt053(content: &str) -> Result<Statement, String> {
+    let document = roxmltree::Document::parse(content).map_err(|e| format!("Invalid camt.053 XML: {}", e))?;
+    let statement = document
+        .descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == "Stmt")
+        .ok_or("camt.053 document has no Stmt element")?;
+    let account = text_at(statement, &["Acct", "Id", "IBAN"])
+        .or_else(|| text_at(statement, &["Acct", "Id", "Othr", "Id"]))
+        .map(str::to_string);
+
+    let mut lines = Vec::new();
+    let entries = statement.children().filter(|n| n.is_element() && n.tag_name().name() == "Ntry");
+    for (index, entry) in entries.enumerate() {
+        let line = index + 1;
+        let status = text_at(entry, &["Sts", "Cd"]).or_else(|| text_at(entry, &["Sts"]));
+        if status.is_some_and(|s| s != "BOOK") {
+            continue;
+        }
+        let amount_element = child(entry, "Amt").ok_or_else(|| format!("Entry {}: no Amt", line))?;
+        let amount: f64 = amount_element
+            .text()
+            .unwrap_or("")
+            .trim()
+            .parse()
+            .map_err(|_| format!("Entry {}: invalid amount", line))?;
+        let amount = match text_at(entry, &["CdtDbtInd"]) {
+            Some("CRDT") => amount,
+            Some("DBIT") => -amount,
+            _ => return Err(format!("Entry {}: CdtDbtInd must be CRDT or DBIT", line)),
+        };
+        let date = match date_at(entry, &["BookgDt"])? {
+            Some(date) => date,
+            None => date_at(entry, &["ValDt"])?.ok_or_else(|| format!("Entry {}: no booking or value date", line))?,
+        };
+        let details = ["NtryDtls", "TxDtls"];
+        let reference = text_at(entry, &[details[0], details[1], "Refs", "EndToEndId"])
+            .filter(|r| *r != "NOTPROVIDED")
+            .or_else(|| text_at(entry, &["NtryRef"]))
+            .or_else(|| text_at(entry, &["AcctSvcrRef"]));
+        let description = text_at(entry, &["AddtlNtryInf"]).or_else(|| text_at(entry, &[details[0], details[1], "RmtInf", "Ustrd"]));
+        lines.push(StatementLine {
+            line,
+            date,
+            amount,
+            currency: amount_element.attribute("Ccy").map(str::to_string),
+            reference: reference.map(str::to_string),
+            description: description.map(str::to_string),
+        });
+    }
+    Ok(Statement {
+        format: StatementFormat::Camt053,
+        account,
+        from: date_at(statement, &["FrToDt", "FrDtTm"])?,
+        to: date_at(statement, &["FrToDt", "ToDtTm"])?,
+        lines,
+    })
+}
+
+/// Differences between a line and a movement, or `None` if they cannot be a pair
+fn compare(line: &StatementLine, movement: &LedgerMovement, tolerance: &ToleranceRules) -> Option<Vec<Break>> {
+    if (line.amount < 0.0) != (movement.amount < 0.0) {
+        return None;
+    }
+    let difference = line.amount - movement.amount;
+    let amount_ok = difference.abs() <= tolerance.amount + AMOUNT_EPSILON;
+    let days = movement.date.days_until(line.date);
+    let date_ok = days.abs() <= tolerance.days;
+    let reference_agrees = line.reference.as_ref().is_some_and(|r| r.eq_ignore_ascii_case(&movement.reference));
+    if !(reference_agrees || amount_ok && date_ok) {
+        return None;
+    }
+    let mut breaks = Vec::new();
+    if !amount_ok {
+        breaks.push(Break::Amount { difference });
+    }
+    if !date_ok {
+        breaks.push(Break::Date { days });
+    }
+    if let Some(reference) = line.reference.as_ref().filter(|_| !reference_agrees) {
+        breaks.push(Break::Reference {
+            statement: reference.clone(),
+            ledger: movement.reference.clone(),
+        });
+    }
+    Some(breaks)
+}
+
+/// Pair statement lines with movements, exact matches first and then the closest partial ones
+fn match_lines(lines: &[StatementLine], movements: &[LedgerMovement], tolerance: &ToleranceRules) -> Vec<ReconciliationItem> {
+    let mut paired: Vec<Option<(usize, Vec<Break>)>> = vec![None; lines.len()];
+    let mut used = vec![false; movements.len()];
+    for exact in [true, false] {
+        for (l, line) in lines.iter().enumerate() {
+            if paired[l].is_some() {
+                continue;
+            }
+            let best = movements
+                .iter()
+                .enumerate()
+                .filter(|(m, _)| !used[*m])
+                .filter_map(|(m, movement)| compare(line, movement, tolerance).map(|breaks| (m, movement, breaks)))
+                .filter(|(_, _, breaks)| !exact || breaks.is_empty())
+                .min_by(|(_, a, a_breaks), (_, b, b_breaks)| {
+                    let key = |movement: &LedgerMovement, breaks: &Vec<Break>| {
+                        (breaks.len(), movement.date.days_until(line.date).abs())
+                    };
+                    key(a, a_breaks)
+                        .cmp(&key(b, b_breaks))
+                        .then((line.amount - a.amount).abs().total_cmp(&(line.amount - b.amount).abs()))
+                });
+            if let Some((m, _, breaks)) = best {
+                used[m] = true;
+                paired[l] = Some((m, breaks));
+            }
+        }
+    }
+
+    let mut items: Vec<ReconciliationItem> = lines
+        .iter()
+        .zip(paired)
+        .map(|(line, pair)| match pair {
+            Some((m, breaks)) => ReconciliationItem {
+                status: if breaks.is_empty() { MatchStatus::Matched } else { MatchStatus::PartiallyMatched },
+                statement_line: Some(line.clone()),
+                movement: Some(movements[m].clone()),
+                breaks,
+            },
+            None => ReconciliationItem {
+                status: MatchStatus::UnmatchedStatement,
+                statement_line: Some(line.clone()),
+                movement: None,
+                breaks: Vec::new(),
+            },
+        })
+        .collect();
+    items.extend(movements.iter().zip(used).filter(|(_, used)| !used).map(|(movement, _)| ReconciliationItem {
+        status: MatchStatus::UnmatchedLedger,
+        statement_line: None,
+        movement: Some(movement.clone()),
+        breaks: Vec::new(),
+    }));
+    items
+}
+
+fn describe_break(item: &Break) -> String {
+    match item {
+        Break::Amount { difference } => format!("amount differs by {}", difference),
+        Break::Date { days } => format!("booked {} days apart", days),
+        Break::Reference { statement, ledger } => format!("reference {} vs {}", statement, ledger),
+    }
+}
+
+/// A reconciliation report as CSV, one row per item
+pub fn reconciliation_csv(reconciliation: &Reconciliation) -> Result<String, String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "status",
+            "statement_line",
+            "statement_date",
+            "statement_amount",
+            "statement_reference",
+            "ledger_reference",
+            "ledger_date",
+            "ledger_amount",
+            "breaks",
+        ])
+        .map_err(|e| e.to_string())?;
+    for item in &reconciliation.items {
+        let line = item.statement_line.as_ref();
+        let movement = item.movement.as_ref();
+        writer
+            .write_record([
+                format!("{:?}", item.status),
+                line.map(|l| l.line.to_string()).unwrap_or_default(),
+                line.map(|l| l.date.to_string()).unwrap_or_default(),
+                line.map(|l| l.amount.to_string()).unwrap_or_default(),
+                line.and_then(|l| l.reference.clone()).unwrap_or_default(),
+                movement.map(|m| m.reference.clone()).unwrap_or_default(),
+                movement.map(|m| m.date.to_string()).unwrap_or_default(),
+                movement.map(|m| m.amount.to_string()).unwrap_or_default(),
+                item.breaks.iter().map(describe_break).collect::<Vec<_>>().join("; "),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+impl TokenizationCore {
+    /// A wallet's movements of one asset, or of cash when no asset is given, from the event stream
+    fn ledger_movements(&self, wallet_id: &str, asset_id: Option<&str>) -> Result<Vec<LedgerMovement>, String> {
+        let mut movements = Vec::new();
+        for stored in self.event_store.events_after(0)? {
+            let amount = match (&stored.event, asset_id) {
+                (DomainEvent::AssetMinted { wallet_id: w, asset_id: a, quantity }, Some(asset)) if w == wallet_id && a == asset => *quantity,
+                (DomainEvent::AssetBurned { wallet_id: w, asset_id: a, quantity }, Some(asset)) if w == wallet_id && a == asset => -quantity,
+                (DomainEvent::AssetTransferred { from_wallet_id, to_wallet_id, asset_id: a, quantity }, Some(asset)) if a == asset => {
+                    // A transfer to oneself nets out
+                    match (from_wallet_id == wallet_id, to_wallet_id == wallet_id) {
+                        (true, false) => -quantity,
+                        (false, true) => *quantity,
+                        _ => continue,
+                    }
+                }
+                (DomainEvent::WalletBalanceChanged { wallet_id: w, amount }, None) if w == wallet_id => *amount,
+                _ => continue,
+            };
+            movements.push(LedgerMovement {
+                reference: format!("event-{}", stored.sequence),
+                sequence: stored.sequence,
+                timestamp: stored.timestamp,
+                date: Date::from_timestamp(stored.timestamp),
+                amount,
+            });
+        }
+        Ok(movements)
+    }
+
+    /// Reconcile a statement against a wallet's movements of an asset, or of cash when no asset is given
+    pub fn reconcile_statement(
+        &self,
+        wallet_id: &str,
+        asset_id: Option<&str>,
+        statement: Statement,
+        tolerance: ToleranceRules,
+    ) -> Result<Reconciliation, String> {
+        authorize_current(Permission::ManageCustody, None)?;
+        if tolerance.amount < 0.0 || tolerance.days < 0 {
+            return Err("Tolerances must not be negative".to_string());
+        }
+        {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            if !wallets.contains_key(wallet_id) {
+                return Err("Wallet not found".to_string());
+            }
+        }
+        let first = statement.from.or_else(|| statement.lines.iter().map(|l| l.date).min());
+        let last = statement.to.or_else(|| statement.lines.iter().map(|l| l.date).max());
+        let movements: Vec<LedgerMovement> = match (first, last) {
+            (Some(first), Some(last)) => {
+                let (first, last) = (first.add_days(-tolerance.days), last.add_days(tolerance.days));
+                self.ledger_movements(wallet_id, asset_id)?
+                    .into_iter()
+                    .filter(|m| m.date >= first && m.date <= last)
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let items = match_lines(&statement.lines, &movements, &tolerance);
+        let mut summary = ReconciliationSummary::default();
+        for item in &items {
+            match item.status {
+                MatchStatus::Matched => summary.matched += 1,
+                MatchStatus::PartiallyMatched => summary.partially_matched += 1,
+                MatchStatus::UnmatchedStatement => summary.unmatched_statement += 1,
+                MatchStatus::UnmatchedLedger => summary.unmatched_ledger += 1,
+            }
+        }
+        let mut reconciliations = self.reconciliations.write().map_err(|_| "Failed to acquire write lock")?;
+        let reconciliation = Reconciliation {
+            id: format!("recon-{}", reconciliations.len() + 1),
+            wallet_id: wallet_id.to_string(),
+            asset_id: asset_id.map(str::to_string),
+            format: statement.format,
+            account: statement.account,
+            from: statement.from,
+            to: statement.to,
+            tolerance,
+            run_at: self.now(),
+            run_by: current_actor(),
+            summary,
+            items,
+        };
+        reconciliations.insert(reconciliation.id.clone(), reconciliation.clone());
+        drop(reconciliations);
+        self.record_audit(
+            AuditAction::Create,
+            EntityType::Reconciliation,
+            &reconciliation.id,
+            None::<&ReconciliationSummary>,
+            Some(&reconciliation.summary),
+        )?;
+        Ok(reconciliation)
+    }
+
+    /// A reconciliation report
+    pub fn get_reconciliation(&self, reconciliation_id: &str) -> Result<Option<Reconciliation>, String> {
+        authorize_current(Permission::ReadReports, None)?;
+        let reconciliations = self.reconciliations.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(reconciliations.get(reconciliation_id).cloned())
+    }
+
+    /// Reconciliation reports, optionally only those of one wallet, oldest first
+    pub fn get_reconciliations(&self, wallet_id: Option<&str>) -> Result<Vec<Reconciliation>, String> {
+        authorize_current(Permission::ReadReports, None)?;
+        let reconciliations = self.reconciliations.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut found: Vec<Reconciliation> = reconciliations
+            .values()
+            .filter(|r| wallet_id.is_none_or(|id| r.wallet_id == id))
+            .cloned()
+            .collect();
+        // IDs are numbered in run order
+        found.sort_by_key(|r| r.id.trim_start_matches("recon-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    /// Midnight UTC on 2024-03-01
+    const MARCH_1: u64 = 1_709_251_200;
+    const DAY: u64 = 86_400;
+
+    /// Gold minted to w1 on 1 March (event 4), moved to w2 on 2 March (event 5) and
+    /// minted to w1 again on 5 March (event 6)
+    fn setup() -> TokenizationCore {
+        let clock = ManualClock::new(MARCH_1);
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("gold", "issuer", AssetType::Commodity)
+        })
+        .unwrap();
+        for id in ["w1", "w2"] {
+            core.create_wallet(wallet(id, "alice", WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("w1", "gold", 100.0).unwrap();
+        clock.advance(DAY);
+        core.transfer_asset("w1", "w2", "gold", 30.0).unwrap();
+        clock.advance(3 * DAY);
+        core.mint_to_wallet("w1", "gold", 5.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_csv_statement_matches_within_tolerance() {
+        let core = setup();
+        let movements = core.ledger_movements("w1", Some("gold")).unwrap();
+        let references: Vec<&str> = movements.iter().map(|m| m.reference.as_str()).collect();
+        assert_eq!(references, ["event-4", "event-5", "event-6"]);
+
+        let csv = "date,amount,reference,description\n\
+                   2024-03-01,100,EVENT-4,Deposit\n\
+                   2024-03-03,-29.5,event-5,Withdrawal\n\
+                   2024-03-04,12,,Unknown credit\n";
+        let statement = parse_statement(StatementFormat::Csv, csv).unwrap();
+        let reconciliation = core.reconcile_statement("w1", Some("gold"), statement, ToleranceRules::default()).unwrap();
+        assert_eq!(
+            reconciliation.summary,
+            ReconciliationSummary {
+                matched: 1,
+                partially_matched: 1,
+                unmatched_statement: 1,
+                unmatched_ledger: 1,
+            }
+        );
+        assert_eq!(reconciliation.items[1].breaks, vec![Break::Amount { difference: 0.5 }]);
+        assert_eq!(reconciliation.items[3].movement.as_ref().unwrap().reference, "event-6");
+
+        let export = reconciliation_csv(&reconciliation).unwrap();
+        let rows: Vec<&str> = export.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[2], "PartiallyMatched,2,2024-03-03,-29.5,event-5,event-5,2024-03-02,-30,amount differs by 0.5");
+
+        let auditor = Subject::new("auditor", &[Role::Auditor]);
+        assert_eq!(core.as_subject(&auditor, || core.get_reconciliations(Some("w1"))).unwrap().len(), 1);
+        let statement = parse_statement(StatementFormat::Csv, csv).unwrap();
+        assert!(core.as_subject(&auditor, || core.reconcile_statement("w1", None, statement, ToleranceRules::default())).is_err());
+    }
+
+    #[test]
+    fn test_camt053_statement_breaks() {
+        let core = setup();
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Id>STMT-1</Id>
+      <Acct><Id><IBAN>CH9300762011623852957</IBAN></Id></Acct>
+      <FrToDt><FrDtTm>2024-03-01T00:00:00</FrDtTm><ToDtTm>2024-03-02T23:59:59</ToDtTm></FrToDt>
+      <Ntry>
+        <Amt Ccy="XAU">100.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2024-03-01</Dt></BookgDt>
+        <NtryDtls><TxDtls><Refs><EndToEndId>custodian-77</EndToEndId></Refs></TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="XAU">30.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2024-03-08</Dt></BookgDt>
+        <NtryRef>event-5</NtryRef>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="XAU">1.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts>PDNG</Sts>
+        <BookgDt><Dt>2024-03-02</Dt></BookgDt>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+        let statement = parse_statement(StatementFormat::Camt053, xml).unwrap();
+        assert_eq!(statement.account.as_deref(), Some("CH9300762011623852957"));
+        assert_eq!(statement.lines.len(), 2);
+        assert_eq!(statement.lines[1].amount, -30.0);
+        assert_eq!(statement.lines[0].currency.as_deref(), Some("XAU"));
+
+        let reconciliation = core.reconcile_statement("w1", Some("gold"), statement, ToleranceRules::default()).unwrap();
+        let breaks: Vec<&Vec<Break>> = reconciliation.items.iter().map(|i| &i.breaks).collect();
+        assert_eq!(
+            breaks,
+            [
+                &vec![Break::Reference {
+                    statement: "custodian-77".to_string(),
+                    ledger: "event-4".to_string(),
+                }],
+                &vec![Break::Date { days: 6 }],
+            ]
+        );
+        // The 5 March mint falls outside the statement period
+        assert_eq!(reconciliation.summary.unmatched_ledger, 0);
+
+        assert!(resolve_statement_path(Path::new("statements"), "../secrets.csv").is_err());
+        assert!(resolve_statement_path(Path::new("statements"), "/etc/passwd").is_err());
+        assert_eq!(
+            resolve_statement_path(Path::new("statements"), "bank/march.xml").unwrap(),
+            Path::new("statements/bank/march.xml")
+        );
+    }
+}
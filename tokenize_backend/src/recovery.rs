@@ -0,0 +1,628 @@
+//! Guardian-based recovery and inheritance of wallets
+//!
+//! A wallet's owner can name guardians, a threshold of them and a delay. When the
+//! owner loses access, a guardian initiates a recovery that either hands the wallet
+//! to a new owner or replaces its keys with a new one. Once enough guardians have
+//! approved, the recovery waits out the delay before it can run, and until then
+//! the current owner can veto it.
+//!
+//! The owner can also name a beneficiary who may claim the wallet after a period
+//! of inactivity, a dead-man switch. The owner shows activity by checking in or
+//! by touching the wallet's guardians or recoveries. A claim is a recovery to the
+//! beneficiary that needs no guardian approvals but waits out the same delay and
+//! can be vetoed the same way.
+//!
+//! A recovery that changes the owner revokes the wallet's keys and removes its
+//! threshold signers, cancelling their pending proposals, since they belong to the
+//! previous owner. All timings come from the core's clock.
+
+use crate::access_control::{without_subject, Permission, PERMISSION_DENIED};
+use crate::audit::{current_actor, AuditAction, EntityType, SYSTEM_ACTOR};
+use crate::core_component::TokenizationCore;
+use crate::events::DomainEvent;
+use crate::signatures::{check_public_key, KeyAlgorithm};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Seconds between runs of recoveries whose delay has passed
+pub const RECOVERY_INTERVAL_SECS: u64 = 60;
+
+/// Shortest delay an owner may choose, so that a veto is always possible
+pub const MIN_RECOVERY_DELAY_SECS: u64 = 86_400;
+
+/// Who may claim a wallet after its owner goes quiet, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InheritancePlan {
+    pub beneficiary: String,
+    /// Seconds without owner activity after which the beneficiary may claim
+    pub inactivity_secs: u64,
+}
+
+/// Guardians and inheritance terms chosen by a wallet's owner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardianConfig {
+    pub guardians: Vec<String>,
+    pub threshold: usize,
+    /// Seconds between a recovery being approved and it running, during which the owner can veto
+    pub delay_secs: u64,
+    #[serde(default)]
+    pub inheritance: Option<InheritancePlan>,
+}
+
+/// A wallet's recovery arrangements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardianSet {
+    pub wallet_id: String,
+    pub guardians: Vec<String>,
+    pub threshold: usize,
+    pub delay_secs: u64,
+    pub inheritance: Option<InheritancePlan>,
+    /// Last time the owner showed they still control the wallet
+    pub owner_active_at: u64,
+}
+
+/// What a recovery does to the wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecoveryAction {
+    /// Hand the wallet to a new owner
+    ChangeOwner { new_owner: String },
+    /// Revoke the wallet's keys and register a new one
+    RotateKey { algorithm: KeyAlgorithm, public_key: String },
+}
+
+/// Where a recovery stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecoveryStatus {
+    Pending,
+    /// Claimed by a caller and being applied
+    Executing,
+    Executed,
+    Vetoed,
+    Failed { error: String },
+}
+
+/// A guardian's approval of a recovery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuardianApproval {
+    pub guardian: String,
+    pub approved_at: u64,
+}
+
+/// A recovery or inheritance claim in progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryRequest {
+    pub id: String,
+    pub wallet_id: String,
+    pub action: RecoveryAction,
+    /// Claimed by the beneficiary after inactivity rather than initiated by a guardian
+    pub inheritance: bool,
+    pub initiated_by: String,
+    pub initiated_at: u64,
+    pub approvals: Vec<GuardianApproval>,
+    /// When the recovery may run; set once it has enough approvals
+    pub executable_at: Option<u64>,
+    pub status: RecoveryStatus,
+    pub closed_at: Option<u64>,
+}
+
+impl GuardianConfig {
+    fn validate(&self, owner: &str) -> Result<(), String> {
+        if self.guardians.is_empty() {
+            return Err("At least one guardian is required".to_string());
+        }
+        if self.threshold == 0 || self.threshold > self.guardians.len() {
+            return Err(format!("Threshold must be between 1 and {}, the number of guardians", self.guardians.len()));
+        }
+        if self.delay_secs < MIN_RECOVERY_DELAY_SECS {
+            return Err(format!("Delay must be at least {} seconds", MIN_RECOVERY_DELAY_SECS));
+        }
+        let mut seen = HashSet::new();
+        for guardian in &self.guardians {
+            if guardian.trim().is_empty() {
+                return Err("Guardian ID must not be empty".to_string());
+            }
+            if guardian == owner {
+                return Err("The owner cannot be their own guardian".to_string());
+            }
+            if !seen.insert(guardian.as_str()) {
+                return Err(format!("{} is listed as a guardian more than once", guardian));
+            }
+        }
+        if let Some(plan) = &self.inheritance {
+            if plan.beneficiary.trim().is_empty() || plan.beneficiary == owner {
+                return Err("Beneficiary must be someone other than the owner".to_string());
+            }
+            if plan.inactivity_secs == 0 {
+                return Err("Inactivity period must be positive".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TokenizationCore {
+    fn guardian_set(&self, wallet_id: &str) -> Result<GuardianSet, String> {
+        let guardians = self.wallet_guardians.read().map_err(|_| "Failed to acquire read lock")?;
+        guardians.get(wallet_id).cloned().ok_or_else(|| "Wallet has no guardians".to_string())
+    }
+
+    fn wallet_owner(&self, wallet_id: &str) -> Result<String, String> {
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        wallets.get(wallet_id).map(|w| w.owner.clone()).ok_or_else(|| "Wallet not found".to_string())
+    }
+
+    /// Refuse unless the caller owns the wallet, returning the owner
+    fn ensure_wallet_owner(&self, wallet_id: &str) -> Result<String, String> {
+        let owner = self.wallet_owner(wallet_id)?;
+        let actor = current_actor();
+        if actor != owner {
+            return Err(format!("{}: only the owner of wallet {} may do this", PERMISSION_DENIED, wallet_id));
+        }
+        Ok(owner)
+    }
+
+    /// Note that the owner of a wallet with guardians has shown activity
+    fn touch_owner_activity(&self, wallet_id: &str) -> Result<(), String> {
+        let mut guardians = self.wallet_guardians.write().map_err(|_| "Failed to acquire write lock")?;
+        if let Some(set) = guardians.get_mut(wallet_id) {
+            set.owner_active_at = self.now();
+        }
+        Ok(())
+    }
+
+    /// Name a wallet's guardians, replacing any earlier ones
+    pub fn set_guardians(&self, wallet_id: &str, config: GuardianConfig) -> Result<GuardianSet, String> {
+        self.authorize_wallet(Permission::UpdateWallet, wallet_id)?;
+        let owner = self.ensure_wallet_owner(wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[])?;
+        config.validate(&owner)?;
+        let set = GuardianSet {
+            wallet_id: wallet_id.to_string(),
+            guardians: config.guardians,
+            threshold: config.threshold,
+            delay_secs: config.delay_secs,
+            inheritance: config.inheritance,
+            owner_active_at: self.now(),
+        };
+        let mut guardians = self.wallet_guardians.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = guardians.insert(wallet_id.to_string(), set.clone());
+        drop(guardians);
+        let action = if before.is_some() { AuditAction::Update } else { AuditAction::Create };
+        self.record_audit(action, EntityType::Guardians, wallet_id, before.as_ref(), Some(&set))?;
+        Ok(set)
+    }
+
+    /// A wallet's guardians; visible to them, the beneficiary and whoever may read the wallet
+    pub fn get_guardians(&self, wallet_id: &str) -> Result<Option<GuardianSet>, String> {
+        let set = {
+            let guardians = self.wallet_guardians.read().map_err(|_| "Failed to acquire read lock")?;
+            guardians.get(wallet_id).cloned()
+        };
+        let actor = current_actor();
+        let involved = set.as_ref().is_some_and(|s| {
+            s.guardians.contains(&actor) || s.inheritance.as_ref().is_some_and(|p| p.beneficiary == actor)
+        });
+        if !involved {
+            self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        }
+        Ok(set)
+    }
+
+    /// Record that the owner still controls the wallet, restarting the inactivity period
+    pub fn check_in(&self, wallet_id: &str) -> Result<GuardianSet, String> {
+        self.ensure_wallet_owner(wallet_id)?;
+        let before = self.guardian_set(wallet_id)?;
+        self.touch_owner_activity(wallet_id)?;
+        let after = self.guardian_set(wallet_id)?;
+        self.record_audit(AuditAction::Update, EntityType::Guardians, wallet_id, Some(&before), Some(&after))?;
+        Ok(after)
+    }
+
+    fn insert_recovery(&self, wallet_id: &str, build: impl FnOnce(String) -> RecoveryRequest) -> Result<RecoveryRequest, String> {
+        let mut recoveries = self.recoveries.write().map_err(|_| "Failed to acquire write lock")?;
+        if recoveries.values().any(|r| r.wallet_id == wallet_id && r.status == RecoveryStatus::Pending) {
+            return Err("Wallet already has a recovery in progress".to_string());
+        }
+        let recovery = build(format!("recovery-{}", recoveries.len() + 1));
+        recoveries.insert(recovery.id.clone(), recovery.clone());
+        drop(recoveries);
+        self.record_audit(AuditAction::Create, EntityType::Recovery, &recovery.id, None::<&RecoveryRequest>, Some(&recovery))?;
+        Ok(recovery)
+    }
+
+    /// Start recovering a wallet as one of its guardians, counting as their approval
+    pub fn initiate_recovery(&self, wallet_id: &str, action: RecoveryAction) -> Result<RecoveryRequest, String> {
+        let set = self.guardian_set(wallet_id)?;
+        let actor = current_actor();
+        if !set.guardians.contains(&actor) {
+            return Err(format!("{}: {} is not a guardian of wallet {}", PERMISSION_DENIED, actor, wallet_id));
+        }
+        match &action {
+            RecoveryAction::ChangeOwner { new_owner } => {
+                if new_owner.trim().is_empty() || *new_owner == self.wallet_owner(wallet_id)? {
+                    return Err("New owner must be someone other than the current owner".to_string());
+                }
+            }
+            RecoveryAction::RotateKey { algorithm, public_key } => check_public_key(*algorithm, public_key)?,
+        }
+        let now = self.now();
+        self.insert_recovery(wallet_id, |id| RecoveryRequest {
+            id,
+            wallet_id: wallet_id.to_string(),
+            action,
+            inheritance: false,
+            initiated_by: actor.clone(),
+            initiated_at: now,
+            approvals: vec![GuardianApproval {
+                guardian: actor,
+                approved_at: now,
+            }],
+            executable_at: (set.threshold <= 1).then_some(now + set.delay_secs),
+            status: RecoveryStatus::Pending,
+            closed_at: None,
+        })
+    }
+
+    /// Approve a recovery as a guardian; the delay starts once the threshold is met
+    pub fn approve_recovery(&self, recovery_id: &str) -> Result<RecoveryRequest, String> {
+        let now = self.now();
+        let wallet_id = self.get_recovery_unchecked(recovery_id)?.wallet_id;
+        let set = self.guardian_set(&wallet_id)?;
+        let actor = current_actor();
+        if !set.guardians.contains(&actor) {
+            return Err(format!("{}: {} is not a guardian of wallet {}", PERMISSION_DENIED, actor, wallet_id));
+        }
+        let mut recoveries = self.recoveries.write().map_err(|_| "Failed to acquire write lock")?;
+        let recovery = recoveries.get_mut(recovery_id).ok_or("Recovery not found")?;
+        if recovery.status != RecoveryStatus::Pending {
+            return Err(format!("Recovery is already {:?}", recovery.status));
+        }
+        if recovery.inheritance {
+            return Err("Inheritance claims need no guardian approval".to_string());
+        }
+        if recovery.approvals.iter().any(|a| a.guardian == actor) {
+            return Err(format!("{} has already approved this recovery", actor));
+        }
+        let before = recovery.clone();
+        recovery.approvals.push(GuardianApproval {
+            guardian: actor,
+            approved_at: now,
+        });
+        let approved = recovery.approvals.iter().filter(|a| set.guardians.contains(&a.guardian)).count();
+        if approved >= set.threshold && recovery.executable_at.is_none() {
+            recovery.executable_at = Some(now + set.delay_secs);
+        }
+        let recovery = recovery.clone();
+        drop(recoveries);
+        self.record_audit(AuditAction::Approve, EntityType::Recovery, recovery_id, Some(&before), Some(&recovery))?;
+        Ok(recovery)
+    }
+
+    /// Stop a pending recovery or inheritance claim as the wallet's current owner
+    pub fn veto_recovery(&self, recovery_id: &str) -> Result<RecoveryRequest, String> {
+        let wallet_id = self.get_recovery_unchecked(recovery_id)?.wallet_id;
+        self.ensure_wallet_owner(&wallet_id)?;
+        let mut recoveries = self.recoveries.write().map_err(|_| "Failed to acquire write lock")?;
+        let recovery = recoveries.get_mut(recovery_id).ok_or("Recovery not found")?;
+        if recovery.status != RecoveryStatus::Pending {
+            return Err(format!("Recovery is already {:?}", recovery.status));
+        }
+        let before = recovery.clone();
+        recovery.status = RecoveryStatus::Vetoed;
+        recovery.closed_at = Some(self.now());
+        let recovery = recovery.clone();
+        drop(recoveries);
+        self.record_audit(AuditAction::Reject, EntityType::Recovery, recovery_id, Some(&before), Some(&recovery))?;
+        self.touch_owner_activity(&wallet_id)?;
+        Ok(recovery)
+    }
+
+    /// Claim a wallet as its beneficiary once the owner has been inactive long enough
+    pub fn claim_inheritance(&self, wallet_id: &str) -> Result<RecoveryRequest, String> {
+        let set = self.guardian_set(wallet_id)?;
+        let actor = current_actor();
+        let plan = set
+            .inheritance
+            .as_ref()
+            .filter(|p| p.beneficiary == actor)
+            .ok_or_else(|| format!("{}: {} is not the beneficiary of wallet {}", PERMISSION_DENIED, actor, wallet_id))?;
+        let now = self.now();
+        let claimable_at = set.owner_active_at + plan.inactivity_secs;
+        if now < claimable_at {
+            return Err(format!("Owner was last active at {}; the wallet can be claimed from {}", set.owner_active_at, claimable_at));
+        }
+        self.insert_recovery(wallet_id, |id| RecoveryRequest {
+            id,
+            wallet_id: wallet_id.to_string(),
+            action: RecoveryAction::ChangeOwner {
+                new_owner: actor.clone(),
+            },
+            inheritance: true,
+            initiated_by: actor,
+            initiated_at: now,
+            approvals: Vec::new(),
+            executable_at: Some(now + set.delay_secs),
+            status: RecoveryStatus::Pending,
+            closed_at: None,
+        })
+    }
+
+    /// Run a recovery whose delay has passed
+    pub fn execute_recovery(&self, recovery_id: &str) -> Result<RecoveryRequest, String> {
+        let now = self.now();
+        // Claim the recovery so that a concurrent run cannot apply it twice
+        let (before, recovery) = {
+            let mut recoveries = self.recoveries.write().map_err(|_| "Failed to acquire write lock")?;
+            let recovery = recoveries.get_mut(recovery_id).ok_or("Recovery not found")?;
+            if recovery.status != RecoveryStatus::Pending {
+                return Err(format!("Recovery is already {:?}", recovery.status));
+            }
+            match recovery.executable_at {
+                None => return Err("Recovery does not have enough guardian approvals".to_string()),
+                Some(at) if now < at => return Err(format!("Recovery cannot run until {}", at)),
+                Some(_) => {}
+            }
+            let before = recovery.clone();
+            recovery.status = RecoveryStatus::Executing;
+            (before, recovery.clone())
+        };
+        // Guardians may have changed since the threshold was met
+        let set = self.guardian_set(&recovery.wallet_id)?;
+        let approved = recovery.approvals.iter().filter(|a| set.guardians.contains(&a.guardian)).count();
+        let outcome = if !recovery.inheritance && approved < set.threshold {
+            Err("Recovery no longer has enough guardian approvals".to_string())
+        } else {
+            // The guardians' approval stands in for the owner's: run outside the caller's permissions
+            without_subject(|| self.with_actor(&recovery.initiated_by, || self.apply_recovery(&recovery)))
+        };
+        let mut recoveries = self.recoveries.write().map_err(|_| "Failed to acquire write lock")?;
+        let stored = recoveries.get_mut(recovery_id).ok_or("Recovery not found")?;
+        stored.status = match outcome {
+            Ok(()) => RecoveryStatus::Executed,
+            Err(error) => RecoveryStatus::Failed { error },
+        };
+        stored.closed_at = Some(now);
+        let recovery = stored.clone();
+        drop(recoveries);
+        self.record_audit(AuditAction::Process, EntityType::Recovery, recovery_id, Some(&before), Some(&recovery))?;
+        Ok(recovery)
+    }
+
+    fn apply_recovery(&self, recovery: &RecoveryRequest) -> Result<(), String> {
+        let now = self.now();
+        match &recovery.action {
+            RecoveryAction::ChangeOwner { new_owner } => {
+                {
+                    let mut wallets = self.wallets.write().map_err(|_| "Failed to acquire write lock")?;
+                    let wallet = wallets.get_mut(&recovery.wallet_id).ok_or("Wallet not found")?;
+                    self.ensure_not_frozen(&[wallet], &[])?;
+                    let before = wallet.clone();
+                    wallet.owner = new_owner.clone();
+                    wallet.updated_at = now;
+                    self.record_audit(AuditAction::Update, EntityType::Wallet, &wallet.id, Some(&before), Some(&*wallet))?;
+                    self.emit_event(DomainEvent::WalletOwnerChanged {
+                        wallet_id: wallet.id.clone(),
+                        new_owner: new_owner.clone(),
+                    })?;
+                }
+                self.revoke_all_wallet_keys(&recovery.wallet_id)?;
+                self.clear_wallet_signers(&recovery.wallet_id)?;
+                // The new owner cannot guard against themselves or inherit from themselves
+                let mut guardians = self.wallet_guardians.write().map_err(|_| "Failed to acquire write lock")?;
+                if let Some(set) = guardians.get_mut(&recovery.wallet_id) {
+                    let before = set.clone();
+                    set.guardians.retain(|g| g != new_owner);
+                    set.threshold = set.threshold.min(set.guardians.len());
+                    if set.inheritance.as_ref().is_some_and(|p| p.beneficiary == *new_owner) {
+                        set.inheritance = None;
+                    }
+                    set.owner_active_at = now;
+                    let after = set.clone();
+                    if after.guardians.is_empty() {
+                        guardians.remove(&recovery.wallet_id);
+                    }
+                    drop(guardians);
+                    let after = Some(&after).filter(|a| !a.guardians.is_empty());
+                    self.record_audit(AuditAction::Update, EntityType::Guardians, &recovery.wallet_id, Some(&before), after)?;
+                }
+                Ok(())
+            }
+            RecoveryAction::RotateKey { algorithm, public_key } => {
+                {
+                    let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+                    let wallet = wallets.get(&recovery.wallet_id).ok_or("Wallet not found")?;
+                    self.ensure_not_frozen(&[wallet], &[])?;
+                }
+                self.revoke_all_wallet_keys(&recovery.wallet_id)?;
+                self.register_wallet_key(&recovery.wallet_id, *algorithm, public_key)?;
+                self.touch_owner_activity(&recovery.wallet_id)
+            }
+        }
+    }
+
+    fn revoke_all_wallet_keys(&self, wallet_id: &str) -> Result<(), String> {
+        for key in self.get_wallet_keys(wallet_id)?.keys.into_iter().filter(|k| k.revoked_at.is_none()) {
+            self.revoke_wallet_key(wallet_id, &key.id)?;
+        }
+        Ok(())
+    }
+
+    /// Run every approved recovery whose delay has passed, as the system
+    pub fn execute_due_recoveries(&self) -> Result<Vec<RecoveryRequest>, String> {
+        let now = self.now();
+        let mut due: Vec<String> = {
+            let recoveries = self.recoveries.read().map_err(|_| "Failed to acquire read lock")?;
+            recoveries
+                .values()
+                .filter(|r| r.status == RecoveryStatus::Pending && r.executable_at.is_some_and(|at| at <= now))
+                .map(|r| r.id.clone())
+                .collect()
+        };
+        due.sort();
+        self.with_actor(SYSTEM_ACTOR, || due.iter().map(|id| self.execute_recovery(id)).collect())
+    }
+
+    fn get_recovery_unchecked(&self, recovery_id: &str) -> Result<RecoveryRequest, String> {
+        let recoveries = self.recoveries.read().map_err(|_| "Failed to acquire read lock")?;
+        recoveries.get(recovery_id).cloned().ok_or_else(|| "Recovery not found".to_string())
+    }
+
+    /// A recovery, if the caller is involved in it or may read its wallet
+    pub fn get_recovery(&self, recovery_id: &str) -> Result<Option<RecoveryRequest>, String> {
+        let recovery = match self.get_recovery_unchecked(recovery_id) {
+            Ok(recovery) => recovery,
+            Err(_) => return Ok(None),
+        };
+        if recovery.initiated_by != current_actor() {
+            self.get_guardians(&recovery.wallet_id)?;
+        }
+        Ok(Some(recovery))
+    }
+
+    /// Recoveries the caller is involved in or may read the wallet of, optionally
+    /// only those of one wallet, oldest first
+    pub fn get_recoveries(&self, wallet_id: Option<&str>) -> Result<Vec<RecoveryRequest>, String> {
+        let mut found: Vec<RecoveryRequest> = {
+            let recoveries = self.recoveries.read().map_err(|_| "Failed to acquire read lock")?;
+            recoveries
+                .values()
+                .filter(|r| wallet_id.is_none_or(|id| r.wallet_id == id))
+                .cloned()
+                .collect()
+        };
+        found.retain(|r| self.get_guardians(&r.wallet_id).is_ok() || r.initiated_by == current_actor());
+        // IDs are numbered in initiation order
+        found.sort_by_key(|r| r.id.trim_start_matches("recovery-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::WalletType;
+    use crate::custody::SigningStatus;
+    use crate::multisig::{MultisigOperation, WalletSigner};
+    use crate::test_support::wallet;
+    use std::sync::Arc;
+
+    const DAY: u64 = 86_400;
+
+    fn setup() -> (TokenizationCore, ManualClock) {
+        let clock = ManualClock::new(1_000);
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_wallet(wallet("w1", "alice", WalletType::NonCustodial)).unwrap();
+        core.as_subject(&person("alice"), || core.set_guardians("w1", guardian_config(2 * DAY))).unwrap();
+        (core, clock)
+    }
+
+    fn guardian_config(delay_secs: u64) -> GuardianConfig {
+        GuardianConfig {
+            guardians: vec!["g1".to_string(), "g2".to_string(), "g3".to_string()],
+            threshold: 2,
+            delay_secs,
+            inheritance: Some(InheritancePlan {
+                beneficiary: "heir".to_string(),
+                inactivity_secs: 365 * DAY,
+            }),
+        }
+    }
+
+    fn signer(id: &str) -> WalletSigner {
+        WalletSigner {
+            id: id.to_string(),
+            key_id: None,
+        }
+    }
+
+    fn person(id: &str) -> Subject {
+        Subject::new(id, &[Role::Investor])
+    }
+
+    fn change_owner(new_owner: &str) -> RecoveryAction {
+        RecoveryAction::ChangeOwner {
+            new_owner: new_owner.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_guardians_recover_a_wallet_after_the_delay() {
+        let (core, clock) = setup();
+        let public_key = hex::encode(ed25519_dalek::SigningKey::from_bytes(&[1; 32]).verifying_key().to_bytes());
+        core.register_wallet_key("w1", KeyAlgorithm::Ed25519, &public_key).unwrap();
+        let signers = vec![signer("alice"), signer("s2")];
+        core.as_subject(&person("alice"), || core.set_wallet_signers("w1", signers, 2)).unwrap();
+        let reconfigure = MultisigOperation::SetThreshold { threshold: 1 };
+        let pending = core.as_subject(&person("alice"), || core.propose_wallet_operation("w1", reconfigure)).unwrap();
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        assert!(core.as_subject(&agent, || core.set_guardians("w1", guardian_config(2 * DAY))).is_err());
+        assert!(core.as_subject(&person("alice"), || core.set_guardians("w1", guardian_config(0))).is_err());
+        assert!(core.as_subject(&person("mallory"), || core.initiate_recovery("w1", change_owner("mallory"))).is_err());
+
+        let recovery = core.as_subject(&person("g1"), || core.initiate_recovery("w1", change_owner("alice2"))).unwrap();
+        assert_eq!(recovery.executable_at, None);
+        assert!(core.as_subject(&person("g2"), || core.initiate_recovery("w1", change_owner("g2"))).is_err());
+        let recovery = core.as_subject(&person("g2"), || core.approve_recovery(&recovery.id)).unwrap();
+        assert_eq!(recovery.executable_at, Some(1_000 + 2 * DAY));
+
+        clock.advance(DAY);
+        assert_eq!(core.execute_recovery(&recovery.id).unwrap_err(), format!("Recovery cannot run until {}", 1_000 + 2 * DAY));
+        assert!(core.execute_due_recoveries().unwrap().is_empty());
+        clock.advance(DAY);
+        let executed = core.execute_due_recoveries().unwrap();
+        assert_eq!(executed[0].status, RecoveryStatus::Executed);
+
+        let wallet = core.get_wallet("w1").unwrap().unwrap();
+        assert_eq!(wallet.owner, "alice2");
+        assert_eq!(core.execute_recovery(&recovery.id).unwrap_err(), "Recovery is already Executed");
+        // Replay sees the same owner
+        assert_eq!(core.state_as_of(u64::MAX).unwrap().wallets["w1"], wallet);
+        assert!(core.get_wallet_keys("w1").unwrap().keys.iter().all(|k| k.revoked_at.is_some()));
+        // Nor do its signers
+        assert_eq!(core.get_wallet_signers("w1").unwrap(), None);
+        assert_eq!(core.get_proposal(&pending.id).unwrap().unwrap().status, SigningStatus::Cancelled);
+        // The previous owner no longer controls the wallet
+        assert!(core.as_subject(&person("alice"), || core.check_in("w1")).is_err());
+        core.as_subject(&person("alice2"), || core.check_in("w1")).unwrap();
+    }
+
+    #[test]
+    fn test_owner_vetoes_recovery_and_inheritance() {
+        let (core, clock) = setup();
+        let recovery = core.as_subject(&person("g1"), || core.initiate_recovery("w1", change_owner("eve"))).unwrap();
+        core.as_subject(&person("g3"), || core.approve_recovery(&recovery.id)).unwrap();
+        assert!(core.as_subject(&person("g1"), || core.veto_recovery(&recovery.id)).is_err());
+        let vetoed = core.as_subject(&person("alice"), || core.veto_recovery(&recovery.id)).unwrap();
+        assert_eq!(vetoed.status, RecoveryStatus::Vetoed);
+        clock.advance(3 * DAY);
+        assert!(core.execute_recovery(&recovery.id).is_err());
+        assert_eq!(core.get_wallet("w1").unwrap().unwrap().owner, "alice");
+
+        // Checking in pushes the inheritance date back
+        clock.advance(300 * DAY);
+        core.as_subject(&person("alice"), || core.check_in("w1")).unwrap();
+        clock.advance(300 * DAY);
+        let error = core.as_subject(&person("heir"), || core.claim_inheritance("w1")).unwrap_err();
+        assert!(error.starts_with("Owner was last active at"), "{}", error);
+        clock.advance(65 * DAY);
+        let claim = core.as_subject(&person("heir"), || core.claim_inheritance("w1")).unwrap();
+        assert!(claim.inheritance);
+
+        // An owner who is still around can stop the claim during the delay
+        core.as_subject(&person("alice"), || core.veto_recovery(&claim.id)).unwrap();
+        let claim = core.as_subject(&person("heir"), || core.claim_inheritance("w1"));
+        assert!(claim.is_err());
+
+        // Left unanswered, the claim hands the wallet over
+        clock.advance(365 * DAY);
+        let claim = core.as_subject(&person("heir"), || core.claim_inheritance("w1")).unwrap();
+        clock.advance(2 * DAY);
+        let claim = core.as_subject(&person("heir"), || core.execute_recovery(&claim.id)).unwrap();
+        assert_eq!(claim.status, RecoveryStatus::Executed);
+        assert_eq!(core.get_wallet("w1").unwrap().unwrap().owner, "heir");
+        assert_eq!(core.get_guardians("w1").unwrap().unwrap().inheritance, None);
+    }
+}
@@ -0,0 +1,388 @@
+//! Proof of liabilities for custodial wallets
+//!
+//! A reserve report commits to what the platform owes the holders of custodial
+//! wallets. For each asset it builds a Merkle sum tree whose leaves are the
+//! custodial wallets holding it: every node carries a hash and the sum of the
+//! quantities below it, so the published root commits to the total as well as to
+//! each balance. A holder receives an inclusion proof per asset, the sibling hashes
+//! and sums on the way to the root, and checks with `verify_inclusion_proof` that
+//! their balance is counted in the published total. `tokenize_wasm` carries the
+//! same check for the browser.
+//!
+//! Each leaf is salted so that siblings in a proof reveal sums but not who holds
+//! them. Quantities are committed as integer base units of `LIABILITY_SCALE` per
+//! unit, so that a proof checks exactly wherever it is verified. Reserves held
+//! against the liabilities can be attested when a report is generated and are
+//! reported beside them.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, WalletType};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+/// Base units committed per unit of an asset
+pub const LIABILITY_SCALE: f64 = 100_000_000.0;
+
+const LEAF_DOMAIN: &[u8] = b"tokenize-reserves-leaf:v1";
+const NODE_DOMAIN: &[u8] = b"tokenize-reserves-node:v1";
+
+/// Liabilities in one asset and the reserves held against them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetLiabilities {
+    pub asset_id: String,
+    /// Hex root of the asset's Merkle sum tree; `None` if no custodial wallet holds it
+    pub root: Option<String>,
+    /// Sum of custodial balances in base units, as committed to by the root
+    pub total_units: u64,
+    pub total: f64,
+    pub wallet_count: usize,
+    /// Reserves attested when the report was generated
+    pub reserves: Option<f64>,
+    /// Whether the attested reserves cover the liabilities
+    pub covered: Option<bool>,
+}
+
+/// A published proof of liabilities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReserveReport {
+    pub id: String,
+    pub generated_at: u64,
+    pub assets: Vec<AssetLiabilities>,
+}
+
+/// Which side of the path a sibling sits on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A sibling on the way from a leaf to the root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash: String,
+    pub sum: u64,
+}
+
+/// Evidence that a wallet's balance in an asset is counted in a report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub report_id: String,
+    pub asset_id: String,
+    pub wallet_id: String,
+    pub units: u64,
+    /// Hex salt of the wallet's leaf
+    pub salt: String,
+    pub path: Vec<ProofStep>,
+}
+
+/// A custodial balance committed to by a report
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LiabilityLeaf {
+    wallet_id: String,
+    units: u64,
+    salt: [u8; 16],
+}
+
+/// A report with the leaves its proofs are drawn from
+#[derive(Debug, Clone)]
+pub(crate) struct StoredReserveReport {
+    report: ReserveReport,
+    leaves: HashMap<String, Vec<LiabilityLeaf>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Node {
+    hash: [u8; 32],
+    sum: u64,
+}
+
+/// Convert a quantity to the base units committed to
+pub fn to_units(quantity: f64) -> Result<u64, String> {
+    let units = (quantity * LIABILITY_SCALE).round();
+    if !units.is_finite() || units < 0.0 || units >= u64::MAX as f64 {
+        return Err(format!("Quantity {} cannot be committed to", quantity));
+    }
+    Ok(units as u64)
+}
+
+fn leaf_hash(asset_id: &str, wallet_id: &str, units: u64, salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(LEAF_DOMAIN);
+    hasher.update(salt);
+    for field in [asset_id, wallet_id] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(units.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn parent(left: &Node, right: &Node) -> Result<Node, String> {
+    let sum = left.sum.checked_add(right.sum).ok_or("Liabilities overflow")?;
+    let mut hasher = Sha256::new();
+    hasher.update(NODE_DOMAIN);
+    hasher.update(left.hash);
+    hasher.update(left.sum.to_be_bytes());
+    hasher.update(right.hash);
+    hasher.update(right.sum.to_be_bytes());
+    Ok(Node {
+        hash: hasher.finalize().into(),
+        sum,
+    })
+}
+
+/// Every level of a tree, leaves first; an unpaired last node moves up unchanged
+fn tree_levels(leaves: Vec<Node>) -> Result<Vec<Vec<Node>>, String> {
+    let mut levels = vec![leaves];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let level = levels.last().expect("levels are never empty");
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => parent(left, right),
+                [single] => Ok(*single),
+                _ => unreachable!("chunks of two"),
+            })
+            .collect::<Result<Vec<Node>, String>>()?;
+        levels.push(next);
+    }
+    Ok(levels)
+}
+
+fn leaf_nodes(asset_id: &str, leaves: &[LiabilityLeaf]) -> Vec<Node> {
+    leaves
+        .iter()
+        .map(|leaf| Node {
+            hash: leaf_hash(asset_id, &leaf.wallet_id, leaf.units, &leaf.salt),
+            sum: leaf.units,
+        })
+        .collect()
+}
+
+/// Check that a proof leads to a published root and total
+pub fn verify_inclusion_proof(proof: &InclusionProof, root: &str, total_units: u64) -> Result<(), String> {
+    let salt = hex::decode(&proof.salt).map_err(|_| "Salt is not valid hex")?;
+    let mut node = Node {
+        hash: leaf_hash(&proof.asset_id, &proof.wallet_id, proof.units, &salt),
+        sum: proof.units,
+    };
+    for step in &proof.path {
+        let hash: [u8; 32] = hex::decode(&step.hash)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("Proof hashes are 32 bytes of hex")?;
+        let sibling = Node { hash, sum: step.sum };
+        node = match step.side {
+            Side::Left => parent(&sibling, &node)?,
+            Side::Right => parent(&node, &sibling)?,
+        };
+    }
+    if hex::encode(node.hash) != root.to_lowercase() {
+        return Err("Proof does not lead to the published root".to_string());
+    }
+    if node.sum != total_units {
+        return Err(format!("Proof sums to {} units, not the published {}", node.sum, total_units));
+    }
+    Ok(())
+}
+
+impl TokenizationCore {
+    /// Commit to every custodial balance, reporting attested reserves by asset beside the liabilities
+    pub fn generate_reserve_report(&self, reserves: HashMap<String, f64>) -> Result<ReserveReport, String> {
+        authorize_current(Permission::ManageCustody, None)?;
+        let mut leaves: BTreeMap<String, Vec<LiabilityLeaf>> = BTreeMap::new();
+        {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            let holdings = self.holdings.read().map_err(|_| "Failed to acquire read lock")?;
+            for wallet in wallets.values().filter(|w| w.wallet_type == WalletType::Custodial) {
+                for (asset_id, quantity) in holdings.get(&wallet.id).into_iter().flatten() {
+                    let units = to_units(*quantity)?;
+                    if units == 0 {
+                        continue;
+                    }
+                    let mut salt = [0u8; 16];
+                    rand::rngs::OsRng.fill_bytes(&mut salt);
+                    leaves.entry(asset_id.clone()).or_default().push(LiabilityLeaf {
+                        wallet_id: wallet.id.clone(),
+                        units,
+                        salt,
+                    });
+                }
+            }
+        }
+        for asset_id in reserves.keys() {
+            leaves.entry(asset_id.clone()).or_default();
+        }
+
+        let mut assets = Vec::new();
+        for (asset_id, asset_leaves) in &mut leaves {
+            asset_leaves.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+            let levels = tree_levels(leaf_nodes(asset_id, asset_leaves))?;
+            let root = levels.last().and_then(|level| level.first()).copied();
+            let total_units = root.map_or(0, |node| node.sum);
+            let total = total_units as f64 / LIABILITY_SCALE;
+            let held = reserves.get(asset_id).copied();
+            assets.push(AssetLiabilities {
+                asset_id: asset_id.clone(),
+                root: root.map(|node| hex::encode(node.hash)),
+                total_units,
+                total,
+                wallet_count: asset_leaves.len(),
+                reserves: held,
+                covered: held.map(|held| to_units(held).is_ok_and(|units| units >= total_units)),
+            });
+        }
+
+        let mut reports = self.reserve_reports.write().map_err(|_| "Failed to acquire write lock")?;
+        let report = ReserveReport {
+            id: format!("reserves-{}", reports.len() + 1),
+            generated_at: self.now(),
+            assets,
+        };
+        reports.insert(report.id.clone(), StoredReserveReport {
+            report: report.clone(),
+            leaves: leaves.into_iter().collect(),
+        });
+        drop(reports);
+        self.record_audit(AuditAction::Create, EntityType::ReserveReport, &report.id, None::<&ReserveReport>, Some(&report))?;
+        Ok(report)
+    }
+
+    /// Published reports, oldest first
+    pub fn get_reserve_reports(&self) -> Result<Vec<ReserveReport>, String> {
+        let reports = self.reserve_reports.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut found: Vec<ReserveReport> = reports.values().map(|stored| stored.report.clone()).collect();
+        // IDs are numbered in generation order
+        found.sort_by_key(|r| r.id.trim_start_matches("reserves-").parse::<u64>().unwrap_or(0));
+        Ok(found)
+    }
+
+    /// A published report
+    pub fn get_reserve_report(&self, report_id: &str) -> Result<Option<ReserveReport>, String> {
+        let reports = self.reserve_reports.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(reports.get(report_id).map(|stored| stored.report.clone()))
+    }
+
+    /// Proofs that a wallet's balances are counted in a report, one per asset it held
+    pub fn get_reserve_proofs(&self, report_id: &str, wallet_id: &str) -> Result<Vec<InclusionProof>, String> {
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        let reports = self.reserve_reports.read().map_err(|_| "Failed to acquire read lock")?;
+        let stored = reports.get(report_id).ok_or("Reserve report not found")?;
+        let mut proofs = Vec::new();
+        for asset in &stored.report.assets {
+            let asset_leaves = &stored.leaves[&asset.asset_id];
+            let Some(mut index) = asset_leaves.iter().position(|leaf| leaf.wallet_id == wallet_id) else {
+                continue;
+            };
+            let leaf = &asset_leaves[index];
+            let levels = tree_levels(leaf_nodes(&asset.asset_id, asset_leaves))?;
+            let mut path = Vec::new();
+            for level in &levels[..levels.len() - 1] {
+                let sibling = index ^ 1;
+                if let Some(node) = level.get(sibling) {
+                    path.push(ProofStep {
+                        side: if sibling < index { Side::Left } else { Side::Right },
+                        hash: hex::encode(node.hash),
+                        sum: node.sum,
+                    });
+                }
+                index /= 2;
+            }
+            proofs.push(InclusionProof {
+                report_id: report_id.to_string(),
+                asset_id: asset.asset_id.clone(),
+                wallet_id: wallet_id.to_string(),
+                units: leaf.units,
+                salt: hex::encode(leaf.salt),
+                path,
+            });
+        }
+        Ok(proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{Role, Subject};
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset};
+    use crate::test_support::{asset, wallet};
+
+    fn setup() -> TokenizationCore {
+        let core = TokenizationCore::new();
+        for asset_id in ["gold", "silver"] {
+            core.create_asset(TokenizedAsset {
+                compliance_status: ComplianceStatus::Approved,
+                ..asset(asset_id, "issuer", AssetType::Commodity)
+            })
+            .unwrap();
+        }
+        let wallets = [
+            ("alice-c", "alice", WalletType::Custodial),
+            ("bob-c", "bob", WalletType::Custodial),
+            ("carol-c", "carol", WalletType::Custodial),
+            ("dave-c", "dave", WalletType::Custodial),
+            ("erin-c", "erin", WalletType::Custodial),
+            ("alice-own", "alice", WalletType::NonCustodial),
+        ];
+        for (id, owner, wallet_type) in wallets {
+            core.create_wallet(wallet(id, owner, wallet_type)).unwrap();
+        }
+        for (wallet_id, quantity) in [("alice-c", 10.5), ("bob-c", 20.0), ("carol-c", 0.25), ("dave-c", 4.0), ("erin-c", 65.25), ("alice-own", 1000.0)] {
+            core.mint_to_wallet(wallet_id, "gold", quantity).unwrap();
+        }
+        core.mint_to_wallet("bob-c", "silver", 3.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_every_custodial_holder_can_verify_their_balance() {
+        let core = setup();
+        let report = core.generate_reserve_report(HashMap::from([("gold".to_string(), 99.0), ("silver".to_string(), 3.0)])).unwrap();
+        let gold = &report.assets[0];
+        assert_eq!((gold.asset_id.as_str(), gold.total, gold.wallet_count), ("gold", 100.0, 5));
+        assert_eq!(gold.covered, Some(false));
+        assert_eq!(report.assets[1].covered, Some(true));
+
+        for wallet_id in ["alice-c", "bob-c", "carol-c", "dave-c", "erin-c"] {
+            let proof = core.get_reserve_proofs(&report.id, wallet_id).unwrap().remove(0);
+            verify_inclusion_proof(&proof, gold.root.as_ref().unwrap(), gold.total_units).unwrap();
+        }
+        // Non-custodial holdings are not the platform's liability
+        assert!(core.get_reserve_proofs(&report.id, "alice-own").unwrap().is_empty());
+        assert_eq!(core.get_reserve_proofs(&report.id, "bob-c").unwrap().len(), 2);
+
+        let alice = Subject::new("alice", &[Role::Investor]);
+        assert!(core.as_subject(&alice, || core.get_reserve_proofs(&report.id, "bob-c")).is_err());
+        assert!(core.as_subject(&alice, || core.generate_reserve_report(HashMap::new())).is_err());
+        assert_eq!(core.as_subject(&alice, || core.get_reserve_proofs(&report.id, "alice-c")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let core = setup();
+        let report = core.generate_reserve_report(HashMap::new()).unwrap();
+        let gold = &report.assets[0];
+        let root = gold.root.clone().unwrap();
+        let proof = core.get_reserve_proofs(&report.id, "carol-c").unwrap().remove(0);
+
+        let mut understated = proof.clone();
+        understated.units -= 1;
+        assert!(verify_inclusion_proof(&understated, &root, gold.total_units).is_err());
+        let mut hidden = proof.clone();
+        hidden.path[0].sum = 0;
+        assert!(verify_inclusion_proof(&hidden, &root, gold.total_units).is_err());
+        let error = verify_inclusion_proof(&proof, &root, gold.total_units - 1).unwrap_err();
+        assert_eq!(error, format!("Proof sums to {} units, not the published {}", gold.total_units, gold.total_units - 1));
+
+        // A later report salts its leaves afresh
+        let later = core.generate_reserve_report(HashMap::new()).unwrap();
+        assert_ne!(later.assets[0].root, gold.root);
+        assert_eq!(core.get_reserve_reports().unwrap().len(), 2);
+    }
+}
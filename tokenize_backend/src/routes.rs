@@ -1,5 +1,32 @@
 use warp::Filter;
-use crate::models::{ApiResponse, TokenizationComponent, InMemoryDatabase};
+use warp::http::StatusCode;
+use crate::models::{ApiResponse, TokenizationComponent, InMemoryDatabase, NewTokenizationComponent};
+use crate::core_component::TokenizationCore;
+use crate::access_control::{self, Permission, Subject};
+use crate::approvals::{ApprovalOperation, ApprovalRule, PendingAction, Submission};
+use crate::policy::{self, PolicyBundle, PolicyDecision, PolicyDocument};
+use crate::spending_limits::{LimitTarget, LimitUtilisation, SpendingLimits};
+use crate::freezes::{self, Freeze, FreezeReason, FreezeTarget};
+use crate::custody::{self, CustodyAccount, CustodyModel, OutgoingTransfer, SigningRequest, SigningStatus};
+use crate::signatures::{self, KeyAlgorithm, OperationOutcome, SignedOperation, WalletKey, WalletKeyring};
+use crate::multisig::{MultisigOperation, Proposal, SignerPolicy, WalletSigner};
+use crate::key_management::{KeySignature, ManagedKey};
+use crate::recovery::{GuardianConfig, GuardianSet, RecoveryAction, RecoveryRequest};
+use crate::reserves::{InclusionProof, ReserveReport};
+use crate::reconciliation::{self, Reconciliation, StatementFormat, ToleranceRules};
+use crate::auth::{self, ApiKeyRecord, Authenticator, IssuedApiKey, NewApiKey, Principal};
+use crate::audit::{AuditEntry, AuditQuery, AuditViolation};
+use crate::cap_table::{CapTable, IntegrityReport};
+use crate::events::{StoredEvent, WalletView};
+use crate::live_feed::{FeedItem, LiveFeed, TopicFilter};
+use crate::nav::NavRecord;
+use crate::notifications::{NotificationHub, CATALOG_TOPIC, COMPONENT_ADDED_EVENT};
+use crate::webhooks::{NewWebhookSubscription, WebhookDelivery, WebhookDispatcher, WebhookSubscription};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
 pub fn components_routes(
@@ -28,6 +55,1007 @@ pub fn components_routes(
     get_all.or(get_by_type).or(get_by_subtype)
 }
 
+/// Query parameters for time-based lookups
+#[derive(Debug, Deserialize)]
+pub struct AtQuery {
+    pub at: Option<u64>,
+}
+
+/// Query parameters for reading the event stream
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    pub after: Option<u64>,
+}
+
+/// Routes that change the component catalog and publish the change
+pub fn catalog_routes(
+    db: Arc<RwLock<InMemoryDatabase>>,
+    notifications: NotificationHub,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let db_filter = warp::any().map(move || db.clone());
+    let notifications_filter = warp::any().map(move || notifications.clone());
+
+    // Add a component to the catalog
+    warp::path!("api" / "components")
+        .and(warp::post())
+        .and(auth::require_permission(auth.clone(), Permission::ManageCatalog))
+        .and(warp::body::json())
+        .and(db_filter)
+        .and(notifications_filter)
+        .and_then(add_component)
+}
+
+/// Query parameters for the webhook delivery log
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub subscription_id: Option<String>,
+}
+
+pub fn webhook_routes(
+    dispatcher: Arc<WebhookDispatcher>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let dispatcher_filter = warp::any().map(move || dispatcher.clone());
+
+    // Register a webhook endpoint
+    let register = warp::path!("api" / "webhooks")
+        .and(warp::post())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(warp::body::json())
+        .and(dispatcher_filter.clone())
+        .and_then(register_webhook);
+
+    // List webhook endpoints
+    let list = warp::path!("api" / "webhooks")
+        .and(warp::get())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(dispatcher_filter.clone())
+        .and_then(list_webhooks);
+
+    // Remove a webhook endpoint
+    let unregister = warp::path!("api" / "webhooks" / String)
+        .and(warp::delete())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(dispatcher_filter.clone())
+        .and_then(unregister_webhook);
+
+    // Delivery log, optionally for one endpoint
+    let deliveries = warp::path!("api" / "webhooks" / "deliveries")
+        .and(warp::get())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(warp::query::<DeliveriesQuery>())
+        .and(dispatcher_filter.clone())
+        .and_then(list_webhook_deliveries);
+
+    // Dead-letter queue
+    let dead_letters = warp::path!("api" / "webhooks" / "dead-letters")
+        .and(warp::get())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(dispatcher_filter.clone())
+        .and_then(list_dead_letters);
+
+    // Requeue a dead-lettered delivery
+    let redeliver = warp::path!("api" / "webhooks" / "deliveries" / String / "retry")
+        .and(warp::post())
+        .and(auth::require_permission(auth.clone(), Permission::ManageWebhooks))
+        .and(dispatcher_filter)
+        .and_then(redeliver_webhook);
+
+    register.or(list).or(unregister).or(deliveries).or(dead_letters).or(redeliver)
+}
+
+/// Query parameters for the live stream
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    pub topics: Option<String>,
+    pub last_event_id: Option<u64>,
+}
+
+/// Live feed of changes over WebSocket or Server-Sent Events
+pub fn stream_routes(
+    feed: Arc<LiveFeed>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let feed_filter = warp::any().map(move || feed.clone());
+
+    // WebSocket clients resume with the last_event_id query parameter
+    let websocket = warp::path!("api" / "stream")
+        .and(warp::ws())
+        .and(stream_access(auth.clone()))
+        .and(feed_filter.clone())
+        .map(|ws: warp::ws::Ws, query: StreamQuery, feed: Arc<LiveFeed>| {
+            ws.on_upgrade(move |socket| stream_websocket(socket, query, feed))
+        });
+
+    // SSE clients resume with the Last-Event-ID header or the query parameter
+    let sse = warp::path!("api" / "stream")
+        .and(warp::get())
+        .and(stream_access(auth))
+        .and(warp::header::optional::<u64>("last-event-id"))
+        .and(feed_filter)
+        .and_then(stream_sse);
+
+    websocket.or(sse)
+}
+
+/// Only the public component catalog can be streamed anonymously; other topics need `ReadEvents`
+fn stream_access(
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = (StreamQuery,), Error = warp::Rejection> + Clone {
+    warp::query::<StreamQuery>()
+        .and(auth::with_optional_principal(auth))
+        .and_then(|query: StreamQuery, principal: Option<Principal>| async move {
+            if TopicFilter::parse(query.topics.as_deref()).is_within(CATALOG_TOPIC) {
+                return Ok(query);
+            }
+            match principal {
+                None => Err(warp::reject::custom(auth::AuthError::Unauthorized(
+                    "Authentication required for topics other than catalog".to_string(),
+                ))),
+                Some(principal) if !Subject::from(&principal).may(Permission::ReadEvents) => {
+                    Err(warp::reject::custom(auth::AuthError::Forbidden(format!(
+                        "{} may not perform {:?}",
+                        principal.subject,
+                        Permission::ReadEvents
+                    ))))
+                }
+                Some(_) => Ok(query),
+            }
+        })
+}
+
+/// Routes for the authenticated caller and API key management
+pub fn auth_routes(
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let auth_filter = {
+        let auth = auth.clone();
+        warp::any().map(move || auth.clone())
+    };
+
+    // Who the caller is authenticated as
+    let whoami = warp::path!("api" / "auth" / "me")
+        .and(warp::get())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_current_principal);
+
+    // Issue an API key
+    let issue = warp::path!("api" / "auth" / "api-keys")
+        .and(warp::post())
+        .and(auth::with_permission(auth.clone(), Permission::ManageApiKeys))
+        .and(warp::body::json())
+        .and(auth_filter.clone())
+        .and_then(issue_api_key);
+
+    // List API keys
+    let list = warp::path!("api" / "auth" / "api-keys")
+        .and(warp::get())
+        .and(auth::require_permission(auth.clone(), Permission::ManageApiKeys))
+        .and(auth_filter.clone())
+        .and_then(list_api_keys);
+
+    // Revoke an API key
+    let revoke = warp::path!("api" / "auth" / "api-keys" / String)
+        .and(warp::delete())
+        .and(auth::require_permission(auth, Permission::ManageApiKeys))
+        .and(auth_filter)
+        .and_then(revoke_api_key);
+
+    whoami.or(issue).or(list).or(revoke)
+}
+
+/// Query parameters for listing pending actions
+#[derive(Debug, Deserialize)]
+pub struct ActionsQuery {
+    pub status: Option<String>,
+}
+
+/// Body of an approve or reject request
+#[derive(Debug, Deserialize)]
+pub struct DecisionRequest {
+    pub comment: Option<String>,
+}
+
+/// Four-eyes approval of sensitive core operations
+pub fn approval_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Submit an operation; it runs at once unless an approval rule holds it
+    let submit = warp::path!("api" / "core" / "approvals")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(submit_operation);
+
+    // List pending actions, optionally by status
+    let list = warp::path!("api" / "core" / "approvals")
+        .and(warp::get())
+        .and(warp::query::<ActionsQuery>())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ApproveActions))
+        .and_then(list_pending_actions);
+
+    // Get one pending action with its approval history
+    let get = warp::path!("api" / "core" / "approvals" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ApproveActions))
+        .and_then(get_pending_action);
+
+    // Approve a pending action
+    let approve = warp::path!("api" / "core" / "approvals" / String / "approve")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ApproveActions))
+        .map(|id: String, request: DecisionRequest, core: Arc<TokenizationCore>, principal: Principal| {
+            (id, request, core, principal, true)
+        })
+        .untuple_one()
+        .and_then(decide_pending_action);
+
+    // Reject a pending action
+    let reject = warp::path!("api" / "core" / "approvals" / String / "reject")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ApproveActions))
+        .map(|id: String, request: DecisionRequest, core: Arc<TokenizationCore>, principal: Principal| {
+            (id, request, core, principal, false)
+        })
+        .untuple_one()
+        .and_then(decide_pending_action);
+
+    // List approval rules
+    let get_rules = warp::path!("api" / "core" / "approval-rules")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ApproveActions))
+        .and_then(list_approval_rules);
+
+    // Add or replace the approval rule for an operation kind
+    let put_rule = warp::path!("api" / "core" / "approval-rules")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(core_filter)
+        .and(auth::with_permission(auth, Permission::ConfigureApprovals))
+        .and_then(configure_approval_rule);
+
+    submit.or(list).or(get).or(approve).or(reject).or(get_rules).or(put_rule)
+}
+
+/// Body of a policy dry run
+#[derive(Debug, Deserialize)]
+pub struct DryRunRequest {
+    /// Input document, e.g. `{"actor", "operation", "wallet", "asset"}`
+    pub input: serde_json::Value,
+    /// Documents to test instead of the loaded bundle
+    pub policies: Option<Vec<PolicyDocument>>,
+}
+
+/// Inspection, reload and dry runs of the core's policy bundle
+pub fn policy_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Get the loaded bundle
+    let get_bundle = warp::path!("api" / "policies")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ManagePolicies))
+        .and_then(get_policy_bundle);
+
+    // Reload the bundle from its directory
+    let reload = warp::path!("api" / "policies" / "reload")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ManagePolicies))
+        .and_then(reload_policies);
+
+    // Evaluate an input without performing any operation
+    let dry_run = warp::path!("api" / "policies" / "dry-run")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter)
+        .and(auth::require_permission(auth, Permission::ManagePolicies))
+        .and_then(dry_run_policies);
+
+    get_bundle.or(reload).or(dry_run)
+}
+
+/// Spending limits of wallets (`/api/core/wallets/{id}/limits`) and owners
+/// (`/api/core/owners/{owner}/limits`)
+pub fn limit_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+    let target = warp::path!("api" / "core" / String / String / "limits").and_then(
+        |kind: String, id: String| async move {
+            match kind.as_str() {
+                "wallets" => Ok(LimitTarget::Wallet(id)),
+                "owners" => Ok(LimitTarget::Owner(id)),
+                _ => Err(warp::reject::not_found()),
+            }
+        },
+    );
+
+    // Get limits and their current utilisation
+    let get = target
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadWallet))
+        .and_then(get_limit_utilisation);
+
+    // Set or replace limits
+    let put = target
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManagePolicies))
+        .and_then(set_spending_limits);
+
+    // Remove limits
+    let delete = target
+        .and(warp::delete())
+        .and(core_filter)
+        .and(auth::with_permission(auth, Permission::ManagePolicies))
+        .and_then(remove_spending_limits);
+
+    get.or(put).or(delete)
+}
+
+/// Body of a freeze request
+#[derive(Debug, Deserialize)]
+pub struct FreezeRequest {
+    pub target: FreezeTarget,
+    pub reason: FreezeReason,
+    pub note: Option<String>,
+    pub expires_at: Option<u64>,
+}
+
+/// Freezes of wallets, assets and owners, and the platform kill switch
+pub fn freeze_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // List freezes in force
+    let list = warp::path!("api" / "core" / "freezes")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::require_permission(auth.clone(), Permission::ManageFreezes))
+        .and_then(list_freezes);
+
+    // Freeze a target
+    let freeze = warp::path!("api" / "core" / "freezes")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageFreezes))
+        .and_then(freeze_target);
+
+    // Lift the freeze on a wallet, asset or owner
+    let target = warp::path!("api" / "core" / "freezes" / String / String).and_then(
+        |kind: String, id: String| async move {
+            match kind.as_str() {
+                "wallets" => Ok(FreezeTarget::Wallet(id)),
+                "assets" => Ok(FreezeTarget::Asset(id)),
+                "owners" => Ok(FreezeTarget::Owner(id)),
+                _ => Err(warp::reject::not_found()),
+            }
+        },
+    );
+    let unfreeze = target
+        .and(warp::delete())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageFreezes))
+        .and_then(unfreeze_target);
+
+    // Lift the kill switch
+    let unfreeze_global = warp::path!("api" / "core" / "freezes" / "global")
+        .map(|| FreezeTarget::Global)
+        .and(warp::delete())
+        .and(core_filter)
+        .and(auth::with_permission(auth, Permission::ManageFreezes))
+        .and_then(unfreeze_target);
+
+    list.or(freeze).or(unfreeze_global).or(unfreeze)
+}
+
+/// Query parameters for listing signing requests
+#[derive(Debug, Deserialize)]
+pub struct SigningRequestsQuery {
+    pub wallet_id: Option<String>,
+}
+
+/// Custody accounts of custodial wallets and co-signing of outgoing transfers
+pub fn custody_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // List custody accounts with their sub-ledgers
+    let accounts = warp::path!("api" / "core" / "custody" / "accounts")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(list_custody_accounts);
+
+    // Get a wallet's custody model
+    let get_model = warp::path!("api" / "core" / "wallets" / String / "custody")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadWallet))
+        .and_then(get_custody_model);
+
+    // Move a custodial wallet into an omnibus account or its own segregated one
+    let put_model = warp::path!("api" / "core" / "wallets" / String / "custody")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageCustody))
+        .and_then(set_custody_model);
+
+    // Open a signing request for a transfer, signed by the caller
+    let request = warp::path!("api" / "core" / "signing-requests")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(request_transfer_signatures);
+
+    // List signing requests visible to the caller
+    let list = warp::path!("api" / "core" / "signing-requests")
+        .and(warp::get())
+        .and(warp::query::<SigningRequestsQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(list_signing_requests);
+
+    // Sign a request
+    let sign = warp::path!("api" / "core" / "signing-requests" / String / "sign")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| (id, core, principal, true))
+        .untuple_one()
+        .and_then(close_signing_request);
+
+    // Cancel a request
+    let cancel = warp::path!("api" / "core" / "signing-requests" / String / "cancel")
+        .and(warp::post())
+        .and(core_filter)
+        .and(auth::with_principal(auth))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| (id, core, principal, false))
+        .untuple_one()
+        .and_then(close_signing_request);
+
+    accounts.or(get_model).or(put_model).or(request).or(list).or(sign).or(cancel)
+}
+
+/// Body of a key registration
+#[derive(Debug, Deserialize)]
+pub struct NewWalletKey {
+    pub algorithm: KeyAlgorithm,
+    /// Hex-encoded
+    pub public_key: String,
+}
+
+/// Wallet keys and operations signed with them
+pub fn signature_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // List a wallet's keys and its last used nonce
+    let list = warp::path!("api" / "core" / "wallets" / String / "keys")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadWallet))
+        .and_then(get_wallet_keys);
+
+    // Register a public key
+    let register = warp::path!("api" / "core" / "wallets" / String / "keys")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UpdateWallet))
+        .and_then(register_wallet_key);
+
+    // Revoke a key
+    let revoke = warp::path!("api" / "core" / "wallets" / String / "keys" / String)
+        .and(warp::delete())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UpdateWallet))
+        .and_then(revoke_wallet_key);
+
+    // Verify and apply a signed operation
+    let submit = warp::path!("api" / "core" / "signed-operations")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter)
+        .and(auth::with_principal(auth))
+        .and_then(submit_signed_operation);
+
+    list.or(register).or(revoke).or(submit)
+}
+
+/// Body of a wallet's initial signer set
+#[derive(Debug, Deserialize)]
+pub struct SignerSetup {
+    pub signers: Vec<WalletSigner>,
+    pub threshold: usize,
+}
+
+/// Body of a proposal on a threshold wallet
+#[derive(Debug, Deserialize)]
+pub struct NewProposal {
+    pub wallet_id: String,
+    pub operation: MultisigOperation,
+}
+
+/// Query string of a proposal listing
+#[derive(Debug, Deserialize)]
+pub struct ProposalsQuery {
+    pub wallet_id: Option<String>,
+}
+
+/// Threshold signers of wallets and the proposals they approve
+pub fn multisig_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Get a wallet's signers and threshold
+    let get_signers = warp::path!("api" / "core" / "wallets" / String / "signers")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_wallet_signers);
+
+    // Put a wallet under the control of its signers
+    let set_signers = warp::path!("api" / "core" / "wallets" / String / "signers")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UpdateWallet))
+        .and_then(set_wallet_signers);
+
+    // Propose an operation as one of the wallet's signers
+    let propose = warp::path!("api" / "core" / "proposals")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(propose_wallet_operation);
+
+    // List proposals visible to the caller
+    let list = warp::path!("api" / "core" / "proposals")
+        .and(warp::get())
+        .and(warp::query::<ProposalsQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(list_proposals);
+
+    // Get a proposal
+    let get = warp::path!("api" / "core" / "proposals" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_proposal);
+
+    // Approve a proposal
+    let approve = warp::path!("api" / "core" / "proposals" / String / "approve")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| (id, core, principal, true))
+        .untuple_one()
+        .and_then(close_proposal);
+
+    // Cancel a proposal
+    let cancel = warp::path!("api" / "core" / "proposals" / String / "cancel")
+        .and(warp::post())
+        .and(core_filter)
+        .and(auth::with_principal(auth))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| (id, core, principal, false))
+        .untuple_one()
+        .and_then(close_proposal);
+
+    get_signers.or(set_signers).or(propose).or(list).or(get).or(approve).or(cancel)
+}
+
+/// Body of a guardian-initiated recovery
+#[derive(Debug, Deserialize)]
+pub struct NewRecovery {
+    pub wallet_id: String,
+    pub action: RecoveryAction,
+}
+
+/// Query string of a recovery listing
+#[derive(Debug, Deserialize)]
+pub struct RecoveriesQuery {
+    pub wallet_id: Option<String>,
+}
+
+/// A step taken on a pending recovery
+type RecoveryStep = fn(&TokenizationCore, &str) -> Result<RecoveryRequest, String>;
+
+/// Guardians of wallets, their recoveries and inheritance claims
+pub fn recovery_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Get a wallet's guardians
+    let get_guardians = warp::path!("api" / "core" / "wallets" / String / "guardians")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_wallet_guardians);
+
+    // Name a wallet's guardians and inheritance terms
+    let set_guardians = warp::path!("api" / "core" / "wallets" / String / "guardians")
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UpdateWallet))
+        .and_then(set_wallet_guardians);
+
+    // Show the owner still controls the wallet
+    let check_in = warp::path!("api" / "core" / "wallets" / String / "check-in")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(check_in_wallet);
+
+    // Claim an inactive owner's wallet as its beneficiary
+    let claim = warp::path!("api" / "core" / "wallets" / String / "inheritance" / "claim")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(claim_inheritance);
+
+    // Initiate a recovery as a guardian
+    let initiate = warp::path!("api" / "core" / "recoveries")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(initiate_recovery);
+
+    // List recoveries visible to the caller
+    let list = warp::path!("api" / "core" / "recoveries")
+        .and(warp::get())
+        .and(warp::query::<RecoveriesQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(list_recoveries);
+
+    // Get a recovery
+    let get = warp::path!("api" / "core" / "recoveries" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_recovery);
+
+    // Approve a recovery as a guardian
+    let approve = warp::path!("api" / "core" / "recoveries" / String / "approve")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| {
+            (id, core, principal, TokenizationCore::approve_recovery as RecoveryStep)
+        })
+        .untuple_one()
+        .and_then(step_recovery);
+
+    // Veto a recovery as the wallet's owner
+    let veto = warp::path!("api" / "core" / "recoveries" / String / "veto")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| {
+            (id, core, principal, TokenizationCore::veto_recovery as RecoveryStep)
+        })
+        .untuple_one()
+        .and_then(step_recovery);
+
+    // Run a recovery whose delay has passed
+    let execute = warp::path!("api" / "core" / "recoveries" / String / "execute")
+        .and(warp::post())
+        .and(core_filter)
+        .and(auth::with_principal(auth))
+        .map(|id: String, core: Arc<TokenizationCore>, principal: Principal| {
+            (id, core, principal, TokenizationCore::execute_recovery as RecoveryStep)
+        })
+        .untuple_one()
+        .and_then(step_recovery);
+
+    get_guardians
+        .or(set_guardians)
+        .or(check_in)
+        .or(claim)
+        .or(initiate)
+        .or(list)
+        .or(get)
+        .or(approve)
+        .or(veto)
+        .or(execute)
+}
+
+/// Body of a reserve report generation
+#[derive(Debug, Deserialize)]
+pub struct NewReserveReport {
+    /// Reserves held per asset ID, attested beside the liabilities
+    #[serde(default)]
+    pub reserves: HashMap<String, f64>,
+}
+
+/// Proof of liabilities reports and holders' inclusion proofs
+pub fn reserve_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Commit to the current custodial liabilities
+    let generate = warp::path!("api" / "core" / "reserves")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageCustody))
+        .and_then(generate_reserve_report);
+
+    // List published reports
+    let list = warp::path!("api" / "core" / "reserves")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(list_reserve_reports);
+
+    // Get a published report
+    let get = warp::path!("api" / "core" / "reserves" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_principal(auth.clone()))
+        .and_then(get_reserve_report);
+
+    // Get a wallet's inclusion proofs in a report
+    let proofs = warp::path!("api" / "core" / "reserves" / String / "proofs" / String)
+        .and(warp::get())
+        .and(core_filter)
+        .and(auth::with_principal(auth))
+        .and_then(get_reserve_proofs);
+
+    generate.or(list).or(get).or(proofs)
+}
+
+/// Body of a reconciliation run
+#[derive(Debug, Deserialize)]
+pub struct NewReconciliation {
+    pub wallet_id: String,
+    /// Asset to reconcile; the wallet's cash balance if absent
+    pub asset_id: Option<String>,
+    /// Statement file, relative to the statements directory
+    pub file: String,
+    /// Statement format; taken from the file's extension if absent
+    pub format: Option<StatementFormat>,
+    #[serde(default)]
+    pub tolerance: ToleranceRules,
+}
+
+/// Query string of a reconciliation listing
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationsQuery {
+    pub wallet_id: Option<String>,
+}
+
+/// Reconciliations of statement files in `statements_dir` against the ledger
+pub fn reconciliation_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>,
+    statements_dir: PathBuf
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+    let dir_filter = warp::any().map(move || statements_dir.clone());
+
+    // Reconcile a statement file against a wallet
+    let run = warp::path!("api" / "core" / "reconciliations")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(dir_filter)
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageCustody))
+        .and_then(run_reconciliation);
+
+    // List reconciliation reports
+    let list = warp::path!("api" / "core" / "reconciliations")
+        .and(warp::get())
+        .and(warp::query::<ReconciliationsQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(list_reconciliations);
+
+    // Get a reconciliation report
+    let get = warp::path!("api" / "core" / "reconciliations" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(get_reconciliation);
+
+    // Download a reconciliation report as CSV
+    let export = warp::path!("api" / "core" / "reconciliations" / String / "export.csv")
+        .and(warp::get())
+        .and(core_filter)
+        .and(auth::with_permission(auth, Permission::ReadReports))
+        .and_then(export_reconciliation);
+
+    run.or(list).or(get).or(export)
+}
+
+/// Body of a platform key generation
+#[derive(Debug, Deserialize)]
+pub struct NewManagedKey {
+    pub id: String,
+    pub algorithm: KeyAlgorithm,
+}
+
+/// Body of a signing call with a platform key
+#[derive(Debug, Deserialize)]
+pub struct SignMessage {
+    /// Hex-encoded bytes to sign
+    pub message: String,
+}
+
+/// Platform keys held by the key store
+pub fn key_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // List platform keys
+    let list = warp::path!("api" / "core" / "keys")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UseKeys))
+        .and_then(get_managed_keys);
+
+    // Generate a key
+    let generate = warp::path!("api" / "core" / "keys")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageKeys))
+        .and_then(generate_managed_key);
+
+    // Get a key
+    let get = warp::path!("api" / "core" / "keys" / String)
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::UseKeys))
+        .and_then(get_managed_key);
+
+    // Rotate a key to a new version
+    let rotate = warp::path!("api" / "core" / "keys" / String / "rotate")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageKeys))
+        .and_then(rotate_managed_key);
+
+    // Retire a key
+    let retire = warp::path!("api" / "core" / "keys" / String / "retire")
+        .and(warp::post())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ManageKeys))
+        .and_then(retire_managed_key);
+
+    // Sign with a key's active version
+    let sign = warp::path!("api" / "core" / "keys" / String / "sign")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(core_filter)
+        .and(auth::with_permission(auth, Permission::UseKeys))
+        .and_then(sign_with_managed_key);
+
+    list.or(generate).or(get).or(rotate).or(retire).or(sign)
+}
+
+pub fn core_routes(
+    core: Arc<TokenizationCore>,
+    auth: Arc<Authenticator>
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let core_filter = warp::any().map(move || core.clone());
+
+    // Get the NAV of a fund asset at a point in time
+    let get_nav = warp::path!("api" / "core" / "assets" / String / "nav")
+        .and(warp::get())
+        .and(warp::query::<AtQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(get_asset_nav);
+
+    // Get the cap table of an asset
+    let get_cap_table = warp::path!("api" / "core" / "assets" / String / "cap-table")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(get_asset_cap_table);
+
+    // Run the ledger integrity check
+    let get_integrity = warp::path!("api" / "core" / "integrity")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadReports))
+        .and_then(get_integrity_report);
+
+    // Query the audit trail by entity, actor and time range
+    let get_audit = warp::path!("api" / "core" / "audit")
+        .and(warp::get())
+        .and(warp::query::<AuditQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadAudit))
+        .and_then(get_audit_entries);
+
+    // Verify the audit trail's hash chain
+    let verify_audit = warp::path!("api" / "core" / "audit" / "verify")
+        .and(warp::get())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadAudit))
+        .and_then(verify_audit_trail);
+
+    // Get a wallet and its holdings, optionally as they stood at a point in time
+    let get_wallet = warp::path!("api" / "core" / "wallets" / String)
+        .and(warp::get())
+        .and(warp::query::<AtQuery>())
+        .and(core_filter.clone())
+        .and(auth::with_permission(auth.clone(), Permission::ReadWallet))
+        .and_then(get_wallet_view);
+
+    // Read the domain event stream
+    let get_events = warp::path!("api" / "core" / "events")
+        .and(warp::get())
+        .and(warp::query::<EventsQuery>())
+        .and(core_filter)
+        .and(auth::with_permission(auth.clone(), Permission::ReadEvents))
+        .and_then(get_domain_events);
+
+    get_nav
+        .or(get_cap_table)
+        .or(get_integrity)
+        .or(get_audit)
+        .or(verify_audit)
+        .or(get_wallet)
+        .or(get_events)
+}
+
+/// Status for an error from the core, reporting authorization failures as 403
+fn core_error_status(error: &str, otherwise: StatusCode) -> StatusCode {
+    if access_control::is_permission_denied(error) || policy::is_policy_denied(error) || custody::is_signature_required(error) {
+        StatusCode::FORBIDDEN
+    } else if signatures::is_invalid_signature(error) {
+        StatusCode::UNAUTHORIZED
+    } else if freezes::is_frozen(error) {
+        StatusCode::LOCKED
+    } else {
+        otherwise
+    }
+}
+
 async fn get_all_components(
     db: Arc<RwLock<InMemoryDatabase>>
 ) -> Result<impl warp::Reply, warp::Rejection> {
@@ -94,4 +1122,2018 @@ async fn get_components_by_subtype(
         };
         Ok(warp::reply::json(&response))
     }
-}
\ No newline at end of file
+}
+
+async fn get_asset_nav(
+    asset_id: String,
+    query: AtQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let at = query.at.unwrap_or_else(|| core.now());
+    match core.as_subject(&Subject::from(&principal), || core.get_nav_at(&asset_id, at)) {
+        Ok(Some(nav)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(nav),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<NavRecord> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("No NAV available for asset {} at {}", asset_id, at)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<NavRecord> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_asset_cap_table(
+    asset_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_cap_table(&asset_id)) {
+        Ok(cap_table) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(cap_table),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<CapTable> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_integrity_report(
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.check_integrity()) {
+        Ok(report) => {
+            let message = if report.is_consistent() {
+                None
+            } else {
+                Some(format!("{} integrity issues found", report.issues.len()))
+            };
+            let response = ApiResponse {
+                success: true,
+                data: Some(report),
+                message,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<IntegrityReport> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_audit_entries(
+    query: AuditQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.query_audit_log(&query)) {
+        Ok(entries) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(entries),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<AuditEntry>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn verify_audit_trail(
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.verify_audit_log()) {
+        Ok(Ok(())) => {
+            let response: ApiResponse<AuditViolation> = ApiResponse {
+                success: true,
+                data: None,
+                message: Some("Audit trail verified".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(Err(violation)) => {
+            let message = format!("Audit trail broken at entry {}: {}", violation.sequence, violation.reason);
+            let response = ApiResponse {
+                success: false,
+                data: Some(violation),
+                message: Some(message),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CONFLICT))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<AuditViolation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_wallet_view(
+    wallet_id: String,
+    query: AtQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let subject = Subject::from(&principal);
+    let view = core.as_subject(&subject, || match query.at {
+        Some(at) => core.wallet_as_of(&wallet_id, at),
+        None => core.get_wallet(&wallet_id).and_then(|wallet| match wallet {
+            Some(wallet) => Ok(Some(WalletView {
+                holdings: core.get_wallet_holdings(&wallet_id)?,
+                wallet,
+            })),
+            None => Ok(None),
+        }),
+    });
+    // Checked here as well as in the core, so a view never leaves for a caller who may not see it
+    let view = view.and_then(|view| match &view {
+        Some(found) => access_control::authorize(&subject, Permission::ReadWallet, Some(&found.wallet.owner)).map(|_| view),
+        None => Ok(view),
+    });
+    match view {
+        Ok(Some(view)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(view),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<WalletView> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("Wallet {} not found", wallet_id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<WalletView> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_domain_events(
+    query: EventsQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_events(query.after.unwrap_or(0))) {
+        Ok(events) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(events),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<StoredEvent>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn add_component(
+    component: NewTokenizationComponent,
+    db: Arc<RwLock<InMemoryDatabase>>,
+    notifications: NotificationHub
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if component.main_type.trim().is_empty() || component.sub_type.trim().is_empty() {
+        let response: ApiResponse<TokenizationComponent> = ApiResponse {
+            success: false,
+            data: None,
+            message: Some("Main type and sub type are required".to_string()),
+        };
+        return Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::BAD_REQUEST));
+    }
+    let added = db.write().unwrap().insert_component(component);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let message = notifications
+        .publish(COMPONENT_ADDED_EVENT, now, serde_json::to_value(&added).unwrap_or_default())
+        .err();
+    let response = ApiResponse {
+        success: true,
+        data: Some(added),
+        message,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+}
+
+async fn register_webhook(
+    subscription: NewWebhookSubscription,
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match dispatcher.register(subscription) {
+        Ok(subscription) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(subscription),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let response: ApiResponse<WebhookSubscription> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::BAD_REQUEST))
+        }
+    }
+}
+
+async fn list_webhooks(
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match dispatcher.get_subscriptions() {
+        Ok(subscriptions) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(subscriptions),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<WebhookSubscription>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn unregister_webhook(
+    subscription_id: String,
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (status, response) = match dispatcher.unregister(&subscription_id) {
+        Ok(()) => (StatusCode::OK, ApiResponse::<()> {
+            success: true,
+            data: None,
+            message: Some(format!("Webhook {} removed", subscription_id)),
+        }),
+        Err(e) => (StatusCode::NOT_FOUND, ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(e),
+        }),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), status))
+}
+
+async fn list_webhook_deliveries(
+    query: DeliveriesQuery,
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match dispatcher.get_deliveries(query.subscription_id.as_deref()) {
+        Ok(deliveries) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(deliveries),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<WebhookDelivery>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn list_dead_letters(
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match dispatcher.get_dead_letters() {
+        Ok(deliveries) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(deliveries),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<WebhookDelivery>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn redeliver_webhook(
+    delivery_id: String,
+    dispatcher: Arc<WebhookDispatcher>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (status, response) = match dispatcher.redeliver(&delivery_id) {
+        Ok(()) => (StatusCode::ACCEPTED, ApiResponse::<()> {
+            success: true,
+            data: None,
+            message: Some(format!("Delivery {} requeued", delivery_id)),
+        }),
+        Err(e) => (StatusCode::BAD_REQUEST, ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(e),
+        }),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), status))
+}
+
+async fn stream_sse(
+    query: StreamQuery,
+    last_event_id: Option<u64>,
+    feed: Arc<LiveFeed>
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::Reply;
+    let subscription = match feed.subscribe(last_event_id.or(query.last_event_id), TopicFilter::parse(query.topics.as_deref())) {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            let response: ApiResponse<()> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            return Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR).into_response());
+        }
+    };
+    let events = futures_util::stream::unfold(subscription, |mut subscription| async move {
+        let item = subscription.next().await?;
+        let event = match &item {
+            FeedItem::Event(notification) => warp::sse::Event::default()
+                .id(notification.id.to_string())
+                .event(notification.event_type.clone()),
+            FeedItem::Gap { event_type, .. } | FeedItem::Reset { event_type, .. } => {
+                warp::sse::Event::default().event(event_type.clone())
+            }
+        };
+        let event = event.json_data(&item).unwrap_or_default();
+        Some((Ok::<_, Infallible>(event), subscription))
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)).into_response())
+}
+
+async fn stream_websocket(socket: warp::ws::WebSocket, query: StreamQuery, feed: Arc<LiveFeed>) {
+    let (mut outgoing, mut incoming) = socket.split();
+    let Ok(mut subscription) = feed.subscribe(query.last_event_id, TopicFilter::parse(query.topics.as_deref())) else {
+        let _ = outgoing.close().await;
+        return;
+    };
+    loop {
+        tokio::select! {
+            item = subscription.next() => {
+                let Some(item) = item else { break };
+                let text = serde_json::to_string(&item).unwrap_or_default();
+                if outgoing.send(warp::ws::Message::text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = incoming.next() => match message {
+                Some(Ok(message)) if !message.is_close() => {}
+                _ => break,
+            },
+        }
+    }
+    let _ = outgoing.close().await;
+}
+
+async fn get_current_principal(
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let response = ApiResponse {
+        success: true,
+        data: Some(principal),
+        message: None,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+}
+
+async fn issue_api_key(
+    principal: Principal,
+    request: NewApiKey,
+    auth: Arc<Authenticator>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match auth.issue_api_key(request, &principal.subject) {
+        Ok(issued) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(issued),
+                message: Some("Store this key now; it cannot be shown again".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let response: ApiResponse<IssuedApiKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::BAD_REQUEST))
+        }
+    }
+}
+
+async fn list_api_keys(
+    auth: Arc<Authenticator>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match auth.get_api_keys() {
+        Ok(records) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(records),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<ApiKeyRecord>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn revoke_api_key(
+    id: String,
+    auth: Arc<Authenticator>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match auth.revoke_api_key(&id) {
+        Ok(record) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(record),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<ApiKeyRecord> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+    }
+}
+
+async fn submit_operation(
+    operation: ApprovalOperation,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.submit_operation(operation)) {
+        Ok(Submission::Executed) => {
+            let response: ApiResponse<PendingAction> = ApiResponse {
+                success: true,
+                data: None,
+                message: Some("Operation executed".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(Submission::Pending(action)) => {
+            let message = format!("Operation needs {} approvals", action.required_approvals);
+            let response = ApiResponse {
+                success: true,
+                data: Some(*action),
+                message: Some(message),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::ACCEPTED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<PendingAction> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_pending_actions(
+    query: ActionsQuery,
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.get_pending_actions(query.status.as_deref()) {
+        Ok(actions) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(actions),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<PendingAction>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn get_pending_action(
+    id: String,
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.get_pending_action(&id) {
+        Ok(Some(action)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(action),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<PendingAction> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("Pending action {} not found", id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let response: ApiResponse<PendingAction> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn decide_pending_action(
+    id: String,
+    request: DecisionRequest,
+    core: Arc<TokenizationCore>,
+    principal: Principal,
+    approve: bool
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        if approve {
+            core.approve_action(&id, request.comment)
+        } else {
+            core.reject_action(&id, request.comment)
+        }
+    });
+    match result {
+        Ok(action) => {
+            let message = format!("Action is {}", action.status.label());
+            let response = ApiResponse {
+                success: true,
+                data: Some(action),
+                message: Some(message),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::CONFLICT);
+            let response: ApiResponse<PendingAction> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_approval_rules(
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.get_approval_rules() {
+        Ok(rules) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(rules),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<ApprovalRule>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn configure_approval_rule(
+    rule: ApprovalRule,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.configure_approval_rule(rule.clone())) {
+        Ok(()) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(rule),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<ApprovalRule> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_policy_bundle(
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.policies().bundle() {
+        Ok(bundle) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(bundle),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<PolicyBundle> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn reload_policies(
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let policies = core.policies();
+    match policies.reload().and_then(|_| policies.bundle()) {
+        Ok(bundle) => {
+            let message = format!("Loaded {} policies", bundle.documents.len());
+            let response = ApiResponse {
+                success: true,
+                data: Some(bundle),
+                message: Some(message),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<PolicyBundle> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::UNPROCESSABLE_ENTITY))
+        }
+    }
+}
+
+async fn dry_run_policies(
+    request: DryRunRequest,
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let decision = match request.policies {
+        Some(documents) => documents
+            .iter()
+            .try_for_each(PolicyDocument::validate)
+            .map(|_| policy::evaluate_documents(&documents, &request.input)),
+        None => core.policies().evaluate(&request.input),
+    };
+    match decision {
+        Ok(decision) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(decision),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<PolicyDecision> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::BAD_REQUEST))
+        }
+    }
+}
+
+async fn get_wallet_keys(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_wallet_keys(&wallet_id)) {
+        Ok(keyring) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(keyring),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<WalletKeyring> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn register_wallet_key(
+    wallet_id: String,
+    key: NewWalletKey,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        core.register_wallet_key(&wallet_id, key.algorithm, &key.public_key)
+    });
+    match result {
+        Ok(key) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(key),
+                message: Some(format!("Key registered for wallet {}", wallet_id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<WalletKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn revoke_wallet_key(
+    wallet_id: String,
+    key_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.revoke_wallet_key(&wallet_id, &key_id)) {
+        Ok(key) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(key),
+                message: Some(format!("Key {} revoked", key_id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<WalletKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_wallet_signers(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_wallet_signers(&wallet_id)) {
+        Ok(Some(policy)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(policy),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<SignerPolicy> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Wallet has no signers".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<SignerPolicy> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn set_wallet_signers(
+    wallet_id: String,
+    setup: SignerSetup,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        core.set_wallet_signers(&wallet_id, setup.signers, setup.threshold)
+    });
+    match result {
+        Ok(policy) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(policy),
+                message: Some(format!("Wallet {} is now controlled by its signers", wallet_id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<SignerPolicy> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn propose_wallet_operation(
+    proposal: NewProposal,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        core.propose_wallet_operation(&proposal.wallet_id, proposal.operation)
+    });
+    match result {
+        Ok(proposal) => {
+            let status = if proposal.status == SigningStatus::Pending { StatusCode::ACCEPTED } else { StatusCode::OK };
+            let response = ApiResponse {
+                success: true,
+                data: Some(proposal),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<Proposal> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_proposals(
+    query: ProposalsQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_proposals(query.wallet_id.as_deref())) {
+        Ok(proposals) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(proposals),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<Proposal>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_proposal(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_proposal(&id)) {
+        Ok(Some(proposal)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(proposal),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<Proposal> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Proposal not found".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Proposal> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn close_proposal(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal,
+    approve: bool
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        if approve { core.approve_proposal(&id) } else { core.cancel_proposal(&id) }
+    });
+    match result {
+        Ok(proposal) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(proposal),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<Proposal> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_wallet_guardians(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_guardians(&wallet_id)) {
+        Ok(Some(guardians)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(guardians),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<GuardianSet> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Wallet has no guardians".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<GuardianSet> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn set_wallet_guardians(
+    wallet_id: String,
+    config: GuardianConfig,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.set_guardians(&wallet_id, config)) {
+        Ok(guardians) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(guardians),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<GuardianSet> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn check_in_wallet(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.check_in(&wallet_id)) {
+        Ok(guardians) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(guardians),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<GuardianSet> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn claim_inheritance(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.claim_inheritance(&wallet_id)) {
+        Ok(recovery) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(recovery),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<RecoveryRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn initiate_recovery(
+    recovery: NewRecovery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.initiate_recovery(&recovery.wallet_id, recovery.action)) {
+        Ok(recovery) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(recovery),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<RecoveryRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_recoveries(
+    query: RecoveriesQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_recoveries(query.wallet_id.as_deref())) {
+        Ok(recoveries) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(recoveries),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<RecoveryRequest>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_recovery(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_recovery(&id)) {
+        Ok(Some(recovery)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(recovery),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<RecoveryRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Recovery not found".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<RecoveryRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn step_recovery(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal,
+    step: RecoveryStep
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || step(&core, &id)) {
+        Ok(recovery) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(recovery),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<RecoveryRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn generate_reserve_report(
+    request: NewReserveReport,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.generate_reserve_report(request.reserves)) {
+        Ok(report) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(report),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<ReserveReport> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_reserve_reports(
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_reserve_reports()) {
+        Ok(reports) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(reports),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<ReserveReport>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_reserve_report(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_reserve_report(&id)) {
+        Ok(Some(report)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(report),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<ReserveReport> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Reserve report not found".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<ReserveReport> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_reserve_proofs(
+    id: String,
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_reserve_proofs(&id, &wallet_id)) {
+        Ok(proofs) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(proofs),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<Vec<InclusionProof>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn run_reconciliation(
+    request: NewReconciliation,
+    statements_dir: PathBuf,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = reconciliation::resolve_statement_path(&statements_dir, &request.file)
+        .and_then(|path| reconciliation::load_statement(&path, request.format))
+        .and_then(|statement| {
+            core.as_subject(&Subject::from(&principal), || {
+                core.reconcile_statement(&request.wallet_id, request.asset_id.as_deref(), statement, request.tolerance)
+            })
+        });
+    match result {
+        Ok(reconciliation) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(reconciliation),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<Reconciliation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_reconciliations(
+    query: ReconciliationsQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_reconciliations(query.wallet_id.as_deref())) {
+        Ok(reconciliations) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(reconciliations),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<Reconciliation>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_reconciliation(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_reconciliation(&id)) {
+        Ok(Some(reconciliation)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(reconciliation),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<Reconciliation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Reconciliation not found".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Reconciliation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn export_reconciliation(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::Reply;
+    let result = core
+        .as_subject(&Subject::from(&principal), || core.get_reconciliation(&id))
+        .and_then(|found| found.ok_or_else(|| "Reconciliation not found".to_string()))
+        .and_then(|found| reconciliation::reconciliation_csv(&found));
+    match result {
+        Ok(csv) => {
+            let reply = warp::reply::with_header(csv, "Content-Type", "text/csv");
+            let disposition = format!("attachment; filename=\"{}.csv\"", id);
+            Ok(warp::reply::with_header(reply, "Content-Disposition", disposition).into_response())
+        }
+        Err(e) => {
+            let status = if e == "Reconciliation not found" { StatusCode::NOT_FOUND } else { core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR) };
+            let response: ApiResponse<Reconciliation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status).into_response())
+        }
+    }
+}
+
+async fn get_managed_keys(
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_managed_keys()) {
+        Ok(keys) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(keys),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<ManagedKey>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_managed_key(
+    key_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_managed_key(&key_id)) {
+        Ok(Some(key)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(key),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Ok(None) => {
+            let response: ApiResponse<ManagedKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some("Key not found".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::NOT_FOUND))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<ManagedKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn generate_managed_key(
+    key: NewManagedKey,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.generate_managed_key(&key.id, key.algorithm)) {
+        Ok(generated) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(generated),
+                message: Some(format!("Key {} generated", key.id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<ManagedKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn rotate_managed_key(
+    key_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    managed_key_change(core.as_subject(&Subject::from(&principal), || core.rotate_managed_key(&key_id)), "rotated")
+}
+
+async fn retire_managed_key(
+    key_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    managed_key_change(core.as_subject(&Subject::from(&principal), || core.retire_managed_key(&key_id)), "retired")
+}
+
+fn managed_key_change(
+    result: Result<ManagedKey, String>,
+    done: &str
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    match result {
+        Ok(key) => {
+            let message = format!("Key {} {}", key.id, done);
+            let response = ApiResponse {
+                success: true,
+                data: Some(key),
+                message: Some(message),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let otherwise = if e == "Key not found" { StatusCode::NOT_FOUND } else { StatusCode::CONFLICT };
+            let status = core_error_status(&e, otherwise);
+            let response: ApiResponse<ManagedKey> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn sign_with_managed_key(
+    key_id: String,
+    body: SignMessage,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = hex::decode(&body.message)
+        .map_err(|_| "Message is not valid hex".to_string())
+        .and_then(|message| core.as_subject(&Subject::from(&principal), || core.sign_with_managed_key(&key_id, &message)));
+    match result {
+        Ok(signature) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(signature),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let otherwise = if e == "Key not found" { StatusCode::NOT_FOUND } else { StatusCode::BAD_REQUEST };
+            let status = core_error_status(&e, otherwise);
+            let response: ApiResponse<KeySignature> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn submit_signed_operation(
+    signed: SignedOperation,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.submit_signed_operation(signed)) {
+        Ok(outcome) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(outcome),
+                message: Some("Signed operation applied".to_string()),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<OperationOutcome> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_custody_accounts(
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_custody_accounts()) {
+        Ok(accounts) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(accounts),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<Vec<CustodyAccount>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_custody_model(
+    wallet_id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_custody_model(&wallet_id)) {
+        Ok(model) => {
+            let response = ApiResponse {
+                success: true,
+                message: model.is_none().then(|| format!("Wallet {} is not custodial", wallet_id)),
+                data: model,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<CustodyModel> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn set_custody_model(
+    wallet_id: String,
+    model: CustodyModel,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.set_custody_model(&wallet_id, model.clone())) {
+        Ok(()) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(model),
+                message: Some(format!("Custody model set for wallet {}", wallet_id)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<CustodyModel> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn request_transfer_signatures(
+    transfer: OutgoingTransfer,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.request_transfer_signatures(transfer)) {
+        Ok(request) => {
+            let status = if request.status == SigningStatus::Pending { StatusCode::ACCEPTED } else { StatusCode::OK };
+            let response = ApiResponse {
+                success: true,
+                data: Some(request),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<SigningRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_signing_requests(
+    query: SigningRequestsQuery,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_signing_requests(query.wallet_id.as_deref())) {
+        Ok(requests) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(requests),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<SigningRequest>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn close_signing_request(
+    id: String,
+    core: Arc<TokenizationCore>,
+    principal: Principal,
+    sign: bool
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        if sign { core.sign_request(&id) } else { core.cancel_signing_request(&id) }
+    });
+    match result {
+        Ok(request) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(request),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<SigningRequest> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn list_freezes(
+    core: Arc<TokenizationCore>
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.get_freezes() {
+        Ok(freezes) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(freezes),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let response: ApiResponse<Vec<Freeze>> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+async fn freeze_target(
+    request: FreezeRequest,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = core.as_subject(&Subject::from(&principal), || {
+        core.freeze(request.target, request.reason, request.note, request.expires_at)
+    });
+    match result {
+        Ok(freeze) => {
+            let response = ApiResponse {
+                message: Some(format!("Froze {}", freeze.target)),
+                success: true,
+                data: Some(freeze),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::CREATED))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<Freeze> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn unfreeze_target(
+    target: FreezeTarget,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.unfreeze(&target)) {
+        Ok(freeze) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(freeze),
+                message: Some(format!("Unfroze {}", target)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<Freeze> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn get_limit_utilisation(
+    target: LimitTarget,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.get_limit_utilisation(&target)) {
+        Ok(utilisation) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(utilisation),
+                message: None,
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::INTERNAL_SERVER_ERROR);
+            let response: ApiResponse<LimitUtilisation> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn set_spending_limits(
+    target: LimitTarget,
+    limits: SpendingLimits,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.set_spending_limits(target.clone(), limits.clone())) {
+        Ok(()) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(limits),
+                message: Some(format!("Spending limits set for {}", target)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::BAD_REQUEST);
+            let response: ApiResponse<SpendingLimits> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
+
+async fn remove_spending_limits(
+    target: LimitTarget,
+    core: Arc<TokenizationCore>,
+    principal: Principal
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match core.as_subject(&Subject::from(&principal), || core.remove_spending_limits(&target)) {
+        Ok(()) => {
+            let response: ApiResponse<SpendingLimits> = ApiResponse {
+                success: true,
+                data: None,
+                message: Some(format!("Spending limits removed for {}", target)),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), StatusCode::OK))
+        }
+        Err(e) => {
+            let status = core_error_status(&e, StatusCode::NOT_FOUND);
+            let response: ApiResponse<SpendingLimits> = ApiResponse {
+                success: false,
+                data: None,
+                message: Some(e),
+            };
+            Ok(warp::reply::with_status(warp::reply::json(&response), status))
+        }
+    }
+}
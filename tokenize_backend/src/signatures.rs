@@ -0,0 +1,504 @@
+//! Cryptographic proof that a wallet's owner authorized an operation
+//!
+//! Wallets register Ed25519 or secp256k1 public keys. An operation signed by the
+//! owner carries its payload, the ID of the signing key and a hex signature over
+//! the payload's canonical form: `SIGNING_DOMAIN`, a newline, then the payload as
+//! JSON with object keys sorted and no whitespace. Ed25519 signs those bytes
+//! directly; secp256k1 signs their SHA-256 digest with ECDSA, as a 64-byte `r || s`.
+//!
+//! Each payload carries a nonce that must exceed the last one accepted for the
+//! wallet, so a signed operation can only ever be applied once. The signature and
+//! nonce are checked, and the nonce used up, before anything is changed; an
+//! operation that then fails needs signing again under a new nonce.
+//!
+//! A verified operation counts as the client's signature under the wallet type's
+//! signing requirements, and once a wallet has a key it is the only way for the
+//! client to sign. On a wallet with threshold signers, keys bound to signers only
+//! approve proposals; the quorum signs for the client.
+
+use crate::access_control::Permission;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::TokenizationCore;
+use crate::custody::{SignerRole, SigningRequest};
+use crate::multisig::Proposal;
+use ed25519_dalek::Verifier as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Domain separator prefixed to every signed payload
+pub const SIGNING_DOMAIN: &str = "tokenize-wallet-operation:v1";
+
+/// Prefix of every rejection of a signed operation
+pub const INVALID_SIGNATURE: &str = "Invalid signature";
+
+/// Signature scheme of a wallet key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAlgorithm {
+    /// 32-byte public keys, 64-byte signatures
+    Ed25519,
+    /// SEC1 public keys, compressed or not; 64-byte ECDSA signatures over SHA-256
+    Secp256k1,
+}
+
+/// A public key registered for a wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletKey {
+    pub id: String,
+    pub wallet_id: String,
+    pub algorithm: KeyAlgorithm,
+    /// Hex-encoded
+    pub public_key: String,
+    pub registered_at: u64,
+    pub revoked_at: Option<u64>,
+}
+
+/// A wallet's keys and the last nonce it has used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletKeyring {
+    pub wallet_id: String,
+    pub keys: Vec<WalletKey>,
+    pub last_nonce: Option<u64>,
+}
+
+/// What a signed payload asks the platform to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WalletOperation {
+    /// Transfer out of the signing wallet
+    Transfer {
+        to_wallet_id: String,
+        asset_id: String,
+        quantity: f64,
+    },
+    /// Sign a pending signing request on the wallet as its client
+    SignRequest { request_id: String },
+    /// Approve a proposal on a threshold wallet as the signer bound to the key
+    ApproveProposal { proposal_id: String },
+}
+
+/// What applying a signed operation led to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum OperationOutcome {
+    Transferred,
+    SigningRequest { request: SigningRequest },
+    Proposal { proposal: Proposal },
+}
+
+/// The signed part of an operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationPayload {
+    pub wallet_id: String,
+    pub nonce: u64,
+    pub operation: WalletOperation,
+}
+
+impl OperationPayload {
+    /// Bytes the wallet's key signs
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, String> {
+        let value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        Ok(format!("{}\n{}", SIGNING_DOMAIN, canonical_json(&value)).into_bytes())
+    }
+}
+
+/// An operation with the owner's signature over its payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedOperation {
+    pub payload: OperationPayload,
+    pub key_id: String,
+    /// Hex-encoded
+    pub signature: String,
+}
+
+/// JSON with object keys sorted and no whitespace
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(",")),
+        other => other.to_string(),
+    }
+}
+
+enum PublicKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    Secp256k1(k256::ecdsa::VerifyingKey),
+}
+
+fn parse_public_key(algorithm: KeyAlgorithm, public_key: &str) -> Result<PublicKey, String> {
+    let bytes = hex::decode(public_key).map_err(|_| "Public key is not valid hex")?;
+    match algorithm {
+        KeyAlgorithm::Ed25519 => {
+            let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| "Ed25519 public keys are 32 bytes")?;
+            ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+                .map(PublicKey::Ed25519)
+                .map_err(|_| "Invalid Ed25519 public key".to_string())
+        }
+        KeyAlgorithm::Secp256k1 => k256::ecdsa::VerifyingKey::from_sec1_bytes(&bytes)
+            .map(PublicKey::Secp256k1)
+            .map_err(|_| "Invalid secp256k1 public key".to_string()),
+    }
+}
+
+/// Refuse a public key that is not valid hex or not a point of the algorithm's curve
+pub(crate) fn check_public_key(algorithm: KeyAlgorithm, public_key: &str) -> Result<(), String> {
+    parse_public_key(algorithm, public_key).map(|_| ())
+}
+
+/// Check a hex signature over `message` against a hex public key
+pub fn verify_signature(algorithm: KeyAlgorithm, public_key: &str, message: &[u8], signature: &str) -> Result<(), String> {
+    let invalid = |reason: &str| format!("{}: {}", INVALID_SIGNATURE, reason);
+    let signature = hex::decode(signature).map_err(|_| invalid("not valid hex"))?;
+    let verified = match parse_public_key(algorithm, public_key)? {
+        PublicKey::Ed25519(key) => {
+            let signature = ed25519_dalek::Signature::from_slice(&signature).map_err(|_| invalid("malformed"))?;
+            key.verify(message, &signature).is_ok()
+        }
+        PublicKey::Secp256k1(key) => {
+            let signature = k256::ecdsa::Signature::from_slice(&signature).map_err(|_| invalid("malformed"))?;
+            key.verify(message, &signature).is_ok()
+        }
+    };
+    if verified {
+        Ok(())
+    } else {
+        Err(invalid("does not match the payload"))
+    }
+}
+
+/// Whether an error from the core is a rejected signature
+pub fn is_invalid_signature(error: &str) -> bool {
+    error.starts_with(INVALID_SIGNATURE)
+}
+
+impl TokenizationCore {
+    /// Register a public key the wallet's owner signs operations with
+    pub fn register_wallet_key(&self, wallet_id: &str, algorithm: KeyAlgorithm, public_key: &str) -> Result<WalletKey, String> {
+        self.authorize_wallet(Permission::UpdateWallet, wallet_id)?;
+        self.authorize_wallet_owner(wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[])?;
+        parse_public_key(algorithm, public_key)?;
+        let public_key = public_key.to_lowercase();
+        let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+        if !wallets.contains_key(wallet_id) {
+            return Err("Wallet not found".to_string());
+        }
+        let mut keys = self.wallet_keys.write().map_err(|_| "Failed to acquire write lock")?;
+        if keys.values().any(|k| k.wallet_id == wallet_id && k.public_key == public_key) {
+            return Err("Key is already registered for this wallet".to_string());
+        }
+        let key = WalletKey {
+            id: format!("key-{}", keys.len() + 1),
+            wallet_id: wallet_id.to_string(),
+            algorithm,
+            public_key,
+            registered_at: self.now(),
+            revoked_at: None,
+        };
+        keys.insert(key.id.clone(), key.clone());
+        self.record_audit(AuditAction::Create, EntityType::WalletKey, &key.id, None::<&WalletKey>, Some(&key))?;
+        Ok(key)
+    }
+
+    /// Stop accepting signatures made with a key
+    pub fn revoke_wallet_key(&self, wallet_id: &str, key_id: &str) -> Result<WalletKey, String> {
+        self.authorize_wallet(Permission::UpdateWallet, wallet_id)?;
+        self.authorize_wallet_owner(wallet_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[])?;
+        let mut keys = self.wallet_keys.write().map_err(|_| "Failed to acquire write lock")?;
+        let key = keys.get_mut(key_id).filter(|k| k.wallet_id == wallet_id).ok_or("Key not found")?;
+        if key.revoked_at.is_some() {
+            return Err("Key is already revoked".to_string());
+        }
+        let before = key.clone();
+        key.revoked_at = Some(self.now());
+        let key = key.clone();
+        self.record_audit(AuditAction::Delete, EntityType::WalletKey, key_id, Some(&before), Some(&key))?;
+        Ok(key)
+    }
+
+    /// A wallet's keys, revoked ones included, and its last used nonce
+    pub fn get_wallet_keys(&self, wallet_id: &str) -> Result<WalletKeyring, String> {
+        self.authorize_wallet(Permission::ReadWallet, wallet_id)?;
+        let keys = self.wallet_keys.read().map_err(|_| "Failed to acquire read lock")?;
+        let nonces = self.wallet_nonces.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut found: Vec<WalletKey> = keys.values().filter(|k| k.wallet_id == wallet_id).cloned().collect();
+        found.sort_by_key(|k| k.id.trim_start_matches("key-").parse::<u64>().unwrap_or(0));
+        Ok(WalletKeyring {
+            wallet_id: wallet_id.to_string(),
+            keys: found,
+            last_nonce: nonces.get(wallet_id).copied(),
+        })
+    }
+
+    /// Whether the wallet has a key that is not revoked
+    pub(crate) fn has_wallet_keys(&self, wallet_id: &str) -> Result<bool, String> {
+        let keys = self.wallet_keys.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(keys.values().any(|k| k.wallet_id == wallet_id && k.revoked_at.is_none()))
+    }
+
+    /// Check an operation's signature and nonce, using the nonce up
+    fn verify_signed_operation(&self, signed: &SignedOperation) -> Result<(), String> {
+        let payload = &signed.payload;
+        let key = {
+            let keys = self.wallet_keys.read().map_err(|_| "Failed to acquire read lock")?;
+            keys.get(&signed.key_id)
+                .filter(|k| k.wallet_id == payload.wallet_id && k.revoked_at.is_none())
+                .cloned()
+                .ok_or_else(|| format!("{}: no active key {} on wallet {}", INVALID_SIGNATURE, signed.key_id, payload.wallet_id))?
+        };
+        verify_signature(key.algorithm, &key.public_key, &payload.signing_bytes()?, &signed.signature)?;
+        let mut nonces = self.wallet_nonces.write().map_err(|_| "Failed to acquire write lock")?;
+        if let Some(last) = nonces.get(&payload.wallet_id)
+            && payload.nonce <= *last
+        {
+            return Err(format!("{}: nonce {} is not above {}, the last one used", INVALID_SIGNATURE, payload.nonce, last));
+        }
+        nonces.insert(payload.wallet_id.clone(), payload.nonce);
+        Ok(())
+    }
+
+    /// Verify and apply an operation signed by a wallet's owner or one of its
+    /// signers. The caller still needs the usual permissions; when the caller signs
+    /// for the platform the operation is co-signed as well.
+    pub fn submit_signed_operation(&self, signed: SignedOperation) -> Result<OperationOutcome, String> {
+        let wallet = {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            wallets.get(&signed.payload.wallet_id).cloned().ok_or("Wallet not found")?
+        };
+        self.verify_signed_operation(&signed)?;
+        let controlled_by_signers = self.has_wallet_signers(&wallet.id)?;
+        let mut roles = vec![SignerRole::Client];
+        if self.caller_signatures(&wallet)?.contains(&SignerRole::Platform) {
+            roles.push(SignerRole::Platform);
+        }
+        match signed.payload.operation {
+            WalletOperation::ApproveProposal { proposal_id } => {
+                let proposal = self.approve_proposal_with_key(&proposal_id, &wallet.id, &signed.key_id)?;
+                Ok(OperationOutcome::Proposal { proposal })
+            }
+            _ if controlled_by_signers => {
+                Err(format!("Wallet {} is controlled by its signers; propose the operation instead", wallet.id))
+            }
+            WalletOperation::Transfer {
+                to_wallet_id,
+                asset_id,
+                quantity,
+            } => {
+                self.transfer_signed(&wallet.id, &to_wallet_id, &asset_id, quantity, Some(&roles))?;
+                Ok(OperationOutcome::Transferred)
+            }
+            WalletOperation::SignRequest { request_id } => {
+                let request = self.get_signing_request(&request_id)?.ok_or("Signing request not found")?;
+                if request.transfer.from_wallet_id != wallet.id {
+                    return Err(format!("Signing request is not for wallet {}", wallet.id));
+                }
+                let request = self.add_signature(&request_id, wallet.owner, &[SignerRole::Client])?;
+                Ok(OperationOutcome::SigningRequest { request })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{is_permission_denied, Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::custody::{OutgoingTransfer, SigningStatus};
+    use crate::freezes::{is_frozen, FreezeReason, FreezeTarget};
+    use crate::test_support::{asset, wallet};
+    use ed25519_dalek::Signer as _;
+    use std::sync::Arc;
+
+    fn setup(wallet_type: WalletType) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(ManualClock::new(1_000)));
+        core.create_asset(TokenizedAsset {
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Commodity)
+        })
+        .unwrap();
+        for (id, owner, wallet_type) in [("w1", "alice", wallet_type), ("w2", "bob", WalletType::Custodial)] {
+            core.create_wallet(wallet(id, owner, wallet_type)).unwrap();
+        }
+        core.mint_to_wallet("w1", "asset1", 100.0).unwrap();
+        core
+    }
+
+    fn transfer(nonce: u64, quantity: f64) -> OperationPayload {
+        OperationPayload {
+            wallet_id: "w1".to_string(),
+            nonce,
+            operation: WalletOperation::Transfer {
+                to_wallet_id: "w2".to_string(),
+                asset_id: "asset1".to_string(),
+                quantity,
+            },
+        }
+    }
+
+    fn sign_ed25519(key: &ed25519_dalek::SigningKey, key_id: &str, payload: OperationPayload) -> SignedOperation {
+        let signature = key.sign(&payload.signing_bytes().unwrap());
+        SignedOperation {
+            payload,
+            key_id: key_id.to_string(),
+            signature: hex::encode(signature.to_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_canonical_payload() {
+        let bytes = transfer(7, 10.0).signing_bytes().unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "tokenize-wallet-operation:v1\n\
+             {\"nonce\":7,\"operation\":{\"asset_id\":\"asset1\",\"quantity\":10.0,\"to_wallet_id\":\"w2\",\"type\":\"Transfer\"},\"wallet_id\":\"w1\"}"
+        );
+    }
+
+    #[test]
+    fn test_signatures_from_the_browser_module_verify() {
+        // Same vectors as tokenize_wasm's signing tests, signed there with a fixed secret key
+        let payload: OperationPayload = serde_json::from_str(
+            r#"{"wallet_id":"wallet-α","nonce":7,"operation":{"type":"Transfer","to_wallet_id":"wallet \"b\"","asset_id":"gold","quantity":12.5}}"#,
+        )
+        .unwrap();
+        let bytes = payload.signing_bytes().unwrap();
+        assert_eq!(
+            String::from_utf8(bytes.clone()).unwrap(),
+            "tokenize-wallet-operation:v1\n\
+             {\"nonce\":7,\"operation\":{\"asset_id\":\"gold\",\"quantity\":12.5,\"to_wallet_id\":\"wallet \\\"b\\\"\",\"type\":\"Transfer\"},\"wallet_id\":\"wallet-α\"}"
+        );
+        for (algorithm, public_key, signature) in [
+            (
+                KeyAlgorithm::Ed25519,
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "743b50b9bd9fbfb33fb04e109b0baffe023c6ef759f5912b329a74f0f6ac3ab7d8109bc559234b94979e47aae026a3c7cd914b662d9dd00023007c7586b7a901",
+            ),
+            (
+                KeyAlgorithm::Secp256k1,
+                "028db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd91",
+                "956c24d1dcbf9a60dca6e71484d9bb6327e5a7acc1f47ad8d8ec82fb182609fa77530f7356d54a7d219c913d7b7e452087aa1c788773048c95f9432138286ef4",
+            ),
+        ] {
+            verify_signature(algorithm, public_key, &bytes, signature).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_signed_transfers_are_verified_before_anything_changes() {
+        let core = setup(WalletType::NonCustodial);
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+        let public_key = hex::encode(signing_key.verifying_key().to_bytes());
+        assert!(core.register_wallet_key("w1", KeyAlgorithm::Ed25519, "abcd").is_err());
+        let key = core.as_subject(&alice, || core.register_wallet_key("w1", KeyAlgorithm::Ed25519, &public_key)).unwrap();
+
+        // With a key registered, the owner's session alone no longer signs
+        let error = core.as_subject(&alice, || core.transfer_asset("w1", "w2", "asset1", 1.0)).unwrap_err();
+        assert!(error.starts_with("Signature required"), "{}", error);
+
+        let signed = sign_ed25519(&signing_key, &key.id, transfer(1, 10.0));
+        core.as_subject(&alice, || core.submit_signed_operation(signed.clone())).unwrap();
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 10.0);
+
+        // Replays, tampering and stale nonces are all refused without effect
+        let error = core.as_subject(&alice, || core.submit_signed_operation(signed.clone())).unwrap_err();
+        assert_eq!(error, "Invalid signature: nonce 1 is not above 1, the last one used");
+        let mut tampered = sign_ed25519(&signing_key, &key.id, transfer(2, 10.0));
+        tampered.payload = transfer(2, 90.0);
+        let error = core.as_subject(&alice, || core.submit_signed_operation(tampered)).unwrap_err();
+        assert_eq!(error, "Invalid signature: does not match the payload");
+        let other_key = ed25519_dalek::SigningKey::from_bytes(&[8; 32]);
+        assert!(is_invalid_signature(
+            &core.submit_signed_operation(sign_ed25519(&other_key, &key.id, transfer(3, 1.0))).unwrap_err()
+        ));
+        assert_eq!(core.get_holding("w1", "asset1").unwrap(), 90.0);
+        assert_eq!(core.get_wallet_keys("w1").unwrap().last_nonce, Some(1));
+
+        core.submit_signed_operation(sign_ed25519(&signing_key, &key.id, transfer(5, 5.0))).unwrap();
+        assert!(core.submit_signed_operation(sign_ed25519(&signing_key, &key.id, transfer(4, 5.0))).is_err());
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 15.0);
+
+        core.as_subject(&alice, || core.revoke_wallet_key("w1", &key.id)).unwrap();
+        assert!(core.submit_signed_operation(sign_ed25519(&signing_key, &key.id, transfer(6, 5.0))).is_err());
+        assert_eq!(core.get_wallet_keys("w1").unwrap().keys[0].revoked_at, Some(core.now()));
+    }
+
+    #[test]
+    fn test_only_the_owner_manages_wallet_keys() {
+        let core = setup(WalletType::Hybrid);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let alice = Subject::new("alice", &[Role::Investor]);
+        let agent_key = ed25519_dalek::SigningKey::from_bytes(&[9; 32]);
+        let agent_public = hex::encode(agent_key.verifying_key().to_bytes());
+
+        // Holding UpdateWallet on every wallet does not let the agent sign as the client
+        let error = core
+            .as_subject(&agent, || core.register_wallet_key("w1", KeyAlgorithm::Ed25519, &agent_public))
+            .unwrap_err();
+        assert!(is_permission_denied(&error), "{}", error);
+        assert!(core.get_wallet_keys("w1").unwrap().keys.is_empty());
+
+        let key = core.as_subject(&alice, || core.register_wallet_key("w1", KeyAlgorithm::Ed25519, &agent_public)).unwrap();
+        assert!(is_permission_denied(&core.as_subject(&agent, || core.revoke_wallet_key("w1", &key.id)).unwrap_err()));
+
+        core.freeze(FreezeTarget::Wallet("w1".to_string()), FreezeReason::Sanctions, None, None).unwrap();
+        let error = core.as_subject(&alice, || core.revoke_wallet_key("w1", &key.id)).unwrap_err();
+        assert!(is_frozen(&error), "{}", error);
+        assert_eq!(core.get_wallet_keys("w1").unwrap().keys[0].revoked_at, None);
+    }
+
+    #[test]
+    fn test_secp256k1_signature_cosigns_hybrid_transfers() {
+        let core = setup(WalletType::Hybrid);
+        let agent = Subject::new("agent", &[Role::TransferAgent]);
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[9; 32]).unwrap();
+        let public_key = hex::encode(signing_key.verifying_key().to_sec1_bytes());
+        let key = core.register_wallet_key("w1", KeyAlgorithm::Secp256k1, &public_key).unwrap();
+        let sign = |payload: OperationPayload| {
+            let signature: k256::ecdsa::Signature = signing_key.sign(&payload.signing_bytes().unwrap());
+            SignedOperation {
+                payload,
+                key_id: key.id.clone(),
+                signature: hex::encode(signature.to_bytes()),
+            }
+        };
+
+        // Relayed by the platform, the client's signed transfer is co-signed
+        core.as_subject(&agent, || core.submit_signed_operation(sign(transfer(1, 20.0)))).unwrap();
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 20.0);
+
+        // The client can also sign a request the platform opened
+        let request = core
+            .as_subject(&agent, || {
+                core.request_transfer_signatures(OutgoingTransfer {
+                    from_wallet_id: "w1".to_string(),
+                    to_wallet_id: "w2".to_string(),
+                    asset_id: "asset1".to_string(),
+                    quantity: 5.0,
+                })
+            })
+            .unwrap();
+        let signed = sign(OperationPayload {
+            wallet_id: "w1".to_string(),
+            nonce: 2,
+            operation: WalletOperation::SignRequest { request_id: request.id.clone() },
+        });
+        let OperationOutcome::SigningRequest { request } = core.submit_signed_operation(signed).unwrap() else {
+            panic!("expected a signing request");
+        };
+        assert_eq!(request.status, SigningStatus::Executed);
+        assert_eq!(request.signatures.iter().map(|s| s.signer.as_str()).collect::<Vec<_>>(), ["agent", "alice"]);
+        assert_eq!(core.get_holding("w2", "asset1").unwrap(), 25.0);
+    }
+}
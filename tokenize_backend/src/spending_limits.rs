@@ -0,0 +1,369 @@
+//! Spending limits and velocity controls on outgoing transfers
+//!
+//! Limits attach to a single wallet or to an owner identity, in which case they
+//! cover every wallet of that owner together. A transfer is valued at its quantity
+//! times the asset's `value` and checked against the per-transaction cap, the
+//! rolling 24-hour and 7-day caps, the counterparty lists and the time-of-day
+//! window of both the sending wallet and its owner. Spending is only tracked for
+//! targets that have limits configured, from the moment they are set, and it is
+//! kept when limits are replaced or removed so that usage cannot be reset.
+//!
+//! Limits restrain the owner, so only compliance can set or lift them.
+
+use crate::access_control::{authorize_current, Permission};
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{DigitalWallet, TokenizationCore};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Prefix of every rejection caused by a spending limit
+pub const SPENDING_LIMIT_EXCEEDED: &str = "Spending limit exceeded";
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+/// What a set of limits applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum LimitTarget {
+    Wallet(String),
+    /// Every wallet of an owner, counted together
+    Owner(String),
+}
+
+impl fmt::Display for LimitTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitTarget::Wallet(id) => write!(f, "wallet {}", id),
+            LimitTarget::Owner(id) => write!(f, "owner {}", id),
+        }
+    }
+}
+
+/// Time of day, in seconds after midnight UTC, during which transfers may be sent
+///
+/// A window whose end is before its start wraps around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start_secs: u64,
+    pub end_secs: u64,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, timestamp: u64) -> bool {
+        let time = timestamp % SECONDS_PER_DAY;
+        if self.start_secs <= self.end_secs {
+            time >= self.start_secs && time < self.end_secs
+        } else {
+            time >= self.start_secs || time < self.end_secs
+        }
+    }
+}
+
+/// Limits on a wallet's or an owner's outgoing transfers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendingLimits {
+    /// Largest value of a single transfer
+    pub per_transaction: Option<f64>,
+    /// Largest value sent in any rolling 24 hours
+    pub daily: Option<f64>,
+    /// Largest value sent in any rolling 7 days
+    pub weekly: Option<f64>,
+    /// Wallet IDs or owners that may receive; `None` allows all not denied
+    #[serde(default)]
+    pub allowed_counterparties: Option<Vec<String>>,
+    /// Wallet IDs or owners that may never receive
+    #[serde(default)]
+    pub denied_counterparties: Vec<String>,
+    pub window: Option<TimeWindow>,
+}
+
+impl SpendingLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        for cap in [self.per_transaction, self.daily, self.weekly].into_iter().flatten() {
+            if cap < 0.0 {
+                return Err("Spending caps must not be negative".to_string());
+            }
+        }
+        if let Some(window) = &self.window
+            && (window.start_secs >= SECONDS_PER_DAY || window.end_secs > SECONDS_PER_DAY)
+        {
+            return Err("Time window must fall within a day".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Value sent by a limited target at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendRecord {
+    pub at: u64,
+    pub value: f64,
+}
+
+/// Current use of a target's limits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitUtilisation {
+    pub target: LimitTarget,
+    pub limits: Option<SpendingLimits>,
+    pub as_of: u64,
+    pub daily_spent: f64,
+    pub weekly_spent: f64,
+    pub daily_remaining: Option<f64>,
+    pub weekly_remaining: Option<f64>,
+    /// Whether transfers are currently inside the time-of-day window
+    pub within_window: bool,
+}
+
+fn spent_since(log: Option<&Vec<SpendRecord>>, since: u64) -> f64 {
+    log.map(|records| records.iter().filter(|r| r.at > since).map(|r| r.value).sum())
+        .unwrap_or(0.0)
+}
+
+impl TokenizationCore {
+    fn authorize_limit_target(&self, permission: Permission, target: &LimitTarget) -> Result<(), String> {
+        match target {
+            LimitTarget::Wallet(wallet_id) => self.authorize_wallet(permission, wallet_id),
+            LimitTarget::Owner(owner) => authorize_current(permission, Some(owner)),
+        }
+    }
+
+    /// Set or replace the spending limits of a wallet or owner
+    pub fn set_spending_limits(&self, target: LimitTarget, limits: SpendingLimits) -> Result<(), String> {
+        authorize_current(Permission::ManagePolicies, None)?;
+        limits.validate()?;
+        if let LimitTarget::Wallet(wallet_id) = &target {
+            let wallets = self.wallets.read().map_err(|_| "Failed to acquire read lock")?;
+            if !wallets.contains_key(wallet_id) {
+                return Err("Wallet not found".to_string());
+            }
+        }
+        let mut all_limits = self.spending_limits.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = all_limits.insert(target.clone(), limits.clone());
+        let action = if before.is_some() { AuditAction::Update } else { AuditAction::Create };
+        self.record_audit(action, EntityType::SpendingLimits, &target.to_string(), before.as_ref(), Some(&limits))
+    }
+
+    /// Remove the spending limits of a wallet or owner
+    pub fn remove_spending_limits(&self, target: &LimitTarget) -> Result<(), String> {
+        authorize_current(Permission::ManagePolicies, None)?;
+        let mut all_limits = self.spending_limits.write().map_err(|_| "Failed to acquire write lock")?;
+        let before = all_limits.remove(target).ok_or("Spending limits not found")?;
+        self.record_audit(
+            AuditAction::Delete,
+            EntityType::SpendingLimits,
+            &target.to_string(),
+            Some(&before),
+            None::<&SpendingLimits>,
+        )
+    }
+
+    /// Limits of a wallet or owner and how much of them has been used
+    pub fn get_limit_utilisation(&self, target: &LimitTarget) -> Result<LimitUtilisation, String> {
+        self.authorize_limit_target(Permission::ReadWallet, target)?;
+        let now = self.now();
+        let all_limits = self.spending_limits.read().map_err(|_| "Failed to acquire read lock")?;
+        let log = self.spend_log.read().map_err(|_| "Failed to acquire read lock")?;
+        let limits = all_limits.get(target).cloned();
+        let daily_spent = spent_since(log.get(target), now.saturating_sub(SECONDS_PER_DAY));
+        let weekly_spent = spent_since(log.get(target), now.saturating_sub(SECONDS_PER_WEEK));
+        let remaining = |cap: Option<f64>, spent: f64| cap.map(|cap| (cap - spent).max(0.0));
+        Ok(LimitUtilisation {
+            target: target.clone(),
+            daily_remaining: remaining(limits.as_ref().and_then(|l| l.daily), daily_spent),
+            weekly_remaining: remaining(limits.as_ref().and_then(|l| l.weekly), weekly_spent),
+            within_window: limits.as_ref().and_then(|l| l.window).is_none_or(|w| w.contains(now)),
+            limits,
+            as_of: now,
+            daily_spent,
+            weekly_spent,
+        })
+    }
+
+    /// Check an outgoing transfer of `value` against the limits of the sender and its
+    /// owner, returning the targets the transfer counts against
+    pub(crate) fn check_spending_limits(
+        &self,
+        from: &DigitalWallet,
+        to: &DigitalWallet,
+        value: f64,
+        now: u64,
+    ) -> Result<Vec<LimitTarget>, String> {
+        let all_limits = self.spending_limits.read().map_err(|_| "Failed to acquire read lock")?;
+        let log = self.spend_log.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut applicable = Vec::new();
+        for target in [LimitTarget::Wallet(from.id.clone()), LimitTarget::Owner(from.owner.clone())] {
+            let Some(limits) = all_limits.get(&target) else {
+                continue;
+            };
+            let reject = |reason: String| Err(format!("{}: {} {}", SPENDING_LIMIT_EXCEEDED, target, reason));
+            let is_counterparty = |entry: &String| *entry == to.id || *entry == to.owner;
+            if limits.denied_counterparties.iter().any(is_counterparty) {
+                return reject(format!("may not send to {}", to.id));
+            }
+            if let Some(allowed) = &limits.allowed_counterparties
+                && !allowed.iter().any(is_counterparty)
+            {
+                return reject(format!("may only send to {}", allowed.join(", ")));
+            }
+            if let Some(window) = &limits.window
+                && !window.contains(now)
+            {
+                return reject(format!(
+                    "may only send between {}s and {}s after midnight UTC",
+                    window.start_secs, window.end_secs
+                ));
+            }
+            if let Some(cap) = limits.per_transaction
+                && value > cap
+            {
+                return reject(format!("per-transaction cap of {} is below {}", cap, value));
+            }
+            for (name, cap, period) in [("daily", limits.daily, SECONDS_PER_DAY), ("weekly", limits.weekly, SECONDS_PER_WEEK)] {
+                let Some(cap) = cap else {
+                    continue;
+                };
+                let spent = spent_since(log.get(&target), now.saturating_sub(period));
+                if spent + value > cap {
+                    return reject(format!("{} cap of {} would be exceeded ({} used, {} requested)", name, cap, spent, value));
+                }
+            }
+            applicable.push(target);
+        }
+        Ok(applicable)
+    }
+
+    /// Count a completed transfer against the given targets
+    pub(crate) fn record_spend(&self, targets: Vec<LimitTarget>, value: f64, now: u64) -> Result<(), String> {
+        let mut log = self.spend_log.write().map_err(|_| "Failed to acquire write lock")?;
+        for target in targets {
+            let records = log.entry(target).or_default();
+            records.retain(|r| r.at > now.saturating_sub(SECONDS_PER_WEEK));
+            records.push(SpendRecord { at: now, value });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{is_permission_denied, Role, Subject};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            value: 2.0,
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for (id, owner) in [("wallet1", "alice"), ("wallet1b", "alice"), ("wallet2", "bob"), ("wallet3", "carol")] {
+            core.create_wallet(wallet(id, owner, WalletType::Custodial)).unwrap();
+        }
+        core.mint_to_wallet("wallet1", "asset1", 1_000.0).unwrap();
+        core.mint_to_wallet("wallet1b", "asset1", 1_000.0).unwrap();
+        core
+    }
+
+    #[test]
+    fn test_caps_roll_over_time_and_cover_all_of_an_owners_wallets() {
+        let clock = ManualClock::new(10 * SECONDS_PER_DAY);
+        let core = setup(&clock);
+        let wallet = LimitTarget::Wallet("wallet1".to_string());
+        let owner = LimitTarget::Owner("alice".to_string());
+        core.set_spending_limits(
+            wallet.clone(),
+            SpendingLimits {
+                per_transaction: Some(100.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        core.set_spending_limits(
+            owner.clone(),
+            SpendingLimits {
+                daily: Some(300.0),
+                weekly: Some(500.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // 60 units at 2.0 is 120, above the wallet's per-transaction cap
+        let error = core.transfer_asset("wallet1", "wallet2", "asset1", 60.0).unwrap_err();
+        assert!(error.starts_with(SPENDING_LIMIT_EXCEEDED), "{}", error);
+        assert!(error.contains("per-transaction cap of 100"));
+
+        core.transfer_asset("wallet1", "wallet2", "asset1", 50.0).unwrap();
+        core.transfer_asset("wallet1b", "wallet2", "asset1", 75.0).unwrap();
+        let error = core.transfer_asset("wallet1b", "wallet2", "asset1", 30.0).unwrap_err();
+        assert!(error.contains("owner alice daily cap of 300 would be exceeded (250 used, 60 requested)"), "{}", error);
+
+        let utilisation = core.get_limit_utilisation(&owner).unwrap();
+        assert_eq!((utilisation.daily_spent, utilisation.daily_remaining), (250.0, Some(50.0)));
+        assert_eq!(core.get_limit_utilisation(&wallet).unwrap().daily_spent, 100.0);
+
+        // A day later the daily cap has room again but the weekly one does not
+        clock.advance(SECONDS_PER_DAY);
+        core.transfer_asset("wallet1b", "wallet2", "asset1", 100.0).unwrap();
+        let error = core.transfer_asset("wallet1b", "wallet2", "asset1", 30.0).unwrap_err();
+        assert!(error.contains("weekly cap of 500"), "{}", error);
+        let utilisation = core.get_limit_utilisation(&owner).unwrap();
+        assert_eq!((utilisation.daily_spent, utilisation.weekly_spent), (200.0, 450.0));
+
+        // Other owners are unaffected
+        core.transfer_asset("wallet2", "wallet3", "asset1", 200.0).unwrap();
+
+        // The owner cannot lift their own limits, and lifting and restoring them keeps the usage
+        let alice = Subject::new("alice", &[Role::Investor]);
+        assert!(is_permission_denied(&core.as_subject(&alice, || core.remove_spending_limits(&owner)).unwrap_err()));
+        let officer = Subject::new("officer", &[Role::ComplianceOfficer]);
+        let limits = core.get_limit_utilisation(&owner).unwrap().limits.unwrap();
+        core.as_subject(&officer, || core.remove_spending_limits(&owner)).unwrap();
+        core.as_subject(&officer, || core.set_spending_limits(owner.clone(), limits)).unwrap();
+        assert_eq!(core.get_limit_utilisation(&owner).unwrap().weekly_spent, 450.0);
+    }
+
+    #[test]
+    fn test_counterparty_lists_and_time_windows() {
+        let clock = ManualClock::new(10 * SECONDS_PER_DAY + 8 * 3_600);
+        let core = setup(&clock);
+        core.set_spending_limits(
+            LimitTarget::Wallet("wallet1".to_string()),
+            SpendingLimits {
+                allowed_counterparties: Some(vec!["bob".to_string(), "wallet3".to_string()]),
+                denied_counterparties: vec!["wallet3".to_string()],
+                // 09:00 to 17:00 UTC
+                window: Some(TimeWindow {
+                    start_secs: 9 * 3_600,
+                    end_secs: 17 * 3_600,
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let error = core.transfer_asset("wallet1", "wallet2", "asset1", 1.0).unwrap_err();
+        assert!(error.contains("may only send between"), "{}", error);
+        assert!(!core.get_limit_utilisation(&LimitTarget::Wallet("wallet1".to_string())).unwrap().within_window);
+
+        clock.advance(3_600);
+        core.transfer_asset("wallet1", "wallet2", "asset1", 1.0).unwrap();
+        let error = core.transfer_asset("wallet1", "wallet3", "asset1", 1.0).unwrap_err();
+        assert!(error.contains("may not send to wallet3"), "{}", error);
+        let error = core.transfer_asset("wallet1", "wallet1b", "asset1", 1.0).unwrap_err();
+        assert!(error.contains("may only send to bob, wallet3"), "{}", error);
+
+        let wraps = TimeWindow {
+            start_secs: 22 * 3_600,
+            end_secs: 2 * 3_600,
+        };
+        assert!(wraps.contains(23 * 3_600) && wraps.contains(SECONDS_PER_DAY + 3_600));
+        assert!(!wraps.contains(12 * 3_600));
+    }
+}
@@ -0,0 +1,34 @@
+//! Fixtures shared by the unit tests
+
+use crate::core_component::{AssetType, ComplianceStatus, DigitalWallet, TokenizedAsset, WalletType};
+use std::collections::HashMap;
+
+/// A pending asset named after its ID, worth 1 per unit
+///
+/// Override other fields with struct update syntax, e.g. `TokenizedAsset { value: 10.0, ..asset(..) }`.
+pub fn asset(id: &str, owner: &str, asset_type: AssetType) -> TokenizedAsset {
+    TokenizedAsset {
+        id: id.to_string(),
+        name: id.to_string(),
+        asset_type,
+        value: 1.0,
+        owner: owner.to_string(),
+        metadata: HashMap::new(),
+        compliance_status: ComplianceStatus::Pending,
+        created_at: 0,
+        updated_at: 0,
+    }
+}
+
+/// An empty wallet with no balance
+pub fn wallet(id: &str, owner: &str, wallet_type: WalletType) -> DigitalWallet {
+    DigitalWallet {
+        id: id.to_string(),
+        owner: owner.to_string(),
+        assets: vec![],
+        balance: 0.0,
+        wallet_type,
+        created_at: 0,
+        updated_at: 0,
+    }
+}
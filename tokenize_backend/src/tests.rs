@@ -1,9 +1,34 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::auth::{self, Authenticator, NewApiKey, ADMIN_ROLE};
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, DigitalWallet, TokenizationCore, TokenizedAsset, WalletType};
+    use crate::live_feed::LiveFeed;
+    use crate::models::InMemoryDatabase;
+    use crate::nav::FundProfile;
+    use crate::notifications::NotificationHub;
+    use crate::routes;
+    use crate::webhooks::{HttpTransport, RetryPolicy, WebhookDispatcher};
+    use warp::Filter;
     use warp::test::request;
+    use std::collections::HashMap;
     use std::sync::{Arc, RwLock};
 
+    /// Authenticator with one admin API key
+    fn test_auth() -> (Arc<Authenticator>, String) {
+        let auth = Authenticator::new(Arc::new(ManualClock::new(100)));
+        let issued = auth
+            .issue_api_key(
+                NewApiKey {
+                    subject: "admin".to_string(),
+                    roles: vec![ADMIN_ROLE.to_string()],
+                },
+                "test",
+            )
+            .unwrap();
+        (Arc::new(auth), issued.key)
+    }
+
     #[tokio::test]
     async fn test_get_all_components() {
         // Create a simple in-memory database for testing
@@ -23,4 +48,624 @@ mod tests {
             
         assert_eq!(resp.status(), 200);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_get_asset_nav() {
+        let clock = ManualClock::new(1_000);
+        let core = Arc::new(TokenizationCore::with_clock(Arc::new(clock.clone())));
+        core.create_asset(TokenizedAsset {
+            id: "fund1".to_string(),
+            name: "Income Fund".to_string(),
+            asset_type: AssetType::Fund,
+            value: 0.0,
+            owner: "manager".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.register_fund(FundProfile::new("fund1", 10.0, vec![], 1_000)).unwrap();
+        core.calculate_nav("fund1").unwrap();
+
+        let (auth, key) = test_auth();
+        let api = routes::core_routes(core, auth);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/assets/fund1/nav?at=1500")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["nav_per_unit"], 10.0);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/assets/fund1/nav?at=999")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_get_cap_table_and_integrity() {
+        let core = Arc::new(TokenizationCore::new());
+        core.create_asset(TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Common Stock".to_string(),
+            asset_type: AssetType::Equity,
+            value: 1.0,
+            owner: "issuer".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.create_wallet(DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "user1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.mint_to_wallet("wallet1", "asset1", 10.0).unwrap();
+
+        let (auth, key) = test_auth();
+        let api = routes::core_routes(core, auth);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/assets/asset1/cap-table")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["entries"][0]["ownership_pct"], 100.0);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/integrity")
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["issues"].as_array().unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_and_verify_audit_trail() {
+        let core = Arc::new(TokenizationCore::new());
+        core.with_actor("issuer-admin", || {
+            core.create_asset(TokenizedAsset {
+                id: "asset1".to_string(),
+                name: "Common Stock".to_string(),
+                asset_type: AssetType::Equity,
+                value: 1.0,
+                owner: "issuer".to_string(),
+                metadata: HashMap::new(),
+                compliance_status: ComplianceStatus::Pending,
+                created_at: 0,
+                updated_at: 0,
+            })
+        }).unwrap();
+        core.perform_compliance_check("asset1").unwrap();
+
+        let (auth, key) = test_auth();
+        let api = routes::core_routes(core, auth);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/audit?entity_type=Asset&entity_id=asset1&actor=issuer-admin")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"][0]["action"], "Create");
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/audit/verify")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_get_wallet_as_of() {
+        let clock = ManualClock::new(100);
+        let core = Arc::new(TokenizationCore::with_clock(Arc::new(clock.clone())));
+        core.create_asset(TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Common Stock".to_string(),
+            asset_type: AssetType::Equity,
+            value: 1.0,
+            owner: "issuer".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.create_wallet(DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "user1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        clock.set(200);
+        core.mint_to_wallet("wallet1", "asset1", 10.0).unwrap();
+        clock.set(300);
+        core.burn_from_wallet("wallet1", "asset1", 4.0).unwrap();
+
+        let (auth, key) = test_auth();
+        let api = routes::core_routes(core, auth);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/wallets/wallet1?at=250")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["holdings"]["asset1"], 10.0);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/wallets/wallet1")
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["holdings"]["asset1"], 6.0);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/core/events?after=2")
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_change_queues_webhook_delivery() {
+        let db = Arc::new(RwLock::new(InMemoryDatabase {
+            components: Vec::new(),
+            main_type_index: HashMap::new(),
+            sub_type_index: HashMap::new(),
+        }));
+        let hub = NotificationHub::new();
+        let dispatcher = WebhookDispatcher::new(
+            Arc::new(HttpTransport::default()),
+            RetryPolicy::default(),
+            Arc::new(ManualClock::new(100)),
+        );
+        hub.subscribe(dispatcher.clone()).unwrap();
+        let (auth, key) = test_auth();
+        let api = routes::catalog_routes(db.clone(), hub, auth.clone()).or(routes::webhook_routes(dispatcher, auth));
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("POST")
+            .path("/api/webhooks")
+            .json(&serde_json::json!({
+                "url": "http://127.0.0.1:9/hook",
+                "secret": "s3cret",
+                "event_types": ["catalog.*"]
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 201);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(body["data"].get("secret").is_none());
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("POST")
+            .path("/api/components")
+            .json(&serde_json::json!({
+                "main_type": "Custody",
+                "sub_type": "Cold Storage",
+                "components": "HSM"
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 201);
+        assert_eq!(db.read().unwrap().components[0].id, 1);
+
+        let resp = request()
+            .header("x-api-key", &key)
+            .method("GET")
+            .path("/api/webhooks/deliveries")
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"][0]["notification"]["event_type"], "catalog.component_added");
+        assert_eq!(body["data"][0]["status"], "Pending");
+    }
+
+    #[tokio::test]
+    async fn test_stream_resumes_over_websocket() {
+        let hub = NotificationHub::new();
+        let feed = LiveFeed::new(10);
+        hub.subscribe(feed.clone()).unwrap();
+        hub.publish("catalog.component_added", 100, serde_json::json!({"id": 1})).unwrap();
+        hub.publish("asset.created", 100, serde_json::json!({"id": "asset1"})).unwrap();
+        hub.publish("catalog.component_added", 101, serde_json::json!({"id": 2})).unwrap();
+        let (auth, _) = test_auth();
+        let api = routes::stream_routes(feed, auth);
+
+        let mut client = warp::test::ws()
+            .path("/api/stream?topics=catalog&last_event_id=1")
+            .handshake(api)
+            .await
+            .unwrap();
+        let message = client.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+        assert_eq!(body["id"], 3);
+        assert_eq!(body["data"]["id"], 2);
+
+        hub.publish("asset.minted", 102, serde_json::Value::Null).unwrap();
+        hub.publish("catalog.component_added", 102, serde_json::json!({"id": 3})).unwrap();
+        let message = client.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+        assert_eq!(body["id"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_auth_rejections_use_api_response() {
+        let clock = ManualClock::new(100);
+        let core = Arc::new(TokenizationCore::with_clock(Arc::new(clock)));
+        let (auth, admin_key) = test_auth();
+        let investor_key = auth
+            .issue_api_key(
+                NewApiKey {
+                    subject: "investor-1".to_string(),
+                    roles: vec!["investor".to_string()],
+                },
+                "admin",
+            )
+            .unwrap()
+            .key;
+        let api = routes::core_routes(core, auth.clone())
+            .or(routes::auth_routes(auth.clone()))
+            .or(routes::stream_routes(LiveFeed::new(10), auth))
+            .recover(auth::handle_rejection);
+
+        let resp = request().method("GET").path("/api/core/integrity").reply(&api).await;
+        assert_eq!(resp.status(), 401);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["message"], "Authentication required");
+
+        let resp = request()
+            .method("GET")
+            .path("/api/core/integrity")
+            .header("authorization", "Bearer not-a-jwt")
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 401);
+
+        let resp = request()
+            .method("GET")
+            .path("/api/auth/me")
+            .header("x-api-key", &investor_key)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["subject"], "investor-1");
+
+        // Only admins manage API keys
+        let resp = request()
+            .method("POST")
+            .path("/api/auth/api-keys")
+            .header("x-api-key", &investor_key)
+            .json(&serde_json::json!({"subject": "someone"}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["message"], "investor-1 may not perform ManageApiKeys");
+
+        let resp = request()
+            .method("POST")
+            .path("/api/auth/api-keys")
+            .header("x-api-key", &admin_key)
+            .json(&serde_json::json!({"subject": "auditor-1", "roles": ["auditor"]}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 201);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["record"]["created_by"], "admin");
+
+        // The catalog can be streamed anonymously, other topics cannot
+        let resp = request().method("GET").path("/api/stream?topics=asset").reply(&api).await;
+        assert_eq!(resp.status(), 401);
+        assert!(warp::test::ws()
+            .path("/api/stream?topics=catalog")
+            .handshake(api.clone())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_role_and_ownership_checks_at_http_layer() {
+        let clock = ManualClock::new(100);
+        let core = Arc::new(TokenizationCore::with_clock(Arc::new(clock)));
+        for (id, owner) in [("wallet1", "investor-1"), ("wallet2", "investor-2")] {
+            core.create_wallet(DigitalWallet {
+                id: id.to_string(),
+                owner: owner.to_string(),
+                assets: vec![],
+                balance: 0.0,
+                wallet_type: WalletType::Custodial,
+                created_at: 0,
+                updated_at: 0,
+            }).unwrap();
+        }
+        let (auth, _) = test_auth();
+        let key_for = |subject: &str, role: &str| {
+            auth.issue_api_key(
+                NewApiKey {
+                    subject: subject.to_string(),
+                    roles: vec![role.to_string()],
+                },
+                "admin",
+            )
+            .unwrap()
+            .key
+        };
+        let investor = key_for("investor-1", "investor");
+        let auditor = key_for("auditor-1", "auditor");
+        let api = routes::core_routes(core, auth.clone()).recover(auth::handle_rejection);
+
+        let cases = [
+            (&investor, "/api/core/wallets/wallet1", 200),
+            (&investor, "/api/core/wallets/wallet2", 403),
+            (&investor, "/api/core/wallets/wallet2?at=100", 403),
+            (&investor, "/api/core/audit", 403),
+            (&investor, "/api/core/integrity", 403),
+            (&auditor, "/api/core/wallets/wallet2", 200),
+            (&auditor, "/api/core/audit", 200),
+            (&auditor, "/api/core/events", 200),
+        ];
+        for (key, path, status) in cases {
+            let resp = request()
+                .method("GET")
+                .path(path)
+                .header("x-api-key", key.as_str())
+                .reply(&api)
+                .await;
+            assert_eq!(resp.status(), status, "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mint_goes_through_approval_over_http() {
+        let core = Arc::new(TokenizationCore::with_clock(Arc::new(ManualClock::new(100))));
+        core.create_asset(TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Common Stock".to_string(),
+            asset_type: AssetType::Equity,
+            value: 1.0,
+            owner: "issuer-1".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.create_wallet(DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "investor-1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        let (auth, admin) = test_auth();
+        let key_for = |subject: &str, role: &str| {
+            auth.issue_api_key(
+                NewApiKey {
+                    subject: subject.to_string(),
+                    roles: vec![role.to_string()],
+                },
+                "admin",
+            )
+            .unwrap()
+            .key
+        };
+        let issuer = key_for("issuer-1", "issuer");
+        let officer = key_for("officer-1", "compliance_officer");
+        let api = routes::approval_routes(core.clone(), auth.clone()).recover(auth::handle_rejection);
+
+        let resp = request()
+            .method("PUT")
+            .path("/api/core/approval-rules")
+            .header("x-api-key", issuer.as_str())
+            .json(&serde_json::json!({"operation": "Mint", "min_quantity": null, "required_approvals": 1, "expires_after_secs": 3600}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let resp = request()
+            .method("PUT")
+            .path("/api/core/approval-rules")
+            .header("x-api-key", admin.as_str())
+            .json(&serde_json::json!({"operation": "Mint", "min_quantity": null, "required_approvals": 1, "expires_after_secs": 3600}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = request()
+            .method("POST")
+            .path("/api/core/approvals")
+            .header("x-api-key", issuer.as_str())
+            .json(&serde_json::json!({"type": "Mint", "wallet_id": "wallet1", "asset_id": "asset1", "quantity": 50.0}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 202);
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 0.0);
+
+        // Issuers hold no ApproveActions permission, so cannot approve their own request
+        let resp = request()
+            .method("POST")
+            .path("/api/core/approvals/action-1/approve")
+            .header("x-api-key", issuer.as_str())
+            .json(&serde_json::json!({"comment": null}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let resp = request()
+            .method("POST")
+            .path("/api/core/approvals/action-1/approve")
+            .header("x-api-key", officer.as_str())
+            .json(&serde_json::json!({"comment": "checked"}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(core.get_holding("wallet1", "asset1").unwrap(), 50.0);
+
+        let resp = request()
+            .method("GET")
+            .path("/api/core/approvals?status=Executed")
+            .header("x-api-key", officer.as_str())
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"][0]["history"][0]["approver"], "officer-1");
+    }
+
+    #[tokio::test]
+    async fn test_policy_dry_run() {
+        let core = Arc::new(TokenizationCore::new());
+        let (auth, admin) = test_auth();
+        let investor = auth
+            .issue_api_key(
+                NewApiKey {
+                    subject: "investor-1".to_string(),
+                    roles: vec!["investor".to_string()],
+                },
+                "admin",
+            )
+            .unwrap()
+            .key;
+        let api = routes::policy_routes(core, auth.clone()).recover(auth::handle_rejection);
+        let body = serde_json::json!({
+            "input": {"operation": {"kind": "transfer", "quantity": 1000}},
+            "policies": [{
+                "name": "limits",
+                "rules": [{
+                    "id": "cap",
+                    "effect": "deny",
+                    "operations": ["transfer"],
+                    "when": [{"path": "operation.quantity", "op": "gte", "value": 1000}],
+                    "message": "Too large"
+                }]
+            }]
+        });
+
+        let resp = request()
+            .method("POST")
+            .path("/api/policies/dry-run")
+            .header("x-api-key", investor.as_str())
+            .json(&body)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 403);
+
+        let resp = request()
+            .method("POST")
+            .path("/api/policies/dry-run")
+            .header("x-api-key", admin.as_str())
+            .json(&body)
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"]["allowed"], false);
+        assert_eq!(body["data"]["explanations"][0]["rule"], "cap");
+    }
+
+    #[tokio::test]
+    async fn test_frozen_wallet_is_locked_over_http() {
+        let core = Arc::new(TokenizationCore::new());
+        core.create_asset(TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Common Stock".to_string(),
+            asset_type: AssetType::Equity,
+            value: 1.0,
+            owner: "issuer-1".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Approved,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        core.create_wallet(DigitalWallet {
+            id: "wallet1".to_string(),
+            owner: "investor-1".to_string(),
+            assets: vec![],
+            balance: 0.0,
+            wallet_type: WalletType::Custodial,
+            created_at: 0,
+            updated_at: 0,
+        }).unwrap();
+        let (auth, admin) = test_auth();
+        let api = routes::freeze_routes(core.clone(), auth.clone())
+            .or(routes::approval_routes(core.clone(), auth.clone()))
+            .recover(auth::handle_rejection);
+
+        let resp = request()
+            .method("POST")
+            .path("/api/core/freezes")
+            .header("x-api-key", admin.as_str())
+            .json(&serde_json::json!({"target": {"type": "Wallet", "id": "wallet1"}, "reason": "suspected_fraud", "note": null, "expires_at": null}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 201);
+
+        let resp = request()
+            .method("POST")
+            .path("/api/core/approvals")
+            .header("x-api-key", admin.as_str())
+            .json(&serde_json::json!({"type": "Mint", "wallet_id": "wallet1", "asset_id": "asset1", "quantity": 50.0}))
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 423);
+
+        let resp = request()
+            .method("DELETE")
+            .path("/api/core/freezes/global")
+            .header("x-api-key", admin.as_str())
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 404);
+        let resp = request()
+            .method("DELETE")
+            .path("/api/core/freezes/wallets/wallet1")
+            .header("x-api-key", admin.as_str())
+            .reply(&api)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp = request()
+            .method("GET")
+            .path("/api/core/freezes")
+            .header("x-api-key", admin.as_str())
+            .reply(&api)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["data"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,474 @@
+//! Lockups, vesting schedules and holding-period clocks
+//!
+//! Restrictions are attached to a single holding (a wallet's position in one asset).
+//! Unvested units and units under an unexpired holding-period clock (lockups,
+//! Rule 144, Reg S distribution compliance periods) cannot be transferred out of
+//! the wallet until they are released.
+
+use crate::access_control::Permission;
+use crate::audit::{AuditAction, EntityType};
+use crate::core_component::{TokenizationCore, QUANTITY_EPSILON};
+use serde::{Deserialize, Serialize};
+
+/// A fixed quantity released at a given time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tranche {
+    pub release_at: u64,
+    pub quantity: f64,
+}
+
+/// Schedule describing how a grant of units vests over time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum VestingSchedule {
+    /// The full grant vests at the cliff
+    Cliff { total: f64, cliff_at: u64 },
+    /// The grant accrues linearly from `start` to `end`, but nothing vests before `cliff_at`
+    Linear {
+        total: f64,
+        start: u64,
+        cliff_at: u64,
+        end: u64,
+    },
+    /// The grant vests in explicit tranches
+    Custom { tranches: Vec<Tranche> },
+}
+
+impl VestingSchedule {
+    /// Check that the schedule is well formed
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            VestingSchedule::Cliff { total, .. } => {
+                if *total <= 0.0 {
+                    return Err("Vesting total must be positive".to_string());
+                }
+            }
+            VestingSchedule::Linear {
+                total,
+                start,
+                cliff_at,
+                end,
+            } => {
+                if *total <= 0.0 {
+                    return Err("Vesting total must be positive".to_string());
+                }
+                if end <= start {
+                    return Err("Vesting end must be after start".to_string());
+                }
+                if cliff_at < start || cliff_at > end {
+                    return Err("Vesting cliff must fall between start and end".to_string());
+                }
+            }
+            VestingSchedule::Custom { tranches } => {
+                if tranches.is_empty() {
+                    return Err("Custom schedule needs at least one tranche".to_string());
+                }
+                if tranches.iter().any(|t| t.quantity <= 0.0) {
+                    return Err("Tranche quantity must be positive".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Total number of units covered by the schedule
+    pub fn total(&self) -> f64 {
+        match self {
+            VestingSchedule::Cliff { total, .. } | VestingSchedule::Linear { total, .. } => *total,
+            VestingSchedule::Custom { tranches } => tranches.iter().map(|t| t.quantity).sum(),
+        }
+    }
+
+    /// Number of units vested at the given timestamp
+    pub fn vested_at(&self, at: u64) -> f64 {
+        match self {
+            VestingSchedule::Cliff { total, cliff_at } => {
+                if at >= *cliff_at {
+                    *total
+                } else {
+                    0.0
+                }
+            }
+            VestingSchedule::Linear {
+                total,
+                start,
+                cliff_at,
+                end,
+            } => {
+                if at < *cliff_at {
+                    0.0
+                } else if at >= *end {
+                    *total
+                } else {
+                    total * (at - start) as f64 / (end - start) as f64
+                }
+            }
+            VestingSchedule::Custom { tranches } => tranches
+                .iter()
+                .filter(|t| at >= t.release_at)
+                .map(|t| t.quantity)
+                .sum(),
+        }
+    }
+
+    /// Number of units still unvested at the given timestamp
+    pub fn unvested_at(&self, at: u64) -> f64 {
+        (self.total() - self.vested_at(at)).max(0.0)
+    }
+}
+
+/// Regulatory or contractual basis for a holding-period clock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HoldingPeriodRule {
+    Lockup,
+    Rule144,
+    RegS,
+    Custom(String),
+}
+
+/// Clock that keeps a quantity of units locked until its release time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldingPeriod {
+    pub rule: HoldingPeriodRule,
+    pub quantity: f64,
+    pub starts_at: u64,
+    pub release_at: u64,
+}
+
+impl HoldingPeriod {
+    /// Create a holding period running for `duration_secs` from `starts_at`
+    pub fn new(rule: HoldingPeriodRule, quantity: f64, starts_at: u64, duration_secs: u64) -> Self {
+        Self {
+            rule,
+            quantity,
+            starts_at,
+            release_at: starts_at.saturating_add(duration_secs),
+        }
+    }
+
+    /// Whether the clock still restricts its units at the given timestamp
+    pub fn is_active_at(&self, at: u64) -> bool {
+        at < self.release_at
+    }
+}
+
+/// All time-based restrictions attached to one holding
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HoldingRestrictions {
+    pub vesting: Vec<VestingSchedule>,
+    pub holding_periods: Vec<HoldingPeriod>,
+}
+
+impl HoldingRestrictions {
+    /// Units vested across all schedules at the given timestamp
+    pub fn vested_at(&self, at: u64) -> f64 {
+        self.vesting.iter().map(|s| s.vested_at(at)).sum()
+    }
+
+    /// Units still unvested across all schedules at the given timestamp
+    pub fn unvested_at(&self, at: u64) -> f64 {
+        self.vesting.iter().map(|s| s.unvested_at(at)).sum()
+    }
+
+    /// Units held back by active holding-period clocks at the given timestamp
+    pub fn locked_at(&self, at: u64) -> f64 {
+        self.holding_periods
+            .iter()
+            .filter(|p| p.is_active_at(at))
+            .map(|p| p.quantity)
+            .sum()
+    }
+
+    /// Units held back by vesting or holding periods at the given timestamp
+    ///
+    /// A grant is usually both vesting and under a lockup, so the two restrictions
+    /// cover the same units: the larger of them applies rather than their sum.
+    pub fn restricted_at(&self, at: u64) -> f64 {
+        self.unvested_at(at).max(self.locked_at(at))
+    }
+
+    /// Units of a holding of size `held` that may be transferred at the given timestamp
+    pub fn transferable_at(&self, held: f64, at: u64) -> f64 {
+        (held - self.restricted_at(at)).max(0.0)
+    }
+
+    /// Earliest future time at which any restricted units are released
+    pub fn next_release_after(&self, at: u64) -> Option<u64> {
+        let vesting_events = self.vesting.iter().filter_map(|s| match s {
+            VestingSchedule::Cliff { cliff_at, .. } => Some(*cliff_at),
+            VestingSchedule::Linear { cliff_at, end, .. } => {
+                if at < *cliff_at {
+                    Some(*cliff_at)
+                } else {
+                    Some(*end)
+                }
+            }
+            VestingSchedule::Custom { tranches } => tranches
+                .iter()
+                .map(|t| t.release_at)
+                .filter(|r| *r > at)
+                .min(),
+        });
+        let clock_events = self.holding_periods.iter().map(|p| p.release_at);
+        vesting_events.chain(clock_events).filter(|r| *r > at).min()
+    }
+
+    /// Rescale every restricted quantity, e.g. after a split
+    pub fn scale(&mut self, factor: f64) {
+        for schedule in &mut self.vesting {
+            match schedule {
+                VestingSchedule::Cliff { total, .. } | VestingSchedule::Linear { total, .. } => *total *= factor,
+                VestingSchedule::Custom { tranches } => {
+                    for tranche in tranches {
+                        tranche.quantity *= factor;
+                    }
+                }
+            }
+        }
+        for period in &mut self.holding_periods {
+            period.quantity *= factor;
+        }
+    }
+
+    /// Reject a transfer of `quantity` out of a holding of size `held` if it would
+    /// touch restricted units
+    pub fn check_transfer(&self, held: f64, quantity: f64, at: u64) -> Result<(), String> {
+        let transferable = self.transferable_at(held, at);
+        if quantity > transferable + QUANTITY_EPSILON {
+            let release = self
+                .next_release_after(at)
+                .map(|r| format!(", next release at {}", r))
+                .unwrap_or_default();
+            return Err(format!(
+                "Transfer of {} exceeds transferable quantity {} (unvested {}, locked {}){}",
+                quantity,
+                transferable,
+                self.unvested_at(at),
+                self.locked_at(at),
+                release
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TokenizationCore {
+    /// Attach a vesting schedule to a wallet's holding of an asset
+    pub fn add_vesting_schedule(
+        &self,
+        wallet_id: &str,
+        asset_id: &str,
+        schedule: VestingSchedule,
+    ) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        schedule.validate()?;
+        self.ensure_wallet_and_asset_exist(wallet_id, asset_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[asset_id])?;
+        let mut restrictions = self.restrictions.write().map_err(|_| "Failed to acquire write lock")?;
+        let restriction = restrictions.entry((wallet_id.to_string(), asset_id.to_string())).or_default();
+        let before = restriction.clone();
+        restriction.vesting.push(schedule);
+        self.record_audit(
+            AuditAction::Update,
+            EntityType::HoldingRestriction,
+            &format!("{}/{}", wallet_id, asset_id),
+            Some(&before),
+            Some(&*restriction),
+        )?;
+        Ok(())
+    }
+
+    /// Start a holding-period clock on a wallet's holding of an asset
+    pub fn add_holding_period(
+        &self,
+        wallet_id: &str,
+        asset_id: &str,
+        period: HoldingPeriod,
+    ) -> Result<(), String> {
+        self.authorize_asset(Permission::UpdateAsset, asset_id)?;
+        if period.quantity <= 0.0 {
+            return Err("Holding period quantity must be positive".to_string());
+        }
+        self.ensure_wallet_and_asset_exist(wallet_id, asset_id)?;
+        self.ensure_ids_not_frozen(&[wallet_id], &[asset_id])?;
+        let mut restrictions = self.restrictions.write().map_err(|_| "Failed to acquire write lock")?;
+        let restriction = restrictions.entry((wallet_id.to_string(), asset_id.to_string())).or_default();
+        let before = restriction.clone();
+        restriction.holding_periods.push(period);
+        self.record_audit(
+            AuditAction::Update,
+            EntityType::HoldingRestriction,
+            &format!("{}/{}", wallet_id, asset_id),
+            Some(&before),
+            Some(&*restriction),
+        )?;
+        Ok(())
+    }
+
+    /// Get the restrictions attached to a wallet's holding of an asset
+    pub fn get_holding_restrictions(&self, wallet_id: &str, asset_id: &str) -> Result<HoldingRestrictions, String> {
+        let restrictions = self.restrictions.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(restrictions
+            .get(&(wallet_id.to_string(), asset_id.to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Units of a holding that are vested at the given timestamp
+    ///
+    /// Units not covered by any vesting schedule count as vested.
+    pub fn vested_quantity(&self, wallet_id: &str, asset_id: &str, at: u64) -> Result<f64, String> {
+        let held = self.get_holding(wallet_id, asset_id)?;
+        let unvested = self.unvested_quantity(wallet_id, asset_id, at)?;
+        Ok((held - unvested).max(0.0))
+    }
+
+    /// Units of a holding that are still unvested at the given timestamp
+    pub fn unvested_quantity(&self, wallet_id: &str, asset_id: &str, at: u64) -> Result<f64, String> {
+        let held = self.get_holding(wallet_id, asset_id)?;
+        let restrictions = self.get_holding_restrictions(wallet_id, asset_id)?;
+        Ok(restrictions.unvested_at(at).min(held))
+    }
+
+    /// Units of a holding that may be transferred at the given timestamp
+    pub fn transferable_quantity(&self, wallet_id: &str, asset_id: &str, at: u64) -> Result<f64, String> {
+        let held = self.get_holding(wallet_id, asset_id)?;
+        let restrictions = self.get_holding_restrictions(wallet_id, asset_id)?;
+        Ok(restrictions.transferable_at(held, at))
+    }
+
+    fn ensure_wallet_and_asset_exist(&self, wallet_id: &str, asset_id: &str) -> Result<(), String> {
+        if self.get_wallet(wallet_id)?.is_none() {
+            return Err("Wallet not found".to_string());
+        }
+        if self.get_asset(asset_id)?.is_none() {
+            return Err("Asset not found".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizedAsset, WalletType};
+    use crate::test_support::{asset, wallet};
+    use std::sync::Arc;
+
+    fn setup(clock: &ManualClock) -> TokenizationCore {
+        let core = TokenizationCore::with_clock(Arc::new(clock.clone()));
+        core.create_asset(TokenizedAsset {
+            value: 10.0,
+            compliance_status: ComplianceStatus::Approved,
+            ..asset("asset1", "issuer", AssetType::Equity)
+        })
+        .unwrap();
+        for id in ["wallet1", "wallet2"] {
+            core.create_wallet(wallet(id, id, WalletType::Custodial)).unwrap();
+        }
+        core
+    }
+
+    #[test]
+    fn test_schedule_vesting_curves() {
+        let cliff = VestingSchedule::Cliff {
+            total: 100.0,
+            cliff_at: 50,
+        };
+        assert_eq!(cliff.vested_at(49), 0.0);
+        assert_eq!(cliff.vested_at(50), 100.0);
+
+        let linear = VestingSchedule::Linear {
+            total: 100.0,
+            start: 0,
+            cliff_at: 25,
+            end: 100,
+        };
+        assert_eq!(linear.vested_at(24), 0.0);
+        assert_eq!(linear.vested_at(25), 25.0);
+        assert_eq!(linear.vested_at(60), 60.0);
+        assert_eq!(linear.vested_at(200), 100.0);
+
+        let custom = VestingSchedule::Custom {
+            tranches: vec![
+                Tranche { release_at: 10, quantity: 30.0 },
+                Tranche { release_at: 20, quantity: 70.0 },
+            ],
+        };
+        assert_eq!(custom.total(), 100.0);
+        assert_eq!(custom.vested_at(15), 30.0);
+        assert_eq!(custom.unvested_at(15), 70.0);
+    }
+
+    #[test]
+    fn test_unvested_units_block_transfers_until_release() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.mint_to_wallet("wallet1", "asset1", 100.0).unwrap();
+        core.add_vesting_schedule(
+            "wallet1",
+            "asset1",
+            VestingSchedule::Linear {
+                total: 100.0,
+                start: 0,
+                cliff_at: 100,
+                end: 400,
+            },
+        )
+        .unwrap();
+
+        assert!(core.transfer_asset("wallet1", "wallet2", "asset1", 1.0).is_err());
+
+        clock.set(200);
+        assert_eq!(core.vested_quantity("wallet1", "asset1", 200).unwrap(), 50.0);
+        assert_eq!(core.unvested_quantity("wallet1", "asset1", 200).unwrap(), 50.0);
+        assert!(core.transfer_asset("wallet1", "wallet2", "asset1", 60.0).is_err());
+        core.transfer_asset("wallet1", "wallet2", "asset1", 50.0).unwrap();
+        assert_eq!(core.get_holding("wallet2", "asset1").unwrap(), 50.0);
+    }
+
+    #[test]
+    fn test_holding_period_clock_releases_at_expiry() {
+        let clock = ManualClock::new(1_000);
+        let core = setup(&clock);
+        core.mint_to_wallet("wallet1", "asset1", 40.0).unwrap();
+        core.add_holding_period(
+            "wallet1",
+            "asset1",
+            HoldingPeriod::new(HoldingPeriodRule::Rule144, 40.0, 1_000, 500),
+        )
+        .unwrap();
+
+        let err = core.transfer_asset("wallet1", "wallet2", "asset1", 10.0).unwrap_err();
+        assert!(err.contains("next release at 1500"));
+        assert_eq!(core.transferable_quantity("wallet1", "asset1", 1_499).unwrap(), 0.0);
+
+        clock.set(1_500);
+        core.transfer_asset("wallet1", "wallet2", "asset1", 40.0).unwrap();
+        assert!(!core.get_wallet("wallet1").unwrap().unwrap().assets.contains(&"asset1".to_string()));
+    }
+
+    #[test]
+    fn test_vesting_and_lockup_on_the_same_grant_overlap() {
+        let clock = ManualClock::new(0);
+        let core = setup(&clock);
+        core.mint_to_wallet("wallet1", "asset1", 100.0).unwrap();
+        core.add_vesting_schedule(
+            "wallet1",
+            "asset1",
+            VestingSchedule::Cliff {
+                total: 60.0,
+                cliff_at: 200,
+            },
+        )
+        .unwrap();
+        core.add_holding_period("wallet1", "asset1", HoldingPeriod::new(HoldingPeriodRule::Lockup, 80.0, 0, 100))
+            .unwrap();
+
+        // 80 locked, 60 of them also unvested: 20 may move, not 100 - 60 - 80
+        assert_eq!(core.transferable_quantity("wallet1", "asset1", 50).unwrap(), 20.0);
+        assert_eq!(core.transferable_quantity("wallet1", "asset1", 150).unwrap(), 40.0);
+        assert_eq!(core.transferable_quantity("wallet1", "asset1", 250).unwrap(), 100.0);
+        assert!(core.add_holding_period("ghost", "asset1", HoldingPeriod::new(HoldingPeriodRule::Lockup, 1.0, 0, 1)).is_err());
+    }
+}
@@ -0,0 +1,565 @@
+//! Outbound webhooks
+//!
+//! Subscribers register a URL, a shared secret and the event types they care
+//! about. Every matching notification becomes a delivery: the notification is
+//! POSTed as JSON, signed with HMAC-SHA256 over `"{timestamp}.{body}"`, and retried
+//! with exponential backoff until it succeeds or the retry budget runs out, at
+//! which point it moves to the dead-letter queue. Every attempt is kept in the
+//! delivery log.
+
+use crate::clock::Clock;
+use crate::notifications::{event_type_matches, Notification, NotificationSink};
+use hmac::{Hmac, Mac};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Header carrying `sha256=<hex digest>` of the signed payload
+pub const SIGNATURE_HEADER: &str = "X-Tokenize-Signature";
+/// Header carrying the Unix timestamp that was signed together with the body
+pub const TIMESTAMP_HEADER: &str = "X-Tokenize-Timestamp";
+/// Header carrying the notification's event type
+pub const EVENT_HEADER: &str = "X-Tokenize-Event";
+/// Header carrying the delivery ID, stable across retries
+pub const DELIVERY_HEADER: &str = "X-Tokenize-Delivery";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signature of a payload as sent in `SIGNATURE_HEADER`
+pub fn sign_payload(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check a received signature in constant time
+pub fn verify_signature(secret: &str, timestamp: u64, body: &str, signature: &str) -> bool {
+    let Some(digest) = signature.strip_prefix("sha256=").and_then(|h| hex::decode(h).ok()) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac.verify_slice(&digest).is_ok()
+}
+
+/// A registered webhook endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// Event type filters such as `asset.transferred` or `wallet.*`; empty means all
+    pub event_types: Vec<String>,
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    pub active: bool,
+    pub created_at: u64,
+}
+
+impl WebhookSubscription {
+    pub fn wants(&self, event_type: &str) -> bool {
+        self.active
+            && (self.event_types.is_empty() || self.event_types.iter().any(|f| event_type_matches(f, event_type)))
+    }
+}
+
+/// Request body for registering a webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    pub secret: String,
+}
+
+/// How failed deliveries are retried
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first, before a delivery is dead-lettered
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub backoff_multiplier: u32,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            initial_backoff_ms: 1_000,
+            backoff_multiplier: 2,
+            max_backoff_ms: 300_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the attempt following failed attempt number `attempt` (1-based)
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        let factor = (self.backoff_multiplier.max(1) as u64).saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// Where a delivery stands
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    DeadLettered,
+}
+
+/// One POST of a delivery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub attempt: u32,
+    pub attempted_at: u64,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// A notification on its way to one subscriber
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub notification: Notification,
+    pub status: DeliveryStatus,
+    pub attempts: Vec<DeliveryAttempt>,
+}
+
+/// Boxed future returned by transports
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<u16, String>> + Send + 'a>>;
+
+/// Sends a signed payload and reports the HTTP status code
+pub trait WebhookTransport: Send + Sync {
+    fn post<'a>(&'a self, url: &'a str, headers: Vec<(&'static str, String)>, body: String) -> TransportFuture<'a>;
+}
+
+/// HTTP and HTTPS transport backed by hyper, verifying servers against the webpki roots
+pub struct HttpTransport {
+    client: hyper::Client<HttpsConnector<hyper::client::HttpConnector>>,
+    timeout: Duration,
+}
+
+impl HttpTransport {
+    pub fn new(timeout: Duration) -> Self {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+        Self {
+            client: hyper::Client::builder().build(connector),
+            timeout,
+        }
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+impl WebhookTransport for HttpTransport {
+    fn post<'a>(&'a self, url: &'a str, headers: Vec<(&'static str, String)>, body: String) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let mut request = hyper::Request::post(url).header("Content-Type", "application/json");
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let request = request.body(hyper::Body::from(body)).map_err(|e| e.to_string())?;
+            match tokio::time::timeout(self.timeout, self.client.request(request)).await {
+                Ok(Ok(response)) => Ok(response.status().as_u16()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("Request timed out".to_string()),
+            }
+        })
+    }
+}
+
+/// Registry of subscriptions plus the delivery queue, log and dead-letter queue
+pub struct WebhookDispatcher {
+    subscriptions: RwLock<HashMap<String, WebhookSubscription>>,
+    /// Delivery log, oldest first
+    deliveries: RwLock<Vec<WebhookDelivery>>,
+    queue: mpsc::UnboundedSender<String>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+    transport: Arc<dyn WebhookTransport>,
+    retry: RetryPolicy,
+    clock: Arc<dyn Clock>,
+    next_id: AtomicU64,
+}
+
+impl WebhookDispatcher {
+    pub fn new(transport: Arc<dyn WebhookTransport>, retry: RetryPolicy, clock: Arc<dyn Clock>) -> Arc<Self> {
+        let (queue, receiver) = mpsc::unbounded_channel();
+        Arc::new(Self {
+            subscriptions: RwLock::new(HashMap::new()),
+            deliveries: RwLock::new(Vec::new()),
+            queue,
+            receiver: Mutex::new(Some(receiver)),
+            transport,
+            retry,
+            clock,
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Spawn the worker that performs queued deliveries; only the first call starts one
+    pub fn start(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let mut receiver = self.receiver.lock().ok()?.take()?;
+        let dispatcher = self.clone();
+        Some(tokio::spawn(async move {
+            while let Some(delivery_id) = receiver.recv().await {
+                let dispatcher = dispatcher.clone();
+                tokio::spawn(async move { dispatcher.deliver(&delivery_id).await });
+            }
+        }))
+    }
+
+    fn next_id(&self, prefix: &str) -> String {
+        format!("{}-{}", prefix, self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    /// Register a webhook endpoint
+    pub fn register(&self, new: NewWebhookSubscription) -> Result<WebhookSubscription, String> {
+        if !new.url.starts_with("http://") && !new.url.starts_with("https://") {
+            return Err("Webhook URL must be http or https".to_string());
+        }
+        if new.secret.is_empty() {
+            return Err("Webhook secret must not be empty".to_string());
+        }
+        let subscription = WebhookSubscription {
+            id: self.next_id("wh"),
+            url: new.url,
+            event_types: new.event_types,
+            secret: new.secret,
+            active: true,
+            created_at: self.clock.now(),
+        };
+        let mut subscriptions = self.subscriptions.write().map_err(|_| "Failed to acquire write lock")?;
+        subscriptions.insert(subscription.id.clone(), subscription.clone());
+        Ok(subscription)
+    }
+
+    /// Remove a webhook endpoint; its delivery history is kept
+    pub fn unregister(&self, subscription_id: &str) -> Result<(), String> {
+        let mut subscriptions = self.subscriptions.write().map_err(|_| "Failed to acquire write lock")?;
+        subscriptions.remove(subscription_id).map(|_| ()).ok_or("Webhook not found".to_string())
+    }
+
+    /// Get every registered endpoint, oldest first
+    pub fn get_subscriptions(&self) -> Result<Vec<WebhookSubscription>, String> {
+        let subscriptions = self.subscriptions.read().map_err(|_| "Failed to acquire read lock")?;
+        let mut result: Vec<WebhookSubscription> = subscriptions.values().cloned().collect();
+        result.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        Ok(result)
+    }
+
+    /// Get the delivery log, optionally for one subscriber, oldest first
+    pub fn get_deliveries(&self, subscription_id: Option<&str>) -> Result<Vec<WebhookDelivery>, String> {
+        let deliveries = self.deliveries.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(deliveries
+            .iter()
+            .filter(|d| subscription_id.is_none_or(|id| d.subscription_id == id))
+            .cloned()
+            .collect())
+    }
+
+    /// Get a single delivery
+    pub fn get_delivery(&self, delivery_id: &str) -> Result<Option<WebhookDelivery>, String> {
+        let deliveries = self.deliveries.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(deliveries.iter().find(|d| d.id == delivery_id).cloned())
+    }
+
+    /// Get deliveries that exhausted their retries
+    pub fn get_dead_letters(&self) -> Result<Vec<WebhookDelivery>, String> {
+        let deliveries = self.deliveries.read().map_err(|_| "Failed to acquire read lock")?;
+        Ok(deliveries
+            .iter()
+            .filter(|d| d.status == DeliveryStatus::DeadLettered)
+            .cloned()
+            .collect())
+    }
+
+    /// Move a dead-lettered delivery back onto the queue with a fresh retry budget
+    pub fn redeliver(&self, delivery_id: &str) -> Result<(), String> {
+        {
+            let mut deliveries = self.deliveries.write().map_err(|_| "Failed to acquire write lock")?;
+            let delivery = deliveries.iter_mut().find(|d| d.id == delivery_id).ok_or("Delivery not found")?;
+            if delivery.status != DeliveryStatus::DeadLettered {
+                return Err("Only dead-lettered deliveries can be redelivered".to_string());
+            }
+            delivery.status = DeliveryStatus::Pending;
+        }
+        self.queue.send(delivery_id.to_string()).map_err(|_| "Webhook queue is closed".to_string())
+    }
+
+    /// Run one delivery until it succeeds or is dead-lettered
+    async fn deliver(&self, delivery_id: &str) {
+        let Ok(Some(delivery)) = self.get_delivery(delivery_id) else {
+            return;
+        };
+        let Ok(Some(subscription)) = self
+            .subscriptions
+            .read()
+            .map(|s| s.get(&delivery.subscription_id).cloned())
+        else {
+            self.finish(delivery_id, DeliveryStatus::DeadLettered);
+            return;
+        };
+        let body = serde_json::to_string(&delivery.notification).unwrap_or_default();
+        // Attempts already made before a redelivery do not count against the new budget
+        let first_attempt = delivery.attempts.len() as u32 + 1;
+
+        for attempt in first_attempt..first_attempt + self.retry.max_attempts {
+            let timestamp = self.clock.now();
+            let headers = vec![
+                (SIGNATURE_HEADER, sign_payload(&subscription.secret, timestamp, &body)),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+                (EVENT_HEADER, delivery.notification.event_type.clone()),
+                (DELIVERY_HEADER, delivery.id.clone()),
+            ];
+            let result = self.transport.post(&subscription.url, headers, body.clone()).await;
+            let delivered = matches!(result, Ok(code) if (200..300).contains(&code));
+            let record = DeliveryAttempt {
+                attempt,
+                attempted_at: timestamp,
+                status_code: result.as_ref().ok().copied(),
+                error: match result {
+                    Ok(code) if !delivered => Some(format!("Endpoint responded with status {}", code)),
+                    Ok(_) => None,
+                    Err(e) => Some(e),
+                },
+            };
+            if let Ok(mut deliveries) = self.deliveries.write()
+                && let Some(entry) = deliveries.iter_mut().find(|d| d.id == delivery_id)
+            {
+                entry.attempts.push(record);
+            }
+            if delivered {
+                self.finish(delivery_id, DeliveryStatus::Delivered);
+                return;
+            }
+            if attempt + 1 < first_attempt + self.retry.max_attempts {
+                tokio::time::sleep(self.retry.backoff_after(attempt + 1 - first_attempt)).await;
+            }
+        }
+        self.finish(delivery_id, DeliveryStatus::DeadLettered);
+    }
+
+    fn finish(&self, delivery_id: &str, status: DeliveryStatus) {
+        if let Ok(mut deliveries) = self.deliveries.write()
+            && let Some(entry) = deliveries.iter_mut().find(|d| d.id == delivery_id)
+        {
+            entry.status = status;
+        }
+    }
+}
+
+impl NotificationSink for WebhookDispatcher {
+    fn notify(&self, notification: &Notification) {
+        let Ok(subscriptions) = self.subscriptions.read() else {
+            return;
+        };
+        let mut matching: Vec<&WebhookSubscription> =
+            subscriptions.values().filter(|s| s.wants(&notification.event_type)).collect();
+        matching.sort_by(|a, b| a.id.cmp(&b.id));
+        let Ok(mut deliveries) = self.deliveries.write() else {
+            return;
+        };
+        for subscription in matching {
+            let delivery = WebhookDelivery {
+                id: self.next_id("dlv"),
+                subscription_id: subscription.id.clone(),
+                notification: notification.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: Vec::new(),
+            };
+            let _ = self.queue.send(delivery.id.clone());
+            deliveries.push(delivery);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::core_component::{AssetType, ComplianceStatus, TokenizationCore, TokenizedAsset};
+    use std::net::SocketAddr;
+    use warp::Filter;
+
+    #[derive(Clone, Default)]
+    struct Stub {
+        received: Arc<Mutex<Vec<(String, String, String)>>>,
+        failures_left: Arc<AtomicU64>,
+    }
+
+    /// Local HTTP endpoint that fails the first `failures` requests with a 500
+    async fn start_stub(failures: u64) -> (Stub, SocketAddr) {
+        let stub = Stub::default();
+        stub.failures_left.store(failures, Ordering::SeqCst);
+        let state = stub.clone();
+        let route = warp::post()
+            .and(warp::header::<String>("x-tokenize-signature"))
+            .and(warp::header::<String>("x-tokenize-timestamp"))
+            .and(warp::body::bytes())
+            .map(move |signature: String, timestamp: String, body: warp::hyper::body::Bytes| {
+                let body = String::from_utf8_lossy(&body).to_string();
+                state.received.lock().unwrap().push((signature, timestamp, body));
+                let failing = state
+                    .failures_left
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok();
+                let status = if failing {
+                    warp::http::StatusCode::INTERNAL_SERVER_ERROR
+                } else {
+                    warp::http::StatusCode::OK
+                };
+                warp::reply::with_status("", status)
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        (stub, addr)
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 5,
+            backoff_multiplier: 2,
+            max_backoff_ms: 20,
+        }
+    }
+
+    async fn wait_until_settled(dispatcher: &WebhookDispatcher) -> Vec<WebhookDelivery> {
+        for _ in 0..200 {
+            let deliveries = dispatcher.get_deliveries(None).unwrap();
+            if !deliveries.is_empty() && deliveries.iter().all(|d| d.status != DeliveryStatus::Pending) {
+                return deliveries;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("deliveries did not settle");
+    }
+
+    fn create_asset(core: &TokenizationCore) {
+        core.create_asset(TokenizedAsset {
+            id: "asset1".to_string(),
+            name: "Test Asset".to_string(),
+            asset_type: AssetType::Equity,
+            value: 100.0,
+            owner: "issuer".to_string(),
+            metadata: HashMap::new(),
+            compliance_status: ComplianceStatus::Pending,
+            created_at: 0,
+            updated_at: 0,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_signature_and_backoff() {
+        let signature = sign_payload("secret", 100, "{}");
+        assert!(verify_signature("secret", 100, "{}", &signature));
+        assert!(!verify_signature("secret", 101, "{}", &signature));
+        assert!(!verify_signature("other", 100, "{}", &signature));
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff_after(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff_after(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff_after(20), Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn test_core_events_are_signed_filtered_and_retried() {
+        let (stub, addr) = start_stub(2).await;
+        let dispatcher = WebhookDispatcher::new(Arc::new(HttpTransport::default()), fast_retry(5), Arc::new(SystemClock));
+        dispatcher.start();
+        dispatcher
+            .register(NewWebhookSubscription {
+                url: format!("http://{}/hook", addr),
+                event_types: vec!["compliance.*".to_string()],
+                secret: "s3cret".to_string(),
+            })
+            .unwrap();
+        let core = TokenizationCore::new();
+        core.notifications().subscribe(dispatcher.clone()).unwrap();
+
+        create_asset(&core);
+        core.perform_compliance_check("asset1").unwrap();
+
+        let deliveries = wait_until_settled(&dispatcher).await;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].notification.event_type, "compliance.decided");
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts.len(), 3);
+        assert_eq!(deliveries[0].attempts[0].status_code, Some(500));
+
+        let received = stub.received.lock().unwrap();
+        let (signature, timestamp, body) = received.last().unwrap();
+        assert!(verify_signature("s3cret", timestamp.parse().unwrap(), body, signature));
+        let payload: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["data"]["asset_id"], "asset1");
+    }
+
+    #[tokio::test]
+    async fn test_https_urls_are_dialled_over_tls() {
+        let dispatcher = WebhookDispatcher::new(Arc::new(HttpTransport::default()), fast_retry(1), Arc::new(SystemClock));
+        let new = |url: &str| NewWebhookSubscription {
+            url: url.to_string(),
+            event_types: vec![],
+            secret: "s3cret".to_string(),
+        };
+        assert!(dispatcher.register(new("https://example.com/hook")).is_ok());
+        assert!(dispatcher.register(new("ftp://example.com/hook")).is_err());
+
+        // A plain HTTP server fails the TLS handshake instead of the URL being refused outright
+        let (stub, addr) = start_stub(0).await;
+        let err = HttpTransport::default()
+            .post(&format!("https://{}/hook", addr), vec![], "{}".to_string())
+            .await
+            .unwrap_err();
+        assert!(!err.contains("scheme is not http"), "{}", err);
+        assert!(stub.received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_deliveries_are_dead_lettered_and_redeliverable() {
+        let (_stub, addr) = start_stub(3).await;
+        let dispatcher = WebhookDispatcher::new(Arc::new(HttpTransport::default()), fast_retry(2), Arc::new(SystemClock));
+        dispatcher.start();
+        dispatcher
+            .register(NewWebhookSubscription {
+                url: format!("http://{}/hook", addr),
+                event_types: vec![],
+                secret: "s3cret".to_string(),
+            })
+            .unwrap();
+        dispatcher.notify(&Notification {
+            id: 1,
+            event_type: "catalog.component_added".to_string(),
+            occurred_at: 0,
+            data: serde_json::Value::Null,
+        });
+
+        let deliveries = wait_until_settled(&dispatcher).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::DeadLettered);
+        assert_eq!(dispatcher.get_dead_letters().unwrap().len(), 1);
+
+        dispatcher.redeliver(&deliveries[0].id).unwrap();
+        let deliveries = wait_until_settled(&dispatcher).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts.len(), 4);
+        assert!(dispatcher.get_dead_letters().unwrap().is_empty());
+    }
+}
@@ -73,6 +73,28 @@ function displayError(message) {
     container.innerHTML = `<div class="error">${message}</div>`;
 }
 
+// Currently selected filter, refreshed when the catalog changes
+let currentType = 'all';
+
+async function refreshCurrentView() {
+    if (currentType === 'all') {
+        await fetchComponents();
+    } else {
+        await fetchComponentsByType(currentType);
+    }
+}
+
+// Subscribe to catalog changes so the list updates without polling.
+// EventSource reconnects on its own and resumes with Last-Event-ID.
+function subscribeToCatalogChanges() {
+    if (!window.EventSource) {
+        return;
+    }
+    const stream = new EventSource(`${API_BASE}/stream?topics=catalog`);
+    stream.addEventListener('catalog.component_added', () => refreshCurrentView());
+    stream.addEventListener('stream.gap', () => refreshCurrentView());
+}
+
 // Initialize the app
 document.addEventListener('DOMContentLoaded', async () => {
     console.log('App initialized');
@@ -83,12 +105,10 @@ document.addEventListener('DOMContentLoaded', async () => {
     const filterButtons = document.querySelectorAll('.filter-btn');
     filterButtons.forEach(button => {
         button.addEventListener('click', async () => {
-            const type = button.getAttribute('data-type');
-            if (type === 'all') {
-                await fetchComponents();
-            } else {
-                await fetchComponentsByType(type);
-            }
+            currentType = button.getAttribute('data-type');
+            await refreshCurrentView();
         });
     });
+
+    subscribeToCatalogChanges();
 });
\ No newline at end of file
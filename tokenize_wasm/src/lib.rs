@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsCast;
 use web_sys::{Request, RequestInit, Response};
 
+mod reserves;
+mod signing;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenizationComponent {
     pub id: u32,
@@ -31,8 +34,8 @@ pub fn main() {
 
 #[wasm_bindgen]
 pub async fn fetch_components() -> JsValue {
-    let mut opts = RequestInit::new();
-    opts.method("GET");
+    let opts = RequestInit::new();
+    opts.set_method("GET");
     
     let request = Request::new_with_str_and_init(
         "http://localhost:3030/api/components",
@@ -55,7 +58,7 @@ pub async fn fetch_components() -> JsValue {
     // Parse the response
     match serde_json::from_str::<ApiResponse>(&text_string) {
         Ok(api_response) => {
-            JsValue::from_serde(&api_response).unwrap()
+            serde_wasm_bindgen::to_value(&api_response).unwrap()
         }
         Err(e) => {
             log(&format!("Error parsing response: {:?}", e));
@@ -71,7 +74,7 @@ pub fn greet(name: &str) -> String {
 
 #[wasm_bindgen]
 pub fn process_components(components: &JsValue) -> JsValue {
-    match components.into_serde::<Vec<TokenizationComponent>>() {
+    match serde_wasm_bindgen::from_value::<Vec<TokenizationComponent>>(components.clone()) {
         Ok(components_vec) => {
             log(&format!("Processing {} components", components_vec.len()));
             
@@ -83,7 +86,7 @@ pub fn process_components(components: &JsValue) -> JsValue {
             
             // Convert to a serializable format
             let result: Vec<(String, usize)> = counts.into_iter().collect();
-            JsValue::from_serde(&result).unwrap()
+            serde_wasm_bindgen::to_value(&result).unwrap()
         }
         Err(e) => {
             log(&format!("Error processing components: {:?}", e));
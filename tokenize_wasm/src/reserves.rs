@@ -0,0 +1,130 @@
+//! Browser-side checking of proof of liabilities
+//!
+//! Mirrors the backend's Merkle sum tree so that a holder can confirm, without
+//! trusting the server, that an inclusion proof from
+//! `/api/core/reserves/{id}/proofs/{wallet_id}` leads to the root and total
+//! published in the report.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+
+const LEAF_DOMAIN: &[u8] = b"tokenize-reserves-leaf:v1";
+const NODE_DOMAIN: &[u8] = b"tokenize-reserves-node:v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash: String,
+    pub sum: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub report_id: String,
+    pub asset_id: String,
+    pub wallet_id: String,
+    pub units: u64,
+    pub salt: String,
+    pub path: Vec<ProofStep>,
+}
+
+struct Node {
+    hash: [u8; 32],
+    sum: u64,
+}
+
+fn leaf_hash(asset_id: &str, wallet_id: &str, units: u64, salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(LEAF_DOMAIN);
+    hasher.update(salt);
+    for field in [asset_id, wallet_id] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(units.to_be_bytes());
+    hasher.finalize().into()
+}
+
+fn parent(left: &Node, right: &Node) -> Option<Node> {
+    let sum = left.sum.checked_add(right.sum)?;
+    let mut hasher = Sha256::new();
+    hasher.update(NODE_DOMAIN);
+    hasher.update(left.hash);
+    hasher.update(left.sum.to_be_bytes());
+    hasher.update(right.hash);
+    hasher.update(right.sum.to_be_bytes());
+    Some(Node {
+        hash: hasher.finalize().into(),
+        sum,
+    })
+}
+
+/// Whether an inclusion proof, as JSON, leads to a report's published root and
+/// total in base units
+#[wasm_bindgen]
+pub fn verify_reserve_proof(proof_json: &str, root: &str, total_units: u64) -> Result<bool, JsValue> {
+    let proof: InclusionProof =
+        serde_json::from_str(proof_json).map_err(|e| JsValue::from_str(&format!("Invalid proof: {}", e)))?;
+    let salt = hex::decode(&proof.salt).map_err(|_| JsValue::from_str("Salt is not valid hex"))?;
+    let mut node = Node {
+        hash: leaf_hash(&proof.asset_id, &proof.wallet_id, proof.units, &salt),
+        sum: proof.units,
+    };
+    for step in &proof.path {
+        let hash: [u8; 32] = hex::decode(&step.hash)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| JsValue::from_str("Proof hashes are 32 bytes of hex"))?;
+        let sibling = Node { hash, sum: step.sum };
+        let next = match step.side {
+            Side::Left => parent(&sibling, &node),
+            Side::Right => parent(&node, &sibling),
+        };
+        match next {
+            Some(next) => node = next,
+            None => return Ok(false),
+        }
+    }
+    Ok(hex::encode(node.hash) == root.to_lowercase() && node.sum == total_units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generated by the backend for wallets holding 10, 2.5 and 7.25 gold; this is w2's proof
+    const ROOT: &str = "955061d097889663e96b46950bd0ee0c50bb9548ff1246493edfff007679544b";
+    const TOTAL_UNITS: u64 = 1_975_000_000;
+    const PROOF: &str = r#"{
+        "report_id": "reserves-1",
+        "asset_id": "gold",
+        "wallet_id": "w2",
+        "units": 250000000,
+        "salt": "8272dff00850569fefbda5421f30a785",
+        "path": [
+            {"side": "Left", "hash": "a74712a23df58dbb688773d619f0838b9a7c420a87acbdc25145e7b2f6399ad3", "sum": 1000000000},
+            {"side": "Right", "hash": "d16b1e8f8d626da9889808a2b43ae464c03b317828608d557a0c1bf1a9cc5afa", "sum": 725000000}
+        ]
+    }"#;
+
+    #[test]
+    fn test_backend_proof_verifies() {
+        assert!(verify_reserve_proof(PROOF, ROOT, TOTAL_UNITS).unwrap());
+        assert!(verify_reserve_proof(PROOF, &ROOT.to_uppercase(), TOTAL_UNITS).unwrap());
+    }
+
+    #[test]
+    fn test_altered_proof_or_report_is_rejected() {
+        assert!(!verify_reserve_proof(PROOF, ROOT, TOTAL_UNITS + 1).unwrap());
+        assert!(!verify_reserve_proof(&PROOF.replace("250000000", "250000001"), ROOT, TOTAL_UNITS).unwrap());
+        assert!(!verify_reserve_proof(&PROOF.replace("\"w2\"", "\"w3\""), ROOT, TOTAL_UNITS).unwrap());
+        assert!(!verify_reserve_proof(&PROOF.replace("Left", "Right"), ROOT, TOTAL_UNITS).unwrap());
+    }
+}
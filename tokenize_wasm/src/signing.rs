@@ -0,0 +1,160 @@
+//! Browser-side signing of wallet operations
+//!
+//! Mirrors the backend's payload types so that the canonical form signed here is
+//! byte for byte the one the backend verifies: `tokenize-wallet-operation:v1`, a
+//! newline, then the payload as JSON with object keys sorted and no whitespace.
+
+use ed25519_dalek::Signer as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+const SIGNING_DOMAIN: &str = "tokenize-wallet-operation:v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WalletOperation {
+    Transfer {
+        to_wallet_id: String,
+        asset_id: String,
+        quantity: f64,
+    },
+    SignRequest {
+        request_id: String,
+    },
+    ApproveProposal {
+        proposal_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationPayload {
+    pub wallet_id: String,
+    pub nonce: u64,
+    pub operation: WalletOperation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedOperation {
+    pub payload: OperationPayload,
+    pub key_id: String,
+    pub signature: String,
+}
+
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical_json(&map[key])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => format!("[{}]", items.iter().map(canonical_json).collect::<Vec<_>>().join(",")),
+        other => other.to_string(),
+    }
+}
+
+fn signing_string(payload: &OperationPayload) -> Result<String, JsValue> {
+    let value = serde_json::to_value(payload).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(format!("{}\n{}", SIGNING_DOMAIN, canonical_json(&value)))
+}
+
+fn parse_payload(payload_json: &str) -> Result<OperationPayload, JsValue> {
+    serde_json::from_str(payload_json).map_err(|e| JsValue::from_str(&format!("Invalid payload: {}", e)))
+}
+
+fn secret_key(secret_key_hex: &str) -> Result<[u8; 32], JsValue> {
+    let bytes = hex::decode(secret_key_hex).map_err(|_| JsValue::from_str("Secret key is not valid hex"))?;
+    bytes.try_into().map_err(|_| JsValue::from_str("Secret keys are 32 bytes"))
+}
+
+fn sign_bytes(algorithm: &str, secret_key_hex: &str, message: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let secret = secret_key(secret_key_hex)?;
+    match algorithm {
+        "ed25519" => Ok(ed25519_dalek::SigningKey::from_bytes(&secret).sign(message).to_bytes().to_vec()),
+        "secp256k1" => {
+            let key = k256::ecdsa::SigningKey::from_slice(&secret)
+                .map_err(|_| JsValue::from_str("Invalid secp256k1 secret key"))?;
+            let signature: k256::ecdsa::Signature = key.sign(message);
+            Ok(signature.to_bytes().to_vec())
+        }
+        _ => Err(JsValue::from_str("Algorithm must be ed25519 or secp256k1")),
+    }
+}
+
+/// The exact text a payload's signature covers
+#[wasm_bindgen]
+pub fn canonical_payload(payload_json: &str) -> Result<String, JsValue> {
+    signing_string(&parse_payload(payload_json)?)
+}
+
+/// Hex public key to register for a hex secret key
+#[wasm_bindgen]
+pub fn public_key(algorithm: &str, secret_key_hex: &str) -> Result<String, JsValue> {
+    let secret = secret_key(secret_key_hex)?;
+    match algorithm {
+        "ed25519" => Ok(hex::encode(ed25519_dalek::SigningKey::from_bytes(&secret).verifying_key().to_bytes())),
+        "secp256k1" => {
+            let key = k256::ecdsa::SigningKey::from_slice(&secret)
+                .map_err(|_| JsValue::from_str("Invalid secp256k1 secret key"))?;
+            Ok(hex::encode(key.verifying_key().to_sec1_bytes()))
+        }
+        _ => Err(JsValue::from_str("Algorithm must be ed25519 or secp256k1")),
+    }
+}
+
+/// Sign a payload with a wallet key, returning the signed operation as JSON ready
+/// to post to `/api/core/signed-operations`
+#[wasm_bindgen]
+pub fn sign_operation(payload_json: &str, key_id: &str, algorithm: &str, secret_key_hex: &str) -> Result<String, JsValue> {
+    let payload = parse_payload(payload_json)?;
+    let signature = sign_bytes(algorithm, secret_key_hex, signing_string(&payload)?.as_bytes())?;
+    let signed = SignedOperation {
+        payload,
+        key_id: key_id.to_string(),
+        signature: hex::encode(signature),
+    };
+    serde_json::to_string(&signed).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shared with the backend's signature tests; both sides must agree on every byte
+    const PAYLOAD: &str = r#"{"wallet_id":"wallet-α","nonce":7,"operation":{"type":"Transfer","to_wallet_id":"wallet \"b\"","asset_id":"gold","quantity":12.5}}"#;
+    const SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+    #[test]
+    fn test_canonical_payload_matches_backend() {
+        assert_eq!(
+            canonical_payload(PAYLOAD).unwrap(),
+            "tokenize-wallet-operation:v1\n\
+             {\"nonce\":7,\"operation\":{\"asset_id\":\"gold\",\"quantity\":12.5,\"to_wallet_id\":\"wallet \\\"b\\\"\",\"type\":\"Transfer\"},\"wallet_id\":\"wallet-α\"}"
+        );
+    }
+
+    #[test]
+    fn test_signatures_match_backend_vectors() {
+        for (algorithm, public, signature) in [
+            (
+                "ed25519",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "743b50b9bd9fbfb33fb04e109b0baffe023c6ef759f5912b329a74f0f6ac3ab7d8109bc559234b94979e47aae026a3c7cd914b662d9dd00023007c7586b7a901",
+            ),
+            (
+                "secp256k1",
+                "028db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd91",
+                "956c24d1dcbf9a60dca6e71484d9bb6327e5a7acc1f47ad8d8ec82fb182609fa77530f7356d54a7d219c913d7b7e452087aa1c788773048c95f9432138286ef4",
+            ),
+        ] {
+            assert_eq!(public_key(algorithm, SECRET_KEY).unwrap(), public);
+            let signed: SignedOperation =
+                serde_json::from_str(&sign_operation(PAYLOAD, "key-1", algorithm, SECRET_KEY).unwrap()).unwrap();
+            assert_eq!(signed.signature, signature, "{}", algorithm);
+        }
+    }
+}